@@ -0,0 +1,66 @@
+//! Benchmark for the horizontal placement of deep trees.
+//!
+//! The placement used to scan all nodes for every layer, which is quadratic in the depth of the
+//! tree. The benchmark embeds caterpillar trees of growing depth and reports the time per tree
+//! together with the growth against the previous depth. The regression check itself is the
+//! `deep_tree_scaling` test.
+//!
+//! Run it with `cargo bench --bench deep_tree_scaling`.
+
+use std::fmt::{Formatter, Result};
+use std::time::{Duration, Instant};
+
+use syntree::{Builder, FlavorDefault, Tree};
+use syntree_layout::{Layouter, Visualize};
+
+#[derive(Copy, Clone, Debug)]
+struct Node(u32);
+
+impl Visualize for Node {
+    fn visualize(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A chain of inner nodes of the given depth, each with an additional leaf.
+fn caterpillar(depth: u32) -> Tree<Node, FlavorDefault> {
+    let mut tree = Builder::new();
+    for i in 0..depth {
+        tree.open(Node(i)).unwrap();
+        tree.token(Node(i), 1).unwrap();
+    }
+    for _ in 0..depth {
+        tree.close().unwrap();
+    }
+    tree.build().unwrap()
+}
+
+/// The fastest of a few runs of the embedding.
+fn measure(tree: &Tree<Node, FlavorDefault>) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            let layouter = Layouter::new(tree).embed_with_visualize().unwrap();
+            let elapsed = start.elapsed();
+            assert_eq!(layouter.embedding().len(), tree.len());
+            elapsed
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let mut previous: Option<Duration> = None;
+    for depth in [2_000, 4_000, 8_000, 16_000] {
+        let tree = caterpillar(depth);
+        let elapsed = measure(&tree);
+        match previous {
+            Some(previous) => {
+                let ratio = elapsed.as_secs_f64() / previous.as_secs_f64();
+                println!("depth {depth:>6}: {elapsed:?} ({ratio:.1}x)");
+            }
+            None => println!("depth {depth:>6}: {elapsed:?}"),
+        }
+        previous = Some(elapsed);
+    }
+}
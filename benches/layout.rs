@@ -0,0 +1,141 @@
+//! Benchmarks of the embedding and the SVG rendering of synthetic trees.
+//!
+//! The trees come in three shapes, deep chains with a leaf on each level, wide trees with many
+//! children per node and randomly shaped trees. Each shape is embedded with the default and the
+//! compact layout and rendered to SVG. The throughput of the rendering is the size of the SVG
+//! output, so criterion reports it along with the times.
+//!
+//! Run it with `cargo bench --bench layout`.
+
+use std::fmt::{Formatter, Result};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use syntree::{Builder, FlavorDefault, Tree};
+use syntree_layout::{Layouter, Visualize};
+
+#[derive(Copy, Clone, Debug)]
+struct Node(u32);
+
+impl Visualize for Node {
+    fn visualize(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "node {}", self.0)
+    }
+}
+
+/// A chain of inner nodes of the given depth, each with an additional leaf.
+fn deep(depth: u32) -> Tree<Node, FlavorDefault> {
+    let mut tree = Builder::new();
+    for i in 0..depth {
+        tree.open(Node(i)).unwrap();
+        tree.token(Node(i), 1).unwrap();
+    }
+    for _ in 0..depth {
+        tree.close().unwrap();
+    }
+    tree.build().unwrap()
+}
+
+/// A complete tree with the given number of children per inner node and three levels.
+fn wide(width: u32) -> Tree<Node, FlavorDefault> {
+    let mut tree = Builder::new();
+    let mut n = 0;
+    let mut next = || {
+        n += 1;
+        Node(n)
+    };
+    tree.open(next()).unwrap();
+    for _ in 0..width {
+        tree.open(next()).unwrap();
+        for _ in 0..width {
+            tree.token(next(), 1).unwrap();
+        }
+        tree.close().unwrap();
+    }
+    tree.close().unwrap();
+    tree.build().unwrap()
+}
+
+/// A tree of the given size whose shape is chosen by a fixed pseudo random sequence, so all runs
+/// measure the same tree.
+fn random(size: u32) -> Tree<Node, FlavorDefault> {
+    // xorshift32
+    let mut state = 0x2545_f491_u32;
+    let mut next_random = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    let mut tree = Builder::new();
+    tree.open(Node(0)).unwrap();
+    let mut depth = 1;
+    for i in 1..size {
+        match next_random() % 3 {
+            0 => {
+                tree.open(Node(i)).unwrap();
+                depth += 1;
+            }
+            1 if depth > 1 => {
+                tree.close().unwrap();
+                depth -= 1;
+                tree.token(Node(i), 1).unwrap();
+            }
+            _ => {
+                tree.token(Node(i), 1).unwrap();
+            }
+        }
+    }
+    for _ in 0..depth {
+        tree.close().unwrap();
+    }
+    tree.build().unwrap()
+}
+
+fn shapes() -> Vec<(&'static str, Tree<Node, FlavorDefault>)> {
+    vec![
+        ("deep", deep(1_000)),
+        ("wide", wide(45)),
+        ("random", random(2_000)),
+    ]
+}
+
+fn embed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("embed");
+    for (name, tree) in shapes() {
+        group.throughput(Throughput::Elements(tree.len() as u64));
+        for compact in [false, true] {
+            let id = BenchmarkId::new(name, if compact { "compact" } else { "default" });
+            group.bench_with_input(id, &tree, |b, tree| {
+                b.iter(|| {
+                    Layouter::new(tree)
+                        .with_compact(compact)
+                        .embed_with_visualize()
+                        .unwrap()
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn render_svg(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_svg");
+    for (name, tree) in shapes() {
+        for compact in [false, true] {
+            let layouter = Layouter::new(&tree)
+                .with_compact(compact)
+                .embed_with_visualize()
+                .unwrap();
+            let size = layouter.to_svg_string().unwrap().len();
+            group.throughput(Throughput::Bytes(size as u64));
+            let id = BenchmarkId::new(name, if compact { "compact" } else { "default" });
+            group.bench_with_input(id, &layouter, |b, layouter| {
+                b.iter(|| layouter.to_svg_string().unwrap());
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, embed, render_svg);
+criterion_main!(benches);
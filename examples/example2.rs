@@ -153,5 +153,13 @@ fn main() -> std::result::Result<(), anyhow::Error> {
         .embed_with_visualize()
         .map_err(|e| anyhow::anyhow!(e))?
         .write()
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    Layouter::new(&tree)
+        .with_file_path("examples/example2_compact.svg")
+        .with_compact(true)
+        .embed_with_visualize()
+        .map_err(|e| anyhow::anyhow!(e))?
+        .write()
         .map_err(|e| anyhow::anyhow!(e))
 }
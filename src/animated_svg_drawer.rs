@@ -0,0 +1,138 @@
+//! The module with the drawer for animated Svg images.
+
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::{DrawContext, Drawer, EmbeddedNode, LayouterError, Result, SvgDrawer};
+
+/// The kinds of the elements that belong to a node, see the ids written by the [SvgDrawer]
+const NODE_ELEMENTS: [&str; 5] = ["node-", "box-", "edge-", "source-link-", "source-span-"];
+
+///
+/// The `AnimatedSvgDrawer` type draws the tree as Svg image in which the nodes appear one after
+/// another in the order in which the builder created them, i.e. ordered by their `ord`. This
+/// shows how a parser builds the tree step by step.
+///
+/// The tree is drawn by an [SvgDrawer] and the animation is done with CSS keyframes, which all
+/// current browsers play without scripts. Each node fades in together with the edge to its
+/// parent.
+///
+#[derive(Debug)]
+pub struct AnimatedSvgDrawer {
+    svg_drawer: SvgDrawer,
+    step: f32,
+    fade: f32,
+}
+
+impl AnimatedSvgDrawer {
+    /// Method to create a fresh instance of the `AnimatedSvgDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            svg_drawer: SvgDrawer::new(),
+            step: 0.5,
+            fade: 0.3,
+        }
+    }
+
+    /// Sets the [SvgDrawer] that draws the tree and thereby its style.
+    pub fn with_svg_drawer(self, svg_drawer: SvgDrawer) -> Self {
+        Self { svg_drawer, ..self }
+    }
+
+    /// Sets the time in seconds between the appearance of two consecutive nodes, the default is
+    /// half a second.
+    pub fn with_step(self, step: f32) -> Self {
+        Self { step, ..self }
+    }
+
+    /// Sets the time in seconds a node takes to fade in, the default is 0.3 seconds.
+    pub fn with_fade(self, fade: f32) -> Self {
+        Self { fade, ..self }
+    }
+
+    ///
+    /// Renders the given embedding into an animated Svg image and returns it as a `String`.
+    ///
+    /// ```
+    /// use syntree_layout::{AnimatedSvgDrawer, EmbeddedNode};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let svg = AnimatedSvgDrawer::new().with_step(1.0).render_string(&embedding).unwrap();
+    /// assert!(svg.contains("@keyframes reveal"));
+    /// assert!(svg.contains("animation-delay: 1s"));
+    /// ```
+    ///
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        let svg = self.svg_drawer.render_string(embedding)?;
+        // The stylesheet goes right behind the start tag of the svg element
+        let insert_at = svg
+            .find("<svg")
+            .and_then(|start| svg[start..].find('>').map(|end| start + end + 1))
+            .ok_or_else(|| LayouterError::MalformedSvg {
+                msg: "missing svg element".to_string(),
+            })?;
+        let mut svg = svg;
+        svg.insert_str(insert_at, &self.stylesheet(embedding));
+        Ok(svg)
+    }
+
+    /// Creates the stylesheet that hides all node elements until their turn has come.
+    fn stylesheet(&self, embedding: &[EmbeddedNode]) -> String {
+        let prefix = css_string(self.svg_drawer.id_prefix());
+        let mut css = format!(
+            "\n<style><![CDATA[\n\
+             @keyframes reveal {{ from {{ opacity: 0; }} to {{ opacity: 1; }} }}\n\
+             {} {{ opacity: 0; animation: reveal {}s ease-in forwards; }}\n",
+            NODE_ELEMENTS
+                .iter()
+                .map(|kind| format!("[id^=\"{}{}\"]", prefix, kind))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.fade,
+        );
+        for node in embedding {
+            let selector = NODE_ELEMENTS
+                .iter()
+                .map(|kind| format!("[id=\"{}{}{}\"]", prefix, kind, node.ord))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = writeln!(
+                css,
+                "{} {{ animation-delay: {}s; }}",
+                selector,
+                node.ord as f32 * self.step
+            );
+        }
+        css.push_str("]]></style>");
+        css
+    }
+}
+
+impl Default for AnimatedSvgDrawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Escapes a text for a CSS string in double quotes inside of a CDATA section.
+fn css_string(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '"' | '\\' | '>' => format!("\\{:x} ", c as u32),
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `AnimatedSvgDrawer`.
+///
+impl Drawer for AnimatedSvgDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let svg = self.render_string(ctx.embedding())?;
+        fs::write(ctx.file_name(), svg).map_err(LayouterError::from_io_error)
+    }
+}
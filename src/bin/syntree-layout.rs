@@ -0,0 +1,277 @@
+//! The `syntree-layout` command line tool.
+//!
+//! It reads a tree description from stdin and writes the drawing to stdout, so that tools that
+//! are not written in Rust can use the layout engine in shell pipelines:
+//!
+//! ```text
+//! echo '(Expr (Num 1) + (Num 2))' | syntree-layout --format svg > expr.svg
+//! ```
+//!
+//! Two input formats are understood, the format is detected from the first character unless it
+//! is given with `--input`:
+//! * JSON: a node is an object `{"label": "Expr", "children": [...]}`, leaves may give the length
+//!   of their span with `"len"`. A string is a shorthand for a leaf.
+//! * S-expressions: `(label child ...)` is an inner node, an atom is a leaf. They are read with
+//!   `syntree_layout::from_text`, which also describes the quoting of atoms and accepts the dumps
+//!   of `syntree::print` as well.
+//!
+//! The span of a leaf defaults to the number of characters of its label.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use syntree::{Builder, FlavorDefault, Tree};
+use syntree_layout::{from_text, DotDrawer, EmbeddedNode, Layouter, SvgDrawer};
+
+const USAGE: &str = "\
+Usage: syntree-layout [OPTIONS] < TREE
+
+Reads a tree description from stdin and writes the drawing to stdout.
+
+Options:
+    -i, --input <json|sexp>         The format of the input, detected if omitted
+    -f, --format <svg|dot|ascii>    The format of the output [default: svg]
+        --compact                   Creates a compact layout
+    -h, --help                      Prints this help";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Json,
+    Sexp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Svg,
+    Dot,
+    Ascii,
+}
+
+#[derive(Debug)]
+struct Options {
+    input: Option<InputFormat>,
+    format: OutputFormat,
+    compact: bool,
+}
+
+///
+/// A node of the tree description. Inner nodes have children, leaves have the length of their
+/// span.
+///
+#[derive(Debug)]
+enum Node {
+    Inner { label: String, children: Vec<Node> },
+    Leaf { label: String, len: usize },
+}
+
+impl Node {
+    fn leaf(label: String) -> Self {
+        let len = label.chars().count();
+        Node::Leaf { label, len }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parses the command line, returns `None` if the help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>> {
+    let mut options = Options {
+        input: None,
+        format: OutputFormat::Svg,
+        compact: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value of option {}", name))
+        };
+        match arg.as_str() {
+            "-i" | "--input" => {
+                options.input = Some(match value(&arg)?.as_str() {
+                    "json" => InputFormat::Json,
+                    "sexp" => InputFormat::Sexp,
+                    other => bail!("Unknown input format '{}'", other),
+                })
+            }
+            "-f" | "--format" => {
+                options.format = match value(&arg)?.as_str() {
+                    "svg" => OutputFormat::Svg,
+                    "dot" => OutputFormat::Dot,
+                    "ascii" => OutputFormat::Ascii,
+                    other => bail!("Unknown output format '{}'", other),
+                }
+            }
+            "--compact" => options.compact = true,
+            "-h" | "--help" => return Ok(None),
+            other => bail!("Unknown argument '{}'", other),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn run(options: &Options) -> Result<()> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read the tree from stdin")?;
+    let format = options.input.unwrap_or_else(|| {
+        if input.trim_start().starts_with(['{', '[', '"']) {
+            InputFormat::Json
+        } else {
+            InputFormat::Sexp
+        }
+    });
+    let output = match format {
+        InputFormat::Json => {
+            let value: Value = serde_json::from_str(&input).context("Invalid JSON")?;
+            // The node values are the indices of the labels
+            let mut labels = Vec::new();
+            let mut builder = Builder::new();
+            build(&from_json(&value)?, &mut builder, &mut labels)?;
+            let tree: Tree<usize, _> = builder.build()?;
+            render(&tree, options, |index, f| f.write_str(&labels[*index]))?
+        }
+        InputFormat::Sexp => render(&from_text(&input)?, options, |label, f| {
+            write!(f, "{}", label)
+        })?,
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .context("Failed to write the output")
+}
+
+/// Draws the tree in the requested output format, the labels are written by the given function.
+fn render<T: Copy>(
+    tree: &Tree<T, FlavorDefault>,
+    options: &Options,
+    label: impl Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+) -> Result<String> {
+    let layouter = Layouter::new(tree)
+        .with_compact(options.compact)
+        .embed_with(label, |_| false)?;
+    Ok(match options.format {
+        OutputFormat::Svg => SvgDrawer::new().render_string(layouter.embedding())?,
+        OutputFormat::Dot => DotDrawer::new().render_string(layouter.embedding())?,
+        OutputFormat::Ascii => ascii(layouter.embedding()),
+    })
+}
+
+/// Adds the given node and its descendants to the builder.
+fn build(
+    root: &Node,
+    builder: &mut Builder<usize, FlavorDefault>,
+    labels: &mut Vec<String>,
+) -> Result<()> {
+    // The siblings that remain to be added on each level, deep trees don't exhaust the stack
+    let mut levels = vec![std::slice::from_ref(root).iter()];
+    while let Some(siblings) = levels.last_mut() {
+        match siblings.next() {
+            Some(Node::Inner { label, children }) => {
+                labels.push(label.clone());
+                builder.open(labels.len() - 1)?;
+                levels.push(children.iter());
+            }
+            Some(Node::Leaf { label, len }) => {
+                labels.push(label.clone());
+                builder.token(labels.len() - 1, *len)?;
+            }
+            None => {
+                levels.pop();
+                if !levels.is_empty() {
+                    builder.close()?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts a JSON value into a node.
+fn from_json(value: &Value) -> Result<Node> {
+    let object = match value {
+        Value::String(label) => return Ok(Node::leaf(label.clone())),
+        Value::Object(object) => object,
+        _ => bail!("A node must be an object or a string, found {}", value),
+    };
+    let label = match object.get("label") {
+        Some(Value::String(label)) => label.clone(),
+        Some(label) => label.to_string(),
+        None => bail!("Missing label of node {}", value),
+    };
+    match (object.get("children"), object.get("len")) {
+        (Some(_), Some(_)) => bail!("Node '{}' has children and a len", label),
+        (Some(Value::Array(children)), None) => Ok(Node::Inner {
+            label,
+            children: children.iter().map(from_json).collect::<Result<_>>()?,
+        }),
+        (Some(_), None) => bail!("The children of node '{}' must be an array", label),
+        (None, Some(len)) => {
+            let len = len
+                .as_u64()
+                .ok_or_else(|| anyhow!("The len of node '{}' must be a number", label))?;
+            Ok(Node::Leaf {
+                label,
+                len: len as usize,
+            })
+        }
+        (None, None) => Ok(Node::leaf(label)),
+    }
+}
+
+/// Draws the embedding as an outline with box drawing characters.
+fn ascii(embedding: &[EmbeddedNode]) -> String {
+    let mut children = vec![Vec::new(); embedding.len()];
+    let mut roots = Vec::new();
+    for node in embedding {
+        match node.parent {
+            Some(parent) => children[parent].push(node.ord),
+            None => roots.push(node.ord),
+        }
+    }
+
+    let mut output = String::new();
+    for root in roots {
+        output.push_str(&embedding[root].text.replace('\n', " "));
+        output.push('\n');
+        // The children that remain to be drawn on each level below the root and whether the
+        // node of the level was the last of its siblings, which determines the indentation
+        let mut levels = vec![(children[root].iter().peekable(), true)];
+        while let Some((kids, _)) = levels.last_mut() {
+            let Some(child) = kids.next() else {
+                levels.pop();
+                continue;
+            };
+            let last = kids.peek().is_none();
+            for (_, ancestor_last) in &levels[1..] {
+                output.push_str(if *ancestor_last { "    " } else { "│   " });
+            }
+            output.push_str(if last { "└── " } else { "├── " });
+            output.push_str(&embedding[*child].text.replace('\n', " "));
+            output.push('\n');
+            levels.push((children[*child].iter().peekable(), last));
+        }
+    }
+    output
+}
@@ -0,0 +1,234 @@
+//! The module with the composition of several trees into one drawing.
+
+use std::path::Path;
+
+use syntree::Flavor;
+
+use crate::{
+    embedding::tree_width, DrawContext, Drawer, EmbeddedNode, Embedding, Layouter, Result,
+    SvgDrawer, WriteOptions,
+};
+
+///
+/// The `Canvas` places the embeddings of several trees side by side in one drawing, e.g. to show
+/// the parse tree before and after a grammar change or the concrete and the abstract syntax tree
+/// in one picture.
+///
+/// The trees are placed from left to right in the order in which they are added, separated by
+/// the [gap][Canvas::with_gap]. All trees are drawn by the same drawer and thus at the same
+/// scale.
+///
+/// ```
+/// use std::fmt;
+/// use syntree_layout::{Canvas, Layouter, Visualize};
+/// use syntree::{Tree, Builder};
+///
+/// #[derive(Copy, Clone, Debug)]
+/// struct MyNodeData(i32);
+///
+/// impl Visualize for MyNodeData {
+///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+/// }
+///
+/// let mut builder = Builder::new();
+/// builder.open(MyNodeData(0)).unwrap();
+/// builder.token(MyNodeData(1), 1).unwrap();
+/// builder.close().unwrap();
+/// let tree = builder.build().unwrap();
+///
+/// let canvas = Canvas::new()
+///     .add(Layouter::new(&tree).embed_with_visualize().unwrap())
+///     .add(Layouter::new(&tree).embed_with_visualize().unwrap());
+/// let embedding = canvas.embedding();
+/// assert_eq!(4, embedding.len());
+/// assert_eq!(vec![1, 1, 7, 7], embedding.iter().map(|e| e.x_center).collect::<Vec<_>>());
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    embeddings: Vec<Embedding>,
+    gap: usize,
+    aligned_layers: bool,
+    write_options: WriteOptions,
+}
+
+impl Canvas {
+    /// Creates an empty canvas.
+    pub const fn new() -> Self {
+        Self {
+            embeddings: Vec::new(),
+            gap: 4,
+            aligned_layers: false,
+            write_options: WriteOptions::new(),
+        }
+    }
+
+    /// Adds the embedding of the given layouter right of the trees added so far.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<T, F, D>(self, layouter: Layouter<'_, T, F, D>) -> Self
+    where
+        T: Copy,
+        F: Flavor,
+        D: ?Sized + Drawer,
+    {
+        self.add_embedding(layouter.into_embedding())
+    }
+
+    /// Adds the given embedding right of the trees added so far.
+    pub fn add_embedding(mut self, embedding: Embedding) -> Self {
+        self.embeddings.push(embedding);
+        self
+    }
+
+    /// Sets the horizontal distance between neighboring trees in logical units, the default is 4.
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    ///
+    /// When set to true the layers of all trees are aligned, i.e. the nodes of the same depth
+    /// are drawn on the same line in every tree, which makes derivations of the same input
+    /// comparable. Trees that were embedded with different
+    /// [level gaps][crate::Spacing::level_gap] are spread to the largest gap, and the leaves of
+    /// trees embedded with [LeafAlignment::Bottom][crate::LeafAlignment::Bottom] are moved to
+    /// the deepest level of all trees.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Canvas, Layouter, LeafAlignment, Spacing};
+    ///
+    /// let first = from_text("(S (NP she) (VP saw (NP him)))").unwrap();
+    /// let second = from_text("(S she (VP saw him))").unwrap();
+    /// let canvas = Canvas::new()
+    ///     .with_aligned_layers(true)
+    ///     .add(Layouter::new(&first).with_spacing(Spacing { level_gap: 2, ..Spacing::new() }).embed().unwrap())
+    ///     .add(Layouter::new(&second).with_leaf_alignment(LeafAlignment::Bottom).embed().unwrap());
+    /// let embedding = canvas.embedding();
+    /// let level = |text: &str| embedding.iter().rev().find(|e| e.text == text).unwrap().y_order;
+    /// assert_eq!(2, level("VP"));
+    /// // The tokens of the second tree are on the level of the deepest token of the first one
+    /// assert_eq!(6, level("him"));
+    /// assert_eq!(6, level("she"));
+    /// ```
+    ///
+    pub fn with_aligned_layers(mut self, aligned_layers: bool) -> Self {
+        self.aligned_layers = aligned_layers;
+        self
+    }
+
+    /// Sets how the output files are written, see [WriteOptions].
+    pub fn with_write_options(mut self, write_options: WriteOptions) -> Self {
+        self.write_options = write_options;
+        self
+    }
+
+    ///
+    /// Returns the composed embedding. It has one root for each added tree, the `ord`s of the
+    /// nodes are renumbered from left to right.
+    ///
+    pub fn embedding(&self) -> Embedding {
+        let mut nodes = Vec::with_capacity(self.embeddings.iter().map(|e| e.len()).sum());
+        let mut offset = 0;
+        for embedding in &self.embeddings {
+            let base = nodes.len();
+            nodes.extend(embedding.iter().cloned().map(|mut node| {
+                node.x_center += offset;
+                node.ord += base;
+                node.parent = node.parent.map(|parent| parent + base);
+                node
+            }));
+            offset += tree_width(embedding) + self.gap;
+        }
+        if self.aligned_layers {
+            self.align_layers(&mut nodes);
+        }
+        nodes.into()
+    }
+
+    ///
+    /// Moves the nodes of the composed trees to common levels. The level gap of each tree is the
+    /// level of the root's children.
+    ///
+    fn align_layers(&self, nodes: &mut [EmbeddedNode]) {
+        let gaps = self
+            .embeddings
+            .iter()
+            .map(|embedding| {
+                embedding
+                    .iter()
+                    .filter(|e| e.parent.is_some())
+                    .map(|e| e.y_order)
+                    .min()
+                    .unwrap_or(1)
+                    .max(1)
+            })
+            .collect::<Vec<_>>();
+        let gap = gaps.iter().copied().max().unwrap_or(1);
+        // Leaves that hang lower than one level below their parents were aligned at the bottom
+        let bottom_aligned = self
+            .embeddings
+            .iter()
+            .zip(&gaps)
+            .map(|(embedding, tree_gap)| {
+                embedding.iter().any(|e| {
+                    e.is_leaf()
+                        && e.parent
+                            .and_then(|parent| embedding.node(parent))
+                            .is_some_and(|parent| e.y_order > parent.y_order + tree_gap)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut start = 0;
+        for (i, embedding) in self.embeddings.iter().enumerate() {
+            for node in &mut nodes[start..start + embedding.len()] {
+                node.y_order = node.y_order / gaps[i] * gap;
+            }
+            start += embedding.len();
+        }
+        let deepest = nodes.iter().map(|e| e.y_order).max().unwrap_or_default();
+        let mut start = 0;
+        for (i, embedding) in self.embeddings.iter().enumerate() {
+            if bottom_aligned[i] {
+                for node in nodes[start..start + embedding.len()]
+                    .iter_mut()
+                    .filter(|e| e.is_leaf())
+                {
+                    node.y_order = deepest;
+                }
+            }
+            start += embedding.len();
+        }
+    }
+
+    ///
+    /// Draws the composed embedding with the default [SvgDrawer] into the given file.
+    ///
+    pub fn write(&self, file_name: impl AsRef<Path>) -> Result<()> {
+        self.write_with(&SvgDrawer::new(), file_name)
+    }
+
+    ///
+    /// Draws the composed embedding with the given drawer into the given file according to the
+    /// [write options][Canvas::with_write_options].
+    ///
+    pub fn write_with(&self, drawer: &dyn Drawer, file_name: impl AsRef<Path>) -> Result<()> {
+        let embedding = self.embedding();
+        self.write_options.write(file_name.as_ref(), |path| {
+            drawer.draw(&DrawContext::new(path, &embedding))
+        })
+    }
+
+    ///
+    /// Renders the composed embedding with the default [SvgDrawer] and returns the SVG document.
+    ///
+    pub fn to_svg_string(&self) -> Result<String> {
+        SvgDrawer::new().render_string(&self.embedding())
+    }
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,61 @@
+//! The module with the `ColorClass` type used to color nodes by their kind.
+
+///
+/// The `ColorClass` of a node is the semantic category of the node, e.g. whether a token is a
+/// keyword or a literal. It is obtained from the function given to
+/// [Layouter::with_color_by][crate::Layouter::with_color_by] and determines the fill color of
+/// the node's box unless the node's style has a fill of its own.
+///
+/// The fill colors are light enough for the default text color, see [ColorClass::color].
+///
+/// ```
+/// use syntree_layout::ColorClass;
+///
+/// assert_eq!(None, ColorClass::None.color());
+/// assert_eq!(Some("#cfe2ff"), ColorClass::Keyword.color());
+/// assert_eq!(ColorClass::Custom(0).color(), ColorClass::Custom(8).color());
+/// ```
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorClass {
+    /// A node without a class, drawn in the drawer's node style
+    #[default]
+    None,
+    /// A keyword of the language
+    Keyword,
+    /// A name, e.g. of a variable or a function
+    Identifier,
+    /// A literal like a number or a string
+    Literal,
+    /// An operator
+    Operator,
+    /// Punctuation like parentheses, commas or semicolons
+    Punctuation,
+    /// A comment
+    Comment,
+    /// A class of its own, the number selects a color of the default palette
+    Custom(usize),
+}
+
+impl ColorClass {
+    /// The colors of the [ColorClass::Custom] classes, repeated for larger numbers
+    pub const CUSTOM_PALETTE: [&'static str; 8] = [
+        "#d0e8f2", "#fde2c8", "#d4edda", "#f8d7da", "#e2d9f3", "#fff3cd", "#d6d8db", "#cce5e5",
+    ];
+
+    /// Returns the fill color of the class in the default palette, `None` for
+    /// [ColorClass::None].
+    pub fn color(&self) -> Option<&'static str> {
+        match self {
+            ColorClass::None => None,
+            ColorClass::Keyword => Some("#cfe2ff"),
+            ColorClass::Identifier => Some("#d1f0d1"),
+            ColorClass::Literal => Some("#ffe4c4"),
+            ColorClass::Operator => Some("#f5d0e6"),
+            ColorClass::Punctuation => Some("#e9ecef"),
+            ColorClass::Comment => Some("#fff8d6"),
+            ColorClass::Custom(n) => Some(Self::CUSTOM_PALETTE[n % Self::CUSTOM_PALETTE.len()]),
+        }
+    }
+}
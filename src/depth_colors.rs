@@ -0,0 +1,64 @@
+//! The module with the coloring of nodes by their depth.
+
+use std::borrow::Cow;
+
+use crate::internal::color::parse_color;
+
+///
+/// The `DepthColors` tint the background of the nodes by their depth in the tree, which makes the
+/// structure of deep trees easier to scan, see
+/// [SvgDrawer::with_depth_colors][crate::SvgDrawer::with_depth_colors].
+///
+/// ```
+/// use syntree_layout::DepthColors;
+///
+/// let palette = DepthColors::Palette(vec!["#eee".into(), "#ddd".into()]);
+/// assert_eq!(Some("#eee".to_string()), palette.color(2, 4));
+///
+/// let gradient = DepthColors::Gradient { from: "white".into(), to: "#000000".into() };
+/// assert_eq!(Some("#ffffff".to_string()), gradient.color(0, 2));
+/// assert_eq!(Some("#808080".to_string()), gradient.color(1, 2));
+/// assert_eq!(Some("#000000".to_string()), gradient.color(2, 2));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum DepthColors {
+    /// The colors of the levels starting with the root, repeated for deeper levels
+    Palette(Vec<Cow<'static, str>>),
+    /// The colors are interpolated from the color of the root to the color of the deepest
+    /// level. Colors are given as `#rgb`, `#rrggbb` or as one of the common color names.
+    Gradient {
+        /// The color of the root
+        from: Cow<'static, str>,
+        /// The color of the deepest level
+        to: Cow<'static, str>,
+    },
+}
+
+impl DepthColors {
+    ///
+    /// Returns the color of the given depth in a tree whose deepest nodes have the given
+    /// `max_depth`, or `None` if the palette is empty or a gradient color can't be parsed.
+    ///
+    pub fn color(&self, depth: usize, max_depth: usize) -> Option<String> {
+        match self {
+            DepthColors::Palette(colors) if colors.is_empty() => None,
+            DepthColors::Palette(colors) => Some(colors[depth % colors.len()].to_string()),
+            DepthColors::Gradient { from, to } => {
+                let (from, to) = (parse_color(from)?, parse_color(to)?);
+                let t = if max_depth == 0 {
+                    0.0
+                } else {
+                    depth.min(max_depth) as f32 / max_depth as f32
+                };
+                let mix = |a: f32, b: f32| ((a + (b - a) * t) * 255.0).round() as u8;
+                Some(format!(
+                    "#{:02x}{:02x}{:02x}",
+                    mix(from.0, to.0),
+                    mix(from.1, to.1),
+                    mix(from.2, to.2)
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! The module with the `DiagnosticLevel` type used to mark erroneous nodes and the
+//! `Diagnostic` type of reported problems.
+
+use std::ops::Range;
+
+///
+/// The `DiagnosticLevel` of a node tells drawers whether the node represents a problem found by
+/// the parser, e.g. a syntax error or a point where the parser recovered from an error.
+/// It is obtained from the [Visualize::diagnostic][crate::Visualize::diagnostic] hook.
+///
+/// The levels are ordered by their severity.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiagnosticLevel {
+    /// A regular node
+    #[default]
+    None,
+    /// A node the parser issued a warning for
+    Warning,
+    /// A node that was created or skipped during error recovery
+    Recovered,
+    /// A node that represents a syntax error
+    Error,
+}
+
+impl DiagnosticLevel {
+    /// Returns true for the levels that denote errors, i.e. `Recovered` and `Error`.
+    pub fn is_error(&self) -> bool {
+        matches!(self, DiagnosticLevel::Recovered | DiagnosticLevel::Error)
+    }
+}
+
+///
+/// A `Diagnostic` is a problem reported for a range of the source text, e.g. by a parser or a
+/// linter. The diagnostics given to [Layouter::with_diagnostics][crate::Layouter::with_diagnostics]
+/// mark the nodes whose spans they touch.
+///
+/// ```
+/// use syntree_layout::{Diagnostic, DiagnosticLevel};
+///
+/// let diagnostic = Diagnostic::new(4..7, DiagnosticLevel::Error, "expected `;`");
+/// assert_eq!(4..7, diagnostic.span);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    /// The range of the source text the diagnostic refers to
+    pub span: Range<usize>,
+    /// The severity of the problem
+    pub severity: DiagnosticLevel,
+    /// The message that describes the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic with the given span, severity and message.
+    pub fn new(span: Range<usize>, severity: DiagnosticLevel, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            severity,
+            message: message.into(),
+        }
+    }
+}
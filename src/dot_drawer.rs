@@ -0,0 +1,193 @@
+//! The module with the drawer for Graphviz DOT documents.
+
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::{
+    BorderStyle, DrawContext, Drawer, EmbeddedNode, LayouterError, NodeShape, Orientation, Result,
+};
+
+///
+/// The `DotDrawer` type provides the transformation of the embedding information into a Graphviz
+/// `digraph`. Graphviz does its own layout, thus only the tree structure, the texts and the
+/// styles of the nodes are taken from the embedding.
+///
+/// Graph-level attributes like `rankdir` or `dpi` can be set with
+/// [DotDrawer::with_graph_attribute], so that the exported files fit into existing Graphviz
+/// workflows without post-editing.
+///
+#[derive(Debug, Default)]
+pub struct DotDrawer {
+    graph_attributes: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+}
+
+impl DotDrawer {
+    /// Method to create a fresh instance of the `DotDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            graph_attributes: Vec::new(),
+        }
+    }
+
+    ///
+    /// Adds a graph-level attribute, e.g. `rankdir` or `dpi`. An attribute that was already set
+    /// is replaced.
+    ///
+    /// ```
+    /// use syntree_layout::{DotDrawer, EmbeddedNode};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "\"leaf\"".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let dot = DotDrawer::new()
+    ///     .with_graph_attribute("rankdir", "LR")
+    ///     .with_graph_attribute("dpi", "300")
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(dot.starts_with("digraph tree {\n    rankdir=\"LR\";\n    dpi=\"300\";\n"));
+    /// assert!(dot.contains("    n1 [label=\"\\\"leaf\\\"\"];\n"));
+    /// assert!(dot.contains("    n0 -> n1;\n"));
+    /// ```
+    ///
+    pub fn with_graph_attribute(
+        mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let key = key.into();
+        let value = value.into();
+        match self.graph_attributes.iter_mut().find(|(k, _)| *k == key) {
+            Some(attribute) => attribute.1 = value,
+            None => self.graph_attributes.push((key, value)),
+        }
+        self
+    }
+
+    ///
+    /// Sets whether Graphviz places the root at the top or at the bottom. This is a shortcut for
+    /// the graph attribute `rankdir`, which is set to `BT` for [Orientation::BottomUp].
+    ///
+    /// ```
+    /// use syntree_layout::{DotDrawer, EmbeddedNode, Orientation};
+    ///
+    /// let embedding = vec![EmbeddedNode { text: "root".to_string(), ..Default::default() }];
+    /// let dot = DotDrawer::new()
+    ///     .with_orientation(Orientation::BottomUp)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(dot.starts_with("digraph tree {\n    rankdir=\"BT\";\n"));
+    /// ```
+    ///
+    pub fn with_orientation(self, orientation: Orientation) -> Self {
+        match orientation {
+            Orientation::TopDown => self.with_graph_attribute("rankdir", "TB"),
+            Orientation::BottomUp => self.with_graph_attribute("rankdir", "BT"),
+        }
+    }
+
+    /// Renders the given embedding into a DOT document and returns it as a `String`.
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        self.build_document(embedding)
+            .map_err(|e| LayouterError::from_description(&e.to_string()))
+    }
+
+    /// Escapes a text for a quoted DOT string.
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    /// Returns the given text as DOT identifier, which is quoted unless it is a plain name.
+    fn id(text: &str) -> Cow<'_, str> {
+        let plain = text.chars().next().is_some_and(|c| !c.is_ascii_digit())
+            && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if plain {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(format!("\"{}\"", Self::escape(text)))
+        }
+    }
+
+    fn build_document(
+        &self,
+        embedding: &[EmbeddedNode],
+    ) -> std::result::Result<String, std::fmt::Error> {
+        let mut doc = String::new();
+        writeln!(doc, "digraph tree {{")?;
+        for (key, value) in &self.graph_attributes {
+            writeln!(doc, "    {}=\"{}\";", Self::id(key), Self::escape(value))?;
+        }
+        writeln!(doc, "    node [shape=box, fontname=\"Courier\"];")?;
+
+        for node in embedding {
+            let mut attributes = vec![format!("label=\"{}\"", Self::escape(&node.text))];
+            let mut styles = Vec::new();
+            match node.style.shape {
+                Some(NodeShape::RoundedRect) => styles.push("rounded"),
+                Some(NodeShape::Ellipse) => attributes.push("shape=ellipse".to_string()),
+                Some(NodeShape::Diamond) => attributes.push("shape=diamond".to_string()),
+                Some(NodeShape::Plain) => attributes.push("shape=plaintext".to_string()),
+                Some(NodeShape::Rect) | None => (),
+            }
+            if node.collapsed > 0 {
+                styles.push("dotted");
+            } else {
+                match node.style.border {
+                    BorderStyle::Solid => (),
+                    BorderStyle::Dashed => styles.push("dashed"),
+                    BorderStyle::Dotted => styles.push("dotted"),
+                }
+            }
+            if node.is_emphasized {
+                attributes.push("fontname=\"Courier-Bold\"".to_string());
+            }
+            if let Some(fill) = &node.style.fill {
+                styles.push("filled");
+                attributes.push(format!("fillcolor=\"{}\"", Self::escape(fill)));
+            }
+            let color = if node.diagnostic.is_error() {
+                Some("red")
+            } else if node.collapsed > 0 {
+                Some("gray")
+            } else {
+                node.style.border_color.as_deref()
+            };
+            if let Some(color) = color {
+                attributes.push(format!("color=\"{}\"", Self::escape(color)));
+            }
+            if let Some(color) = node.style.text_color.as_deref() {
+                attributes.push(format!("fontcolor=\"{}\"", Self::escape(color)));
+            }
+            if !styles.is_empty() {
+                attributes.push(format!("style=\"{}\"", styles.join(",")));
+            }
+            // External labels are placed next to the node without affecting the layout
+            if let Some(badge) = &node.badge {
+                attributes.push(format!("xlabel=\"{}\"", Self::escape(badge)));
+            }
+            writeln!(doc, "    n{} [{}];", node.ord, attributes.join(", "))?;
+        }
+
+        for node in embedding {
+            if let Some(parent) = node.parent {
+                writeln!(doc, "    n{} -> n{};", parent, node.ord)?;
+            }
+        }
+
+        writeln!(doc, "}}")?;
+        Ok(doc)
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `DotDrawer`.
+///
+impl Drawer for DotDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let doc = self.render_string(ctx.embedding())?;
+        fs::write(ctx.file_name(), doc).map_err(LayouterError::from_io_error)
+    }
+}
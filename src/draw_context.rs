@@ -0,0 +1,107 @@
+//! The module with the `DrawContext` passed to drawers.
+
+use std::path::Path;
+
+use crate::{embedding::bounds, EmbeddedNode, SvgDrawerStyle};
+
+///
+/// The `DrawContext` carries everything a [Drawer][crate::Drawer] needs to produce its output:
+/// the output file, the embedding with its precomputed bounds and the optional source text,
+/// title and style given by the user.
+///
+/// The [Layouter][crate::Layouter] creates the context from its configuration, see
+/// [with_title][crate::Layouter::with_title], [with_source][crate::Layouter::with_source] and
+/// [with_drawer_style][crate::Layouter::with_drawer_style]. Drawers are free to ignore the
+/// optional parts.
+///
+/// ```
+/// use std::path::Path;
+/// use syntree_layout::{DrawContext, EmbeddedNode};
+///
+/// let embedding = vec![
+///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+///     EmbeddedNode { text: "leaf".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, y_order: 1, ord: 1, parent: Some(0), ..Default::default() },
+/// ];
+/// let ctx = DrawContext::new(Path::new("tree.svg"), &embedding).with_title("My tree");
+/// assert_eq!((5, 2), ctx.bounds());
+/// assert_eq!(Some("My tree"), ctx.title());
+/// assert_eq!(None, ctx.source());
+/// ```
+///
+#[derive(Debug, Clone, Copy)]
+pub struct DrawContext<'a> {
+    file_name: &'a Path,
+    embedding: &'a [EmbeddedNode],
+    bounds: (usize, usize),
+    source: Option<&'a str>,
+    title: Option<&'a str>,
+    style: Option<&'a SvgDrawerStyle>,
+}
+
+impl<'a> DrawContext<'a> {
+    /// Creates a context for drawing the given embedding into the given file.
+    pub fn new(file_name: &'a Path, embedding: &'a [EmbeddedNode]) -> Self {
+        Self {
+            file_name,
+            embedding,
+            bounds: bounds(embedding),
+            source: None,
+            title: None,
+            style: None,
+        }
+    }
+
+    /// Sets the source text the tree was parsed from.
+    pub fn with_source(self, source: &'a str) -> Self {
+        Self {
+            source: Some(source),
+            ..self
+        }
+    }
+
+    /// Sets the title of the drawing.
+    pub fn with_title(self, title: &'a str) -> Self {
+        Self {
+            title: Some(title),
+            ..self
+        }
+    }
+
+    /// Sets the style the drawing should follow.
+    pub fn with_style(self, style: &'a SvgDrawerStyle) -> Self {
+        Self {
+            style: Some(style),
+            ..self
+        }
+    }
+
+    /// The file the drawer writes to.
+    pub fn file_name(&self) -> &'a Path {
+        self.file_name
+    }
+
+    /// The embedded nodes ordered by their `ord`.
+    pub fn embedding(&self) -> &'a [EmbeddedNode] {
+        self.embedding
+    }
+
+    /// The width of the embedding in logical units and the number of its levels.
+    pub fn bounds(&self) -> (usize, usize) {
+        self.bounds
+    }
+
+    /// The source text the tree was parsed from, if given.
+    pub fn source(&self) -> Option<&'a str> {
+        self.source
+    }
+
+    /// The title of the drawing, if given.
+    pub fn title(&self) -> Option<&'a str> {
+        self.title
+    }
+
+    /// The style the drawing should follow, if given.
+    pub fn style(&self) -> Option<&'a SvgDrawerStyle> {
+        self.style
+    }
+}
@@ -1,10 +1,13 @@
 //! The module with the `Drawer` trait.
-use crate::{EmbeddedNode, Result};
+use crate::{DrawContext, Result};
 
 ///
 /// By implementing this trait anyone can provide his own drawer, for instance one that draws onto
 /// a bitmap, if he don't want to use the `SvgDrawer` used by the crate by default.
 ///
+/// The drawer receives a [DrawContext] with the output file, the embedding and its bounds and
+/// the optional source text, title and style, so it doesn't need to recompute them.
+///
 pub trait Drawer {
-    fn draw(&self, file_name: &std::path::Path, embedding: &[EmbeddedNode]) -> Result<()>;
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()>;
 }
@@ -0,0 +1,124 @@
+//! The module with the registry that selects drawers by file extension.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::{DotDrawer, Drawer, GraphMlDrawer, HtmlDrawer, MermaidDrawer, SvgDrawer, TikzDrawer};
+
+/// A function that creates a drawer
+type DrawerFactory = Box<dyn Fn() -> Box<dyn Drawer>>;
+
+///
+/// The `DrawerRegistry` maps file extensions to drawers. It is consulted by
+/// [Layouter::write_with_registry][crate::Layouter::write_with_registry] to select the output
+/// format by the extension of the file given to
+/// [Layouter::with_file_path][crate::Layouter::with_file_path].
+///
+/// The registry created by [DrawerRegistry::new] knows the drawers of this crate, the one created
+/// by [DrawerRegistry::empty] none. Applications can register their own output formats or replace
+/// the default drawers, e.g. by an [SvgDrawer] with a custom style.
+///
+/// ```
+/// use std::path::Path;
+/// use syntree_layout::{DrawContext, DrawerRegistry, Drawer, Result};
+///
+/// struct TextDrawer;
+///
+/// impl Drawer for TextDrawer {
+///     fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut registry = DrawerRegistry::new();
+/// registry.register("txt", || Box::new(TextDrawer));
+/// assert!(registry.drawer_for(Path::new("tree.TXT")).is_some());
+/// assert!(registry.drawer_for(Path::new("tree.svg")).is_some());
+/// assert!(registry.drawer_for(Path::new("tree.bmp")).is_none());
+///
+/// let mut registry = DrawerRegistry::empty();
+/// registry.register("txt", || Box::new(TextDrawer));
+/// assert_eq!(vec!["txt"], registry.extensions());
+/// ```
+///
+pub struct DrawerRegistry {
+    factories: HashMap<String, DrawerFactory>,
+}
+
+impl DrawerRegistry {
+    ///
+    /// Creates a registry with the drawers of this crate for the extensions `svg`, `html`,
+    /// `dot`, `gv`, `graphml`, `mmd`, `tex` and, with the `pdf` and `serde` features, `pdf` and
+    /// `json`.
+    ///
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("svg", || Box::new(SvgDrawer::new()))
+            .register("html", || Box::new(HtmlDrawer::new()))
+            .register("dot", || Box::new(DotDrawer::new()))
+            .register("gv", || Box::new(DotDrawer::new()))
+            .register("graphml", || Box::new(GraphMlDrawer::new()))
+            .register("mmd", || Box::new(MermaidDrawer::new()))
+            .register("tex", || Box::new(TikzDrawer::new()));
+        #[cfg(feature = "pdf")]
+        registry.register("pdf", || Box::new(crate::PdfDrawer::new()));
+        #[cfg(feature = "serde")]
+        registry.register("json", || Box::new(crate::JsonDrawer::new()));
+        registry
+    }
+
+    /// Creates a registry without any drawers.
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Registers a factory for the drawer used for files with the given extension. The extension
+    /// is given without the leading dot and is matched case-insensitively. A drawer that was
+    /// already registered for the extension is replaced.
+    ///
+    pub fn register(
+        &mut self,
+        extension: &str,
+        factory: impl Fn() -> Box<dyn Drawer> + 'static,
+    ) -> &mut Self {
+        self.factories
+            .insert(extension.to_lowercase(), Box::new(factory));
+        self
+    }
+
+    /// Creates the drawer registered for the extension of the given file, if there is one.
+    pub fn drawer_for(&self, file_name: &Path) -> Option<Box<dyn Drawer>> {
+        let extension = file_name.extension()?.to_str()?.to_lowercase();
+        self.factories.get(&extension).map(|factory| factory())
+    }
+
+    /// Returns the registered extensions in alphabetical order.
+    pub fn extensions(&self) -> Vec<&str> {
+        let mut extensions = self
+            .factories
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        extensions.sort_unstable();
+        extensions
+    }
+}
+
+impl Default for DrawerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for DrawerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrawerRegistry")
+            .field("extensions", &self.extensions())
+            .finish()
+    }
+}
@@ -0,0 +1,20 @@
+//! The module with the styles of the edges between parents and their children.
+
+///
+/// The `EdgeStyle` determines how the edges between parents and their children are drawn in the
+/// layered layout, see [SvgDrawerStyle::edge_style][crate::SvgDrawerStyle::edge_style].
+/// The edges of the radial layout are always curved.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeStyle {
+    /// A straight line from the bottom of the parent to the top of the child
+    #[default]
+    Straight,
+    /// An elbow connector that leaves the parent vertically, runs horizontally halfway between
+    /// both levels and enters the child vertically.
+    /// Wide trees with many siblings read much better this way.
+    Orthogonal,
+    /// A cubic Bézier curve that leaves the parent and enters the child vertically
+    Curved,
+}
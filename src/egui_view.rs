@@ -0,0 +1,262 @@
+//! The module with the tree widget for egui applications.
+
+use std::collections::HashMap;
+
+use egui::{
+    Align2, Color32, CornerRadius, FontId, Id, Rect, Response, Sense, Stroke, StrokeKind, Ui, Vec2,
+    Widget,
+};
+
+use crate::{internal::color::parse_color, EmbeddedNode, SvgDrawer};
+
+///
+/// The `TreeView` is an egui widget that shows an embedding, so that GUI applications like
+/// parser debuggers can display trees interactively.
+///
+/// The nodes are placed like the [SvgDrawer] places them and use its style, see
+/// [TreeView::with_drawer]. The widget fills the available space. Dragging pans the tree, the
+/// scroll wheel pans it too, pinching or scrolling with the zoom modifier (Ctrl or Cmd) zooms it
+/// around the pointer and a double click fits the tree into the widget again. Hovering a node
+/// shows its label, its span and its tooltip.
+///
+/// Labels are always drawn horizontally and node shapes are drawn as rectangles.
+///
+/// This widget is only available with the `egui` feature.
+///
+/// ```
+/// use syntree_layout::{EmbeddedNode, TreeView};
+///
+/// fn tree_panel(ui: &mut egui::Ui, embedding: &[EmbeddedNode]) {
+///     let output = TreeView::new(embedding).show(ui);
+///     if let Some(ord) = output.clicked {
+///         println!("Clicked on {}", embedding[ord].text);
+///     }
+/// }
+/// ```
+///
+pub struct TreeView<'a> {
+    embedding: &'a [EmbeddedNode],
+    drawer: Option<&'a SvgDrawer>,
+    id_salt: Id,
+}
+
+///
+/// The result of showing a [TreeView].
+///
+#[derive(Debug)]
+pub struct TreeViewOutput {
+    /// The response of the widget's area
+    pub response: Response,
+    /// The `ord` of the node under the pointer
+    pub hovered: Option<usize>,
+    /// The `ord` of the node that was clicked in this frame
+    pub clicked: Option<usize>,
+}
+
+/// The pan and zoom of a tree view, kept in egui's memory between frames
+#[derive(Debug, Clone, Copy, Default)]
+struct ViewState {
+    offset: Vec2,
+    zoom: f32,
+    fitted: bool,
+}
+
+impl<'a> TreeView<'a> {
+    /// Creates a tree view of the given embedding.
+    pub fn new(embedding: &'a [EmbeddedNode]) -> Self {
+        Self {
+            embedding,
+            drawer: None,
+            id_salt: Id::new("syntree_layout_tree_view"),
+        }
+    }
+
+    /// Sets the drawer whose style and layout kind place and color the nodes.
+    pub fn with_drawer(self, drawer: &'a SvgDrawer) -> Self {
+        Self {
+            drawer: Some(drawer),
+            ..self
+        }
+    }
+
+    /// Sets the salt of the id under which the pan and zoom are stored, which is needed if more
+    /// than one tree view is shown in the same `Ui`.
+    pub fn with_id_salt(self, id_salt: impl std::hash::Hash) -> Self {
+        Self {
+            id_salt: Id::new(id_salt),
+            ..self
+        }
+    }
+
+    /// Shows the tree view and returns the response together with the hovered and clicked nodes.
+    pub fn show(self, ui: &mut Ui) -> TreeViewOutput {
+        let default_drawer = SvgDrawer::new();
+        let drawer = self.drawer.unwrap_or(&default_drawer);
+        let style = drawer.style();
+        let resolved = drawer.resolve(self.embedding);
+        let tree_size = Vec2::new(resolved.width(), resolved.height());
+
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
+        let id = ui.make_persistent_id(self.id_salt);
+        let mut state = ui.data_mut(|data| data.get_temp::<ViewState>(id).unwrap_or_default());
+        if !state.fitted || response.double_clicked() {
+            state = ViewState::fitted(rect, tree_size);
+        }
+
+        // Pan by dragging and scrolling, zoom around the pointer
+        state.offset += response.drag_delta();
+        if response.hovered() {
+            let (scroll, zoom) = ui.input(|input| (input.smooth_scroll_delta, input.zoom_delta()));
+            state.offset += scroll;
+            if zoom != 1.0 {
+                let pointer = response.hover_pos().unwrap_or(rect.center()) - rect.min;
+                let zoom = (state.zoom * zoom).clamp(0.05, 20.0) / state.zoom;
+                state.offset = pointer - (pointer - state.offset) * zoom;
+                state.zoom *= zoom;
+            }
+        }
+        ui.data_mut(|data| data.insert_temp(id, state));
+
+        let to_screen = |x: f32, y: f32| rect.min + state.offset + Vec2::new(x, y) * state.zoom;
+        let frames = resolved
+            .iter()
+            .map(|node| {
+                Rect::from_min_max(
+                    to_screen(node.x, node.y),
+                    to_screen(node.x + node.width, node.y + node.height),
+                )
+            })
+            .collect::<Vec<_>>();
+        let hovered = response
+            .hover_pos()
+            .and_then(|pos| frames.iter().rposition(|frame| frame.contains(pos)));
+
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        if let Some(background) = style.background.as_deref().and_then(color) {
+            painter.rect_filled(rect, CornerRadius::ZERO, background);
+        }
+        let edge_stroke = Stroke::new(
+            state.zoom.max(0.5),
+            color(&style.edge_color).unwrap_or(visuals.weak_text_color()),
+        );
+        // The frames are stored by the position of the nodes in the embedding
+        let index_of = self
+            .embedding
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.ord, i))
+            .collect::<HashMap<_, _>>();
+        for (node, frame) in self.embedding.iter().zip(&frames) {
+            if let Some(parent_frame) = node
+                .parent
+                .and_then(|parent| index_of.get(&parent))
+                .map(|i| frames[*i])
+            {
+                // Trees drawn bottom up have their parents below the children
+                let points = if parent_frame.center().y <= frame.center().y {
+                    [parent_frame.center_bottom(), frame.center_top()]
+                } else {
+                    [parent_frame.center_top(), frame.center_bottom()]
+                };
+                painter.line_segment(points, edge_stroke);
+            }
+        }
+        for (i, (node, frame)) in self.embedding.iter().zip(&frames).enumerate() {
+            let fill = node
+                .style
+                .fill
+                .as_deref()
+                .or(style.node_fill.as_deref())
+                .and_then(color);
+            let stroke = style
+                .diagnostic_color(node.diagnostic)
+                .or((node.collapsed > 0).then_some(&*style.collapsed_color))
+                .or(node.style.border_color.as_deref())
+                .or(style.node_stroke.as_deref())
+                .and_then(color)
+                .map(|color| Stroke::new(state.zoom.max(0.5), color))
+                .or((hovered == Some(i)).then_some(visuals.widgets.hovered.fg_stroke));
+            let corner_radius = CornerRadius::same((2.0 * state.zoom).min(255.0) as u8);
+            if let Some(fill) = fill {
+                painter.rect_filled(*frame, corner_radius, fill);
+            }
+            if let Some(stroke) = stroke {
+                painter.rect_stroke(*frame, corner_radius, stroke, StrokeKind::Inside);
+            }
+            // Fills are light, thus their texts are drawn in the style's colors also on dark
+            // themes
+            let text_color = node
+                .style
+                .text_color
+                .as_deref()
+                .or(fill.is_some().then_some(if node.is_emphasized {
+                    &*style.emphasized_text_color
+                } else {
+                    &*style.text_color
+                }))
+                .and_then(color)
+                .unwrap_or(if node.is_emphasized {
+                    visuals.strong_text_color()
+                } else {
+                    visuals.text_color()
+                });
+            painter.text(
+                frame.center(),
+                Align2::CENTER_CENTER,
+                &node.text,
+                FontId::monospace(style.font_size * node.scale * state.zoom),
+                text_color,
+            );
+        }
+
+        let clicked = hovered.filter(|_| response.clicked());
+        let response = match hovered.map(|i| &self.embedding[i]) {
+            Some(node) => response.on_hover_ui_at_pointer(|ui| {
+                ui.strong(&node.text);
+                ui.label(format!("{}..{}", node.span.start, node.span.end));
+                if let Some(tooltip) = &node.tooltip {
+                    ui.label(tooltip);
+                }
+            }),
+            None => response,
+        };
+        TreeViewOutput {
+            response,
+            hovered: hovered.map(|i| self.embedding[i].ord),
+            clicked: clicked.map(|i| self.embedding[i].ord),
+        }
+    }
+}
+
+impl Widget for TreeView<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        self.show(ui).response
+    }
+}
+
+impl ViewState {
+    /// Returns the state that centers the tree in the given area, shrunk to fit if necessary.
+    fn fitted(rect: Rect, tree_size: Vec2) -> Self {
+        let zoom = (rect.width() / tree_size.x)
+            .min(rect.height() / tree_size.y)
+            .clamp(0.05, 1.0);
+        let zoom = if zoom.is_finite() { zoom } else { 1.0 };
+        Self {
+            offset: (rect.size() - tree_size * zoom) / 2.0,
+            zoom,
+            fitted: true,
+        }
+    }
+}
+
+/// Converts a color of the style into an egui color.
+fn color(color: &str) -> Option<Color32> {
+    parse_color(color).map(|(r, g, b)| {
+        Color32::from_rgb(
+            (r * 255.0).round() as u8,
+            (g * 255.0).round() as u8,
+            (b * 255.0).round() as u8,
+        )
+    })
+}
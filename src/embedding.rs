@@ -1,39 +1,620 @@
 //! The module with the data structures used in the **Public API**.
 
-use syntree::Flavor;
+use std::collections::HashMap;
+use std::ops::{Deref, Range};
 
-use crate::internal::node::InternalNode;
+use syntree::{Flavor, Pointer};
+
+use crate::{
+    internal::{node::InternalNode, pagination::paginate},
+    span_coverage::span_issues,
+    ColorClass, DiagnosticLevel, LabelOrientation, NodeStyle, ResolvedEmbedding, SpanIssue,
+    TextSegment, TreeStats,
+};
 
 ///
 /// The Embedding is the interface to drawers that need the embedding for the purpose
 /// to transform it to their own output format.
 /// It is accessible at the [Layouter][crate::Layouter::embedding] after calling an embed method
 ///
-pub type Embedding = Vec<EmbeddedNode>;
+/// The embedding dereferences to a slice of [EmbeddedNode]s ordered by their `ord`.
+///
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Embedding(Vec<EmbeddedNode>);
+
+impl Embedding {
+    ///
+    /// Returns all nodes that are marked as errors, i.e. nodes whose
+    /// [DiagnosticLevel::is_error] returns true.
+    ///
+    pub fn errors(&self) -> impl Iterator<Item = &EmbeddedNode> {
+        self.0.iter().filter(|e| e.diagnostic.is_error())
+    }
+
+    /// Returns the node with the given `ord`.
+    pub fn node(&self, ord: usize) -> Option<&EmbeddedNode> {
+        // The nodes are usually stored at the index of their ord
+        self.0
+            .get(ord)
+            .filter(|e| e.ord == ord)
+            .or_else(|| self.0.iter().find(|e| e.ord == ord))
+    }
+
+    ///
+    /// Returns the node that represents the syntax tree node with the given id, e.g. to map a
+    /// node of the tree to its drawing. Use the id's `Pointer::get` to obtain the number.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree::{Builder, Pointer};
+    /// use syntree_layout::{Layouter, Visualize};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// let token = builder.token(MyNodeData(1), 3).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    /// let node = layouter.embedding().node_by_id(token.get()).unwrap();
+    /// assert_eq!("1", node.text);
+    /// assert_eq!(0..3, node.span);
+    /// ```
+    ///
+    pub fn node_by_id(&self, node_id: usize) -> Option<&EmbeddedNode> {
+        // Aggregate nodes share the id of their first aggregated node
+        self.0
+            .iter()
+            .find(|e| e.node_id == node_id && e.aggregated == 0)
+            .or_else(|| self.0.iter().find(|e| e.node_id == node_id))
+    }
+
+    /// Returns the root node, i.e. the node without a parent.
+    pub fn root(&self) -> Option<&EmbeddedNode> {
+        self.0.iter().find(|e| e.parent.is_none())
+    }
+
+    /// Returns the parent of the node with the given `ord`.
+    pub fn parent(&self, ord: usize) -> Option<&EmbeddedNode> {
+        self.node(ord)
+            .and_then(|e| e.parent)
+            .and_then(|parent| self.node(parent))
+    }
+
+    ///
+    /// Returns the children of the node with the given `ord` from left to right.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, Embedding};
+    ///
+    /// let embedding = Embedding::from(vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "left".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    ///     EmbeddedNode { text: "right".to_string(), ord: 2, parent: Some(0), ..Default::default() },
+    /// ]);
+    /// let root = embedding.root().unwrap();
+    /// let children = embedding.children(root.ord).map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["left", "right"], children);
+    /// assert_eq!("root", embedding.parent(2).unwrap().text);
+    /// assert_eq!(0, embedding.children(2).count());
+    /// ```
+    ///
+    pub fn children(&self, ord: usize) -> impl Iterator<Item = &EmbeddedNode> {
+        self.0.iter().filter(move |e| e.parent == Some(ord))
+    }
+
+    ///
+    /// Visits the nodes depth first in pre-order, i.e. each node before its children and the
+    /// children from left to right. The visitor receives each node together with the indices of
+    /// its children in this embedding, which spares drawers deriving the structure of the tree
+    /// from the `parent` fields. Embeddings with several roots are visited from left to right.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, Embedding};
+    ///
+    /// let embedding = Embedding::from(vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, x_center: 2, ..Default::default() },
+    ///     EmbeddedNode { text: "left".to_string(), ord: 1, parent: Some(0), x_center: 1, ..Default::default() },
+    ///     EmbeddedNode { text: "right".to_string(), ord: 2, parent: Some(0), x_center: 3, ..Default::default() },
+    /// ]);
+    /// let mut lines = Vec::new();
+    /// embedding.visit(|node, children| {
+    ///     let children = children.iter().map(|i| embedding[*i].text.as_str()).collect::<Vec<_>>();
+    ///     lines.push(format!("{} -> {:?}", node.text, children));
+    /// });
+    /// assert_eq!(vec![r#"root -> ["left", "right"]"#, "left -> []", "right -> []"], lines);
+    /// ```
+    ///
+    pub fn visit(&self, mut visitor: impl FnMut(&EmbeddedNode, &[usize])) {
+        let structure = Structure::of(&self.0);
+        for i in structure.pre_order() {
+            visitor(&self.0[i], &structure.children[i]);
+        }
+    }
+
+    ///
+    /// Returns the nodes depth first in pre-order, i.e. each node before its children and the
+    /// children from left to right.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter};
+    ///
+    /// let tree = from_text("(a (b c d) e)").unwrap();
+    /// let layouter = Layouter::new(&tree).embed().unwrap();
+    /// let texts = layouter.embedding().pre_order().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["a", "b", "c", "d", "e"], texts);
+    /// ```
+    ///
+    pub fn pre_order(&self) -> impl Iterator<Item = &EmbeddedNode> {
+        Structure::of(&self.0)
+            .pre_order()
+            .into_iter()
+            .map(|i| &self.0[i])
+    }
+
+    ///
+    /// Returns the nodes depth first in post-order, i.e. each node after its children and the
+    /// children from left to right, e.g. to compute values of subtrees bottom up.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter};
+    ///
+    /// let tree = from_text("(a (b c d) e)").unwrap();
+    /// let layouter = Layouter::new(&tree).embed().unwrap();
+    /// let texts = layouter.embedding().post_order().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["c", "d", "b", "e", "a"], texts);
+    /// ```
+    ///
+    pub fn post_order(&self) -> impl Iterator<Item = &EmbeddedNode> {
+        Structure::of(&self.0)
+            .post_order()
+            .into_iter()
+            .map(|i| &self.0[i])
+    }
+
+    ///
+    /// Splits a tree that is wider than `max_width` logical units into pages, e.g. because
+    /// browsers fail to show very wide images. Each page shows neighboring sibling subtrees that
+    /// fit into the width below the chain of their ancestors, which is repeated on every page.
+    /// Subtrees that are too wide on their own are split among their children in the same way.
+    /// The widths include the margins, the nodes of each page are numbered from 0.
+    ///
+    /// Pages can still be wider than `max_width` if a single label or a leaf is wider.
+    /// A tree that fits is returned as the only page.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter};
+    ///
+    /// let tree = from_text("(Module (Fn alpha beta) (Fn gamma delta) (Fn epsilon zeta))").unwrap();
+    /// let layouter = Layouter::new(&tree).embed().unwrap();
+    /// assert!(layouter.stats().width > 20);
+    ///
+    /// let pages = layouter.embedding().paginate(20);
+    /// assert_eq!(3, pages.len());
+    /// for page in &pages {
+    ///     assert_eq!("Module", page[0].text);
+    ///     assert!(page.stats().width <= 20);
+    /// }
+    /// assert_eq!(1, layouter.embedding().paginate(1000).len());
+    /// ```
+    ///
+    pub fn paginate(&self, max_width: usize) -> Vec<Embedding> {
+        paginate(&self.0, max_width)
+            .into_iter()
+            .map(Embedding)
+            .collect()
+    }
+
+    /// Returns the statistics of the tree like its number of nodes and its height.
+    pub fn stats(&self) -> TreeStats {
+        TreeStats::of(&self.0)
+    }
+
+    ///
+    /// Checks that the spans of the leaves cover the given source without gaps and overlaps.
+    /// Gaps that contain only whitespace are accepted, because many tokenizers skip it, and the
+    /// pseudonodes of gaps are ignored. The issues are ordered by their position in the source.
+    ///
+    /// ```
+    /// use syntree_layout::{Layouter, SpanIssue};
+    /// use syntree::Builder;
+    ///
+    /// // The tokenizer dropped the `+`
+    /// let source = "1 + 2";
+    /// let mut builder = Builder::new();
+    /// builder.open("Expr").unwrap();
+    /// builder.token("Num", 1).unwrap();
+    /// builder.token("Ws", 1).unwrap();
+    /// builder.set_cursor(3);
+    /// builder.token("Ws", 1).unwrap();
+    /// builder.token("Num", 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree).embed_with_source(source).unwrap();
+    /// assert_eq!(
+    ///     vec![SpanIssue::Gap { span: 2..3 }],
+    ///     layouter.embedding().span_issues(source)
+    /// );
+    /// ```
+    ///
+    pub fn span_issues(&self, source: &str) -> Vec<SpanIssue> {
+        span_issues(
+            self.leaves()
+                .filter(|node| !node.is_gap)
+                .map(|node| (node.ord, node.span.clone())),
+            source,
+        )
+    }
+
+    /// Returns the leaves of the tree, i.e. the nodes without children, from left to right.
+    pub fn leaves(&self) -> impl Iterator<Item = &EmbeddedNode> {
+        let mut has_children = vec![false; self.0.iter().map(|e| e.ord + 1).max().unwrap_or(0)];
+        for parent in self.0.iter().filter_map(|e| e.parent) {
+            if let Some(flag) = has_children.get_mut(parent) {
+                *flag = true;
+            }
+        }
+        self.0.iter().filter(move |e| !has_children[e.ord])
+    }
+
+    ///
+    /// Returns the node whose box contains the given point, e.g. to find the node that was
+    /// clicked in a GUI. The coordinates are pixels in the geometry that the drawer resolved for
+    /// this embedding, see [SvgDrawer::resolve][crate::SvgDrawer::resolve]. If boxes overlap the
+    /// node drawn last, i.e. the one on top, is returned.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree::Builder;
+    /// use syntree_layout::{Layouter, SvgDrawer, Visualize};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 1).unwrap();
+    /// builder.token(MyNodeData(2), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    /// let embedding = layouter.embedding();
+    /// let geometry = SvgDrawer::new().resolve(embedding);
+    /// let (x, y) = geometry[2].center();
+    /// assert_eq!("2", embedding.node_at(x, y, &geometry).unwrap().text);
+    /// assert!(embedding.node_at(0.0, 0.0, &geometry).is_none());
+    /// ```
+    ///
+    pub fn node_at(&self, x: f32, y: f32, geometry: &ResolvedEmbedding) -> Option<&EmbeddedNode> {
+        geometry
+            .iter()
+            .rev()
+            .find(|node| node.contains(x, y))
+            .and_then(|node| self.node(node.ord))
+    }
+
+    ///
+    /// Returns the nodes whose boxes overlap the given rectangle in the order of their `ord`,
+    /// e.g. to select the nodes inside of a rubber band. The coordinates are pixels in the
+    /// geometry that the drawer resolved for this embedding, see [Embedding::node_at].
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, Embedding, SvgDrawer};
+    ///
+    /// let embedding = Embedding::from(vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 4, x_extent: 5, x_extent_children: 9, ..Default::default() },
+    ///     EmbeddedNode { text: "l".to_string(), ord: 1, parent: Some(0), x_center: 2, x_extent: 2, x_extent_children: 2, y_order: 1, ..Default::default() },
+    ///     EmbeddedNode { text: "r".to_string(), ord: 2, parent: Some(0), x_center: 6, x_extent: 2, x_extent_children: 2, y_order: 1, ..Default::default() },
+    /// ]);
+    /// let geometry = SvgDrawer::new().resolve(&embedding);
+    /// let bottom = geometry[1].y;
+    /// let leaves = embedding
+    ///     .nodes_in_rect(0.0, bottom, geometry.width(), geometry.height() - bottom, &geometry)
+    ///     .map(|e| e.text.as_str())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(vec!["l", "r"], leaves);
+    /// ```
+    ///
+    pub fn nodes_in_rect<'a>(
+        &'a self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        geometry: &'a ResolvedEmbedding,
+    ) -> impl Iterator<Item = &'a EmbeddedNode> {
+        geometry
+            .iter()
+            .filter(move |node| node.intersects(x, y, width, height))
+            .filter_map(|node| self.node(node.ord))
+    }
+
+    /// Consumes the embedding and returns the vector of embedded nodes.
+    pub fn into_inner(self) -> Vec<EmbeddedNode> {
+        self.0
+    }
+
+    /// Mutable access to the nodes for post-processing inside of this crate.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut EmbeddedNode> {
+        self.0.iter_mut()
+    }
+}
+
+///
+/// The structure of the tree resolved from the `parent` fields of the nodes. The nodes are
+/// referenced by their indices in the embedding.
+///
+struct Structure {
+    /// The roots from left to right
+    roots: Vec<usize>,
+    /// The children of each node from left to right
+    children: Vec<Vec<usize>>,
+}
+
+impl Structure {
+    fn of(nodes: &[EmbeddedNode]) -> Self {
+        let index_of = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.ord, i))
+            .collect::<HashMap<_, _>>();
+        let mut roots = Vec::new();
+        let mut children = vec![Vec::new(); nodes.len()];
+        for (i, e) in nodes.iter().enumerate() {
+            match e.parent.and_then(|parent| index_of.get(&parent)) {
+                Some(parent) => children[*parent].push(i),
+                None => roots.push(i),
+            }
+        }
+        roots.sort_by_key(|i| nodes[*i].x_center);
+        for siblings in children.iter_mut() {
+            siblings.sort_by_key(|i| nodes[*i].x_center);
+        }
+        Self { roots, children }
+    }
+
+    /// Returns the indices of the nodes in pre-order.
+    fn pre_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.children.len());
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            order.push(i);
+            stack.extend(self.children[i].iter().rev());
+        }
+        order
+    }
+
+    /// Returns the indices of the nodes in post-order.
+    fn post_order(&self) -> Vec<usize> {
+        // The reversed pre-order of the mirrored tree
+        let mut order = Vec::with_capacity(self.children.len());
+        let mut stack = self.roots.clone();
+        while let Some(i) = stack.pop() {
+            order.push(i);
+            stack.extend(self.children[i].iter());
+        }
+        order.reverse();
+        order
+    }
+}
+
+///
+/// Returns the width of the embedding in logical units, i.e. the right edge of the rightmost
+/// subtree. Embeddings composed of several trees side by side have several roots.
+///
+pub(crate) fn tree_width(embedding: &[EmbeddedNode]) -> usize {
+    embedding
+        .iter()
+        .map(|e| {
+            if e.parent.is_none() {
+                let left = e.x_center.saturating_sub(e.x_extent_children / 2);
+                left + e.x_extent_children
+            } else {
+                e.x_extent_children
+            }
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+///
+/// Returns the width of the embedding in logical units and the number of its levels. The levels
+/// are the distinct `y_order`s, which are spread by the [level gap][crate::Spacing::level_gap].
+///
+pub(crate) fn bounds(embedding: &[EmbeddedNode]) -> (usize, usize) {
+    let mut y_orders = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+    y_orders.sort_unstable();
+    y_orders.dedup();
+    (tree_width(embedding), y_orders.len())
+}
+
+impl Deref for Embedding {
+    type Target = [EmbeddedNode];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<EmbeddedNode>> for Embedding {
+    fn from(nodes: Vec<EmbeddedNode>) -> Self {
+        Self(nodes)
+    }
+}
+
+impl FromIterator<EmbeddedNode> for Embedding {
+    fn from_iter<I: IntoIterator<Item = EmbeddedNode>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Embedding {
+    type Item = EmbeddedNode;
+    type IntoIter = std::vec::IntoIter<EmbeddedNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Embedding {
+    type Item = &'a EmbeddedNode;
+    type IntoIter = std::slice::Iter<'a, EmbeddedNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
 
 ///
 /// The [EmbeddedNode] is the embedding information for one single tree node.
 /// It is used only in a collection type `Embedding`.
 ///
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmbeddedNode {
-    /// The nodes level, root has level 0. Can be used to calculate an y coordinate for the node
+    /// The nodes level, root has level 0. Can be used to calculate an y coordinate for the node.
+    /// The levels are multiplied by the [Spacing::level_gap][crate::Spacing::level_gap].
     pub y_order: usize,
     /// The logical x coordinate of the node's center
     pub x_center: usize,
     /// The x-extent of the nodes text representation in logical coordinate units
     pub x_extent: usize,
-    /// The maximum extent over the nodes text representation and the sum of all children's x-extent
+    /// The maximum extent over the nodes text representation and the sum of all children's
+    /// x-extent including the gaps between them. The root's extent includes the margins.
     pub x_extent_children: usize,
     /// The text representation of the nodes data - created e.g. by the `Visualize` trait's
     /// implementation, by the node type's Display or Debug implementation or by custom methods
     pub text: String,
+    /// The styled segments of a rich text label obtained from the `Visualize` trait, their
+    /// concatenation equals `text`. Empty for plain labels.
+    pub segments: Vec<TextSegment>,
     /// The *emphasize* property obtained from the `Visualize` trait or via a custom method
     pub is_emphasized: bool,
+    /// The *diagnostic* level obtained from the `Visualize` trait
+    pub diagnostic: DiagnosticLevel,
+    /// The visual style obtained from the `Visualize` trait
+    pub style: NodeStyle,
+    /// The orientation of the node's label obtained from the `Visualize` trait
+    pub orientation: LabelOrientation,
+    /// The number of nodes represented by this node if it is a collapsed stub, i.e. the node
+    /// itself and all of its descendants, otherwise 0
+    pub collapsed: usize,
+    /// The number of sibling subtrees represented by this node if it is an aggregate node,
+    /// otherwise 0. The subtrees are the children of the aggregate node if the aggregation is
+    /// expandable.
+    pub aggregated: usize,
     /// The parent's `ord`, if there is one
     pub parent: Option<usize>,
+    /// The number of the node's children in the embedding. Tokens, collapsed stubs and nodes
+    /// whose children were all filtered out have none, see [EmbeddedNode::is_leaf].
+    pub child_count: usize,
     /// A unique number reflecting the topological post-ordering of the nodes in the tree
     pub ord: usize,
+    /// The id of the node in the syntax tree, i.e. the value of the node's `Pointer`.
+    /// Merged chains carry the id of their last node, aggregate nodes the id of their first
+    /// aggregated node.
+    pub node_id: usize,
+    /// The span of the node in the source text
+    pub span: Range<usize>,
+    /// The tooltip text obtained from the `Visualize` trait
+    pub tooltip: Option<String>,
+    /// A short annotation like an inferred type or a count, obtained from the function given to
+    /// [Layouter::with_badges][crate::Layouter::with_badges]. Drawers show it next to the label.
+    pub badge: Option<String>,
+    /// The node is a pseudonode for a range of the source that no leaf covers, see
+    /// [Layouter::with_span_gaps][crate::Layouter::with_span_gaps]
+    pub is_gap: bool,
+    /// The node is a trivia token like whitespace or a comment that is drawn as a narrow marker,
+    /// see [Layouter::with_trivia][crate::Layouter::with_trivia]
+    pub is_trivia: bool,
+    /// The label of the group formed by the node's subtree obtained from the `Visualize` trait
+    pub group: Option<String>,
+    /// The color class obtained from the function given to
+    /// [Layouter::with_color_by][crate::Layouter::with_color_by]
+    pub color_class: ColorClass,
+    /// The factor by which the node is drawn smaller than normal nodes, 1.0 for full size
+    pub scale: f32,
+}
+
+impl Default for EmbeddedNode {
+    fn default() -> Self {
+        Self {
+            y_order: Default::default(),
+            x_center: Default::default(),
+            x_extent: Default::default(),
+            x_extent_children: Default::default(),
+            text: Default::default(),
+            segments: Default::default(),
+            is_emphasized: Default::default(),
+            diagnostic: Default::default(),
+            style: Default::default(),
+            orientation: Default::default(),
+            collapsed: Default::default(),
+            aggregated: Default::default(),
+            parent: Default::default(),
+            child_count: Default::default(),
+            ord: Default::default(),
+            node_id: Default::default(),
+            span: Default::default(),
+            tooltip: Default::default(),
+            badge: Default::default(),
+            is_gap: Default::default(),
+            is_trivia: Default::default(),
+            group: Default::default(),
+            color_class: Default::default(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl EmbeddedNode {
+    ///
+    /// Returns true if the node has no children in the embedding, e.g. to style tokens
+    /// differently from inner nodes.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter};
+    ///
+    /// let tree = from_text("(Expr 1 + 2)").unwrap();
+    /// let layouter = Layouter::new(&tree).embed().unwrap();
+    /// let embedding = layouter.embedding();
+    /// assert!(!embedding[0].is_leaf());
+    /// assert_eq!(3, embedding[0].child_count);
+    /// assert!(embedding[1..].iter().all(|node| node.is_leaf()));
+    /// ```
+    ///
+    pub fn is_leaf(&self) -> bool {
+        self.child_count == 0
+    }
+}
+
+/// Sets the [child_count][EmbeddedNode::child_count] of all nodes from their `parent`s.
+pub(crate) fn count_children(nodes: &mut [EmbeddedNode]) {
+    let mut counts = vec![0; nodes.iter().map(|node| node.ord + 1).max().unwrap_or(0)];
+    for parent in nodes.iter().filter_map(|node| node.parent) {
+        if let Some(count) = counts.get_mut(parent) {
+            *count += 1;
+        }
+    }
+    for node in nodes {
+        node.child_count = counts[node.ord];
+    }
 }
 
 ///
@@ -47,9 +628,25 @@ impl<F: Flavor> From<InternalNode<F>> for EmbeddedNode {
             x_extent: e.x_extent,
             x_extent_children: e.x_extent_children,
             text: e.text,
+            segments: e.segments,
             is_emphasized: e.is_emphasized,
+            diagnostic: e.diagnostic,
+            style: e.style,
+            orientation: e.orientation,
+            collapsed: e.collapsed,
+            aggregated: e.aggregated,
             parent: e.parent,
+            child_count: 0,
             ord: e.ord,
+            node_id: e.node_id.get(),
+            span: e.span,
+            tooltip: e.tooltip,
+            badge: e.badge,
+            is_gap: e.is_gap,
+            is_trivia: e.is_trivia,
+            group: e.group,
+            color_class: e.color_class,
+            scale: e.scale,
         }
     }
 }
@@ -0,0 +1,133 @@
+//! The module with the builder for embeddings created outside of this crate.
+
+use crate::{
+    embedding::count_children, EmbeddedNode, Embedding, LabelOrientation, LayouterError, Result,
+};
+
+///
+/// The `EmbeddingBuilder` is the sanctioned way to create an [Embedding] with an alternative
+/// layout algorithm outside of this crate, e.g. to feed the crate's drawers.
+///
+/// The builder maintains the invariants the drawers rely on:
+/// * Nodes are added in depth first order, i.e. parents are added before their children, and get
+///   consecutive `ord`s starting with 0.
+/// * There is exactly one root.
+/// * `y_order` is the depth of a node and `x_extent` is derived from the node's text, label
+///   orientation and scale.
+/// * `x_extent_children` is calculated from the children when the embedding is built.
+///
+/// Only the `x_center` of each node is left to the layout algorithm.
+///
+/// ```
+/// use syntree_layout::EmbeddingBuilder;
+///
+/// let mut builder = EmbeddingBuilder::new();
+/// let root = builder.add_node(None, "root").unwrap();
+/// let left = builder.add_node(Some(root), "l").unwrap();
+/// let right = builder.add_node(Some(root), "r").unwrap();
+/// builder.set_x_center(root, 2).unwrap();
+/// builder.set_x_center(left, 1).unwrap();
+/// builder.set_x_center(right, 3).unwrap();
+/// builder.node_mut(right).unwrap().is_emphasized = true;
+///
+/// let embedding = builder.build().unwrap();
+/// assert_eq!(3, embedding.len());
+/// assert_eq!(1, embedding[2].y_order);
+/// assert_eq!(5, embedding[0].x_extent_children);
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct EmbeddingBuilder {
+    nodes: Vec<EmbeddedNode>,
+}
+
+impl EmbeddingBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds a node with the given text as child of the given parent and returns the new node's
+    /// `ord`. The first node added must be the root, i.e. have no parent, all further nodes must
+    /// have a parent that was already added.
+    ///
+    pub fn add_node(&mut self, parent: Option<usize>, text: impl Into<String>) -> Result<usize> {
+        let ord = self.nodes.len();
+        let y_order = match parent {
+            None if ord == 0 => 0,
+            None => return Err(LayouterError::MultipleRoots { count: 2 }),
+            Some(parent) => {
+                self.nodes
+                    .get(parent)
+                    .ok_or(LayouterError::NodeLookupFailed { ord: parent })?
+                    .y_order
+                    + 1
+            }
+        };
+        let text = text.into();
+        let x_extent = LabelOrientation::Horizontal.x_extent(&text, 1.0);
+        self.nodes.push(EmbeddedNode {
+            y_order,
+            x_extent,
+            x_extent_children: x_extent,
+            text,
+            parent,
+            ord,
+            ..Default::default()
+        });
+        Ok(ord)
+    }
+
+    /// Sets the logical x coordinate of the center of the node with the given `ord`.
+    pub fn set_x_center(&mut self, ord: usize, x_center: usize) -> Result<()> {
+        self.nodes
+            .get_mut(ord)
+            .ok_or(LayouterError::NodeLookupFailed { ord })?
+            .x_center = x_center;
+        Ok(())
+    }
+
+    ///
+    /// Provides mutable access to the node with the given `ord` to set further properties like
+    /// the emphasis or the style.
+    /// The fields `ord`, `y_order`, `child_count` and `x_extent` are recalculated by
+    /// [EmbeddingBuilder::build], the latter from the node's text, label orientation and scale.
+    ///
+    pub fn node_mut(&mut self, ord: usize) -> Option<&mut EmbeddedNode> {
+        self.nodes.get_mut(ord)
+    }
+
+    /// Finishes the embedding and calculates the `child_count` and the `x_extent_children` of all
+    /// nodes.
+    pub fn build(mut self) -> Result<Embedding> {
+        // Restore the structural fields in case they were modified via `node_mut`
+        let mut y_orders = Vec::with_capacity(self.nodes.len());
+        let roots = self.nodes.iter().filter(|n| n.parent.is_none()).count();
+        for (ord, node) in self.nodes.iter_mut().enumerate() {
+            node.ord = ord;
+            let y_order = match node.parent {
+                Some(parent) if parent < ord => y_orders[parent] + 1,
+                Some(parent) => return Err(LayouterError::NodeLookupFailed { ord: parent }),
+                None if ord == 0 => 0,
+                None => return Err(LayouterError::MultipleRoots { count: roots }),
+            };
+            node.y_order = y_order;
+            node.x_extent = node.orientation.x_extent(&node.text, node.scale);
+            y_orders.push(y_order);
+        }
+
+        // Children have greater ords than their parents, so a reverse pass visits all children
+        // before their parent
+        let mut extent_of_children = vec![0; self.nodes.len()];
+        for ord in (0..self.nodes.len()).rev() {
+            let node = &mut self.nodes[ord];
+            node.x_extent_children = std::cmp::max(node.x_extent, extent_of_children[ord]);
+            if let Some(parent) = node.parent {
+                extent_of_children[parent] += node.x_extent_children;
+            }
+        }
+        count_children(&mut self.nodes);
+        Ok(self.nodes.into())
+    }
+}
@@ -1,4 +1,7 @@
 //! The module with the error and result types of this crate
+use std::ops::Range;
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 ///
@@ -12,6 +15,89 @@ pub enum LayouterError {
     TreeError { source: syntree::Error },
     #[error("Error occurred: {msg}")]
     OtherError { msg: String },
+    #[error("Error at node {ord} with span {span:?}: {msg}")]
+    NodeError {
+        ord: usize,
+        span: Range<usize>,
+        msg: String,
+    },
+    #[error("Currently we support only one root, found {count}")]
+    MultipleRoots { count: usize },
+    #[error("No output file name given - use Layouter::with_file_path.")]
+    MissingFilePath,
+    #[error("No node with ord {ord}")]
+    NodeLookupFailed { ord: usize },
+    #[error("The span {span:?} is out of the bounds of the source or not on a char boundary")]
+    SourceSliceOutOfBounds { span: Range<usize> },
+    #[error("The output file {path:?} already exists")]
+    OutputFileExists { path: PathBuf },
+    #[error("The root {node_id} of the subtree doesn't exist in the tree")]
+    SubtreeRootNotFound { node_id: usize },
+    #[error("Malformed SVG document: {msg}")]
+    MalformedSvg { msg: String },
+    #[error("The SVG documents differ in {count} places:\n{}", differences.join("\n"))]
+    SvgMismatch {
+        count: usize,
+        differences: Vec<String>,
+    },
+    #[error("Compact layouts can't be updated incrementally, embed the tree again")]
+    IncrementalUpdateUnsupported,
+    #[error("No drawer registered for the extension {extension:?}")]
+    NoDrawerForExtension { extension: String },
+    #[error("The output path {path:?} has no file name")]
+    MissingFileName { path: PathBuf },
+}
+
+///
+/// The kind of a [LayouterError], which lets callers react on errors without matching the
+/// fields of the variants, e.g. insert a virtual root on [ErrorKind::MultipleRoots].
+///
+/// ```
+/// use syntree_layout::{ErrorKind, Layouter, Visualize};
+/// use syntree::Builder;
+///
+/// let mut builder = Builder::new();
+/// builder.token(1u8, 1).unwrap();
+/// builder.token(2u8, 1).unwrap();
+/// let tree = builder.build().unwrap();
+///
+/// let err = Layouter::new(&tree).embed_with_debug().err().unwrap();
+/// assert_eq!(ErrorKind::MultipleRoots, err.kind());
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Reading or writing a file failed
+    Io,
+    /// The tree implementation reported an error
+    Tree,
+    /// The tree has more than one root
+    MultipleRoots,
+    /// The layouter has no output file
+    MissingFilePath,
+    /// A node that was referred to doesn't exist
+    NodeLookupFailed,
+    /// The span of a node doesn't fit into the source
+    SourceSliceOutOfBounds,
+    /// Creating the label of a node failed
+    Node,
+    /// The output file exists and must not be overwritten
+    OutputFileExists,
+    /// The root of the subtree to embed doesn't exist in the tree
+    SubtreeRootNotFound,
+    /// An SVG document couldn't be parsed
+    MalformedSvg,
+    /// Two SVG documents that were compared differ
+    SvgMismatch,
+    /// The layout can't be updated incrementally, the tree has to be embedded again
+    IncrementalUpdateUnsupported,
+    /// No drawer is registered for the extension of the output file
+    NoDrawerForExtension,
+    /// The output path has no file name, e.g. it ends with `..`
+    MissingFileName,
+    /// Any other error
+    Other,
 }
 
 impl LayouterError {
@@ -23,6 +109,38 @@ impl LayouterError {
     pub fn from_io_error(io_error: std::io::Error) -> Self {
         LayouterError::IoError { source: io_error }
     }
+    ///
+    /// Adds the context of the node at which the error occurred, i.e. the node's `ord` and span.
+    /// Errors that already carry the span of the node are returned unchanged.
+    ///
+    pub fn at_node(self, ord: usize, span: Range<usize>) -> Self {
+        let msg = match self {
+            LayouterError::OtherError { msg } | LayouterError::NodeError { msg, .. } => msg,
+            LayouterError::SourceSliceOutOfBounds { .. } => return self,
+            other => other.to_string(),
+        };
+        LayouterError::NodeError { ord, span, msg }
+    }
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            LayouterError::IoError { .. } => ErrorKind::Io,
+            LayouterError::TreeError { .. } => ErrorKind::Tree,
+            LayouterError::OtherError { .. } => ErrorKind::Other,
+            LayouterError::NodeError { .. } => ErrorKind::Node,
+            LayouterError::MultipleRoots { .. } => ErrorKind::MultipleRoots,
+            LayouterError::MissingFilePath => ErrorKind::MissingFilePath,
+            LayouterError::NodeLookupFailed { .. } => ErrorKind::NodeLookupFailed,
+            LayouterError::SourceSliceOutOfBounds { .. } => ErrorKind::SourceSliceOutOfBounds,
+            LayouterError::OutputFileExists { .. } => ErrorKind::OutputFileExists,
+            LayouterError::SubtreeRootNotFound { .. } => ErrorKind::SubtreeRootNotFound,
+            LayouterError::MalformedSvg { .. } => ErrorKind::MalformedSvg,
+            LayouterError::SvgMismatch { .. } => ErrorKind::SvgMismatch,
+            LayouterError::IncrementalUpdateUnsupported => ErrorKind::IncrementalUpdateUnsupported,
+            LayouterError::NoDrawerForExtension { .. } => ErrorKind::NoDrawerForExtension,
+            LayouterError::MissingFileName { .. } => ErrorKind::MissingFileName,
+        }
+    }
 }
 
 ///
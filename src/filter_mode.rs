@@ -0,0 +1,15 @@
+//! The module with the modes of the subtree filtering.
+
+///
+/// The `FilterMode` determines what happens to the descendants of the nodes that are dropped by
+/// the filter given to [Layouter::with_filter][crate::Layouter::with_filter].
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FilterMode {
+    /// The whole subtree of a dropped node is dropped
+    #[default]
+    DropSubtree,
+    /// The children of a dropped node are spliced into the dropped node's parent, i.e. they take
+    /// the place of the dropped node
+    Splice,
+}
@@ -0,0 +1,231 @@
+//! The module with the types to embed fonts into generated documents.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::LabelOrientation;
+#[cfg(feature = "font-metrics")]
+use crate::{LayouterError, Result};
+
+///
+/// The format of an [EmbeddedFont]'s data.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontFormat {
+    /// Web Open Font Format
+    Woff,
+    /// Web Open Font Format 2
+    Woff2,
+    /// TrueType
+    TrueType,
+    /// OpenType
+    OpenType,
+}
+
+impl FontFormat {
+    /// The MIME type of the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            FontFormat::Woff => "font/woff",
+            FontFormat::Woff2 => "font/woff2",
+            FontFormat::TrueType => "font/ttf",
+            FontFormat::OpenType => "font/otf",
+        }
+    }
+
+    /// The format name used in CSS `@font-face` rules
+    pub fn css_format(&self) -> &'static str {
+        match self {
+            FontFormat::Woff => "woff",
+            FontFormat::Woff2 => "woff2",
+            FontFormat::TrueType => "truetype",
+            FontFormat::OpenType => "opentype",
+        }
+    }
+}
+
+///
+/// A font that is embedded into the generated SVG as a base64 encoded `@font-face` rule, so that
+/// the labels look identical on machines without the font installed.
+///
+/// The crate embeds the data as it is. To keep the output small you should provide a font that is
+/// subsetted to the characters used in your trees, e.g. with tools like `pyftsubset`.
+///
+/// ```
+/// use syntree_layout::{EmbeddedFont, FontFormat, SvgDrawerStyle};
+///
+/// // Normally the data would come from `include_bytes!("my_font.woff2")`
+/// let font = EmbeddedFont::new("MyMono", FontFormat::Woff2, vec![0x77, 0x4f, 0x46, 0x32]);
+/// let style = SvgDrawerStyle {
+///     embedded_font: Some(font),
+///     ..SvgDrawerStyle::new()
+/// };
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmbeddedFont {
+    /// The font family name under which the font is registered in the document
+    pub family: Cow<'static, str>,
+    /// The format of the font data
+    pub format: FontFormat,
+    /// The raw font data
+    pub data: Cow<'static, [u8]>,
+}
+
+impl EmbeddedFont {
+    /// Creates a new font to embed.
+    pub fn new(
+        family: impl Into<Cow<'static, str>>,
+        format: FontFormat,
+        data: impl Into<Cow<'static, [u8]>>,
+    ) -> Self {
+        Self {
+            family: family.into(),
+            format,
+            data: data.into(),
+        }
+    }
+
+    /// Returns the CSS `@font-face` rule that registers this font.
+    pub fn font_face_rule(&self) -> String {
+        format!(
+            "@font-face {{ font-family: '{}'; src: url(data:{};base64,{}) format('{}'); }}",
+            self.family,
+            self.format.mime_type(),
+            crate::internal::base64::encode(&self.data),
+            self.format.css_format()
+        )
+    }
+}
+
+///
+/// The metrics of the font the labels are rendered with. Give the same metrics to the
+/// [Layouter][crate::Layouter::with_font_metrics] and the
+/// [SvgDrawer][crate::SvgDrawer::with_font], so that the extents of the nodes in the layout match
+/// the width of the rendered glyphs.
+///
+/// The monospace metrics estimate each character from its width in terminal columns. With the
+/// `font-metrics` feature the real advances can be read from a TrueType or OpenType font.
+///
+/// ```
+/// use syntree_layout::FontMetrics;
+///
+/// let metrics = FontMetrics::monospace(16.0, 0.5);
+/// assert_eq!(8.0, metrics.unit_width());
+/// assert_eq!(24.0, metrics.text_width("abc"));
+/// assert_eq!(3, metrics.extent("abc"));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontMetrics {
+    font_size: f32,
+    /// The advance of an average character in em
+    average_advance: f32,
+    /// The advances of the characters found in a font in em
+    advances: HashMap<char, f32>,
+}
+
+impl FontMetrics {
+    /// Creates metrics of a monospaced font with the given size in pixels, where a character
+    /// advances by the given factor of the font size.
+    pub fn monospace(font_size: f32, width_factor: f32) -> Self {
+        Self {
+            font_size,
+            average_advance: width_factor,
+            advances: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Reads the horizontal advances of the characters from the given TrueType or OpenType font
+    /// data. Characters the font doesn't contain are estimated by the average advance of the
+    /// printable ASCII characters.
+    ///
+    /// ```
+    /// use syntree_layout::FontMetrics;
+    ///
+    /// assert!(FontMetrics::from_font_data(&[0, 1, 2, 3], 16.0).is_err());
+    /// ```
+    ///
+    #[cfg(feature = "font-metrics")]
+    pub fn from_font_data(data: &[u8], font_size: f32) -> Result<Self> {
+        let face = ttf_parser::Face::parse(data, 0).map_err(|error| {
+            LayouterError::from_description(&format!("The font can't be parsed: {}", error))
+        })?;
+        let units_per_em = f32::from(face.units_per_em());
+        let mut advances = HashMap::new();
+        if let Some(cmap) = face.tables().cmap {
+            for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+                subtable.codepoints(|code_point| {
+                    let advance = char::from_u32(code_point).zip(
+                        subtable
+                            .glyph_index(code_point)
+                            .and_then(|glyph| face.glyph_hor_advance(glyph)),
+                    );
+                    if let Some((c, advance)) = advance {
+                        advances
+                            .entry(c)
+                            .or_insert(f32::from(advance) / units_per_em);
+                    }
+                });
+            }
+        }
+        let ascii = (' '..='~')
+            .filter_map(|c| advances.get(&c))
+            .collect::<Vec<_>>();
+        if ascii.is_empty() {
+            return Err(LayouterError::from_description(
+                "The font contains no printable ASCII characters",
+            ));
+        }
+        let average_advance = ascii.iter().copied().sum::<f32>() / ascii.len() as f32;
+        Ok(Self {
+            font_size,
+            average_advance,
+            advances,
+        })
+    }
+
+    /// The size of the font in pixels
+    pub fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
+    /// The advance of an average character in pixels, which is the width of one logical
+    /// coordinate unit.
+    pub fn unit_width(&self) -> f32 {
+        self.average_advance * self.font_size
+    }
+
+    /// The rendered width of the longest line of the given text in pixels
+    pub fn text_width(&self, text: &str) -> f32 {
+        text.lines()
+            .map(|line| line.chars().map(|c| self.advance(c)).sum::<f32>())
+            .fold(0.0, f32::max)
+    }
+
+    /// The width of the given text in logical coordinate units, rounded up
+    pub fn extent(&self, text: &str) -> usize {
+        (self.text_width(text) / self.unit_width()).ceil() as usize
+    }
+
+    /// The x-extent of a label like [LabelOrientation::x_extent], measured with these metrics
+    pub(crate) fn x_extent(&self, orientation: LabelOrientation, text: &str, scale: f32) -> usize {
+        if orientation.is_vertical() {
+            orientation.x_extent(text, scale)
+        } else {
+            let extent = self.text_width(text) * scale / self.unit_width();
+            (extent.ceil() as usize).max(1) + 1
+        }
+    }
+
+    /// The advance of the given character in pixels
+    pub(crate) fn advance(&self, c: char) -> f32 {
+        match self.advances.get(&c) {
+            Some(advance) => advance * self.font_size,
+            None => c.width().unwrap_or(0) as f32 * self.unit_width(),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! The module with the parser of textual tree dumps.
+
+use std::fmt::{self, Write as _};
+use std::hash::{Hash, Hasher};
+
+use syntree::{Builder, FlavorDefault, Tree};
+
+use crate::{LayouterError, Result};
+
+///
+/// Parses a textual tree into a [Tree] whose node values are the labels, so that debug dumps can
+/// be turned into diagrams without access to the original node type.
+///
+/// Two formats are understood:
+/// * The indentation-based format written by `syntree::print::print` and
+///   `syntree::print::print_with_source`, i.e. one node per line indented by two spaces per
+///   level, followed by `@` and the node's span. The labels are the printed node values, the
+///   lengths of the tokens are taken from their spans.
+/// * A simple S-expression if the text starts with `(`: `(label child ...)` is an inner node and
+///   an atom is a token whose length is the number of characters of its label. Atoms with spaces
+///   or parentheses are written in double quotes, the quotes are not part of the label. The
+///   escape sequences within quotes are described at [TextLabel].
+///
+/// The labels borrow from the given text, thus the tree can be drawn with
+/// [Layouter::embed][crate::Layouter::embed]. Deeply nested trees are parsed without recursion.
+///
+/// ```
+/// use syntree_layout::{from_text, Layouter};
+///
+/// let dump = "\
+/// Expr@0..5
+///   Number@0..1 \"1\"
+///   Plus@2..3 \"+\"
+///   Number@4..5 \"2\"
+/// ";
+/// let tree = from_text(dump).unwrap();
+/// assert_eq!(4, tree.len());
+/// let layouter = Layouter::new(&tree).embed().unwrap();
+/// assert_eq!("Plus", layouter.embedding()[2].text);
+///
+/// let tree = from_text("(Expr 1 + (Call f))").unwrap();
+/// assert!(tree.first().and_then(|root| root.last()).is_some_and(|n| n.value() == "Call"));
+/// ```
+///
+pub fn from_text(text: &str) -> Result<Tree<TextLabel<'_>, FlavorDefault>> {
+    let mut builder = Builder::new();
+    if text.trim_start().starts_with('(') {
+        let mut parser = SexpParser { text, pos: 0 };
+        parser.tree(&mut builder)?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parse_error(format!(
+                "Unexpected input after the tree at offset {}",
+                parser.pos
+            )));
+        }
+    } else {
+        parse_dump(text, &mut builder)?;
+    }
+    builder.build().map_err(tree_error)
+}
+
+/// Parses the output of `syntree::print::print`.
+fn parse_dump<'t>(
+    text: &'t str,
+    builder: &mut Builder<TextLabel<'t>, FlavorDefault>,
+) -> Result<()> {
+    // The depths of the open nodes
+    let mut open: Vec<usize> = Vec::new();
+    let lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let mut lines = lines.peekable();
+    while let Some((number, line)) = lines.next() {
+        let depth = (line.len() - line.trim_start_matches(' ').len()) / 2;
+        let (label, span) = split_dump_line(line.trim_start_matches(' '))
+            .ok_or_else(|| parse_error(format!("Invalid node in line {}: {}", number + 1, line)))?;
+        let label = TextLabel {
+            raw: label,
+            escaped: false,
+        };
+        if depth > open.len() {
+            return Err(parse_error(format!(
+                "Unexpected indentation in line {}",
+                number + 1
+            )));
+        }
+        while open.len() > depth {
+            open.pop();
+            builder.close().map_err(tree_error)?;
+        }
+        let has_children = lines
+            .peek()
+            .is_some_and(|(_, next)| (next.len() - next.trim_start_matches(' ').len()) / 2 > depth);
+        if has_children {
+            builder.open(label).map_err(tree_error)?;
+            open.push(depth);
+        } else {
+            builder.token(label, span.1 - span.0).map_err(tree_error)?;
+        }
+    }
+    for _ in open {
+        builder.close().map_err(tree_error)?;
+    }
+    Ok(())
+}
+
+///
+/// Splits a line of a dump into the label and the span. The span follows the first `@` that is
+/// followed by `start..end` and the end of the line, a `+` or the quoted source of a token.
+///
+fn split_dump_line(line: &str) -> Option<(&str, (usize, usize))> {
+    line.match_indices('@').find_map(|(at, _)| {
+        let rest = &line[at + 1..];
+        let span_len = rest.find(' ').unwrap_or(rest.len());
+        let tail = rest[span_len..].trim();
+        if !(tail.is_empty() || tail == "+" || tail.starts_with('"')) {
+            return None;
+        }
+        let (start, end) = rest[..span_len].split_once("..")?;
+        let span = (start.parse().ok()?, end.parse().ok()?);
+        (span.0 <= span.1).then_some((&line[..at], span))
+    })
+}
+
+///
+/// The label of a node of a tree parsed by [from_text]. It borrows its text from the parsed text,
+/// the escape sequences of quoted S-expression atoms are resolved when it is displayed or
+/// compared. Within double quotes `\\n` stands for a line break, `\\t` for a tab and a backslash
+/// followed by any other character, e.g. `\\"` or `\\\\`, for that character.
+///
+/// ```
+/// use syntree_layout::from_text;
+///
+/// let tree = from_text(r#"(Str "say \"hi\"")"#).unwrap();
+/// let label = tree.first().and_then(|root| root.first()).unwrap().value();
+/// assert_eq!(r#"say "hi""#, label.to_string());
+/// assert_eq!(8, label.char_count());
+/// ```
+///
+#[derive(Debug, Clone, Copy)]
+pub struct TextLabel<'t> {
+    /// The text of the label as written in the parsed text
+    raw: &'t str,
+    /// Whether the text contains escape sequences
+    escaped: bool,
+}
+
+impl<'t> TextLabel<'t> {
+    /// The characters of the label with resolved escape sequences.
+    fn chars(&self) -> impl Iterator<Item = char> + 't {
+        let mut chars = self.raw.chars();
+        let escaped = self.escaped;
+        std::iter::from_fn(move || match chars.next()? {
+            '\\' if escaped => chars.next().map(|c| match c {
+                'n' => '\n',
+                't' => '\t',
+                other => other,
+            }),
+            c => Some(c),
+        })
+    }
+
+    ///
+    /// Returns the number of characters of the label. It is the length of the span of a token
+    /// written as S-expression atom.
+    ///
+    pub fn char_count(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl fmt::Display for TextLabel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chars().try_for_each(|c| f.write_char(c))
+    }
+}
+
+impl PartialEq for TextLabel<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.chars().eq(other.chars())
+    }
+}
+
+impl Eq for TextLabel<'_> {}
+
+impl PartialEq<&str> for TextLabel<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.chars().eq(other.chars())
+    }
+}
+
+impl Hash for TextLabel<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.chars().for_each(|c| c.hash(state));
+    }
+}
+
+///
+/// A parser for trees written as S-expressions. It adds the nodes to the builder while it reads
+/// them and keeps the open nodes on an explicit stack, so that deep trees don't exhaust the
+/// stack.
+///
+struct SexpParser<'t> {
+    text: &'t str,
+    pos: usize,
+}
+
+impl<'t> SexpParser<'t> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    /// Parses one tree, i.e. a node with its descendants.
+    fn tree(&mut self, builder: &mut Builder<TextLabel<'t>, FlavorDefault>) -> Result<()> {
+        // The labels of the open nodes
+        let mut open = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('(') => {
+                    self.pos += 1;
+                    let label = self.atom()?;
+                    builder.open(label).map_err(tree_error)?;
+                    open.push(label);
+                }
+                Some(')') if !open.is_empty() => {
+                    self.pos += 1;
+                    open.pop();
+                    builder.close().map_err(tree_error)?;
+                }
+                Some(_) => {
+                    let label = self.atom()?;
+                    builder
+                        .token(label, label.char_count())
+                        .map_err(tree_error)?;
+                }
+                None => {
+                    return Err(parse_error(match open.last() {
+                        Some(label) => format!("Missing ')' of node '{}'", label),
+                        None => "Missing tree".to_string(),
+                    }))
+                }
+            }
+            if open.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Result<TextLabel<'t>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let rest = &self.text[self.pos..];
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut escaped = false;
+            let mut chars = quoted.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.pos += i + 2;
+                        return Ok(TextLabel {
+                            raw: &quoted[..i],
+                            escaped,
+                        });
+                    }
+                    '\\' => {
+                        escaped = true;
+                        chars.next();
+                    }
+                    _ => (),
+                }
+            }
+            return Err(parse_error(format!(
+                "Unterminated string at offset {}",
+                start
+            )));
+        }
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '"'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(parse_error(format!("Expected a label at offset {}", start)));
+        }
+        self.pos += end;
+        Ok(TextLabel {
+            raw: &rest[..end],
+            escaped: false,
+        })
+    }
+}
+
+fn parse_error(msg: String) -> LayouterError {
+    LayouterError::OtherError { msg }
+}
+
+fn tree_error(source: syntree::Error) -> LayouterError {
+    LayouterError::TreeError { source }
+}
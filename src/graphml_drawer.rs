@@ -0,0 +1,160 @@
+//! The module with the drawer for GraphML documents.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::Write;
+
+use xml_writer::XmlWriter;
+
+use crate::{DrawContext, Drawer, EmbeddedNode, LayouterError, Result};
+
+///
+/// The `GraphMlDrawer` type provides the transformation of the embedding information into a
+/// GraphML document that can be opened e.g. with yEd or Gephi.
+///
+/// Each node carries its label, its position from the embedding, its span and the id of the
+/// syntax tree node as data. Custom
+/// graph-level data can be added with [GraphMlDrawer::with_graph_data].
+///
+#[derive(Debug, Default)]
+pub struct GraphMlDrawer {
+    graph_data: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+}
+
+impl GraphMlDrawer {
+    /// Method to create a fresh instance of the `GraphMlDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            graph_data: Vec::new(),
+        }
+    }
+
+    ///
+    /// Adds a graph-level key/value pair. A value that was already set for the key is replaced.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, GraphMlDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let graphml = GraphMlDrawer::new()
+    ///     .with_graph_data("grammar", "calc")
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(graphml.contains(r#"<key id="g_grammar" for="graph" attr.name="grammar" attr.type="string"></key>"#));
+    /// assert!(graphml.contains(r#"<data key="g_grammar">calc</data>"#));
+    /// assert!(graphml.contains(r#"<edge source="n0" target="n1"></edge>"#));
+    /// ```
+    ///
+    pub fn with_graph_data(
+        mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let key = key.into();
+        let value = value.into();
+        match self.graph_data.iter_mut().find(|(k, _)| *k == key) {
+            Some(data) => data.1 = value,
+            None => self.graph_data.push((key, value)),
+        }
+        self
+    }
+
+    /// Renders the given embedding into a GraphML document and returns it as a `String`.
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        let mut xml = XmlWriter::new(Vec::new());
+        self.build_xml(&mut xml, embedding)
+            .map_err(LayouterError::from_io_error)?;
+        String::from_utf8(xml.into_inner())
+            .map_err(|e| LayouterError::from_description(&e.to_string()))
+    }
+
+    fn key<W: Write>(
+        xml: &mut XmlWriter<W>,
+        id: &str,
+        domain: &str,
+        name: &str,
+        attr_type: &str,
+    ) -> std::io::Result<()> {
+        xml.begin_elem("key")?;
+        xml.attr_esc("id", id)?;
+        xml.attr("for", domain)?;
+        xml.attr_esc("attr.name", name)?;
+        xml.attr("attr.type", attr_type)?;
+        xml.end_elem()
+    }
+
+    fn data<W: Write>(xml: &mut XmlWriter<W>, key: &str, value: &str) -> std::io::Result<()> {
+        xml.begin_elem("data")?;
+        xml.attr_esc("key", key)?;
+        xml.text(value)?;
+        xml.end_elem()
+    }
+
+    fn build_xml<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        embedding: &[EmbeddedNode],
+    ) -> std::io::Result<()> {
+        xml.dtd("UTF-8")?;
+        xml.begin_elem("graphml")?;
+        xml.attr("xmlns", "http://graphml.graphdrawing.org/xmlns")?;
+
+        for (key, _) in &self.graph_data {
+            Self::key(xml, &format!("g_{}", key), "graph", key, "string")?;
+        }
+        Self::key(xml, "label", "node", "label", "string")?;
+        Self::key(xml, "x", "node", "x", "int")?;
+        Self::key(xml, "y", "node", "y", "int")?;
+        Self::key(xml, "span", "node", "span", "string")?;
+        Self::key(xml, "node_id", "node", "node_id", "int")?;
+
+        xml.begin_elem("graph")?;
+        xml.attr("id", "tree")?;
+        xml.attr("edgedefault", "directed")?;
+        for (key, value) in &self.graph_data {
+            Self::data(xml, &format!("g_{}", key), value)?;
+        }
+
+        for node in embedding {
+            xml.begin_elem("node")?;
+            xml.attr("id", &format!("n{}", node.ord))?;
+            Self::data(xml, "label", &node.text)?;
+            Self::data(xml, "x", &node.x_center.to_string())?;
+            Self::data(xml, "y", &node.y_order.to_string())?;
+            Self::data(
+                xml,
+                "span",
+                &format!("{}..{}", node.span.start, node.span.end),
+            )?;
+            Self::data(xml, "node_id", &node.node_id.to_string())?;
+            xml.end_elem()?;
+        }
+
+        for node in embedding {
+            if let Some(parent) = node.parent {
+                xml.begin_elem("edge")?;
+                xml.attr("source", &format!("n{}", parent))?;
+                xml.attr("target", &format!("n{}", node.ord))?;
+                xml.end_elem()?;
+            }
+        }
+
+        xml.end_elem()?;
+        xml.end_elem()?;
+        xml.close()?;
+        xml.flush()
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `GraphMlDrawer`.
+///
+impl Drawer for GraphMlDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let doc = self.render_string(ctx.embedding())?;
+        fs::write(ctx.file_name(), doc).map_err(LayouterError::from_io_error)
+    }
+}
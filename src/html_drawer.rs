@@ -0,0 +1,182 @@
+//! The module with the drawer for interactive HTML pages.
+
+use std::borrow::Cow;
+use std::fs;
+
+use crate::{
+    svg_drawer::script_string, DrawContext, Drawer, EmbeddedNode, LayouterError, Result, SvgDrawer,
+};
+
+/// The script that folds and unfolds subtrees. It expects the global `parents` array that maps
+/// each node's `ord` to the `ord` of its parent or -1 for the root and the global `aggregates`
+/// array with the `ord`s of the nodes that are initially folded.
+const FOLD_SCRIPT: &str = r#"
+const folded = new Set(aggregates);
+function isHidden(ord) {
+  for (let p = parents[ord]; p >= 0; p = parents[p]) {
+    if (folded.has(p)) { return true; }
+  }
+  return false;
+}
+function update() {
+  parents.forEach((_, ord) => {
+    const display = isHidden(ord) ? "none" : "";
+    ["node-", "box-", "edge-"].forEach((kind) => {
+      const element = document.getElementById(prefix + kind + ord);
+      if (element) { element.style.display = display; }
+    });
+    const text = document.getElementById(prefix + "node-" + ord);
+    if (text) { text.classList.toggle("folded", folded.has(ord)); }
+  });
+}
+parents.forEach((parent, ord) => {
+  if (parent < 0 || !parents.includes(parent)) { return; }
+  ["node-", "box-"].forEach((kind) => {
+    const element = document.getElementById(prefix + kind + parent);
+    if (element && !element.classList.contains("foldable")) {
+      element.classList.add("foldable");
+      element.addEventListener("click", () => {
+        if (!folded.delete(parent)) { folded.add(parent); }
+        update();
+      });
+    }
+  });
+});
+update();
+"#;
+
+/// The stylesheet that marks foldable and folded nodes.
+const FOLD_STYLE: &str = "\
+.foldable { cursor: pointer; }
+.folded { text-decoration: underline dotted; }
+";
+
+///
+/// The `HtmlDrawer` type provides the transformation of the embedding information into a
+/// standalone HTML page. The tree is drawn as inline Svg by an [SvgDrawer] and a small embedded
+/// script allows to collapse and expand the subtree of an inner node by clicking on it.
+/// Folded nodes are underlined with a dotted line. Expandable aggregate nodes, see
+/// [Layouter::with_aggregation][crate::Layouter::with_aggregation], are initially folded.
+///
+/// The page has no external dependencies and can be opened in any browser.
+///
+#[derive(Debug, Default)]
+pub struct HtmlDrawer {
+    svg_drawer: SvgDrawer,
+    title: Cow<'static, str>,
+}
+
+impl HtmlDrawer {
+    /// Method to create a fresh instance of the `HtmlDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            svg_drawer: SvgDrawer::new(),
+            title: Cow::Borrowed("Syntax tree"),
+        }
+    }
+
+    /// Sets the [SvgDrawer] that draws the tree and thereby its style.
+    pub fn with_svg_drawer(self, svg_drawer: SvgDrawer) -> Self {
+        Self { svg_drawer, ..self }
+    }
+
+    /// Sets the title of the HTML page. A title given in the [DrawContext][crate::DrawContext]
+    /// takes precedence.
+    pub fn with_title(self, title: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            title: title.into(),
+            ..self
+        }
+    }
+
+    ///
+    /// Renders the given embedding into an HTML page and returns it as a `String`.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, HtmlDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let html = HtmlDrawer::new().render_string(&embedding).unwrap();
+    /// assert!(html.starts_with("<!DOCTYPE html>"));
+    /// assert!(html.contains("const parents = [-1,0];"));
+    /// ```
+    ///
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        self.render(embedding, &self.title)
+    }
+
+    /// Renders the given embedding into an HTML page with the given title.
+    fn render(&self, embedding: &[EmbeddedNode], title: &str) -> Result<String> {
+        let svg = self.svg_drawer.render_string(embedding)?;
+        // Strip the XML declaration, it is not allowed inside of HTML
+        let svg = svg.find("<svg").map_or(svg.as_str(), |start| &svg[start..]);
+
+        let mut parents = vec![-1_i64; embedding.iter().map(|e| e.ord + 1).max().unwrap_or(0)];
+        for node in embedding {
+            if let Some(parent) = node.parent {
+                parents[node.ord] = parent as i64;
+            }
+        }
+        let parents = parents
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let aggregates = embedding
+            .iter()
+            .filter(|node| {
+                node.aggregated > 0 && embedding.iter().any(|e| e.parent == Some(node.ord))
+            })
+            .map(|node| node.ord.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Ok(format!(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"UTF-8\">\n\
+             <title>{}</title>\n\
+             <style>\n{}</style>\n\
+             </head>\n\
+             <body>\n\
+             {}\n\
+             <script>\n\
+             const prefix = {};\n\
+             const parents = [{}];\n\
+             const aggregates = [{}];\n\
+             {}</script>\n\
+             </body>\n\
+             </html>\n",
+            Self::escape(title),
+            FOLD_STYLE,
+            svg,
+            script_string(self.svg_drawer.id_prefix()),
+            parents,
+            aggregates,
+            FOLD_SCRIPT
+        ))
+    }
+
+    /// Escapes the characters that have a special meaning in HTML texts.
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `HtmlDrawer`.
+///
+impl Drawer for HtmlDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        // The title given to the layouter takes precedence
+        let title = ctx.title().unwrap_or(&self.title);
+        let html = self.render(ctx.embedding(), title)?;
+        fs::write(ctx.file_name(), html).map_err(LayouterError::from_io_error)
+    }
+}
@@ -0,0 +1,50 @@
+//! Conversion of CSS color values for drawers whose output formats don't understand them.
+
+/// Parses a CSS color given as `#rgb`, `#rrggbb` or as one of the commonly used color names and
+/// returns its RGB components in the range 0.0..=1.0.
+pub(crate) fn parse_color(color: &str) -> Option<(f32, f32, f32)> {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        let component = |s: &str| u8::from_str_radix(s, 16).ok().map(|c| c as f32 / 255.0);
+        return match hex.len() {
+            3 => {
+                let r = component(&hex[0..1].repeat(2))?;
+                let g = component(&hex[1..2].repeat(2))?;
+                let b = component(&hex[2..3].repeat(2))?;
+                Some((r, g, b))
+            }
+            6 if hex.is_ascii() => Some((
+                component(&hex[0..2])?,
+                component(&hex[2..4])?,
+                component(&hex[4..6])?,
+            )),
+            _ => None,
+        };
+    }
+    let rgb = match color.to_ascii_lowercase().as_str() {
+        "black" => (0, 0, 0),
+        "white" => (255, 255, 255),
+        "red" => (255, 0, 0),
+        "green" => (0, 128, 0),
+        "blue" => (0, 0, 255),
+        "yellow" => (255, 255, 0),
+        "orange" => (255, 165, 0),
+        "darkorange" => (255, 140, 0),
+        "purple" => (128, 0, 128),
+        "navy" => (0, 0, 128),
+        "gray" | "grey" => (128, 128, 128),
+        "lightgray" | "lightgrey" => (211, 211, 211),
+        "darkgray" | "darkgrey" => (169, 169, 169),
+        "lightgreen" => (144, 238, 144),
+        "darkgreen" => (0, 100, 0),
+        "lightblue" => (173, 216, 230),
+        "darkblue" => (0, 0, 139),
+        "darkred" => (139, 0, 0),
+        _ => return None,
+    };
+    Some((
+        rgb.0 as f32 / 255.0,
+        rgb.1 as f32 / 255.0,
+        rgb.2 as f32 / 255.0,
+    ))
+}
@@ -0,0 +1,142 @@
+//! The module with the contour based compaction of an embedding.
+
+use crate::{EmbeddedNode, Spacing};
+
+/// The horizontal extent of a subtree on one level, relative to the subtree's root
+#[derive(Debug, Clone, Copy)]
+struct LevelExtent {
+    left: i64,
+    right: i64,
+}
+
+impl LevelExtent {
+    fn of_node(node: &EmbeddedNode) -> Self {
+        let left = -(node.x_extent as i64 / 2);
+        Self {
+            left,
+            right: left + node.x_extent as i64,
+        }
+    }
+}
+
+/// The extents of a subtree on all its levels, the first one is the level of the subtree's root
+type Contour = Vec<LevelExtent>;
+
+///
+/// Moves sibling subtrees as close together as their contours allow, i.e. a subtree can move
+/// below a wide sibling as long as their nodes on the same levels don't overlap.
+/// Parents are centered above their first and last child. Neighboring siblings keep the gaps of
+/// the given spacing. If the leaves are aligned at the bottom they occupy all levels down to the
+/// deepest one. Siblings are ordered by the start of their spans if `span_order` is set.
+///
+/// The nodes must be ordered by their `ord` and parents must precede their children.
+/// The `x_center` and `x_extent_children` of all nodes are replaced.
+///
+pub(crate) fn compact(
+    nodes: &mut [EmbeddedNode],
+    spacing: &Spacing,
+    bottom_aligned: bool,
+    span_order: bool,
+) {
+    if nodes.is_empty() {
+        return;
+    }
+    let depth = nodes.iter().map(|node| node.y_order).max().unwrap_or(0);
+    let mut children = vec![Vec::new(); nodes.len()];
+    for node in nodes.iter() {
+        if let Some(parent) = node.parent {
+            children[parent].push(node.ord);
+        }
+    }
+    if span_order {
+        for siblings in children.iter_mut() {
+            siblings.sort_by_key(|ord| (nodes[*ord].span.start, *ord));
+        }
+    }
+
+    // The offset of each node's x_center relative to its parent's x_center
+    let mut offsets = vec![0_i64; nodes.len()];
+    let mut contours: Vec<Option<Contour>> = vec![None; nodes.len()];
+
+    // Children have greater ords than their parents, so a reverse pass visits all children
+    // before their parent
+    for ord in (0..nodes.len()).rev() {
+        let mut children_contour: Contour = Vec::new();
+        let mut child_offsets = Vec::with_capacity(children[ord].len());
+        let mut left_sibling: Option<usize> = None;
+        for &child in &children[ord] {
+            let contour = contours[child].take().unwrap_or_default();
+            // Shift the child right of all levels it shares with its left siblings
+            let offset = match left_sibling {
+                None => 0,
+                Some(left_sibling) => {
+                    let gap = spacing.gap(
+                        !children[left_sibling].is_empty(),
+                        !children[child].is_empty(),
+                    ) as i64;
+                    children_contour
+                        .iter()
+                        .zip(&contour)
+                        .map(|(placed, new)| placed.right - new.left + gap)
+                        .max()
+                        .unwrap_or(0)
+                }
+            };
+            left_sibling = Some(child);
+            for (level, extent) in contour.iter().enumerate() {
+                let shifted = LevelExtent {
+                    left: extent.left + offset,
+                    right: extent.right + offset,
+                };
+                match children_contour.get_mut(level) {
+                    Some(placed) => placed.right = shifted.right,
+                    None => children_contour.push(shifted),
+                }
+            }
+            child_offsets.push(offset);
+        }
+
+        let center = match (child_offsets.first(), child_offsets.last()) {
+            (Some(first), Some(last)) => (first + last) / 2,
+            _ => 0,
+        };
+        for (&child, offset) in children[ord].iter().zip(child_offsets) {
+            offsets[child] = offset - center;
+        }
+        let mut contour = Vec::with_capacity(children_contour.len() + 1);
+        contour.push(LevelExtent::of_node(&nodes[ord]));
+        if bottom_aligned && children[ord].is_empty() {
+            contour.resize(
+                depth - nodes[ord].y_order + 1,
+                LevelExtent::of_node(&nodes[ord]),
+            );
+        }
+        contour.extend(children_contour.into_iter().map(|extent| LevelExtent {
+            left: extent.left - center,
+            right: extent.right - center,
+        }));
+        nodes[ord].x_extent_children = contour
+            .iter()
+            .map(|extent| extent.right)
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(contour.iter().map(|extent| extent.left).min().unwrap_or(0))
+            as usize;
+        contours[ord] = Some(contour);
+    }
+
+    // Convert the relative offsets into absolute positions, the leftmost node touches 0
+    let mut centers = vec![0_i64; nodes.len()];
+    for ord in 0..nodes.len() {
+        centers[ord] = nodes[ord].parent.map_or(0, |parent| centers[parent]) + offsets[ord];
+    }
+    let min_left = nodes
+        .iter()
+        .zip(&centers)
+        .map(|(node, center)| center + LevelExtent::of_node(node).left)
+        .min()
+        .unwrap_or(0);
+    for (node, center) in nodes.iter_mut().zip(centers) {
+        node.x_center = (center - min_left) as usize;
+    }
+}
@@ -1,12 +1,52 @@
 //! The module that holds types to embed nodes of a tree into the plane.
 
-use std::fmt::{self};
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
 
-use syntree::{node::Event, Flavor, Node, Tree};
+use syntree::{Flavor, Node, Pointer, Tree};
 
-use crate::{Embedding, LayouterError, Result};
+use crate::{
+    embedding::count_children, span_coverage::span_issues, ColorClass, EmbeddedNode, Embedding,
+    FilterMode, LayouterError, LeafAlignment, Result, Spacing, SpanIssue, TextSegment,
+};
 
-use super::node::{EmbeddingHelperData, InternalNode};
+use super::{
+    compaction,
+    node::{EmbeddingHelperData, InternalNode, NodeAttributes},
+    options::EmbedOptions,
+};
+
+// Wrapper to help evaluate forwarded Display implementation.
+pub(crate) struct Wrapper<'a, F, T>(pub(crate) &'a F, pub(crate) &'a T);
+
+impl<F, T> fmt::Display for Wrapper<'_, F, T>
+where
+    F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(self.1, f)
+    }
+}
+
+/// The text that replaces the label of collapsed nodes
+const COLLAPSED_TEXT: &str = "\u{2026}";
+
+/// The marker that replaces the label of trivia tokens
+const TRIVIA_MARKER: &str = "\u{b7}";
+
+/// The superscript digits from zero to nine
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// Writes the given number with superscript digits.
+fn superscript(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .map(|digit| SUPERSCRIPT_DIGITS[digit as usize])
+        .collect()
+}
 
 ///
 /// The Embedder type provides a single (accessible) method `embed` to arrange nodes of a tree into
@@ -37,117 +77,203 @@ where
     ///
     pub(crate) fn embed(
         tree: &Tree<T, F>,
+        options: &EmbedOptions<T, F>,
         stringify: impl Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
-        emphasize: impl Fn(&T) -> bool,
+        attributes: impl Fn(&T) -> NodeAttributes,
     ) -> Result<Embedding> {
-        // Insert all tree items with their indices
-        // After this step each item has following properties set:
-        // 'y_order', 'x_extent', 'text', 'is_emphasized', 'ord'
-        let mut items = Self::create_initial_embedding_data(tree, &stringify, &emphasize)?;
-        debug_assert_eq!(items.0.len(), items.1.len());
-
-        // Set widths (x_extent_children, x_extent_of_children) on each InternalNode structure
-        // After this step each item has following properties set:
-        // 'y_order', 'x_extent', 'text', 'is_emphasized', 'ord', 'x_extent_children',
-        // 'x_extent_of_children', 'parent'
-        Self::apply_children_x_extents(tree, &mut items);
-
-        // Finally set the property 'x_center' from leafs to root
-        // After this step each item has all necessary properties set
-        Self::apply_x_center(&mut items)?;
+        Self::try_embed(
+            tree,
+            options,
+            |value: &T| {
+                let mut text = String::new();
+                write!(text, "{}", Wrapper(&stringify, value))
+                    .map_err(|_| LayouterError::from_description("Formatting the node failed"))?;
+                Ok(text)
+            },
+            attributes,
+        )
+    }
 
-        // Transfer result
-        Ok(Self::transfer_result(items))
+    ///
+    /// This method creates an embedding of the nodes of the given tree in the plane. The errors
+    /// of the fallible `stringify` function are reported with the context of the node.
+    ///
+    pub(crate) fn try_embed(
+        tree: &Tree<T, F>,
+        options: &EmbedOptions<T, F>,
+        stringify: impl Fn(&T) -> Result<String>,
+        attributes: impl Fn(&T) -> NodeAttributes,
+    ) -> Result<Embedding> {
+        Self::embed_with_labels(tree, options, None, |node| {
+            let attributes = attributes(&node.value());
+            // Rich text labels replace the text of the stringify function
+            let text = if attributes.segments.is_empty() {
+                stringify(&node.value())?
+            } else {
+                TextSegment::concat(&attributes.segments)
+            };
+            Ok((text, attributes))
+        })
     }
 
     /// Embeds the nodes of the given tree into the plane. The source code is used to display the
     /// text of the nodes, if they are tokens.
-    pub(crate) fn embed_with_source(tree: &Tree<T, F>, source: &str) -> Result<Embedding>
-    where
-        T: Copy,
-        F: Flavor,
-    {
-        // Insert all tree items with their indices
-        // After this step each item has following properties set:
-        // 'y_order', 'x_extent', 'text', 'is_emphasized', 'ord'
-        let mut items = Self::create_initial_embedding_data_with_source(tree, source)?;
-        debug_assert_eq!(items.0.len(), items.1.len());
+    pub(crate) fn embed_with_source(
+        tree: &Tree<T, F>,
+        options: &EmbedOptions<T, F>,
+        source: &str,
+    ) -> Result<Embedding> {
+        Self::embed_with_labels(tree, options, Some(source), |node| {
+            let text = options.source_text(source, node.range())?;
+            Ok((text.to_string(), NodeAttributes::default()))
+        })
+    }
 
-        // Set widths (x_extent_children, x_extent_of_children) on each InternalNode structure
-        // After this step each item has following properties set:
-        // 'y_order', 'x_extent', 'text', 'is_emphasized', 'ord', 'x_extent_children',
-        // 'x_extent_of_children', 'parent'
-        Self::apply_children_x_extents(tree, &mut items);
+    ///
+    /// Embeds the nodes of the given tree into the plane. The `stringify` function receives the
+    /// text of each node in the source together with the node's value.
+    ///
+    pub(crate) fn embed_with_source_and_stringify(
+        tree: &Tree<T, F>,
+        options: &EmbedOptions<T, F>,
+        source: &str,
+        stringify: impl Fn(&T, &str, &mut fmt::Formatter<'_>) -> fmt::Result,
+        attributes: impl Fn(&T) -> NodeAttributes,
+    ) -> Result<Embedding> {
+        // Wrapper to help evaluate forwarded Display implementation.
+        struct Wrapper<'a, F, T>(&'a F, &'a T, &'a str);
 
-        // Finally set the property 'x_center' from leafs to root
-        // After this step each item has all necessary properties set
-        Self::apply_x_center(&mut items)?;
+        impl<F, T> fmt::Display for Wrapper<'_, F, T>
+        where
+            F: Fn(&T, &str, &mut fmt::Formatter<'_>) -> fmt::Result,
+        {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                (self.0)(self.1, self.2, f)
+            }
+        }
 
-        // Transfer result
-        Ok(Self::transfer_result(items))
+        Self::embed_with_labels(tree, options, Some(source), |node| {
+            let attributes = attributes(&node.value());
+            // Rich text labels replace the text of the stringify function
+            let text = if attributes.segments.is_empty() {
+                let node_source = options.source_text(source, node.range())?;
+                let mut text = String::new();
+                write!(text, "{}", Wrapper(&stringify, &node.value(), node_source))
+                    .map_err(|_| LayouterError::from_description("Formatting the node failed"))?;
+                text
+            } else {
+                TextSegment::concat(&attributes.segments)
+            };
+            Ok((text, attributes))
+        })
     }
 
+    /// Embeds the nodes of the given tree into the plane. The source code is used to display the
+    /// text of the tokens, the `Display` implementation is used for the inner nodes.
     pub(crate) fn embed_with_source_and_display(
         tree: &Tree<T, F>,
+        options: &EmbedOptions<T, F>,
         source: &str,
     ) -> Result<Embedding>
     where
-        T: Copy + fmt::Display,
-        F: Flavor,
+        T: fmt::Display,
     {
+        Self::embed_with_labels(tree, options, Some(source), |node| {
+            let text = if node.has_children() {
+                node.value().to_string()
+            } else {
+                format!("'{}'", options.source_text(source, node.range())?)
+            };
+            Ok((text, NodeAttributes::default()))
+        })
+    }
+
+    ///
+    /// The common embedding pipeline. The `label` function provides the text and the attributes
+    /// of each node.
+    ///
+    fn embed_with_labels(
+        tree: &Tree<T, F>,
+        options: &EmbedOptions<T, F>,
+        source: Option<&str>,
+        label: impl Fn(Node<'_, T, F>) -> Result<(String, NodeAttributes)>,
+    ) -> Result<Embedding> {
         // Insert all tree items with their indices
         // After this step each item has following properties set:
-        // 'y_order', 'x_extent', 'text', 'is_emphasized', 'ord'
-        let mut items = Self::create_initial_embedding_data_with_source_and_display(tree, source)?;
-        debug_assert_eq!(items.0.len(), items.1.len());
+        // 'y_order', 'x_extent', 'text', 'is_emphasized', 'diagnostic', 'style', 'orientation',
+        // 'collapsed', 'ord'
+        let mut items = Self::create_initial_embedding_data(tree, options, &label)?;
+        if let Some(source) = source.filter(|_| options.span_gaps) {
+            Self::insert_gap_nodes(tree, &mut items, options, source);
+        }
+        // Merged chains map several node ids to one item and aggregate nodes share the id of
+        // their first aggregated node, but all ids map to existing items
+        debug_assert!(items.1.values().all(|ord| *ord < items.0.len()));
 
         // Set widths (x_extent_children, x_extent_of_children) on each InternalNode structure
         // After this step each item has following properties set:
         // 'y_order', 'x_extent', 'text', 'is_emphasized', 'ord', 'x_extent_children',
         // 'x_extent_of_children', 'parent'
-        Self::apply_children_x_extents(tree, &mut items);
+        Self::apply_children_x_extents(&mut items, options);
 
         // Finally set the property 'x_center' from leafs to root
         // After this step each item has all necessary properties set
-        Self::apply_x_center(&mut items)?;
+        Self::apply_x_center(&mut items, options)?;
 
         // Transfer result
-        Ok(Self::transfer_result(items))
+        let mut embedding = Self::transfer_result(items);
+
+        // Optionally let sibling subtrees interleave
+        let bottom_aligned = options.leaf_alignment == LeafAlignment::Bottom;
+        if options.compact {
+            compaction::compact(
+                &mut embedding,
+                &options.spacing,
+                bottom_aligned,
+                options.span_order,
+            );
+        }
+
+        // Optionally move the leaves down to the deepest level, the horizontal placement already
+        // keeps their columns free
+        if bottom_aligned {
+            Self::align_leaves_at_bottom(&mut embedding);
+        }
+
+        // Spread the levels and add the margins
+        Self::apply_spacing(&mut embedding, &options.spacing);
+        count_children(&mut embedding);
+        Ok(embedding.into())
     }
 
     fn create_from_node(
         ord: usize,
-        depth: usize,
         node: Node<T, F>,
         items: &EmbeddingHelperData<F>,
-        stringify: &impl Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
-        emphasize: &impl Fn(&T) -> bool,
+        text: String,
+        attributes: NodeAttributes,
     ) -> InternalNode<F> {
-        // Wrapper to help evaluate forwarded Display implementation.
-        struct Wrapper<'a, F, T>(&'a F, &'a T);
-
-        impl<F, T> fmt::Display for Wrapper<'_, F, T>
-        where
-            F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
-        {
-            #[inline]
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                (self.0)(self.1, f)
-            }
-        }
-
-        let text = Wrapper(stringify, &node.value()).to_string();
-
-        let y_order = depth;
+        let NodeAttributes {
+            is_emphasized,
+            diagnostic,
+            style,
+            orientation,
+            tooltip,
+            segments,
+            group,
+        } = attributes;
+
+        let parent_item = node.parent().and_then(|p| items.get_by_node_id(&p.id()));
+        // The depth is taken from the parent because merged chains shorten the tree
+        let y_order = parent_item.map_or(0, |p| p.y_order + 1);
         let x_center = 0;
-        let x_extent = text.len() + 1;
+        let x_extent = orientation.x_extent(&text, 1.0);
         let x_extent_of_children = x_extent;
         let x_extent_children = x_extent;
-        let is_emphasized = emphasize(&node.value());
-        let parent = node
-            .parent()
-            .and_then(|p| items.get_by_node_id(&p.id()).map(|n| n.ord));
+        let parent = parent_item.map(|p| p.ord);
         let node_id = node.id();
+        let span = node.range();
 
         InternalNode {
             y_order,
@@ -156,245 +282,541 @@ where
             x_extent_of_children,
             x_extent_children,
             text,
+            segments,
             is_emphasized,
+            diagnostic,
+            style,
+            orientation,
+            collapsed: 0,
+            aggregated: 0,
             parent,
             ord,
             node_id,
+            chain: Vec::new(),
+            span,
+            tooltip,
+            badge: None,
+            is_gap: false,
+            is_trivia: false,
+            group,
+            color_class: ColorClass::None,
+            scale: 1.0,
         }
     }
 
-    fn create_from_node_with_source(
-        ord: usize,
-        depth: usize,
-        node: Node<T, F>,
-        items: &EmbeddingHelperData<F>,
-        source: &str,
-    ) -> InternalNode<F> {
-        let text = source[node.range()].to_string();
-
-        let y_order = depth;
-        let x_center = 0;
-        let x_extent = text.len() + 1;
-        let x_extent_of_children = x_extent;
-        let x_extent_children = x_extent;
-        let parent = node
-            .parent()
-            .and_then(|p| items.get_by_node_id(&p.id()).map(|n| n.ord));
-        let node_id = node.id();
-
-        InternalNode {
-            y_order,
-            x_center,
-            x_extent,
-            x_extent_of_children,
-            x_extent_children,
-            text,
-            is_emphasized: false,
-            parent,
-            ord,
-            node_id,
+    ///
+    /// Returns the chain of nodes starting at the given node in which each node has exactly one
+    /// child that is an inner node itself. Collapsed and filtered nodes end the chain.
+    ///
+    fn unary_chain<'t>(node: Node<'t, T, F>, options: &EmbedOptions<T, F>) -> Vec<Node<'t, T, F>> {
+        let mut chain = vec![node];
+        let mut last = node;
+        while let (Some(child), 1) = (last.first(), last.children().count()) {
+            if !child.has_children()
+                || options.is_collapsed(&child.value())
+                || options.filter_mode(&child).is_some()
+            {
+                break;
+            }
+            chain.push(child);
+            last = child;
         }
+        chain
     }
 
-    fn create_from_node_with_source_and_diplay(
+    ///
+    /// Creates one node for a chain of nodes. The label shows the first and the last node of the
+    /// chain, the other properties are taken from the last node. All nodes of the chain are
+    /// mapped to the created node, the last node's children become its children.
+    ///
+    fn create_from_chain(
         ord: usize,
-        depth: usize,
-        node: Node<T, F>,
+        chain: &[Node<'_, T, F>],
         items: &EmbeddingHelperData<F>,
-        source: &str,
-    ) -> InternalNode<F>
-    where
-        T: fmt::Display,
-    {
-        let text = if node.has_children() {
-            node.value().to_string()
+        label: &impl Fn(Node<'_, T, F>) -> Result<(String, NodeAttributes)>,
+    ) -> Result<InternalNode<F>> {
+        let first = chain[0];
+        let last = chain[chain.len() - 1];
+        let (first_text, _) = label(first).map_err(|err| err.at_node(ord, first.range()))?;
+        let (last_text, attributes) = label(last).map_err(|err| err.at_node(ord, last.range()))?;
+        let text = if chain.len() > 2 {
+            format!(
+                "{} \u{2192} {} \u{2192} {}",
+                first_text, COLLAPSED_TEXT, last_text
+            )
         } else {
-            format!("'{}'", &source.get(node.range()).unwrap_or("range_error"))
+            format!("{} \u{2192} {}", first_text, last_text)
         };
+        let attributes = NodeAttributes {
+            segments: Vec::new(),
+            ..attributes
+        };
+        let mut new_item = Self::create_from_node(ord, first, items, text, attributes);
+        new_item.node_id = last.id();
+        new_item.chain = chain[..chain.len() - 1].iter().map(|n| n.id()).collect();
+        Ok(new_item)
+    }
 
-        let y_order = depth;
-        let x_center = 0;
-        let x_extent = text.len() + 1;
-        let x_extent_of_children = x_extent;
-        let x_extent_children = x_extent;
-        let parent = node
-            .parent()
-            .and_then(|p| items.get_by_node_id(&p.id()).map(|n| n.ord));
-        let node_id = node.id();
-
-        InternalNode {
-            y_order,
-            x_center,
-            x_extent,
-            x_extent_of_children,
-            x_extent_children,
-            text,
-            is_emphasized: false,
-            parent,
-            ord,
-            node_id,
+    ///
+    /// Returns the run of consecutive siblings that are aggregated if the given node starts such
+    /// a run. Runs consist of at least two nodes.
+    ///
+    fn aggregated_run<'t>(
+        node: Node<'t, T, F>,
+        options: &EmbedOptions<T, F>,
+    ) -> Option<Vec<Node<'t, T, F>>> {
+        if !options.is_aggregated(&node.value())
+            || node
+                .prev()
+                .is_some_and(|prev| options.is_aggregated(&prev.value()))
+        {
+            return None;
+        }
+        let mut run = vec![node];
+        let mut last = node;
+        while let Some(next) = last
+            .next()
+            .filter(|next| options.is_aggregated(&next.value()))
+        {
+            run.push(next);
+            last = next;
         }
+        (run.len() > 1).then_some(run)
+    }
+
+    /// Creates the aggregate node for the given run of siblings.
+    fn create_from_run(
+        ord: usize,
+        run: &[Node<'_, T, F>],
+        items: &EmbeddingHelperData<F>,
+        options: &EmbedOptions<T, F>,
+    ) -> InternalNode<F> {
+        let first = run[0];
+        let last = run[run.len() - 1];
+        let text = options
+            .aggregation
+            .as_ref()
+            .map(|(_, label, _)| label(&first.value(), run.len()))
+            .unwrap_or_default();
+        let mut item = Self::create_from_node(ord, first, items, text, NodeAttributes::default());
+        item.aggregated = run.len();
+        item.span = first.range().start..last.range().end;
+        item
     }
 
     fn create_initial_embedding_data(
         tree: &Tree<T, F>,
-        stringify: &impl Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
-        emphasize: &impl Fn(&T) -> bool,
+        options: &EmbedOptions<T, F>,
+        label: &impl Fn(Node<'_, T, F>) -> Result<(String, NodeAttributes)>,
     ) -> Result<EmbeddingHelperData<F>> {
         let mut items = EmbeddingHelperData::with_capacity(tree.len());
-        if tree.children().count() > 1 {
-            return Err(LayouterError::from_description(
-                "Currently we support only one root",
-            ));
+        let count = tree.children().count();
+        if count > 1 {
+            return Err(LayouterError::MultipleRoots { count });
         }
-
-        tree.walk()
-            .with_depths()
-            .enumerate()
-            .for_each(|(ord, (depth, node))| {
-                let new_item =
-                    Self::create_from_node(ord, depth as usize, node, &items, stringify, emphasize);
-                items.insert(ord, new_item);
+        if let Some(root) = options
+            .subtree_root
+            .filter(|root| tree.get(*root).is_none())
+        {
+            return Err(LayouterError::SubtreeRootNotFound {
+                node_id: root.get(),
             });
-
-        Ok(items)
-    }
-
-    fn create_initial_embedding_data_with_source(
-        tree: &Tree<T, F>,
-        source: &str,
-    ) -> Result<EmbeddingHelperData<F>> {
-        let mut items = EmbeddingHelperData::with_capacity(tree.len());
-        if tree.children().count() > 1 {
-            return Err(LayouterError::from_description(
-                "Currently we support only one root",
-            ));
         }
 
-        tree.walk()
-            .with_depths()
-            .enumerate()
-            .for_each(|(ord, (depth, node))| {
-                let new_item =
-                    Self::create_from_node_with_source(ord, depth as usize, node, &items, source);
-                items.insert(ord, new_item);
-            });
+        // The ords of the aggregate nodes by the ids of the aggregated nodes
+        let mut aggregated_by = HashMap::new();
+        // The depth of the subtree's root once it has been reached
+        let mut subtree_at: Option<usize> = None;
+        // The depth of the excluded node whose subtree is currently skipped
+        let mut excluded_at: Option<usize> = None;
+        // The depth and the ord of the collapsed node whose subtree is currently skipped
+        let mut collapsed_at: Option<(usize, usize)> = None;
+        // The depth of the node whose subtree is currently drawn at a reduced scale
+        let mut scaled_at: Option<usize> = None;
+        // The number of embedded children by the ords of their parents
+        let mut child_counts: HashMap<usize, usize> = HashMap::new();
+        // The ords of the placeholders of omitted children by the ords of their parents and the
+        // number of siblings each placeholder stands for
+        let mut placeholders: HashMap<usize, usize> = HashMap::new();
+        let mut omitted: HashMap<usize, usize> = HashMap::new();
+        for (depth, node) in tree.walk().with_depths() {
+            let depth = depth as usize;
+            if let Some(root) = options.subtree_root {
+                match subtree_at {
+                    None if node.id() != root => continue,
+                    None => subtree_at = Some(depth),
+                    Some(root_depth) if depth <= root_depth => break,
+                    Some(_) => (),
+                }
+            }
+            if let Some(excluded_depth) = excluded_at {
+                if depth > excluded_depth {
+                    continue;
+                }
+                excluded_at = None;
+            }
+            if items.contains_node_id(&node.id()) {
+                // The node was merged into a chain
+                continue;
+            }
+            if !options.is_in_clip_span(node.range()) {
+                excluded_at = Some(depth);
+                continue;
+            }
+            if let Some((collapsed_depth, collapsed_ord)) = collapsed_at {
+                if depth > collapsed_depth {
+                    if let Some(collapsed_item) = items.get_mut_by_ord(collapsed_ord) {
+                        collapsed_item.collapsed += 1;
+                    }
+                    continue;
+                }
+                collapsed_at = None;
+            }
+            match options.filter_mode(&node) {
+                Some(FilterMode::DropSubtree) => {
+                    excluded_at = Some(depth);
+                    continue;
+                }
+                Some(FilterMode::Splice) => {
+                    // The children of the node find their parent by the node's id
+                    if let Some(parent_ord) = node.parent().and_then(|p| items.1.get(&p.id())) {
+                        items.insert_alias(node.id(), *parent_ord);
+                    }
+                    continue;
+                }
+                None => (),
+            }
 
-        Ok(items)
-    }
+            // Runs of sibling subtrees are represented by an aggregate node
+            let mut aggregate = aggregated_by.get(&node.id()).copied();
+            if aggregate.is_none() {
+                if let Some(run) = Self::aggregated_run(node, options) {
+                    let ord = items.0.len();
+                    let item = Self::create_from_run(ord, &run, &items, options);
+                    items.insert(ord, item);
+                    aggregated_by.extend(run.iter().map(|member| (member.id(), ord)));
+                    aggregate = Some(ord);
+                }
+            }
+            if aggregate.is_some() && !options.aggregation.as_ref().is_some_and(|a| a.2) {
+                excluded_at = Some(depth);
+                continue;
+            }
 
-    fn create_initial_embedding_data_with_source_and_display(
-        tree: &Tree<T, F>,
-        source: &str,
-    ) -> Result<EmbeddingHelperData<F>>
-    where
-        T: fmt::Display,
-    {
-        let mut items = EmbeddingHelperData::with_capacity(tree.len());
-        if tree.children().count() > 1 {
-            return Err(LayouterError::from_description(
-                "Currently we support only one root",
-            ));
-        }
+            if scaled_at.is_some_and(|scaled_depth| depth <= scaled_depth) {
+                scaled_at = None;
+            }
+            if scaled_at.is_none() && options.is_scaled(&node.value()) {
+                scaled_at = Some(depth);
+            }
+
+            if let Some(max_children) = options.max_children {
+                let parent_ord =
+                    aggregate.or_else(|| node.parent().and_then(|p| items.1.get(&p.id()).copied()));
+                if let Some(parent_ord) = parent_ord {
+                    let count = child_counts.entry(parent_ord).or_default();
+                    *count += 1;
+                    if *count > max_children {
+                        // The omitted siblings and their subtrees are counted like the nodes
+                        // below a collapsed stub
+                        let ord = match placeholders.get(&parent_ord) {
+                            Some(ord) => *ord,
+                            None => {
+                                let ord = items.0.len();
+                                let mut item = Self::create_from_node(
+                                    ord,
+                                    node,
+                                    &items,
+                                    String::new(),
+                                    NodeAttributes::default(),
+                                );
+                                item.parent = Some(parent_ord);
+                                item.y_order = items.0[parent_ord].y_order + 1;
+                                items.insert(ord, item);
+                                placeholders.insert(parent_ord, ord);
+                                ord
+                            }
+                        };
+                        let placeholder = &mut items.0[ord];
+                        placeholder.collapsed += 1;
+                        placeholder.span.end = placeholder.span.end.max(node.range().end);
+                        *omitted.entry(ord).or_default() += 1;
+                        collapsed_at = Some((depth, ord));
+                        continue;
+                    }
+                }
+            }
 
-        tree.walk()
-            .with_depths()
-            .enumerate()
-            .for_each(|(ord, (depth, node))| {
-                let new_item = Self::create_from_node_with_source_and_diplay(
+            let ord = items.0.len();
+            let mut new_item = if options.is_collapsed(&node.value()) {
+                collapsed_at = Some((depth, ord));
+                let mut new_item = Self::create_from_node(
                     ord,
-                    depth as usize,
                     node,
                     &items,
-                    source,
+                    COLLAPSED_TEXT.to_string(),
+                    NodeAttributes::default(),
                 );
-                items.insert(ord, new_item);
-            });
+                new_item.collapsed = 1;
+                new_item
+            } else if let Some(chain) = options
+                .chain_collapsing
+                .then(|| Self::unary_chain(node, options))
+                .filter(|chain| chain.len() > 1)
+            {
+                Self::create_from_chain(ord, &chain, &items, label)?
+            } else {
+                let (text, attributes) =
+                    label(node).map_err(|err| err.at_node(ord, node.range()))?;
+                Self::create_from_node(ord, node, &items, text, attributes)
+            };
+            if options.node_numbers {
+                new_item.append_text(&superscript(ord));
+            }
+            if options.span_labels {
+                let span = format!("@{}..{}", new_item.span.start, new_item.span.end);
+                new_item.append_text(&span);
+            }
+            if let Some(max_label_width) = options.max_label_width {
+                new_item.wrap_text(max_label_width);
+            }
+            if options.is_highlighted(&new_item.span) {
+                new_item.is_emphasized = true;
+            }
+            if options.is_error_node(&node.value()) {
+                new_item.mark_as_error();
+            }
+            if let Some(badges) = &options.badges {
+                new_item.badge = badges(&node.value());
+            }
+            let color_class = options.color_class(&node.value());
+            if color_class != ColorClass::None {
+                new_item.set_color_class(color_class);
+            }
+            if let Some((_, scale)) = scaled_at.and(options.scaled.as_ref()) {
+                new_item.set_scale(*scale);
+            }
+            if let Some(measure) = &options.measure {
+                new_item.set_x_extent(measure(&node.value(), &new_item.text));
+            } else if let Some(metrics) = &options.font_metrics {
+                new_item.set_x_extent(metrics.x_extent(
+                    new_item.orientation,
+                    &new_item.text,
+                    new_item.scale,
+                ));
+            }
+            if options.is_trivia(&node.value()) {
+                new_item.make_trivia(TRIVIA_MARKER);
+            }
+            if let Some(aggregate_item) = aggregate.and_then(|ord| items.get_by_ord(ord)) {
+                // The aggregated subtrees hang below their aggregate node
+                new_item.y_order = aggregate_item.y_order + 1;
+                new_item.parent = Some(aggregate_item.ord);
+            }
+            items.insert(ord, new_item);
+        }
+
+        for (ord, count) in omitted {
+            let placeholder = &mut items.0[ord];
+            placeholder.text = format!("{} {} more", COLLAPSED_TEXT, count);
+            placeholder.set_scale(placeholder.scale);
+        }
 
+        // Splicing the root node may leave several roots
+        let count = items.0.iter().filter(|item| item.parent.is_none()).count();
+        if count > 1 {
+            return Err(LayouterError::MultipleRoots { count });
+        }
+        if !options.diagnostics.is_empty() {
+            Self::apply_diagnostics(&mut items, options);
+        }
         Ok(items)
     }
 
-    fn apply_children_x_extents(tree: &Tree<T, F>, items: &mut EmbeddingHelperData<F>) {
-        tree.walk_events().for_each(|(event, node)| {
-            if let Event::Up = event {
-                let x_extent_of_children = node.children().fold(0, |acc, child| {
-                    if let Some(internal_child) = items.get_by_node_id(&child.id()) {
-                        acc + internal_child.x_extent_children
-                    } else {
-                        acc
-                    }
-                });
-                if let Some(internal_node) = items.get_mut_by_node_id(&node.id()) {
-                    internal_node.x_extent_of_children = x_extent_of_children;
-                    internal_node.x_extent_children =
-                        std::cmp::max(internal_node.x_extent, x_extent_of_children);
-                }
+    ///
+    /// Marks the deepest nodes whose spans touch a diagnostic, i.e. the nodes that touch a
+    /// diagnostic while none of their children does. Their ancestors stay unmarked, otherwise
+    /// every diagnostic would mark the root.
+    ///
+    fn apply_diagnostics(items: &mut EmbeddingHelperData<F>, options: &EmbedOptions<T, F>) {
+        let touched = items
+            .0
+            .iter()
+            .map(|item| options.diagnostics_at(&item.span).next().is_some())
+            .collect::<Vec<_>>();
+        let mut has_touched_child = vec![false; items.0.len()];
+        for item in items.0.iter().filter(|item| touched[item.ord]) {
+            if let Some(parent) = item.parent {
+                has_touched_child[parent] = true;
             }
-        });
+        }
+        for item in items
+            .0
+            .iter_mut()
+            .filter(|item| touched[item.ord] && !has_touched_child[item.ord])
+        {
+            let span = item.span.clone();
+            for diagnostic in options.diagnostics_at(&span) {
+                item.add_diagnostic(diagnostic);
+            }
+        }
     }
 
-    fn x_center_layer(layer: usize, items: &mut EmbeddingHelperData<F>) -> Result<()> {
-        let node_ids_in_layer =
-            items
-                .0
+    ///
+    /// Inserts a pseudonode for each range of the source that no leaf covers. It hangs below the
+    /// deepest inner node whose span contains the gap or, if there is none, below the root.
+    ///
+    /// The gaps are found among the leaves of the whole tree, so that the text of the nodes that
+    /// were filtered, collapsed or pruned on purpose isn't reported as missing. Gaps outside of
+    /// the clip span or of the embedded subtree aren't drawn either.
+    ///
+    fn insert_gap_nodes(
+        tree: &Tree<T, F>,
+        items: &mut EmbeddingHelperData<F>,
+        options: &EmbedOptions<T, F>,
+        source: &str,
+    ) {
+        let mut has_children = vec![false; items.0.len()];
+        for parent in items.0.iter().filter_map(|item| item.parent) {
+            has_children[parent] = true;
+        }
+        let leaves = tree
+            .walk()
+            .filter(|node| !node.has_children())
+            .map(|node| (node.id().get(), node.range()));
+        let subtree_span = options
+            .subtree_root
+            .and_then(|root| tree.get(root))
+            .map(|root| root.range());
+        for issue in span_issues(leaves, source) {
+            let SpanIssue::Gap { span } = issue else {
+                continue;
+            };
+            if !options.is_in_clip_span(span.clone())
+                || subtree_span
+                    .as_ref()
+                    .is_some_and(|outer| span.start < outer.start || outer.end < span.end)
+            {
+                continue;
+            }
+            // The pseudonodes inserted so far are leaves and thus no parents
+            let parent = items.0[..has_children.len()]
                 .iter()
-                .enumerate()
-                .fold(Vec::new(), |mut acc, (ord, item)| {
-                    if item.y_order == layer {
-                        acc.push(ord)
-                    }
-                    acc
-                });
+                .filter(|item| {
+                    has_children[item.ord]
+                        && item.span.start <= span.start
+                        && span.end <= item.span.end
+                })
+                .max_by_key(|item| item.y_order)
+                .or_else(|| items.0.iter().find(|item| item.parent.is_none()));
+            if let Some(parent) = parent {
+                // The pseudonodes are not registered under the id they borrow from their parent
+                let gap = InternalNode::gap(items.0.len(), parent, span, source);
+                items.0.push(gap);
+            }
+        }
+    }
 
-        let parents_in_layer = node_ids_in_layer
-            .iter()
-            .map(|ord| {
-                Ok(items
-                    .get_by_ord(*ord)
-                    .ok_or(LayouterError::from_description("Expecting existing node"))?
-                    .parent)
-            })
-            .collect::<Result<Vec<Option<usize>>>>()?;
-
-        for p in parents_in_layer {
-            let nodes_in_layer_per_parent = node_ids_in_layer
-                .iter()
-                .filter_map(|ord| {
-                    if let Some(node) = items.get_by_ord(*ord) {
-                        if node.parent == p {
-                            Some(*ord)
-                        } else {
-                            None
-                        }
-                    } else {
-                        debug_assert!(false, "Expecting existing node");
-                        None
-                    }
+    fn apply_children_x_extents(items: &mut EmbeddingHelperData<F>, options: &EmbedOptions<T, F>) {
+        let mut children = vec![Vec::new(); items.0.len()];
+        for item in &items.0 {
+            if let Some(parent) = item.parent {
+                children[parent].push(item.ord);
+            }
+        }
+        for siblings in children.iter_mut() {
+            Self::order_siblings(items, siblings, options);
+        }
+        // Children have greater ords than their parents, so a reverse pass visits all children
+        // before their parent. The parents of merged chains and spliced nodes are already
+        // resolved in the items.
+        for ord in (0..items.0.len()).rev() {
+            let siblings = &children[ord];
+            let gaps = siblings
+                .windows(2)
+                .map(|pair| {
+                    options
+                        .spacing
+                        .gap(!children[pair[0]].is_empty(), !children[pair[1]].is_empty())
                 })
-                .collect::<Vec<usize>>();
+                .sum::<usize>();
+            let x_extent_of_children = siblings
+                .iter()
+                .map(|child| items.0[*child].x_extent_children)
+                .sum::<usize>()
+                + gaps;
+            let item = &mut items.0[ord];
+            item.x_extent_of_children = x_extent_of_children;
+            item.x_extent_children = std::cmp::max(item.x_extent, item.x_extent_of_children);
+        }
+    }
 
+    ///
+    /// Sorts the given siblings from left to right, i.e. by their ords or by their spans. The
+    /// pseudonodes of gaps are placed before the first sibling that starts behind them.
+    ///
+    fn order_siblings(
+        items: &EmbeddingHelperData<F>,
+        siblings: &mut [usize],
+        options: &EmbedOptions<T, F>,
+    ) {
+        if options.span_order {
+            siblings.sort_by_key(|ord| (items.0[*ord].span.start, *ord));
+        } else if siblings.iter().any(|ord| items.0[*ord].is_gap) {
+            let (gaps, mut ordered): (Vec<usize>, Vec<usize>) =
+                siblings.iter().partition(|ord| items.0[**ord].is_gap);
+            for gap in gaps {
+                let end = items.0[gap].span.end;
+                let position = ordered
+                    .iter()
+                    .position(|ord| items.0[*ord].span.start >= end)
+                    .unwrap_or(ordered.len());
+                ordered.insert(position, gap);
+            }
+            siblings.copy_from_slice(&ordered);
+        }
+    }
+
+    ///
+    /// Places the nodes of one layer. The nodes come in groups of siblings together with their
+    /// common parent, which has already been placed in the layer above.
+    ///
+    fn x_center_layer(
+        layer: usize,
+        sibling_groups: &[(Option<usize>, Vec<usize>)],
+        items: &mut EmbeddingHelperData<F>,
+        options: &EmbedOptions<T, F>,
+    ) -> Result<()> {
+        for (p, siblings) in sibling_groups {
             let mut moving_x_center = {
                 if let Some(parent_ord) = p {
-                    if let Some(placed_parent_item) = items.get_by_ord(parent_ord) {
+                    if let Some(placed_parent_item) = items.get_by_ord(*parent_ord) {
                         // We start half way left from the parents x center
                         placed_parent_item.x_center - placed_parent_item.x_extent_of_children / 2
                     } else {
                         // This really should not happen
-                        return Err(LayouterError::from_description("Some item expected here!"));
+                        return Err(LayouterError::NodeLookupFailed { ord: *parent_ord });
                     }
                 } else {
                     // `None` means we are in layer 0
                     debug_assert_eq!(layer, 0);
                     // and we should have only one root
-                    debug_assert_eq!(node_ids_in_layer.len(), 1);
+                    debug_assert_eq!(siblings.len(), 1);
                     // We start all the way left
                     0
                 }
             };
-            for ord in nodes_in_layer_per_parent {
-                if let Some(placed_item) = items.get_mut_by_ord(ord) {
+            let mut left_sibling_has_children = None;
+            for ord in siblings {
+                if let Some(placed_item) = items.get_mut_by_ord(*ord) {
+                    let has_children = placed_item.x_extent_of_children > 0;
+                    moving_x_center += left_sibling_has_children
+                        .map_or(0, |left| options.spacing.gap(left, has_children));
                     placed_item.x_center = moving_x_center + placed_item.x_extent_children / 2;
                     moving_x_center += placed_item.x_extent_children;
+                    left_sibling_has_children = Some(has_children);
+                } else {
+                    debug_assert!(false, "Expecting existing node");
                 }
             }
         }
@@ -402,29 +824,70 @@ where
         Ok(())
     }
 
-    fn apply_x_center(items: &mut EmbeddingHelperData<F>) -> Result<()> {
-        let height = items
-            .0
-            .iter()
-            .max_by(|x, y| x.y_order.cmp(&y.y_order))
-            .map(|i| i.y_order)
-            .unwrap_or_default();
-        for l in 0..height + 1 {
-            Self::x_center_layer(l, items)?;
+    fn apply_x_center(
+        items: &mut EmbeddingHelperData<F>,
+        options: &EmbedOptions<T, F>,
+    ) -> Result<()> {
+        // Bucket the siblings by their parents and the sibling groups by their layers once, so
+        // that each layer only visits its own nodes
+        let mut roots = Vec::new();
+        let mut children = vec![Vec::new(); items.0.len()];
+        for item in &items.0 {
+            match item.parent {
+                Some(parent) => children[parent].push(item.ord),
+                None => roots.push(item.ord),
+            }
+        }
+        let mut layers = vec![Vec::new()];
+        if !roots.is_empty() {
+            layers[0].push((None, roots));
+        }
+        for (parent, mut siblings) in children.into_iter().enumerate() {
+            if siblings.is_empty() {
+                continue;
+            }
+            Self::order_siblings(items, &mut siblings, options);
+            let layer = items.0[parent].y_order + 1;
+            if layers.len() <= layer {
+                layers.resize_with(layer + 1, Vec::new);
+            }
+            layers[layer].push((Some(parent), siblings));
+        }
+        for (layer, sibling_groups) in layers.iter().enumerate() {
+            Self::x_center_layer(layer, sibling_groups, items, options)?;
         }
         Ok(())
     }
 
+    /// Places all leaves on the deepest level.
+    fn align_leaves_at_bottom(embedding: &mut [EmbeddedNode]) {
+        let depth = embedding.iter().map(|node| node.y_order).max().unwrap_or(0);
+        let mut has_children = vec![false; embedding.len()];
+        for parent in embedding.iter().filter_map(|node| node.parent) {
+            has_children[parent] = true;
+        }
+        for (node, has_children) in embedding.iter_mut().zip(has_children) {
+            if !has_children {
+                node.y_order = depth;
+            }
+        }
+    }
+
+    /// Multiplies the levels by the level gap and moves the nodes away from the left border by the
+    /// margin. The root's extent includes the margins on both sides.
+    fn apply_spacing(embedding: &mut [EmbeddedNode], spacing: &Spacing) {
+        for node in embedding.iter_mut() {
+            node.y_order *= spacing.level_gap;
+            node.x_center += spacing.margin;
+            if node.parent.is_none() {
+                node.x_extent_children += 2 * spacing.margin;
+            }
+        }
+    }
+
     /// Transforming the internal `EmbeddingHelperMap` to the external representation `Embedding`.
     /// The `items` parameter is hereby consumed.
-    fn transfer_result(items: EmbeddingHelperData<F>) -> Embedding {
-        let len = items.0.len();
-        items
-            .0
-            .into_iter()
-            .fold(Embedding::with_capacity(len), |mut acc, e| {
-                acc.push(e.into());
-                acc
-            })
+    fn transfer_result(items: EmbeddingHelperData<F>) -> Vec<EmbeddedNode> {
+        items.0.into_iter().map(Into::into).collect()
     }
 }
@@ -0,0 +1,223 @@
+//! The module with the updates of an existing embedding after small edits of the tree.
+//!
+//! The updates work on the public representation of the embedding, i.e. with the levels
+//! multiplied by the level gap and the margins applied, and reproduce the results of the
+//! embedder for the layouts that are not compacted.
+
+use crate::{EmbeddedNode, LayouterError, Result, Spacing};
+
+///
+/// The layout settings that the incremental updates have to respect.
+///
+pub(crate) struct Relayout {
+    pub(crate) spacing: Spacing,
+    pub(crate) span_order: bool,
+    pub(crate) bottom_aligned: bool,
+}
+
+impl Relayout {
+    ///
+    /// Recomputes the extents of the node with the given `ord` and of its ancestors as far as
+    /// they change and places the subtree of the highest changed node again. All other nodes
+    /// keep their positions.
+    ///
+    pub(crate) fn update(&self, nodes: &mut [EmbeddedNode], ord: usize) -> Result<()> {
+        self.update_path(nodes, ord, false)
+    }
+
+    ///
+    /// Recomputes the extents from the node with the given `ord` upwards like
+    /// [update][Self::update]. If `new_children` is set the parent is recomputed in any case,
+    /// because the gaps next to the node change if it got its first children or lost its last.
+    ///
+    fn update_path(
+        &self,
+        nodes: &mut [EmbeddedNode],
+        ord: usize,
+        new_children: bool,
+    ) -> Result<()> {
+        check_ords(nodes)?;
+        let children = self.children(nodes);
+        let mut current = ord;
+        loop {
+            let x_extent_children = nodes[current]
+                .x_extent
+                .max(self.x_extent_of_children(nodes, &children, current));
+            let x_extent_children = match nodes[current].parent {
+                Some(_) => x_extent_children,
+                None => x_extent_children + 2 * self.spacing.margin,
+            };
+            let changed = nodes[current].x_extent_children != x_extent_children;
+            nodes[current].x_extent_children = x_extent_children;
+            match nodes[current].parent {
+                Some(parent) if changed || (new_children && current == ord) => current = parent,
+                _ => break,
+            }
+        }
+        if nodes[current].parent.is_none() {
+            let margin = self.spacing.margin;
+            nodes[current].x_center = margin + (nodes[current].x_extent_children - 2 * margin) / 2;
+        }
+        self.place_subtree(nodes, &children, current);
+        Ok(())
+    }
+
+    ///
+    /// Replaces the subtree of the node with the given `ord` by the nodes of the given
+    /// embedding, which take the place and the level of the replaced subtree, and updates the
+    /// layout. The nodes after the replaced subtree are renumbered.
+    ///
+    pub(crate) fn replace_subtree(
+        &self,
+        nodes: Vec<EmbeddedNode>,
+        ord: usize,
+        subtree: Vec<EmbeddedNode>,
+    ) -> Result<Vec<EmbeddedNode>> {
+        check_ords(&nodes)?;
+        check_ords(&subtree)?;
+        let Some(target) = nodes.get(ord) else {
+            return Err(LayouterError::NodeLookupFailed { ord });
+        };
+        let count = subtree.iter().filter(|node| node.parent.is_none()).count();
+        if count != 1 {
+            return Err(LayouterError::MultipleRoots { count });
+        }
+        let parent = target.parent;
+        // Bottom aligned leaves are not on the level below their parents
+        let y_order = parent.map_or(0, |p| nodes[p].y_order + self.spacing.level_gap);
+        // The new root takes the place of the old one, so that the update sees the changed extent
+        let (x_center, x_extent_children) = (target.x_center, target.x_extent_children);
+
+        // Parents precede their children, thus a single pass finds the replaced nodes. The nodes
+        // before the replaced root keep their ords, the new nodes follow it.
+        let mut replaced = vec![false; nodes.len()];
+        let mut new_ords = vec![None; nodes.len()];
+        let mut next_ord = 0;
+        for node in &nodes {
+            replaced[node.ord] = node.ord == ord || node.parent.is_some_and(|p| replaced[p]);
+            if node.ord == ord {
+                next_ord += subtree.len();
+            } else if !replaced[node.ord] {
+                new_ords[node.ord] = Some(next_ord);
+                next_ord += 1;
+            }
+        }
+        let mut subtree = Some(subtree);
+        let mut result = Vec::with_capacity(next_ord);
+        for node in nodes {
+            if node.ord == ord {
+                let new_nodes = subtree.take().unwrap_or_default();
+                result.extend(new_nodes.into_iter().map(|new_node| match new_node.parent {
+                    Some(p) => EmbeddedNode {
+                        ord: new_node.ord + ord,
+                        parent: Some(p + ord),
+                        y_order: new_node.y_order + y_order,
+                        ..new_node
+                    },
+                    None => EmbeddedNode {
+                        ord,
+                        parent,
+                        y_order,
+                        x_center,
+                        x_extent_children,
+                        ..new_node
+                    },
+                }));
+            } else if let Some(new_ord) = new_ords[node.ord] {
+                result.push(EmbeddedNode {
+                    ord: new_ord,
+                    parent: node.parent.and_then(|p| new_ords[p]),
+                    ..node
+                });
+            }
+        }
+
+        if self.bottom_aligned {
+            self.align_leaves_at_bottom(&mut result);
+        }
+        self.update_path(&mut result, ord, true)?;
+        Ok(result)
+    }
+
+    /// Places all leaves on the level below the deepest inner node.
+    fn align_leaves_at_bottom(&self, nodes: &mut [EmbeddedNode]) {
+        let mut has_children = vec![false; nodes.len()];
+        for parent in nodes.iter().filter_map(|node| node.parent) {
+            has_children[parent] = true;
+        }
+        let depth = nodes
+            .iter()
+            .filter(|node| has_children[node.ord])
+            .map(|node| node.y_order + self.spacing.level_gap)
+            .max()
+            .unwrap_or_default();
+        for node in nodes.iter_mut().filter(|node| !has_children[node.ord]) {
+            node.y_order = depth;
+        }
+    }
+
+    /// Returns the children of each node from left to right.
+    fn children(&self, nodes: &[EmbeddedNode]) -> Vec<Vec<usize>> {
+        let mut children = vec![Vec::new(); nodes.len()];
+        for node in nodes {
+            if let Some(parent) = node.parent {
+                children[parent].push(node.ord);
+            }
+        }
+        if self.span_order {
+            for siblings in children.iter_mut() {
+                siblings.sort_by_key(|ord| (nodes[*ord].span.start, *ord));
+            }
+        }
+        children
+    }
+
+    /// Returns the width of the children of the given node including the gaps between them.
+    fn x_extent_of_children(
+        &self,
+        nodes: &[EmbeddedNode],
+        children: &[Vec<usize>],
+        ord: usize,
+    ) -> usize {
+        let siblings = &children[ord];
+        let gaps = siblings
+            .windows(2)
+            .map(|pair| {
+                self.spacing
+                    .gap(!children[pair[0]].is_empty(), !children[pair[1]].is_empty())
+            })
+            .sum::<usize>();
+        siblings
+            .iter()
+            .map(|child| nodes[*child].x_extent_children)
+            .sum::<usize>()
+            + gaps
+    }
+
+    /// Places the descendants of the given node below it, the node itself keeps its position.
+    fn place_subtree(&self, nodes: &mut [EmbeddedNode], children: &[Vec<usize>], ord: usize) {
+        let mut stack = vec![ord];
+        while let Some(parent) = stack.pop() {
+            let mut moving_x_center =
+                nodes[parent].x_center - self.x_extent_of_children(nodes, children, parent) / 2;
+            let mut left_sibling_has_children = None;
+            for child in &children[parent] {
+                let has_children = !children[*child].is_empty();
+                moving_x_center += left_sibling_has_children
+                    .map_or(0, |left| self.spacing.gap(left, has_children));
+                nodes[*child].x_center = moving_x_center + nodes[*child].x_extent_children / 2;
+                moving_x_center += nodes[*child].x_extent_children;
+                left_sibling_has_children = Some(has_children);
+                stack.push(*child);
+            }
+        }
+    }
+}
+
+/// Checks that the nodes are stored at the index of their `ord`.
+fn check_ords(nodes: &[EmbeddedNode]) -> Result<()> {
+    match nodes.iter().enumerate().find(|(i, node)| node.ord != *i) {
+        Some((i, _)) => Err(LayouterError::NodeLookupFailed { ord: i }),
+        None => Ok(()),
+    }
+}
@@ -1,3 +1,10 @@
 //! Internal module with implementation details
+pub(crate) mod base64;
+pub(crate) mod color;
+pub(crate) mod compaction;
 pub(crate) mod embedder;
+pub(crate) mod incremental;
 pub(crate) mod node;
+pub(crate) mod options;
+pub(crate) mod pagination;
+pub(crate) mod streaming;
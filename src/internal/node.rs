@@ -1,9 +1,20 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 
 use syntree::{Flavor, Pointer};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    BorderStyle, ColorClass, Diagnostic, DiagnosticLevel, LabelOrientation, NodeShape, NodeStyle,
+    TextSegment,
+};
 
 pub(crate) type NodeId<F> = <F as Flavor>::Pointer;
 
+/// The number of characters of the missing text shown by the pseudonodes of gaps
+const GAP_EXCERPT_LENGTH: usize = 12;
+
 ///
 /// The [InternalNode] is the internal embedding information for one single tree node.
 ///
@@ -23,15 +34,208 @@ where
     pub(crate) x_extent_children: usize,
     /// The text representation of the nodes data - created by the `Visualize` trait's implementation
     pub(crate) text: String,
+    /// The styled segments of a rich text label possibly obtained from the `Visualize` trait
+    pub(crate) segments: Vec<TextSegment>,
     /// The *emphasize* property possibly obtained from the `Visualize` trait
     pub(crate) is_emphasized: bool,
+    /// The *diagnostic* level possibly obtained from the `Visualize` trait
+    pub(crate) diagnostic: DiagnosticLevel,
+    /// The visual style possibly obtained from the `Visualize` trait
+    pub(crate) style: NodeStyle,
+    /// The orientation of the node's label possibly obtained from the `Visualize` trait
+    pub(crate) orientation: LabelOrientation,
+    /// The number of nodes represented by this node if it is a collapsed stub, otherwise 0
+    pub(crate) collapsed: usize,
+    /// The number of sibling subtrees represented by this node if it is an aggregate, otherwise 0
+    pub(crate) aggregated: usize,
     /// The parent's `ord`, if there is one
     pub(crate) parent: Option<usize>,
     /// A unique number reflecting the depth first walk order of the nodes in the tree
     /// It is assumed that parents are inserted before their child nodes
     pub(crate) ord: usize,
-    /// Internal node id
+    /// Internal node id, the id of the last node if this node represents a merged chain
     pub(crate) node_id: NodeId<F>,
+    /// The ids of the other nodes of a merged chain
+    pub(crate) chain: Vec<NodeId<F>>,
+    /// The span of the node in the source text
+    pub(crate) span: Range<usize>,
+    /// The tooltip text possibly obtained from the `Visualize` trait
+    pub(crate) tooltip: Option<String>,
+    /// The badge text obtained from the function given to `Layouter::with_badges`
+    pub(crate) badge: Option<String>,
+    /// The node is a pseudonode for a range of the source that no leaf covers
+    pub(crate) is_gap: bool,
+    /// The node is a trivia token drawn as a narrow marker
+    pub(crate) is_trivia: bool,
+    /// The label of the group formed by the node's subtree possibly obtained from the
+    /// `Visualize` trait
+    pub(crate) group: Option<String>,
+    /// The color class assigned by the function given to `Layouter::with_color_by`
+    pub(crate) color_class: ColorClass,
+    /// The factor by which the node is drawn smaller than normal nodes
+    pub(crate) scale: f32,
+}
+
+impl<F> InternalNode<F>
+where
+    F: Flavor,
+{
+    /// Sets the scale of the node and adjusts its extents accordingly
+    pub(crate) fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        self.x_extent = self.orientation.x_extent(&self.text, scale);
+        self.x_extent_of_children = self.x_extent;
+        self.x_extent_children = self.x_extent;
+    }
+
+    ///
+    /// Creates the pseudonode with the given `ord` for a range of the source that no leaf covers.
+    /// It hangs below the given parent and shows the beginning of the missing text.
+    ///
+    pub(crate) fn gap(
+        ord: usize,
+        parent: &InternalNode<F>,
+        span: Range<usize>,
+        source: &str,
+    ) -> Self {
+        // The gap may split a character or reach behind the source
+        let bytes = source
+            .as_bytes()
+            .get(span.start.min(source.len())..span.end.min(source.len()))
+            .unwrap_or_default();
+        let missing = String::from_utf8_lossy(bytes);
+        let mut excerpt = missing.chars().take(GAP_EXCERPT_LENGTH).collect::<String>();
+        if excerpt.len() < missing.len() {
+            excerpt.push('\u{2026}');
+        }
+        let mut node = Self {
+            y_order: parent.y_order + 1,
+            text: format!("missing {:?}", excerpt),
+            style: NodeStyle {
+                border_color: Some("gray".to_string()),
+                border: BorderStyle::Dashed,
+                shape: Some(NodeShape::Rect),
+                ..Default::default()
+            },
+            parent: Some(parent.ord),
+            ord,
+            node_id: parent.node_id,
+            span,
+            is_gap: true,
+            ..Default::default()
+        };
+        node.set_scale(1.0);
+        node
+    }
+
+    ///
+    /// Replaces the label of a trivia token by the given marker of minimal width. The original
+    /// label is kept as tooltip, unless the node has its own tooltip.
+    ///
+    pub(crate) fn make_trivia(&mut self, marker: &str) {
+        let text = std::mem::replace(&mut self.text, marker.to_string());
+        self.tooltip.get_or_insert_with(|| format!("{:?}", text));
+        self.segments.clear();
+        self.is_trivia = true;
+        self.set_x_extent(1);
+    }
+
+    /// Sets the extent of the node's label, e.g. measured by the caller, and adjusts the extents
+    /// of the node accordingly
+    pub(crate) fn set_x_extent(&mut self, x_extent: usize) {
+        self.x_extent = x_extent;
+        self.x_extent_of_children = x_extent;
+        self.x_extent_children = x_extent;
+    }
+
+    /// Appends the given text to the node's label and adjusts its extents accordingly
+    pub(crate) fn append_text(&mut self, suffix: &str) {
+        self.text.push_str(suffix);
+        if !self.segments.is_empty() {
+            self.segments.push(TextSegment::plain(suffix));
+        }
+        self.set_scale(self.scale);
+    }
+
+    /// Wraps the node's horizontal label so that no line is wider than the given width and
+    /// adjusts its extents accordingly. The styled segments are dropped from wrapped labels.
+    pub(crate) fn wrap_text(&mut self, max_width: usize) {
+        if self.orientation.is_vertical() || self.text.width() <= max_width {
+            return;
+        }
+        self.text = wrap(&self.text, max_width);
+        self.segments.clear();
+        self.set_scale(self.scale);
+    }
+
+    /// Marks the node as a syntax error, drawers show it with a dashed border in their error color
+    pub(crate) fn mark_as_error(&mut self) {
+        self.diagnostic = self.diagnostic.max(DiagnosticLevel::Error);
+        self.style.border = BorderStyle::Dashed;
+    }
+
+    /// Marks the node with the given diagnostic: the node is emphasized, takes the diagnostic's
+    /// severity if it is higher than its own and shows the message in its tooltip
+    pub(crate) fn add_diagnostic(&mut self, diagnostic: &Diagnostic) {
+        self.is_emphasized = true;
+        if diagnostic.severity.is_error() {
+            self.style.border = BorderStyle::Dashed;
+        }
+        self.diagnostic = self.diagnostic.max(diagnostic.severity);
+        self.tooltip = Some(match self.tooltip.take() {
+            Some(tooltip) => format!("{}\n{}", tooltip, diagnostic.message),
+            None => diagnostic.message.clone(),
+        });
+    }
+
+    /// Sets the color class of the node, the class's color fills the node's box unless its
+    /// style has a fill of its own
+    pub(crate) fn set_color_class(&mut self, color_class: ColorClass) {
+        self.color_class = color_class;
+        if self.style.fill.is_none() {
+            self.style.fill = color_class.color().map(str::to_string);
+        }
+    }
+}
+
+///
+/// Wraps the given text at whitespace into lines that are at most `max_width` columns wide.
+/// Words that don't fit into a line of their own are truncated with an ellipsis.
+///
+pub(crate) fn wrap(text: &str, max_width: usize) -> String {
+    let max_width = max_width.max(1);
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let word = truncate(word, max_width);
+        if !line.is_empty() && line.width() + 1 + word.width() > max_width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    lines.push(line);
+    lines.join("\n")
+}
+
+/// Truncates the given word to at most `max_width` columns, the last of which is an ellipsis.
+fn truncate(word: &str, max_width: usize) -> Cow<'_, str> {
+    if word.width() <= max_width {
+        return Cow::Borrowed(word);
+    }
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in word.chars() {
+        width += c.width().unwrap_or(0);
+        if width + 1 > max_width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push('\u{2026}');
+    Cow::Owned(truncated)
 }
 
 impl<F> Default for InternalNode<F>
@@ -46,14 +250,43 @@ where
             x_extent_of_children: Default::default(),
             x_extent_children: Default::default(),
             text: Default::default(),
+            segments: Default::default(),
             is_emphasized: Default::default(),
+            diagnostic: Default::default(),
+            style: Default::default(),
+            orientation: Default::default(),
+            collapsed: Default::default(),
+            aggregated: Default::default(),
             parent: Default::default(),
             ord: Default::default(),
             node_id: F::Pointer::new(0).unwrap(),
+            chain: Default::default(),
+            span: Default::default(),
+            tooltip: Default::default(),
+            badge: Default::default(),
+            is_gap: Default::default(),
+            is_trivia: Default::default(),
+            group: Default::default(),
+            color_class: Default::default(),
+            scale: 1.0,
         }
     }
 }
 
+///
+/// The properties of a node that are obtained from the node's data besides its text
+///
+#[derive(Debug, Default)]
+pub(crate) struct NodeAttributes {
+    pub(crate) is_emphasized: bool,
+    pub(crate) diagnostic: DiagnosticLevel,
+    pub(crate) style: NodeStyle,
+    pub(crate) orientation: LabelOrientation,
+    pub(crate) tooltip: Option<String>,
+    pub(crate) segments: Vec<TextSegment>,
+    pub(crate) group: Option<String>,
+}
+
 ///
 /// Internal helper data
 ///
@@ -87,15 +320,20 @@ where
         self.1.get(node_id).and_then(|n| self.0.get(*n))
     }
 
-    pub(crate) fn get_mut_by_node_id(
-        &mut self,
-        node_id: &NodeId<F>,
-    ) -> Option<&mut InternalNode<F>> {
-        self.1.get(node_id).and_then(|n| self.0.get_mut(*n))
+    pub(crate) fn contains_node_id(&self, node_id: &NodeId<F>) -> bool {
+        self.1.contains_key(node_id)
     }
 
     pub(crate) fn insert(&mut self, ord: usize, item: InternalNode<F>) {
         self.1.insert(item.node_id, ord);
+        for node_id in &item.chain {
+            self.1.insert(*node_id, ord);
+        }
         self.0.insert(ord, item);
     }
+
+    /// Maps the given node id to the item with the given ord, e.g. for spliced nodes
+    pub(crate) fn insert_alias(&mut self, node_id: NodeId<F>, ord: usize) {
+        self.1.insert(node_id, ord);
+    }
 }
@@ -0,0 +1,240 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use syntree::{Flavor, Node};
+
+use crate::{
+    ColorClass, Diagnostic, FilterMode, FontMetrics, LayouterError, LeafAlignment, Result, Spacing,
+};
+
+/// A predicate on the data of a node
+pub(crate) type NodePredicate<T> = Rc<dyn Fn(&T) -> bool>;
+
+/// A function that assigns a color class to the data of a node
+pub(crate) type NodeClassifier<T> = Rc<dyn Fn(&T) -> ColorClass>;
+
+/// A function that measures the extent of a node from its data and its label
+pub(crate) type NodeMeasure<T> = Rc<dyn Fn(&T, &str) -> usize>;
+
+/// A function that returns the badge text of a node from its data
+pub(crate) type NodeBadge<T> = Rc<dyn Fn(&T) -> Option<String>>;
+
+/// A function that labels an aggregate node from the first aggregated value and their number
+pub(crate) type AggregateLabel<T> = Rc<dyn Fn(&T, usize) -> String>;
+
+/// A predicate on a node of the tree
+pub(crate) type NodeFilter<T, F> = Rc<dyn Fn(&Node<'_, T, F>) -> bool>;
+
+///
+/// The options of the [Layouter][crate::Layouter] that influence the embedding.
+///
+pub(crate) struct EmbedOptions<T: Copy, F: Flavor> {
+    /// Nodes for which this predicate returns true are drawn as stubs without their subtrees
+    pub(crate) collapse: Option<NodePredicate<T>>,
+    /// Only this number of children of each node is embedded, the others are summarized by a
+    /// placeholder
+    pub(crate) max_children: Option<usize>,
+    /// Only nodes whose spans intersect this range are embedded
+    pub(crate) clip_span: Option<Range<usize>>,
+    /// Nodes whose spans intersect this range are emphasized
+    pub(crate) highlight_span: Option<Range<usize>>,
+    /// The spans of the nodes are appended to their labels
+    pub(crate) span_labels: bool,
+    /// The ords of the nodes are appended to their labels as superscript
+    pub(crate) node_numbers: bool,
+    /// Sibling subtrees are moved together as close as their contours allow
+    pub(crate) compact: bool,
+    /// Chains of single-child inner nodes are merged into one node
+    pub(crate) chain_collapsing: bool,
+    /// The subtrees of nodes for which this predicate returns true are drawn at the given scale
+    pub(crate) scaled: Option<(NodePredicate<T>, f32)>,
+    /// Nodes for which this predicate returns true are dropped before the embedding
+    pub(crate) filter: Option<(NodeFilter<T, F>, FilterMode)>,
+    /// Only the subtree rooted at the node with this id is embedded
+    pub(crate) subtree_root: Option<F::Pointer>,
+    /// Runs of sibling subtrees whose roots match the predicate are grouped into aggregate nodes
+    pub(crate) aggregation: Option<(NodePredicate<T>, AggregateLabel<T>, bool)>,
+    /// Labels are wrapped at whitespace so that their lines don't exceed this width
+    pub(crate) max_label_width: Option<usize>,
+    /// The distances between the nodes
+    pub(crate) spacing: Spacing,
+    /// The level on which the leaves are placed
+    pub(crate) leaf_alignment: LeafAlignment,
+    /// Siblings are ordered by the start of their spans instead of their order in the tree
+    pub(crate) span_order: bool,
+    /// Nodes for which this predicate returns true are marked as syntax errors
+    pub(crate) error_nodes: Option<NodePredicate<T>>,
+    /// The label of nodes whose spans don't fit into the source instead of an error
+    pub(crate) source_placeholder: Option<String>,
+    /// The function that assigns the color classes to the nodes
+    pub(crate) color_by: Option<NodeClassifier<T>>,
+    /// The function that replaces the extents of the nodes' labels
+    pub(crate) measure: Option<NodeMeasure<T>>,
+    /// The metrics of the font the labels are measured with if no measure function is set
+    pub(crate) font_metrics: Option<FontMetrics>,
+    /// Ranges of the source that no leaf covers get pseudonodes
+    pub(crate) span_gaps: bool,
+    /// Nodes for which this predicate returns true are drawn as narrow markers
+    pub(crate) trivia: Option<NodePredicate<T>>,
+    /// The diagnostics that mark the nodes whose spans they touch
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    /// The function that assigns the badges to the nodes
+    pub(crate) badges: Option<NodeBadge<T>>,
+}
+
+impl<T: Copy, F: Flavor> EmbedOptions<T, F> {
+    pub(crate) fn is_collapsed(&self, value: &T) -> bool {
+        self.collapse
+            .as_ref()
+            .is_some_and(|collapse| collapse(value))
+    }
+
+    pub(crate) fn is_scaled(&self, value: &T) -> bool {
+        self.scaled
+            .as_ref()
+            .is_some_and(|(scaled, _)| scaled(value))
+    }
+
+    pub(crate) fn is_trivia(&self, value: &T) -> bool {
+        self.trivia.as_ref().is_some_and(|trivia| trivia(value))
+    }
+
+    pub(crate) fn is_error_node(&self, value: &T) -> bool {
+        self.error_nodes
+            .as_ref()
+            .is_some_and(|error_nodes| error_nodes(value))
+    }
+
+    pub(crate) fn color_class(&self, value: &T) -> ColorClass {
+        self.color_by
+            .as_ref()
+            .map_or(ColorClass::None, |color_by| color_by(value))
+    }
+
+    pub(crate) fn is_aggregated(&self, value: &T) -> bool {
+        self.aggregation
+            .as_ref()
+            .is_some_and(|(group, _, _)| group(value))
+    }
+
+    /// Returns the filter mode if the given node is dropped by the filter.
+    pub(crate) fn filter_mode(&self, node: &Node<'_, T, F>) -> Option<FilterMode> {
+        self.filter
+            .as_ref()
+            .and_then(|(filter, mode)| filter(node).then_some(*mode))
+    }
+
+    ///
+    /// Returns the text of the given span in the source. If the span is out of the bounds of the
+    /// source or not on a char boundary the placeholder is returned or, if there is none, an
+    /// error.
+    ///
+    pub(crate) fn source_text<'s>(
+        &'s self,
+        source: &'s str,
+        span: Range<usize>,
+    ) -> Result<&'s str> {
+        match (source.get(span.clone()), &self.source_placeholder) {
+            (Some(text), _) => Ok(text),
+            (None, Some(placeholder)) => Ok(placeholder),
+            (None, None) => Err(LayouterError::SourceSliceOutOfBounds { span }),
+        }
+    }
+
+    /// Checks whether the given span intersects the clip span.
+    pub(crate) fn is_in_clip_span(&self, span: Range<usize>) -> bool {
+        self.clip_span
+            .as_ref()
+            .is_none_or(|clip_span| intersects(&span, clip_span))
+    }
+
+    /// Returns the diagnostics whose spans intersect the given span.
+    pub(crate) fn diagnostics_at<'s>(
+        &'s self,
+        span: &'s Range<usize>,
+    ) -> impl Iterator<Item = &'s Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |diagnostic| intersects(span, &diagnostic.span))
+    }
+
+    /// Checks whether the given span intersects the highlight span.
+    pub(crate) fn is_highlighted(&self, span: &Range<usize>) -> bool {
+        self.highlight_span
+            .as_ref()
+            .is_some_and(|highlight_span| intersects(span, highlight_span))
+    }
+}
+
+/// Checks whether two spans intersect. Empty spans intersect if they touch the other span.
+fn intersects(span: &Range<usize>, other: &Range<usize>) -> bool {
+    if span.is_empty() || other.is_empty() {
+        span.start <= other.end && other.start <= span.end
+    } else {
+        span.start < other.end && other.start < span.end
+    }
+}
+
+impl<T: Copy, F: Flavor> Default for EmbedOptions<T, F> {
+    fn default() -> Self {
+        Self {
+            collapse: None,
+            max_children: None,
+            clip_span: None,
+            highlight_span: None,
+            span_labels: false,
+            node_numbers: false,
+            compact: false,
+            chain_collapsing: false,
+            scaled: None,
+            filter: None,
+            subtree_root: None,
+            aggregation: None,
+            max_label_width: None,
+            spacing: Spacing::new(),
+            leaf_alignment: LeafAlignment::Natural,
+            span_order: false,
+            error_nodes: None,
+            source_placeholder: None,
+            color_by: None,
+            measure: None,
+            font_metrics: None,
+            span_gaps: false,
+            trivia: None,
+            diagnostics: Vec::new(),
+            badges: None,
+        }
+    }
+}
+
+impl<T: Copy, F: Flavor> Clone for EmbedOptions<T, F> {
+    fn clone(&self) -> Self {
+        Self {
+            collapse: self.collapse.clone(),
+            max_children: self.max_children,
+            clip_span: self.clip_span.clone(),
+            highlight_span: self.highlight_span.clone(),
+            span_labels: self.span_labels,
+            node_numbers: self.node_numbers,
+            compact: self.compact,
+            chain_collapsing: self.chain_collapsing,
+            scaled: self.scaled.clone(),
+            filter: self.filter.clone(),
+            subtree_root: self.subtree_root,
+            aggregation: self.aggregation.clone(),
+            max_label_width: self.max_label_width,
+            spacing: self.spacing,
+            leaf_alignment: self.leaf_alignment,
+            span_order: self.span_order,
+            error_nodes: self.error_nodes.clone(),
+            source_placeholder: self.source_placeholder.clone(),
+            color_by: self.color_by.clone(),
+            measure: self.measure.clone(),
+            font_metrics: self.font_metrics.clone(),
+            span_gaps: self.span_gaps,
+            trivia: self.trivia.clone(),
+            diagnostics: self.diagnostics.clone(),
+            badges: self.badges.clone(),
+        }
+    }
+}
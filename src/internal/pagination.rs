@@ -0,0 +1,204 @@
+//! The module with the splitting of wide embeddings into pages.
+//!
+//! A page shows a run of neighboring sibling subtrees below the chain of their ancestors, which
+//! is repeated on every page as a stub. Subtrees that don't fit on a page on their own are split
+//! among their children in the same way.
+
+use std::collections::HashMap;
+
+use crate::{embedding::count_children, EmbeddedNode};
+
+/// The horizontal bounds of the labels of a subtree in logical units
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    left: usize,
+    right: usize,
+}
+
+impl Bounds {
+    fn of(node: &EmbeddedNode) -> Self {
+        let left = node.x_center.saturating_sub(node.x_extent / 2);
+        Self {
+            left,
+            right: left + node.x_extent,
+        }
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            left: self.left.min(other.left),
+            right: self.right.max(other.right),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.right - self.left
+    }
+}
+
+///
+/// The splitting of an embedding whose nodes are stored in depth first order, i.e. parents
+/// precede their children.
+///
+struct Paginator<'a> {
+    nodes: &'a [EmbeddedNode],
+    /// The children of each node by index, ordered from left to right
+    children: Vec<Vec<usize>>,
+    /// The bounds of the subtree of each node by index
+    bounds: Vec<Bounds>,
+    /// The margin left of the tree, kept on every page
+    margin: usize,
+    max_width: usize,
+}
+
+impl<'a> Paginator<'a> {
+    fn new(nodes: &'a [EmbeddedNode], max_width: usize) -> Self {
+        let index_of = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.ord, i))
+            .collect::<HashMap<_, _>>();
+        let mut children = vec![Vec::new(); nodes.len()];
+        for (i, node) in nodes.iter().enumerate() {
+            if let Some(parent) = node.parent.and_then(|parent| index_of.get(&parent)) {
+                children[*parent].push(i);
+            }
+        }
+        for siblings in children.iter_mut() {
+            siblings.sort_by_key(|i| nodes[*i].x_center);
+        }
+        // A reverse pass visits the children before their parents
+        let mut bounds = nodes.iter().map(Bounds::of).collect::<Vec<_>>();
+        for i in (0..nodes.len()).rev() {
+            for child in &children[i] {
+                bounds[i] = bounds[i].union(bounds[*child]);
+            }
+        }
+        let margin = bounds.iter().map(|b| b.left).min().unwrap_or_default();
+        Self {
+            nodes,
+            children,
+            bounds,
+            margin,
+            max_width,
+        }
+    }
+
+    /// Returns true if the given width fits on a page together with the margins.
+    fn fits(&self, width: usize) -> bool {
+        width + 2 * self.margin <= self.max_width
+    }
+
+    ///
+    /// Splits the subtrees of the given siblings below the given chain of ancestors into pages
+    /// and appends them to `pages`.
+    ///
+    fn split(
+        &self,
+        ancestors: &mut Vec<usize>,
+        siblings: &[usize],
+        pages: &mut Vec<Vec<EmbeddedNode>>,
+    ) {
+        let mut run: Vec<usize> = Vec::new();
+        let mut run_bounds: Option<Bounds> = None;
+        for &child in siblings {
+            let bounds = self.bounds[child];
+            if let Some(extended) = run_bounds.map(|run_bounds| run_bounds.union(bounds)) {
+                if self.fits(extended.width()) {
+                    run.push(child);
+                    run_bounds = Some(extended);
+                    continue;
+                }
+                pages.push(self.page(ancestors, &run));
+                run.clear();
+                run_bounds = None;
+            }
+            if self.fits(bounds.width()) || self.children[child].is_empty() {
+                run.push(child);
+                run_bounds = Some(bounds);
+            } else {
+                ancestors.push(child);
+                self.split(ancestors, &self.children[child], pages);
+                ancestors.pop();
+            }
+        }
+        if !run.is_empty() {
+            pages.push(self.page(ancestors, &run));
+        }
+    }
+
+    ///
+    /// Creates the page with the given chain of ancestors above the subtrees of the given run of
+    /// siblings. The run is moved to the left margin and the ancestors are centered above it.
+    ///
+    fn page(&self, ancestors: &[usize], run: &[usize]) -> Vec<EmbeddedNode> {
+        let run_bounds = run
+            .iter()
+            .map(|i| self.bounds[*i])
+            .reduce(Bounds::union)
+            .unwrap_or(Bounds { left: 0, right: 0 });
+        let width = ancestors
+            .iter()
+            .map(|i| self.nodes[*i].x_extent)
+            .fold(run_bounds.width(), usize::max);
+        let shift = self.margin + (width - run_bounds.width()) / 2;
+
+        let mut page: Vec<EmbeddedNode> = Vec::new();
+        let mut new_ords = HashMap::new();
+        for (depth, &i) in ancestors.iter().enumerate() {
+            let node = &self.nodes[i];
+            new_ords.insert(node.ord, depth);
+            page.push(EmbeddedNode {
+                ord: depth,
+                parent: depth.checked_sub(1),
+                x_center: self.margin + width / 2,
+                x_extent_children: if depth == 0 {
+                    width + 2 * self.margin
+                } else {
+                    width
+                },
+                ..node.clone()
+            });
+        }
+        let mut stack = run.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            let ord = page.len();
+            new_ords.insert(node.ord, ord);
+            page.push(EmbeddedNode {
+                ord,
+                parent: node
+                    .parent
+                    .and_then(|parent| new_ords.get(&parent).copied()),
+                x_center: node.x_center - run_bounds.left + shift,
+                ..node.clone()
+            });
+            stack.extend(self.children[i].iter().rev());
+        }
+        count_children(&mut page);
+        page
+    }
+}
+
+///
+/// Splits the given nodes into pages that are at most `max_width` logical units wide, if
+/// possible. Returns a single page with all nodes if the tree fits.
+///
+pub(crate) fn paginate(nodes: &[EmbeddedNode], max_width: usize) -> Vec<Vec<EmbeddedNode>> {
+    let paginator = Paginator::new(nodes, max_width);
+    let mut roots = (0..nodes.len())
+        .filter(|i| nodes[*i].parent.is_none())
+        .collect::<Vec<_>>();
+    roots.sort_by_key(|i| nodes[*i].x_center);
+    let fits = roots
+        .iter()
+        .map(|i| paginator.bounds[*i])
+        .reduce(Bounds::union)
+        .is_none_or(|bounds| paginator.fits(bounds.width()));
+    if fits {
+        return vec![nodes.to_vec()];
+    }
+    let mut pages = Vec::new();
+    paginator.split(&mut Vec::new(), &roots, &mut pages);
+    pages
+}
@@ -0,0 +1,175 @@
+//! The module with the low-memory embedding of huge trees.
+
+use std::fmt::{self, Write as _};
+
+use syntree::{Flavor, Pointer, Tree};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{EmbeddedNode, LayouterError, Result, Spacing, TextSegment};
+
+use super::{embedder::Wrapper, node::NodeAttributes};
+
+///
+/// A `fmt::Write` implementation that only measures the written text in terminal columns, thus
+/// the width of a label is obtained without materializing the label.
+///
+#[derive(Default)]
+struct WidthCounter(usize);
+
+impl fmt::Write for WidthCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.width();
+        Ok(())
+    }
+}
+
+///
+/// Embeds the nodes of the given tree in two passes over the tree and hands them to the sink one
+/// after another in depth first order, i.e. parents before their children.
+///
+/// The first pass only measures the labels and keeps a few numbers per node, the second pass
+/// creates the labels again and passes each node to the sink, which can write it out and drop
+/// it. The resulting nodes equal the nodes of the regular embedding without any options except
+/// the spacing.
+///
+pub(crate) fn stream<T, F>(
+    tree: &Tree<T, F>,
+    spacing: &Spacing,
+    stringify: impl Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    attributes: impl Fn(&T) -> NodeAttributes,
+    mut sink: impl FnMut(EmbeddedNode) -> Result<()>,
+) -> Result<()>
+where
+    T: Copy,
+    F: Flavor,
+{
+    let count = tree.children().count();
+    if count > 1 {
+        return Err(LayouterError::MultipleRoots { count });
+    }
+    let len = tree.len();
+    // The ords of the nodes by their pointers
+    let mut ords = vec![0; len];
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(len);
+    let mut x_extents = Vec::with_capacity(len);
+    let mut has_children = vec![false; len];
+
+    // First pass: the structure and the extents of the labels
+    for (ord, node) in tree.walk().enumerate() {
+        ords[node.id().get()] = ord;
+        let parent = node.parent().map(|parent| ords[parent.id().get()]);
+        if let Some(parent) = parent {
+            has_children[parent] = true;
+        }
+        parents.push(parent);
+
+        let value = node.value();
+        let attributes = attributes(&value);
+        let mut counter = WidthCounter::default();
+        if attributes.segments.is_empty() {
+            write!(counter, "{}", Wrapper(&stringify, &value)).map_err(|_| {
+                LayouterError::from_description("Formatting the node failed")
+                    .at_node(ord, node.range())
+            })?;
+        } else {
+            counter.0 = TextSegment::concat(&attributes.segments).width();
+        }
+        x_extents.push(if attributes.orientation.is_vertical() {
+            2
+        } else {
+            counter.0 + 1
+        });
+    }
+
+    // Children have greater ords than their parents, so a reverse pass visits all children
+    // before their parent
+    let mut x_extents_of_children = vec![0; len];
+    let mut x_extents_children = vec![0; len];
+    // Whether the right neighbor of the currently visited child of each parent has children
+    let mut right_sibling_has_children: Vec<Option<bool>> = vec![None; len];
+    for ord in (0..len).rev() {
+        x_extents_children[ord] = x_extents[ord].max(x_extents_of_children[ord]);
+        if let Some(parent) = parents[ord] {
+            let gap = right_sibling_has_children[parent]
+                .map_or(0, |right| spacing.gap(has_children[ord], right));
+            x_extents_of_children[parent] += x_extents_children[ord] + gap;
+            right_sibling_has_children[parent] = Some(has_children[ord]);
+        }
+    }
+    drop(right_sibling_has_children);
+
+    // Parents precede their children, so a forward pass places the parents first. Each parent
+    // keeps the position at which its next child is placed.
+    let mut x_centers = vec![0; len];
+    let mut next_positions = vec![0; len];
+    let mut left_sibling_has_children: Vec<Option<bool>> = vec![None; len];
+    for ord in 0..len {
+        let position = match parents[ord] {
+            Some(parent) => {
+                let gap = left_sibling_has_children[parent]
+                    .map_or(0, |left| spacing.gap(left, has_children[ord]));
+                left_sibling_has_children[parent] = Some(has_children[ord]);
+                next_positions[parent] + gap
+            }
+            None => 0,
+        };
+        x_centers[ord] = position + x_extents_children[ord] / 2;
+        next_positions[ord] = x_centers[ord] - x_extents_of_children[ord] / 2;
+        if let Some(parent) = parents[ord] {
+            next_positions[parent] = position + x_extents_children[ord];
+        }
+    }
+    drop(left_sibling_has_children);
+    drop(next_positions);
+    drop(x_extents_of_children);
+
+    // Second pass: create the nodes with their labels and hand them to the sink
+    for ((ord, (depth, node)), parent) in tree.walk().with_depths().enumerate().zip(parents) {
+        let value = node.value();
+        let NodeAttributes {
+            is_emphasized,
+            diagnostic,
+            style,
+            orientation,
+            tooltip,
+            segments,
+            group,
+        } = attributes(&value);
+        let text = if segments.is_empty() {
+            let mut text = String::new();
+            write!(text, "{}", Wrapper(&stringify, &value)).map_err(|_| {
+                LayouterError::from_description("Formatting the node failed")
+                    .at_node(ord, node.range())
+            })?;
+            text
+        } else {
+            TextSegment::concat(&segments)
+        };
+        let margins = if parent.is_none() {
+            2 * spacing.margin
+        } else {
+            0
+        };
+        sink(EmbeddedNode {
+            y_order: depth as usize * spacing.level_gap,
+            x_center: x_centers[ord] + spacing.margin,
+            x_extent: x_extents[ord],
+            x_extent_children: x_extents_children[ord] + margins,
+            text,
+            segments,
+            is_emphasized,
+            diagnostic,
+            style,
+            orientation,
+            parent,
+            child_count: node.children().count(),
+            ord,
+            node_id: node.id().get(),
+            span: node.range(),
+            tooltip,
+            group,
+            ..Default::default()
+        })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! The module with the drawer for JSON documents.
+
+use std::fs;
+
+use crate::{DrawContext, Drawer, EmbeddedNode, LayouterError, Result};
+
+///
+/// The `JsonDrawer` type writes the embedding as a JSON array of the embedded nodes. Front-end
+/// tree viewers, e.g. based on d3.js, can consume the layout directly, and the embedding can be
+/// read back with `serde_json` into an [Embedding][crate::Embedding].
+///
+/// This drawer is only available with the `serde` feature.
+///
+/// ```
+/// use syntree_layout::{EmbeddedNode, Embedding, JsonDrawer};
+///
+/// let embedding = vec![
+///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+/// ];
+/// let json = JsonDrawer::new().render_string(&embedding).unwrap();
+/// let read_back: Embedding = serde_json::from_str(&json).unwrap();
+/// assert_eq!(Some(0), read_back[1].parent);
+/// assert_eq!("leaf", read_back[1].text);
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct JsonDrawer {
+    pretty: bool,
+}
+
+impl JsonDrawer {
+    /// Method to create a fresh instance of the `JsonDrawer` type.
+    pub const fn new() -> Self {
+        Self { pretty: true }
+    }
+
+    /// Sets whether the JSON is indented for readability, which is the default.
+    pub fn with_pretty(self, pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    /// Renders the given embedding into a JSON document and returns it as a `String`.
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        let json = if self.pretty {
+            serde_json::to_string_pretty(embedding)
+        } else {
+            serde_json::to_string(embedding)
+        };
+        json.map_err(|e| LayouterError::from_description(&e.to_string()))
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `JsonDrawer`.
+///
+impl Drawer for JsonDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let json = self.render_string(ctx.embedding())?;
+        fs::write(ctx.file_name(), json).map_err(LayouterError::from_io_error)
+    }
+}
@@ -0,0 +1,326 @@
+//! The module with the reusable configuration of layouters.
+
+use std::rc::Rc;
+
+use syntree::{Flavor, Node, Tree};
+
+use crate::{
+    internal::options::EmbedOptions, layouter::has_error_label, ColorClass, Drawer, FilterMode,
+    FontMetrics, Layouter, LeafAlignment, Profile, Spacing, SvgDrawer, SvgDrawerStyle, Visualize,
+    WriteOptions,
+};
+
+///
+/// The `LayoutConfig` holds the settings of a [Layouter] that don't depend on a particular tree,
+/// i.e. the drawer, the write options and the options of the embedding. It is constructed once
+/// and applied to many trees with [LayoutConfig::layout], e.g. in batch jobs that render the
+/// trees of a whole test suite.
+///
+/// Settings that refer to positions in a particular tree, like the subtree to embed or the span
+/// to clip, are set on the [Layouter] returned by [LayoutConfig::layout].
+///
+/// ```
+/// use std::fmt;
+/// use syntree_layout::{LayoutConfig, Spacing, Visualize};
+/// use syntree::{Tree, Builder};
+///
+/// #[derive(Copy, Clone, Debug)]
+/// struct MyNodeData(i32);
+///
+/// impl Visualize for MyNodeData {
+///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+/// }
+///
+/// let config = LayoutConfig::new()
+///     .with_compact(true)
+///     .with_spacing(Spacing { sibling_gap: 1, ..Spacing::new() })
+///     .with_collapsed(|value: &MyNodeData| value.0 < 0);
+///
+/// std::fs::create_dir_all("target/tmp").unwrap();
+/// for case in 0..3 {
+///     let mut builder = Builder::new();
+///     builder.open(MyNodeData(case)).unwrap();
+///     builder.token(MyNodeData(case + 1), 1).unwrap();
+///     builder.close().unwrap();
+///     let tree = builder.build().unwrap();
+///     let path = format!("target/tmp/config_{}.svg", case);
+///     config
+///         .layout(&tree)
+///         .with_file_path(&path)
+///         .embed_with_visualize()
+///         .unwrap()
+///         .write()
+///         .unwrap();
+/// }
+/// ```
+///
+pub struct LayoutConfig<'a, T, F, D = SvgDrawer>
+where
+    T: Copy,
+    F: Flavor,
+    D: Drawer,
+{
+    drawer: &'a D,
+    drawer_style: Option<&'a SvgDrawerStyle>,
+    write_options: WriteOptions,
+    options: EmbedOptions<T, F>,
+}
+
+impl<T, F> LayoutConfig<'_, T, F, SvgDrawer>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// Creates a configuration with the crate's svg-drawer and the default options.
+    pub fn new() -> Self {
+        static DEFAULT_DRAWER: SvgDrawer = SvgDrawer::new();
+
+        Self {
+            drawer: &DEFAULT_DRAWER,
+            drawer_style: None,
+            write_options: WriteOptions::default(),
+            options: EmbedOptions::default(),
+        }
+    }
+
+    ///
+    /// Applies the given profile, i.e. the options of the embedding and the svg-drawer with the
+    /// profile's style, see [Layouter::with_profile].
+    ///
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        profile.apply(&mut self.options);
+        let drawer = profile.drawer();
+        Self {
+            drawer,
+            drawer_style: Some(drawer.style()),
+            ..self
+        }
+    }
+}
+
+impl<T, F> Default for LayoutConfig<'_, T, F, SvgDrawer>
+where
+    T: Copy,
+    F: Flavor,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, F, D> Clone for LayoutConfig<'_, T, F, D>
+where
+    T: Copy,
+    F: Flavor,
+    D: Drawer,
+{
+    fn clone(&self) -> Self {
+        Self {
+            drawer: self.drawer,
+            drawer_style: self.drawer_style,
+            write_options: self.write_options,
+            options: self.options.clone(),
+        }
+    }
+}
+
+impl<'a, T, F, D> LayoutConfig<'a, T, F, D>
+where
+    T: Copy,
+    F: Flavor,
+    D: Drawer,
+{
+    ///
+    /// Creates a [Layouter] for the given tree with this configuration. The layouter still has to
+    /// embed the tree before it can be written.
+    ///
+    pub fn layout<'t>(&self, tree: &'t Tree<T, F>) -> Layouter<'t, T, F, D>
+    where
+        'a: 't,
+    {
+        let layouter = Layouter::new(tree)
+            .with_drawer(self.drawer)
+            .with_write_options(self.write_options)
+            .with_options(self.options.clone());
+        match self.drawer_style {
+            Some(style) => layouter.with_drawer_style(style),
+            None => layouter,
+        }
+    }
+
+    /// Sets the drawer, see [Layouter::with_drawer].
+    pub fn with_drawer<U>(self, drawer: &'a U) -> LayoutConfig<'a, T, F, U>
+    where
+        U: Drawer,
+    {
+        LayoutConfig {
+            drawer,
+            drawer_style: self.drawer_style,
+            write_options: self.write_options,
+            options: self.options,
+        }
+    }
+
+    /// Sets the style that drawers receive, see [Layouter::with_drawer_style].
+    pub fn with_drawer_style(self, style: &'a SvgDrawerStyle) -> Self {
+        Self {
+            drawer_style: Some(style),
+            ..self
+        }
+    }
+
+    /// Sets how the output files are written, see [Layouter::with_write_options].
+    pub fn with_write_options(self, write_options: WriteOptions) -> Self {
+        Self {
+            write_options,
+            ..self
+        }
+    }
+
+    /// Sets the predicate of the collapsed nodes, see [Layouter::with_collapsed].
+    pub fn with_collapsed(mut self, collapse: impl Fn(&T) -> bool + 'static) -> Self {
+        self.options.collapse = Some(Rc::new(collapse));
+        self
+    }
+
+    /// Limits the number of embedded children, see [Layouter::with_max_children].
+    pub fn with_max_children(mut self, max_children: usize) -> Self {
+        self.options.max_children = Some(max_children);
+        self
+    }
+
+    /// Sets whether the spans are appended to the labels, see [Layouter::with_span_labels].
+    pub fn with_span_labels(mut self, span_labels: bool) -> Self {
+        self.options.span_labels = span_labels;
+        self
+    }
+
+    /// Sets whether the nodes are numbered, see [Layouter::with_node_numbers].
+    pub fn with_node_numbers(mut self, node_numbers: bool) -> Self {
+        self.options.node_numbers = node_numbers;
+        self
+    }
+
+    /// Sets the width at which labels are wrapped, see [Layouter::with_max_label_width].
+    pub fn with_max_label_width(mut self, max_label_width: usize) -> Self {
+        self.options.max_label_width = Some(max_label_width);
+        self
+    }
+
+    /// Sets the label of nodes outside of the source, see [Layouter::with_source_placeholder].
+    pub fn with_source_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.options.source_placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// Sets whether subtrees are packed by their contours, see [Layouter::with_compact].
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.options.compact = compact;
+        self
+    }
+
+    /// Sets the distances between the nodes, see [Layouter::with_spacing].
+    pub fn with_spacing(mut self, spacing: Spacing) -> Self {
+        self.options.spacing = spacing.normalized();
+        self
+    }
+
+    /// Sets the level of the leaves, see [Layouter::with_leaf_alignment].
+    pub fn with_leaf_alignment(mut self, leaf_alignment: LeafAlignment) -> Self {
+        self.options.leaf_alignment = leaf_alignment;
+        self
+    }
+
+    /// Sets whether siblings are ordered by their spans, see [Layouter::with_span_order].
+    pub fn with_span_order(mut self, span_order: bool) -> Self {
+        self.options.span_order = span_order;
+        self
+    }
+
+    /// Sets the predicate of the error nodes, see [Layouter::with_error_nodes].
+    pub fn with_error_nodes(mut self, error_nodes: impl Fn(&T) -> bool + 'static) -> Self {
+        self.options.error_nodes = Some(Rc::new(error_nodes));
+        self
+    }
+
+    /// Marks the nodes with error labels as errors, see [Layouter::with_error_nodes_by_label].
+    pub fn with_error_nodes_by_label(self) -> Self
+    where
+        T: Visualize,
+    {
+        self.with_error_nodes(|value: &T| has_error_label(value))
+    }
+
+    /// Sets the function that colors the nodes, see [Layouter::with_color_by].
+    pub fn with_color_by(mut self, color_by: impl Fn(&T) -> ColorClass + 'static) -> Self {
+        self.options.color_by = Some(Rc::new(color_by));
+        self
+    }
+
+    /// Sets the function that measures the labels, see [Layouter::with_measure].
+    pub fn with_measure(mut self, measure: impl Fn(&T, &str) -> usize + 'static) -> Self {
+        self.options.measure = Some(Rc::new(measure));
+        self
+    }
+
+    /// Sets the predicate of the trivia nodes, see [Layouter::with_trivia].
+    pub fn with_trivia(mut self, trivia: impl Fn(&T) -> bool + 'static) -> Self {
+        self.options.trivia = Some(Rc::new(trivia));
+        self
+    }
+
+    /// Sets whether uncovered source gets pseudonodes, see [Layouter::with_span_gaps].
+    pub fn with_span_gaps(mut self, span_gaps: bool) -> Self {
+        self.options.span_gaps = span_gaps;
+        self
+    }
+
+    /// Sets the metrics the labels are measured with, see [Layouter::with_font_metrics].
+    pub fn with_font_metrics(mut self, metrics: FontMetrics) -> Self {
+        self.options.font_metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the function that assigns the badges, see [Layouter::with_badges].
+    pub fn with_badges(mut self, badges: impl Fn(&T) -> Option<String> + 'static) -> Self {
+        self.options.badges = Some(Rc::new(badges));
+        self
+    }
+
+    /// Sets whether chains of inner nodes are merged, see [Layouter::with_chain_collapsing].
+    pub fn with_chain_collapsing(mut self, chain_collapsing: bool) -> Self {
+        self.options.chain_collapsing = chain_collapsing;
+        self
+    }
+
+    /// Sets the subtrees drawn at a smaller scale, see [Layouter::with_scaled_subtrees].
+    pub fn with_scaled_subtrees(
+        mut self,
+        scaled: impl Fn(&T) -> bool + 'static,
+        scale: f32,
+    ) -> Self {
+        self.options.scaled = Some((Rc::new(scaled), scale));
+        self
+    }
+
+    /// Sets the aggregation of sibling runs, see [Layouter::with_aggregation].
+    pub fn with_aggregation(
+        mut self,
+        group: impl Fn(&T) -> bool + 'static,
+        label: impl Fn(&T, usize) -> String + 'static,
+        expandable: bool,
+    ) -> Self {
+        self.options.aggregation = Some((Rc::new(group), Rc::new(label), expandable));
+        self
+    }
+
+    /// Sets the filter of the nodes, see [Layouter::with_filter].
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Node<'_, T, F>) -> bool + 'static,
+        mode: FilterMode,
+    ) -> Self {
+        self.options.filter = Some((Rc::new(filter), mode));
+        self
+    }
+}
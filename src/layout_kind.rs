@@ -0,0 +1,19 @@
+//! The module with the kinds of tree layouts.
+
+///
+/// The `LayoutKind` determines how the logical coordinates of the embedding are mapped into the
+/// plane by the [SvgDrawer][crate::SvgDrawer], see
+/// [SvgDrawer::with_layout_kind][crate::SvgDrawer::with_layout_kind].
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LayoutKind {
+    /// The root is at the top and each level of the tree is drawn one row below its parent level
+    #[default]
+    Layered,
+    /// The root is in the center and the depth of a node maps to its distance from the center.
+    /// The siblings are spread over angular ranges according to the extents of their subtrees and
+    /// are connected to their parents by curved edges.
+    /// Very deep but narrow trees, e.g. of expression grammars, are visualized much better this
+    /// way.
+    Radial,
+}
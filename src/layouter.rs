@@ -1,18 +1,66 @@
 //! The module with the **Public API**.
 
 use std::fmt::{self, Debug, Display};
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
-use syntree::Flavor;
-use syntree::Tree;
+use syntree::{Flavor, Pointer};
+use syntree::{Node, Tree};
+
+use std::rc::Rc;
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    internal::embedder::Embedder, Drawer, Embedding, LayouterError, Result, SvgDrawer, Visualize,
+    internal::{
+        embedder::Embedder,
+        incremental::Relayout,
+        node::{wrap, NodeAttributes},
+        options::EmbedOptions,
+        streaming,
+    },
+    ColorClass, Diagnostic, DrawContext, Drawer, DrawerRegistry, EmbeddedNode, Embedding,
+    FilterMode, FontMetrics, LayouterError, LeafAlignment, Profile, Result, Spacing, SvgDrawer,
+    SvgDrawerStyle, TreeDiff, TreeStats, Visualize, WriteOptions,
 };
 
 ///
 /// The Layouter type provides a simple builder mechanism with a fluent API.
 ///
+/// The bound `T: Copy` is inherited from [Tree], which stores the node data by value and hands
+/// out copies of it. The layouter itself only accesses the data by reference. Node data with
+/// owned payloads like `String` can be kept outside of the tree, which then holds copyable
+/// handles instead, e.g. slices of the source text or indices into an arena.
+///
+/// ```
+/// use std::fmt;
+/// use syntree_layout::{Layouter, Visualize};
+/// use syntree::Builder;
+///
+/// #[derive(Copy, Clone, Debug)]
+/// enum Token<'a> {
+///     Call,
+///     Ident(&'a str),
+/// }
+///
+/// impl Visualize for Token<'_> {
+///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         match self {
+///             Token::Call => write!(f, "call"),
+///             Token::Ident(name) => write!(f, "ident {}", name),
+///         }
+///     }
+/// }
+///
+/// let names = vec!["print".to_string()];
+/// let mut builder = Builder::new();
+/// builder.open(Token::Call).unwrap();
+/// builder.token(Token::Ident(&names[0]), 5).unwrap();
+/// builder.close().unwrap();
+/// let tree = builder.build().unwrap();
+/// let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+/// assert_eq!("ident print", layouter.embedding()[1].text);
+/// ```
+///
 pub struct Layouter<'a, T, F, D>
 where
     T: Copy,
@@ -22,20 +70,1514 @@ where
     tree: &'a Tree<T, F>,
     drawer: &'a D,
     file_name: Option<&'a Path>,
+    title: Option<&'a str>,
+    source: Option<&'a str>,
+    drawer_style: Option<&'a SvgDrawerStyle>,
+    write_options: WriteOptions,
+    options: EmbedOptions<T, F>,
     embedding: Embedding,
 }
 
-impl<'a, T, F> Layouter<'a, T, F, SvgDrawer>
-where
-    T: Copy,
-    F: Flavor,
-{
+impl<'a, T, F> Layouter<'a, T, F, SvgDrawer>
+where
+    T: Copy,
+    F: Flavor,
+{
+    ///
+    /// Creates a new Layouter with the required tree.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    ///     fn emphasize(&self) -> bool { false }
+    /// }
+    ///
+    ///
+    /// let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
+    /// let layouter = Layouter::new(&tree);
+    /// ```
+    ///
+    pub fn new(tree: &'a Tree<T, F>) -> Self {
+        static DEFAULT_DRAWER: SvgDrawer = SvgDrawer::new();
+
+        Self {
+            tree,
+            drawer: &DEFAULT_DRAWER,
+            file_name: None,
+            title: None,
+            source: None,
+            drawer_style: None,
+            write_options: WriteOptions::default(),
+            options: EmbedOptions::default(),
+            embedding: Embedding::default(),
+        }
+    }
+
+    ///
+    /// Renders the embedding with the layouter's svg-drawer and returns the SVG document as a
+    /// `String` instead of writing it to a file.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize, Result};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    ///     fn emphasize(&self) -> bool { false }
+    /// }
+    ///
+    /// fn test() -> Result<String> {
+    ///     let mut builder = Builder::new();
+    ///     builder.open(MyNodeData(0)).unwrap();
+    ///     builder.close().unwrap();
+    ///     let tree = builder.build().unwrap();
+    ///     Layouter::new(&tree)
+    ///         .embed_with_visualize()?
+    ///         .to_svg_string()
+    /// }
+    ///
+    /// assert!(test().expect("Rendering should work").contains("<text"));
+    /// ```
+    ///
+    pub fn to_svg_string(&self) -> Result<String> {
+        self.drawer.render_string(&self.embedding)
+    }
+
+    ///
+    /// Returns the width and the height in pixels of the SVG document the layouter's svg-drawer
+    /// would write for the embedding, e.g. to choose a scale or a pagination before writing.
+    /// The tree must have been embedded before.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    /// let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    /// let (width, height) = layouter.pixel_size();
+    /// assert!(width > 0.0 && height > 0.0);
+    /// ```
+    ///
+    pub fn pixel_size(&self) -> (f32, f32) {
+        self.drawer.document_size(&self.embedding)
+    }
+
+    ///
+    /// Compares two trees node by node, e.g. the parser output before and after a grammar
+    /// change. Both trees are embedded with the default options and their differing nodes are
+    /// colored, see [TreeDiff] for how the nodes are matched.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{DiffKind, Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug, PartialEq)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("expr")).unwrap();
+    /// builder.token(MyNodeData("num"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let before = builder.build().unwrap();
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("expr")).unwrap();
+    /// builder.token(MyNodeData("ident"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let after = builder.build().unwrap();
+    ///
+    /// let diff = Layouter::diff(&before, &after).unwrap();
+    /// assert_eq!(&[DiffKind::Unchanged, DiffKind::Changed], diff.before_kinds());
+    /// assert_eq!(&[DiffKind::Unchanged, DiffKind::Changed], diff.after_kinds());
+    /// assert!(!diff.is_unchanged());
+    /// ```
+    ///
+    pub fn diff(before: &'a Tree<T, F>, after: &'a Tree<T, F>) -> Result<TreeDiff>
+    where
+        T: PartialEq + Visualize,
+    {
+        let embed = |tree| {
+            Layouter::new(tree)
+                .embed_with_visualize()
+                .map(|layouter| layouter.embedding)
+        };
+        Ok(TreeDiff::compute(
+            before,
+            embed(before)?,
+            after,
+            embed(after)?,
+        ))
+    }
+
+    ///
+    /// Applies the given profile, i.e. the layout, the spacing and the width of the labels of
+    /// the embedding as well as the style of the svg-drawer, see [Profile]. The layouter draws
+    /// with an [SvgDrawer] of the profile's style, which also becomes the
+    /// [drawer style][Self::with_drawer_style] of other drawers set afterwards. Individual
+    /// options can be changed after the profile has been applied.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter, Profile};
+    ///
+    /// let tree = from_text("(Call (Path std mem replace) (Args (Ref target) (Lit 0)))").unwrap();
+    /// let classic = Layouter::new(&tree).embed().unwrap();
+    /// let compact = Layouter::new(&tree).with_profile(Profile::Compact).embed().unwrap();
+    /// assert!(compact.bounds().0 < classic.bounds().0);
+    /// assert!(compact.to_svg_string().unwrap().contains("font-size: 12px"));
+    /// ```
+    ///
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        profile.apply(&mut self.options);
+        let drawer = profile.drawer();
+        Self {
+            drawer,
+            drawer_style: Some(drawer.style()),
+            ..self
+        }
+    }
+}
+
+impl<'a, T, F, D> Layouter<'a, T, F, D>
+where
+    T: Copy,
+    F: Flavor,
+    D: ?Sized + Drawer,
+{
+    ///
+    /// Sets the path of the output file on the layouter.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    ///     fn emphasize(&self) -> bool { false }
+    /// }
+    ///
+    ///
+    /// let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_file_path("target/tmp/test.svg");
+    /// ```
+    ///
+    pub fn with_file_path<P>(self, path: &'a P) -> Self
+    where
+        P: ?Sized + AsRef<Path>,
+    {
+        Self {
+            file_name: Some(path.as_ref()),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets the options that control how the output files are written, e.g. to create missing
+    /// parent directories or to keep existing files.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{ErrorKind, Layouter, OverwritePolicy, Visualize, WriteOptions};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.token(MyNodeData(0), 1).unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let _ = std::fs::remove_dir_all("target/tmp/write_options");
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_file_path("target/tmp/write_options/nested/tree.svg")
+    ///     .with_write_options(WriteOptions::new().with_create_dirs(true))
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// layouter.write().unwrap();
+    ///
+    /// let layouter = layouter
+    ///     .with_write_options(WriteOptions::new().with_overwrite(OverwritePolicy::Fail));
+    /// assert_eq!(ErrorKind::OutputFileExists, layouter.write().unwrap_err().kind());
+    /// ```
+    ///
+    pub fn with_write_options(self, write_options: WriteOptions) -> Self {
+        Self {
+            write_options,
+            ..self
+        }
+    }
+
+    ///
+    /// Sets the title of the drawing, which drawers receive in their
+    /// [DrawContext][crate::DrawContext]. The [HtmlDrawer][crate::HtmlDrawer] uses it as title
+    /// of the page.
+    ///
+    pub fn with_title(self, title: &'a str) -> Self {
+        Self {
+            title: Some(title),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets the source text the tree was parsed from, which drawers receive in their
+    /// [DrawContext][crate::DrawContext].
+    ///
+    pub fn with_source(self, source: &'a str) -> Self {
+        Self {
+            source: Some(source),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets the style that drawers receive in their [DrawContext][crate::DrawContext]. This is
+    /// meant for third-party drawers, the crate's own drawers are configured directly.
+    ///
+    pub fn with_drawer_style(self, style: &'a SvgDrawerStyle) -> Self {
+        Self {
+            drawer_style: Some(style),
+            ..self
+        }
+    }
+
+    /// Creates the context for drawing the embedding into the given file.
+    fn draw_context<'c>(
+        &'c self,
+        file_name: &'c Path,
+        embedding: &'c [EmbeddedNode],
+    ) -> DrawContext<'c> {
+        let mut ctx = DrawContext::new(file_name, embedding);
+        if let Some(title) = self.title {
+            ctx = ctx.with_title(title);
+        }
+        if let Some(source) = self.source {
+            ctx = ctx.with_source(source);
+        }
+        if let Some(style) = self.drawer_style {
+            ctx = ctx.with_style(style);
+        }
+        ctx
+    }
+
+    ///
+    /// Sets a different drawer when you don't want to use the default svg-drawer.
+    /// If this method is not called the crate's own svg-drawer is used.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{DrawContext, Drawer, Layouter, Result, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// struct NilDrawer;
+    /// impl Drawer for NilDrawer {
+    ///     fn draw(&self, _ctx: &DrawContext<'_>) -> Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    ///     fn emphasize(&self) -> bool { false }
+    /// }
+    ///
+    ///
+    /// let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
+    /// let drawer = NilDrawer;
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_drawer(&drawer)
+    ///     .with_file_path("target/tmp/test.svg");
+    /// ```
+    ///
+    pub fn with_drawer<U>(self, drawer: &'a U) -> Layouter<'a, T, F, U>
+    where
+        U: Drawer,
+    {
+        Layouter {
+            tree: self.tree,
+            file_name: self.file_name,
+            title: self.title,
+            source: self.source,
+            drawer_style: self.drawer_style,
+            write_options: self.write_options,
+            drawer,
+            options: self.options,
+            embedding: self.embedding,
+        }
+    }
+
+    ///
+    /// Sets a predicate that selects *uninteresting* nodes. These nodes are drawn as small
+    /// collapsed stubs while their subtrees are omitted, which lets a single figure focus on the
+    /// interesting parts without losing the global context.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.open(MyNodeData(1)).unwrap();
+    /// builder.token(MyNodeData(2), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_collapsed(|value: &MyNodeData| value.0 == 1)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// assert_eq!(2, layouter.embedding().len());
+    /// assert_eq!(2, layouter.embedding()[1].collapsed);
+    /// ```
+    ///
+    pub fn with_collapsed(mut self, collapse: impl Fn(&T) -> bool + 'static) -> Self {
+        self.options.collapse = Some(Rc::new(collapse));
+        self
+    }
+
+    /// Replaces the options of the embedding, see [LayoutConfig][crate::LayoutConfig].
+    pub(crate) fn with_options(self, options: EmbedOptions<T, F>) -> Self {
+        Self { options, ..self }
+    }
+
+    ///
+    /// Limits the number of children that are drawn for each node. The children after the first
+    /// `max_children` ones are replaced by a single placeholder node labeled like `… 12 more`,
+    /// which keeps very flat trees like token streams or long item lists narrow. The placeholder
+    /// is drawn like a collapsed stub, its [collapsed][crate::EmbeddedNode::collapsed] count
+    /// includes the subtrees of the omitted children and its span covers them.
+    ///
+    /// ```
+    /// use syntree_layout::Layouter;
+    /// use syntree::Builder;
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(0u8).unwrap();
+    /// for i in 1..=5 {
+    ///     builder.token(i, 1).unwrap();
+    /// }
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_max_children(2)
+    ///     .embed_with_debug()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["0", "1", "2", "\u{2026} 3 more"], texts);
+    /// assert_eq!(3, layouter.embedding()[3].collapsed);
+    /// assert_eq!(2..5, layouter.embedding()[3].span);
+    /// ```
+    ///
+    pub fn with_max_children(mut self, max_children: usize) -> Self {
+        self.options.max_children = Some(max_children);
+        self
+    }
+
+    ///
+    /// Restricts the embedding to the nodes whose spans intersect the given source range, e.g. the
+    /// user's selection in an editor. The result contains the relevant subtrees plus the spine of
+    /// their ancestors, and the drawing is cropped accordingly.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 3).unwrap();
+    /// builder.token(MyNodeData(2), 3).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_clip_span(4..5)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["0", "2"], texts);
+    /// ```
+    ///
+    pub fn with_clip_span(mut self, span: Range<usize>) -> Self {
+        self.options.clip_span = Some(span);
+        self
+    }
+
+    ///
+    /// Emphasizes all nodes whose spans intersect the given range of the source, e.g. to show
+    /// where the location of an error lives in the tree. Empty spans intersect the range if they
+    /// touch it.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 3).unwrap();
+    /// builder.token(MyNodeData(2), 3).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_highlight_span(4..5)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let emphasized = layouter.embedding().iter().map(|e| e.is_emphasized).collect::<Vec<_>>();
+    /// assert_eq!(vec![true, false, true], emphasized);
+    /// ```
+    ///
+    pub fn with_highlight_span(mut self, span: Range<usize>) -> Self {
+        self.options.highlight_span = Some(span);
+        self
+    }
+
+    ///
+    /// Appends the span of each node to its label in the form `@start..end`, like syntree's
+    /// `print_with_source` does.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 3).unwrap();
+    /// builder.token(MyNodeData(2), 3).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_span_labels(true)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["0@0..6", "1@0..3", "2@3..6"], texts);
+    /// assert_eq!(7, layouter.embedding()[1].x_extent);
+    /// ```
+    ///
+    pub fn with_span_labels(mut self, span_labels: bool) -> Self {
+        self.options.span_labels = span_labels;
+        self
+    }
+
+    ///
+    /// Appends the `ord` of each node to its label as superscript, e.g. `Expr³`. The numbers are
+    /// part of the labels and thus shown by all drawers. They help to correlate a diagram with
+    /// traversals of the embedding or assertions in tests.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("Expr")).unwrap();
+    /// builder.token(MyNodeData("a"), 1).unwrap();
+    /// builder.token(MyNodeData("b"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_node_numbers(true)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["Expr⁰", "a¹", "b²"], texts);
+    /// ```
+    ///
+    pub fn with_node_numbers(mut self, node_numbers: bool) -> Self {
+        self.options.node_numbers = node_numbers;
+        self
+    }
+
+    ///
+    /// Limits the width of the node labels to the given number of characters. Longer labels are
+    /// wrapped at whitespace over multiple lines, words that are still too long, like long string
+    /// literals, are truncated with "…". The extents of the nodes are calculated from their
+    /// longest line. Vertical labels are not affected.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("string literal")).unwrap();
+    /// builder.token(MyNodeData("\"abcdefghijklmnop\""), 18).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_max_label_width(8)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["string\nliteral", "\"abcdef…"], texts);
+    /// assert_eq!(8, layouter.embedding()[0].x_extent);
+    /// ```
+    ///
+    pub fn with_max_label_width(mut self, max_label_width: usize) -> Self {
+        self.options.max_label_width = Some(max_label_width);
+        self
+    }
+
+    ///
+    /// Sets the label of nodes whose spans are out of the bounds of the source or not on a char
+    /// boundary when the labels are taken from the source, e.g. with
+    /// [embed_with_source][Layouter::embed_with_source]. Without a placeholder the embedding
+    /// fails with [LayouterError::SourceSliceOutOfBounds].
+    ///
+    /// ```
+    /// use syntree_layout::{ErrorKind, Layouter};
+    /// use syntree::Builder;
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(0u8).unwrap();
+    /// builder.token(1u8, 2).unwrap();
+    /// builder.token(2u8, 4).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// // The source is shorter than the tree's span
+    /// let err = Layouter::new(&tree).embed_with_source("ab").err().unwrap();
+    /// assert_eq!(ErrorKind::SourceSliceOutOfBounds, err.kind());
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_source_placeholder("?")
+    ///     .embed_with_source("ab")
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["?", "ab", "?"], texts);
+    /// ```
+    ///
+    pub fn with_source_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.options.source_placeholder = Some(placeholder.into());
+        self
+    }
+
+    ///
+    /// Restricts the embedding to the subtree rooted at the node with the given id. The node
+    /// becomes the root of the drawing, which is useful to debug a specific grammar rule without
+    /// drawing the whole tree. The embedding fails if the tree has no node with this id.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// let rule = builder.open(MyNodeData(1)).unwrap();
+    /// builder.token(MyNodeData(2), 3).unwrap();
+    /// builder.close().unwrap();
+    /// builder.token(MyNodeData(3), 3).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_subtree(rule)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["1", "2"], texts);
+    /// assert_eq!(None, layouter.embedding()[0].parent);
+    /// ```
+    ///
+    pub fn with_subtree(mut self, node_id: F::Pointer) -> Self {
+        self.options.subtree_root = Some(node_id);
+        self
+    }
+
+    ///
+    /// Enables the compact layout. Instead of reserving a rectangular block for each subtree,
+    /// sibling subtrees are moved together as close as their contours allow. Thus a shallow subtree
+    /// can be placed below a wide node of its deep neighbor, which reduces the width of trees with
+    /// long unary chains considerably.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("root")).unwrap();
+    /// builder.open(MyNodeData("a_very_long_name")).unwrap();
+    /// builder.token(MyNodeData("x"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.open(MyNodeData("b")).unwrap();
+    /// builder.token(MyNodeData("another_long_name"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let wide = Layouter::new(&tree).embed_with_visualize().unwrap();
+    /// let compact = Layouter::new(&tree).with_compact(true).embed_with_visualize().unwrap();
+    /// assert!(compact.embedding()[0].x_extent_children < wide.embedding()[0].x_extent_children);
+    /// ```
+    ///
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.options.compact = compact;
+        self
+    }
+
+    ///
+    /// Sets the distances between the nodes in logical units, see [Spacing].
+    /// The gaps between siblings and subtrees add to the minimal distance of one unit between
+    /// neighboring labels, the margin is added on both sides of the tree and the level gap
+    /// spreads the `y_order` of the levels. A level gap of 0 is treated as 1.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Spacing, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 1).unwrap();
+    /// builder.token(MyNodeData(2), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_spacing(Spacing { level_gap: 2, sibling_gap: 2, subtree_gap: 0, margin: 1 })
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let positions = layouter
+    ///     .embedding()
+    ///     .iter()
+    ///     .map(|e| (e.x_center, e.y_order))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(vec![(4, 0), (2, 2), (6, 2)], positions);
+    /// assert_eq!(8, layouter.embedding()[0].x_extent_children);
+    /// ```
+    ///
+    pub fn with_spacing(mut self, spacing: Spacing) -> Self {
+        self.options.spacing = spacing.normalized();
+        self
+    }
+
+    ///
+    /// Sets the level on which the leaves are placed. With [LeafAlignment::Bottom] all tokens
+    /// are placed on the deepest level, like in the parse trees of textbooks.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, LeafAlignment, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 1).unwrap();
+    /// builder.open(MyNodeData(2)).unwrap();
+    /// builder.token(MyNodeData(3), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_leaf_alignment(LeafAlignment::Bottom)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let levels = layouter.embedding().iter().map(|e| e.y_order).collect::<Vec<_>>();
+    /// assert_eq!(vec![0, 2, 1, 2], levels);
+    /// ```
+    ///
+    pub fn with_leaf_alignment(mut self, leaf_alignment: LeafAlignment) -> Self {
+        self.options.leaf_alignment = leaf_alignment;
+        self
+    }
+
+    ///
+    /// When set to true the siblings are placed from left to right in the order of the starts of
+    /// their spans instead of their order in the tree. This guarantees that the tokens appear in
+    /// source order, even if the tree was built with out of order spans, e.g. by
+    /// [syntree::Builder::token_with] during error recovery. The vertical position of the nodes
+    /// is still their depth.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Builder, Span, Tree};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token_with(MyNodeData(1), Span::new(2, 4)).unwrap();
+    /// builder.token_with(MyNodeData(2), Span::new(0, 2)).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_span_order(true)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let x_centers = layouter.embedding().iter().map(|e| e.x_center).collect::<Vec<_>>();
+    /// assert_eq!(vec![2, 3, 1], x_centers);
+    /// ```
+    ///
+    pub fn with_span_order(mut self, span_order: bool) -> Self {
+        self.options.span_order = span_order;
+        self
+    }
+
+    ///
+    /// Marks the nodes for which the predicate returns true as syntax errors. This is the place
+    /// to tell the layouter how the parser represents error nodes and the nodes created during
+    /// error recovery. Such nodes get at least the diagnostic level
+    /// [Error][crate::DiagnosticLevel::Error] and a dashed border, so drawers show them with a
+    /// red dashed border by default.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{BorderStyle, DiagnosticLevel, Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// enum Kind { Expr, Num, Error }
+    ///
+    /// impl Visualize for Kind {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:?}", self) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(Kind::Expr).unwrap();
+    /// builder.token(Kind::Num, 1).unwrap();
+    /// builder.token(Kind::Error, 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_error_nodes(|kind: &Kind| matches!(kind, Kind::Error))
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let error = &layouter.embedding()[2];
+    /// assert_eq!(DiagnosticLevel::Error, error.diagnostic);
+    /// assert_eq!(BorderStyle::Dashed, error.style.border);
+    /// ```
+    ///
+    pub fn with_error_nodes(mut self, error_nodes: impl Fn(&T) -> bool + 'static) -> Self {
+        self.options.error_nodes = Some(Rc::new(error_nodes));
+        self
+    }
+
+    ///
+    /// Marks the nodes that touch the spans of the given diagnostics, e.g. the errors and
+    /// warnings reported by a parser or a linter, which turns the drawing into a visual error
+    /// report. Of the nodes whose spans intersect a diagnostic only the deepest ones are marked,
+    /// i.e. the tokens in most cases. Marked nodes are emphasized, take the diagnostic's severity
+    /// and show its message in their tooltip, errors get a dashed border like
+    /// [error nodes][Layouter::with_error_nodes]. Empty spans mark the nodes they touch.
+    ///
+    /// See [SvgDrawer::with_squiggles][crate::SvgDrawer::with_squiggles] to underline the marked
+    /// nodes.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Diagnostic, DiagnosticLevel, Layouter};
+    ///
+    /// // The spans of the tokens are 0..3, 3..4 and 4..5
+    /// let tree = from_text("(Stmt let x =)").unwrap();
+    /// let diagnostics = [
+    ///     Diagnostic::new(4..5, DiagnosticLevel::Error, "expected an expression"),
+    ///     Diagnostic::new(3..4, DiagnosticLevel::Warning, "unused variable"),
+    /// ];
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_diagnostics(&diagnostics)
+    ///     .embed()
+    ///     .unwrap();
+    /// let embedding = layouter.embedding();
+    /// assert_eq!(DiagnosticLevel::None, embedding[0].diagnostic);
+    /// assert_eq!(DiagnosticLevel::Warning, embedding[2].diagnostic);
+    /// assert_eq!(DiagnosticLevel::Error, embedding[3].diagnostic);
+    /// assert_eq!(Some("expected an expression"), embedding[3].tooltip.as_deref());
+    /// ```
+    ///
+    pub fn with_diagnostics(mut self, diagnostics: &[Diagnostic]) -> Self {
+        self.options.diagnostics = diagnostics.to_vec();
+        self
+    }
+
+    ///
+    /// Assigns a [ColorClass] to each node, e.g. by the kind of its token. The class is stored in
+    /// [EmbeddedNode::color_class] and the class's color from the default palette fills the
+    /// node's box unless the node's style has a fill of its own, so all drawers show the classes.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{ColorClass, Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// enum Kind { Stmt, Let, Ident, Num }
+    ///
+    /// impl Visualize for Kind {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:?}", self) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(Kind::Stmt).unwrap();
+    /// builder.token(Kind::Let, 3).unwrap();
+    /// builder.token(Kind::Ident, 1).unwrap();
+    /// builder.token(Kind::Num, 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_color_by(|kind: &Kind| match kind {
+    ///         Kind::Stmt => ColorClass::None,
+    ///         Kind::Let => ColorClass::Keyword,
+    ///         Kind::Ident => ColorClass::Identifier,
+    ///         Kind::Num => ColorClass::Literal,
+    ///     })
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let keyword = &layouter.embedding()[1];
+    /// assert_eq!(ColorClass::Keyword, keyword.color_class);
+    /// assert_eq!(ColorClass::Keyword.color(), keyword.style.fill.as_deref());
+    /// assert_eq!(None, layouter.embedding()[0].style.fill);
+    /// ```
+    ///
+    pub fn with_color_by(mut self, color_by: impl Fn(&T) -> ColorClass + 'static) -> Self {
+        self.options.color_by = Some(Rc::new(color_by));
+        self
+    }
+
+    ///
+    /// Sets a function that measures the extent of each node from its data and its label. The
+    /// measured extent replaces the default estimate from the length of the label and is the
+    /// horizontal space the layout reserves for the node, in units of the character width of the
+    /// drawer's font.
+    ///
+    /// Use this if the drawer renders content of a known size that the length of the label
+    /// doesn't reflect, like icons, badges or multi-line labels. The [SvgDrawer] still sizes the
+    /// boxes to their labels.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// enum Kind { Expr, Icon, Name }
+    ///
+    /// impl Visualize for Kind {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:?}", self) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(Kind::Expr).unwrap();
+    /// builder.token(Kind::Icon, 1).unwrap();
+    /// builder.token(Kind::Name, 4).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_measure(|kind: &Kind, label: &str| match kind {
+    ///         // The icon is drawn three characters wide next to the label
+    ///         Kind::Icon => label.len() + 3,
+    ///         _ => label.len(),
+    ///     })
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// assert_eq!(7, layouter.embedding()[1].x_extent);
+    /// assert_eq!(4, layouter.embedding()[2].x_extent);
+    /// ```
+    ///
+    pub fn with_measure(mut self, measure: impl Fn(&T, &str) -> usize + 'static) -> Self {
+        self.options.measure = Some(Rc::new(measure));
+        self
+    }
+
+    ///
+    /// Sets a predicate that selects trivia like whitespace and comments. These nodes are drawn
+    /// as a narrow `·` marker instead of a box with their label, which keeps concrete syntax
+    /// trees from spending most of their width on trivia. The original label becomes the
+    /// node's tooltip, unless it has its own one, and [EmbeddedNode::is_trivia] is set.
+    ///
+    /// ```
+    /// use syntree_layout::Layouter;
+    /// use syntree::Builder;
+    ///
+    /// let source = "a    b";
+    /// let mut builder = Builder::new();
+    /// builder.open("Pair").unwrap();
+    /// builder.token("Ident", 1).unwrap();
+    /// builder.token("Whitespace", 4).unwrap();
+    /// builder.token("Ident", 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_trivia(|kind: &&str| *kind == "Whitespace")
+    ///     .embed_with_source(source)
+    ///     .unwrap();
+    /// let whitespace = &layouter.embedding()[2];
+    /// assert!(whitespace.is_trivia);
+    /// assert_eq!("\u{b7}", whitespace.text);
+    /// assert_eq!(1, whitespace.x_extent);
+    /// assert_eq!(Some(r#""    ""#), whitespace.tooltip.as_deref());
+    /// ```
+    ///
+    pub fn with_trivia(mut self, trivia: impl Fn(&T) -> bool + 'static) -> Self {
+        self.options.trivia = Some(Rc::new(trivia));
+        self
+    }
+
+    ///
+    /// Inserts a pseudonode for each range of the source that no leaf covers, so that tokenizer
+    /// bugs that drop characters become visible. The pseudonodes show the beginning of the
+    /// missing text, have the [EmbeddedNode::is_gap] flag set and are drawn hatched by the
+    /// [SvgDrawer]. Gaps that contain only whitespace are accepted, see
+    /// [Embedding::span_issues].
+    ///
+    /// The gaps are only found by the embed methods that receive the source, like
+    /// [Layouter::embed_with_source]. The text of nodes that are filtered, collapsed or outside of
+    /// the clip span or the subtree is not missing and gets no pseudonodes.
+    ///
+    /// ```
+    /// use syntree_layout::Layouter;
+    /// use syntree::Builder;
+    ///
+    /// // The tokenizer dropped the `+`
+    /// let source = "1 + 2";
+    /// let mut builder = Builder::new();
+    /// builder.open("Expr").unwrap();
+    /// builder.token("Num", 1).unwrap();
+    /// builder.token("Ws", 1).unwrap();
+    /// builder.set_cursor(3);
+    /// builder.token("Ws", 1).unwrap();
+    /// builder.token("Num", 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_span_gaps(true)
+    ///     .embed_with_source(source)
+    ///     .unwrap();
+    /// let gap = layouter.embedding().iter().find(|node| node.is_gap).unwrap();
+    /// assert_eq!("missing \"+\"", gap.text);
+    /// assert_eq!(2..3, gap.span);
+    /// assert_eq!(Some(0), gap.parent);
+    /// ```
+    ///
+    pub fn with_span_gaps(mut self, span_gaps: bool) -> Self {
+        self.options.span_gaps = span_gaps;
+        self
+    }
+
+    ///
+    /// Measures the labels with the metrics of the font they are rendered with instead of
+    /// counting their characters. Give the same metrics to [SvgDrawer::with_font], so that the
+    /// extents of the layout match the width of the rendered glyphs. A function set with
+    /// [Layouter::with_measure] takes precedence.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{FontMetrics, Layouter, SvgDrawer, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.token(MyNodeData("abcd"), 4).unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let metrics = FontMetrics::monospace(16.0, 0.5);
+    /// let drawer = SvgDrawer::new().with_font("monospace", metrics.clone());
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_font_metrics(metrics)
+    ///     .with_drawer(&drawer)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// assert_eq!(5, layouter.embedding()[0].x_extent);
+    /// ```
+    ///
+    pub fn with_font_metrics(mut self, metrics: FontMetrics) -> Self {
+        self.options.font_metrics = Some(metrics);
+        self
+    }
+
+    ///
+    /// Sets a function that returns a short annotation for the data of a node, e.g. the type
+    /// inferred by a type checker or a count. The [SvgDrawer] shows the badges as small chips at
+    /// the top right corner of the nodes, the [DotDrawer][crate::DotDrawer] as external labels.
+    /// The badges don't change the layout.
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter, TextLabel};
+    ///
+    /// let tree = from_text("(Add 1 2.5)").unwrap();
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_badges(|label: &TextLabel| {
+    ///         let label = label.to_string();
+    ///         match label.parse::<f64>() {
+    ///             Ok(_) if label.contains('.') => Some("f64".to_string()),
+    ///             Ok(_) => Some("i32".to_string()),
+    ///             Err(_) => None,
+    ///         }
+    ///     })
+    ///     .embed()
+    ///     .unwrap();
+    /// let badges = layouter.embedding().iter().map(|e| e.badge.as_deref()).collect::<Vec<_>>();
+    /// assert_eq!(vec![None, Some("i32"), Some("f64")], badges);
+    /// ```
+    ///
+    pub fn with_badges(mut self, badges: impl Fn(&T) -> Option<String> + 'static) -> Self {
+        self.options.badges = Some(Rc::new(badges));
+        self
+    }
+
+    ///
+    /// Marks the nodes as syntax errors whose labels follow the common convention for error
+    /// nodes, i.e. the first word of the label starts with `error` in any case. This matches
+    /// labels like `ERROR`, `Error`, `ErrorRecovery` or `error: unexpected token`.
+    /// See [with_error_nodes][Self::with_error_nodes] for a custom predicate.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{DiagnosticLevel, Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("stmt")).unwrap();
+    /// builder.token(MyNodeData("ERROR"), 1).unwrap();
+    /// builder.token(MyNodeData("terror"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_error_nodes_by_label()
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let errors = layouter.embedding().errors().map(|e| e.ord).collect::<Vec<_>>();
+    /// assert_eq!(vec![1], errors);
+    /// ```
+    ///
+    pub fn with_error_nodes_by_label(self) -> Self
+    where
+        T: Visualize,
+    {
+        self.with_error_nodes(|value: &T| has_error_label(value))
+    }
+
+    ///
+    /// Enables the merging of unary chains. Parser generators often emit long chains of inner
+    /// nodes with only one child each, which waste vertical space. Each such chain is drawn as
+    /// one node labeled with the first and the last node of the chain, e.g.
+    /// `logical_or → … → factor`. Chains end above tokens and collapsed nodes.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("expr")).unwrap();
+    /// builder.open(MyNodeData("term")).unwrap();
+    /// builder.open(MyNodeData("factor")).unwrap();
+    /// builder.token(MyNodeData("1"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_chain_collapsing(true)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["expr \u{2192} \u{2026} \u{2192} factor", "1"], texts);
+    /// assert_eq!(1, layouter.embedding()[1].y_order);
+    /// ```
+    ///
+    pub fn with_chain_collapsing(mut self, chain_collapsing: bool) -> Self {
+        self.options.chain_collapsing = chain_collapsing;
+        self
+    }
+
+    ///
+    /// Draws the subtrees of all nodes for which the given predicate returns true at the given
+    /// scale, i.e. with smaller fonts and tighter horizontal spacing, while the rest of the tree
+    /// stays full size. This fisheye-like effect keeps huge trees on one screen while the focus
+    /// stays readable.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("root")).unwrap();
+    /// builder.open(MyNodeData("details")).unwrap();
+    /// builder.token(MyNodeData("some_token"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_scaled_subtrees(|value: &MyNodeData| value.0 == "details", 0.5)
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let scales = layouter.embedding().iter().map(|e| e.scale).collect::<Vec<_>>();
+    /// assert_eq!(vec![1.0, 0.5, 0.5], scales);
+    /// assert_eq!(6, layouter.embedding()[2].x_extent);
+    /// ```
+    ///
+    pub fn with_scaled_subtrees(
+        mut self,
+        scaled: impl Fn(&T) -> bool + 'static,
+        scale: f32,
+    ) -> Self {
+        self.options.scaled = Some((Rc::new(scaled), scale));
+        self
+    }
+
+    ///
+    /// Groups each run of at least two consecutive siblings for which the predicate returns true
+    /// into one aggregate node. The label of the aggregate node is created by the given function
+    /// from the value of the first sibling of the run and the number of siblings, e.g.
+    /// "12 statements".
+    ///
+    /// If the aggregation is expandable, the aggregated subtrees are kept as children of the
+    /// aggregate node. Interactive output like the [HtmlDrawer][crate::HtmlDrawer] then shows
+    /// them folded, and they can be expanded by a click on the aggregate node. Otherwise the
+    /// aggregated subtrees are dropped.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("block")).unwrap();
+    /// builder.token(MyNodeData("stmt"), 1).unwrap();
+    /// builder.token(MyNodeData("stmt"), 1).unwrap();
+    /// builder.token(MyNodeData("stmt"), 1).unwrap();
+    /// builder.token(MyNodeData("}"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_aggregation(
+    ///         |value: &MyNodeData| value.0 == "stmt",
+    ///         |_, count| format!("{} statements", count),
+    ///         false,
+    ///     )
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["block", "3 statements", "}"], texts);
+    /// assert_eq!(3, layouter.embedding()[1].aggregated);
+    /// assert_eq!(0..3, layouter.embedding()[1].span);
+    /// ```
+    ///
+    pub fn with_aggregation(
+        mut self,
+        group: impl Fn(&T) -> bool + 'static,
+        label: impl Fn(&T, usize) -> String + 'static,
+        expandable: bool,
+    ) -> Self {
+        self.options.aggregation = Some((Rc::new(group), Rc::new(label), expandable));
+        self
+    }
+
+    ///
+    /// Drops all nodes for which the given filter returns true before the embedding, e.g.
+    /// whitespace and comment tokens, without the need to rebuild the tree.
+    /// The mode determines whether the subtrees of the dropped nodes are dropped too or whether
+    /// their children are spliced into the dropped nodes' parents.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{FilterMode, Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("root")).unwrap();
+    /// builder.token(MyNodeData("Whitespace"), 1).unwrap();
+    /// builder.open(MyNodeData("group")).unwrap();
+    /// builder.token(MyNodeData("token"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree)
+    ///     .with_filter(
+    ///         |node| matches!(node.value().0, "Whitespace" | "group"),
+    ///         FilterMode::Splice,
+    ///     )
+    ///     .embed_with_visualize()
+    ///     .unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["root", "token"], texts);
+    /// assert_eq!(Some(0), layouter.embedding()[1].parent);
+    /// ```
+    ///
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Node<'_, T, F>) -> bool + 'static,
+        mode: FilterMode,
+    ) -> Self {
+        self.options.filter = Some((Rc::new(filter), mode));
+        self
+    }
+
+    ///
+    /// When the layouter instance is fully configured this method invokes the necessary embedding
+    /// functionality and uses the drawer which writes the result to the output file in its own
+    /// output format.
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize, Result, WriteOptions};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(i32);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    ///     fn emphasize(&self) -> bool { false }
+    /// }
+    ///
+    /// fn test() -> Result<()> {
+    ///     let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
+    ///     Ok(Layouter::new(&tree)
+    ///         .with_file_path("target/tmp/test.svg")
+    ///         .with_write_options(WriteOptions::new().with_create_dirs(true))
+    ///         .embed_with_visualize()?
+    ///         .write().expect("Failed writing layout"))
+    /// }
+    ///
+    /// test().expect("Embedding should work");
+    /// ```
+    ///
+    pub fn write(&self) -> Result<()> {
+        let Some(file_name) = &self.file_name else {
+            return Err(LayouterError::MissingFilePath);
+        };
+
+        self.write_options.write(file_name, |path| {
+            self.drawer.draw(&self.draw_context(path, &self.embedding))
+        })
+    }
+
     ///
-    /// Creates a new Layouter with the required tree.
+    /// Writes a tree that is wider than `max_width` logical units into several files, one per
+    /// page, see [Embedding::paginate] for the splitting. The files are named after the file
+    /// path with the number of the page appended, i.e. `tree.svg` is written as `tree_1.svg`,
+    /// `tree_2.svg` and so on, also if the tree fits on one page. Returns the paths of the
+    /// written files.
+    ///
+    /// The [SvgDrawer] draws one logical unit as wide as one character, i.e. 10 pixels in the
+    /// default style, see [SvgDrawerStyle::units].
+    ///
+    /// ```
+    /// use syntree_layout::{from_text, Layouter};
+    ///
+    /// let tree = from_text("(Module (Fn alpha beta) (Fn gamma delta) (Fn epsilon zeta))").unwrap();
+    /// std::fs::create_dir_all("target/tmp").unwrap();
+    /// let pages = Layouter::new(&tree)
+    ///     .with_file_path("target/tmp/pages.svg")
+    ///     .embed()
+    ///     .unwrap()
+    ///     .write_pages(20)
+    ///     .unwrap();
+    /// assert_eq!(3, pages.len());
+    /// assert!(pages[2].ends_with("pages_3.svg"));
+    /// ```
+    ///
+    pub fn write_pages(&self, max_width: usize) -> Result<Vec<PathBuf>> {
+        let Some(file_name) = &self.file_name else {
+            return Err(LayouterError::MissingFilePath);
+        };
+        let stem = file_name
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let pages = self.embedding.paginate(max_width);
+        let mut paths = Vec::with_capacity(pages.len());
+        for (number, page) in pages.iter().enumerate() {
+            let mut page_name = format!("{}_{}", stem, number + 1);
+            if let Some(extension) = file_name.extension() {
+                page_name.push('.');
+                page_name.push_str(&extension.to_string_lossy());
+            }
+            let path = file_name.with_file_name(page_name);
+            self.write_options.write(&path, |path| {
+                self.drawer.draw(&self.draw_context(path, page))
+            })?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    ///
+    /// Writes the embedding with the drawer the given registry provides for the extension of the
+    /// output file, instead of the layouter's own drawer.
     ///
     /// ```
     /// use std::fmt;
-    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree_layout::{DrawerRegistry, Layouter, Visualize};
     /// use syntree::{Tree, Builder};
     ///
     /// #[derive(Copy, Clone, Debug)]
@@ -43,34 +1585,54 @@ where
     ///
     /// impl Visualize for MyNodeData {
     ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
-    ///     fn emphasize(&self) -> bool { false }
     /// }
     ///
+    /// let mut builder = Builder::new();
+    /// builder.token(MyNodeData(0), 1).unwrap();
+    /// let tree = builder.build().unwrap();
     ///
-    /// let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
-    /// let layouter = Layouter::new(&tree);
+    /// std::fs::create_dir_all("target/tmp").unwrap();
+    /// let registry = DrawerRegistry::new();
+    /// let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    /// layouter
+    ///     .with_file_path("target/tmp/registry.mmd")
+    ///     .write_with_registry(&registry)
+    ///     .unwrap();
+    /// assert!(std::fs::read_to_string("target/tmp/registry.mmd")
+    ///     .unwrap()
+    ///     .starts_with("graph TD"));
     /// ```
     ///
-    pub fn new(tree: &'a Tree<T, F>) -> Self {
-        static DEFAULT_DRAWER: SvgDrawer = SvgDrawer::new();
+    pub fn write_with_registry(&self, registry: &DrawerRegistry) -> Result<()> {
+        let Some(file_name) = &self.file_name else {
+            return Err(LayouterError::MissingFilePath);
+        };
+        let Some(drawer) = registry.drawer_for(file_name) else {
+            let extension = file_name
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            return Err(LayouterError::NoDrawerForExtension { extension });
+        };
 
-        Self {
-            tree,
-            drawer: &DEFAULT_DRAWER,
-            file_name: None,
-            embedding: Vec::default(),
-        }
+        self.write_options.write(file_name, |path| {
+            drawer.draw(&self.draw_context(path, &self.embedding))
+        })
     }
-}
 
-impl<'a, T, F, D> Layouter<'a, T, F, D>
-where
-    T: Copy,
-    F: Flavor,
-    D: ?Sized + Drawer,
-{
     ///
-    /// Sets the path of the output file on the layouter.
+    /// Returns the statistics of the embedded tree, e.g. to decide whether to truncate or split
+    /// a huge tree before rendering it. The tree must have been embedded before, see
+    /// [TreeStats].
+    ///
+    pub fn stats(&self) -> TreeStats {
+        self.embedding.stats()
+    }
+
+    ///
+    /// Returns the width of the embedding in logical units and the number of its levels, the
+    /// same bounds the drawers receive in their [DrawContext]. The tree must have been embedded
+    /// before.
     ///
     /// ```
     /// use std::fmt;
@@ -82,113 +1644,235 @@ where
     ///
     /// impl Visualize for MyNodeData {
     ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
-    ///     fn emphasize(&self) -> bool { false }
     /// }
     ///
-    ///
-    /// let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
-    /// let layouter = Layouter::new(&tree)
-    ///     .with_file_path("target/tmp/test.svg");
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    /// let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    /// assert_eq!(2, layouter.bounds().1);
     /// ```
     ///
-    pub fn with_file_path<P>(self, path: &'a P) -> Self
-    where
-        P: ?Sized + AsRef<Path>,
-    {
-        Self {
-            tree: self.tree,
-            file_name: Some(path.as_ref()),
-            drawer: self.drawer,
-            embedding: self.embedding,
-        }
+    pub fn bounds(&self) -> (usize, usize) {
+        crate::embedding::bounds(&self.embedding)
+    }
+
+    /// Provides access to the embedding data for other uses than drawing, e.g. for tests
+    pub fn embedding(&self) -> &Embedding {
+        &self.embedding
+    }
+
+    /// Consumes the layouter and returns its embedding.
+    pub fn into_embedding(self) -> Embedding {
+        self.embedding
     }
 
     ///
-    /// Sets a different drawer when you don't want to use the default svg-drawer.
-    /// If this method is not called the crate's own svg-drawer is used.
+    /// Applies the given function to each embedded node between embedding and drawing, e.g. to
+    /// re-label nodes, to emphasize them or to shift their coordinates, without writing a custom
+    /// drawer. The nodes are visited in the order of their `ord`.
+    ///
+    /// The layout is not recomputed, thus a longer text may overlap the neighbors of its node.
     ///
     /// ```
     /// use std::fmt;
-    /// use std::path::Path;
-    /// use syntree_layout::{Drawer, Layouter, EmbeddedNode, Result, Visualize};
+    /// use syntree_layout::{Layouter, Visualize};
     /// use syntree::{Tree, Builder};
     ///
-    /// struct NilDrawer;
-    /// impl Drawer for NilDrawer {
-    ///     fn draw(&self, _file_name: &Path, _embedding: &[EmbeddedNode]) -> Result<()> {
-    ///         Ok(())
-    ///     }
-    /// }
     /// #[derive(Copy, Clone, Debug)]
     /// struct MyNodeData(i32);
     ///
     /// impl Visualize for MyNodeData {
     ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
-    ///     fn emphasize(&self) -> bool { false }
     /// }
     ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData(0)).unwrap();
+    /// builder.token(MyNodeData(1), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
     ///
-    /// let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
-    /// let drawer = NilDrawer;
     /// let layouter = Layouter::new(&tree)
-    ///     .with_drawer(&drawer)
-    ///     .with_file_path("target/tmp/test.svg");
+    ///     .embed_with_visualize()
+    ///     .unwrap()
+    ///     .map_embedding(|node| {
+    ///         node.text = format!("#{}", node.text);
+    ///         node.is_emphasized = node.parent.is_none();
+    ///     });
+    /// assert_eq!("#0", layouter.embedding()[0].text);
+    /// assert!(layouter.embedding()[0].is_emphasized);
+    /// assert!(!layouter.embedding()[1].is_emphasized);
     /// ```
     ///
-    pub fn with_drawer<U>(self, drawer: &'a U) -> Layouter<'a, T, F, U>
-    where
-        U: Drawer,
-    {
-        Layouter {
-            tree: self.tree,
-            file_name: self.file_name,
-            drawer,
-            embedding: self.embedding,
-        }
+    pub fn map_embedding(mut self, f: impl FnMut(&mut EmbeddedNode)) -> Self {
+        self.embedding.iter_mut().for_each(f);
+        self
     }
 
     ///
-    /// When the layouter instance is fully configured this method invokes the necessary embedding
-    /// functionality and uses the drawer which writes the result to the output file in its own
-    /// output format.
+    /// Replaces the label of the node with the given `ord` and updates the layout without
+    /// embedding the tree again, e.g. after an identifier was renamed in an editor. Only the
+    /// extents of the node and its ancestors are recomputed and only the subtree whose extent
+    /// changed is placed again, the result equals a new embedding of the changed tree.
+    ///
+    /// The text replaces the complete label including the suffixes of
+    /// [with_span_labels][Self::with_span_labels] and
+    /// [with_node_numbers][Self::with_node_numbers], it is wrapped to the
+    /// [maximum label width][Self::with_max_label_width]. The styled segments of the node are
+    /// dropped.
+    ///
+    /// Compact layouts can't be updated incrementally and return
+    /// [LayouterError::IncrementalUpdateUnsupported].
     ///
     /// ```
     /// use std::fmt;
-    /// use syntree_layout::{Layouter, Visualize, Result};
+    /// use syntree_layout::{Layouter, Visualize};
     /// use syntree::{Tree, Builder};
     ///
     /// #[derive(Copy, Clone, Debug)]
-    /// struct MyNodeData(i32);
+    /// struct MyNodeData(&'static str);
     ///
     /// impl Visualize for MyNodeData {
     ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
-    ///     fn emphasize(&self) -> bool { false }
     /// }
     ///
-    /// fn test() -> Result<()> {
-    ///     let tree: Tree<MyNodeData, _> = Builder::new().build().unwrap();
-    ///     Ok(Layouter::new(&tree)
-    ///         .with_file_path("target/tmp/test.svg")
-    ///         .embed_with_visualize()?
-    ///         .write().expect("Failed writing layout"))
+    /// fn tree(name: &'static str) -> Tree<MyNodeData, syntree::FlavorDefault> {
+    ///     let mut builder = Builder::new();
+    ///     builder.open(MyNodeData("call")).unwrap();
+    ///     builder.token(MyNodeData(name), 1).unwrap();
+    ///     builder.token(MyNodeData("()"), 2).unwrap();
+    ///     builder.close().unwrap();
+    ///     builder.build().unwrap()
     /// }
     ///
-    /// test().expect("Embedding should work");
+    /// let (before, after) = (tree("f"), tree("format"));
+    /// let updated = Layouter::new(&before)
+    ///     .embed_with_visualize()
+    ///     .unwrap()
+    ///     .relabel(1, "format")
+    ///     .unwrap();
+    /// let embedded = Layouter::new(&after).embed_with_visualize().unwrap();
+    /// let positions = |layouter: &Layouter<_, _, _>| {
+    ///     layouter.embedding().iter().map(|e| (e.text.clone(), e.x_center)).collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(positions(&embedded), positions(&updated));
     /// ```
     ///
-    pub fn write(&self) -> Result<()> {
-        let Some(file_name) = &self.file_name else {
-            return Err(LayouterError::from_description(
-                "No output file name given - use Layouter::with_file_path.",
-            ));
+    pub fn relabel(mut self, ord: usize, text: impl Into<String>) -> Result<Self> {
+        let relayout = self.relayout()?;
+        let mut nodes = std::mem::take(&mut self.embedding).into_inner();
+        let Some(node) = nodes.get_mut(ord) else {
+            return Err(LayouterError::NodeLookupFailed { ord });
         };
+        node.text = text.into();
+        node.segments.clear();
+        if let Some(max_label_width) = self.options.max_label_width {
+            if !node.orientation.is_vertical() && node.text.width() > max_label_width {
+                node.text = wrap(&node.text, max_label_width);
+            }
+        }
+        node.x_extent = match &self.options.measure {
+            Some(measure) => {
+                let value = F::Pointer::new(node.node_id)
+                    .and_then(|node_id| self.tree.get(node_id))
+                    .ok_or(LayouterError::NodeLookupFailed { ord })?
+                    .value();
+                measure(&value, &node.text)
+            }
+            None => match &self.options.font_metrics {
+                Some(metrics) => metrics.x_extent(node.orientation, &node.text, node.scale),
+                None => node.orientation.x_extent(&node.text, node.scale),
+            },
+        };
+        relayout.update(&mut nodes, ord)?;
+        self.embedding = nodes.into();
+        Ok(self)
+    }
 
-        self.drawer.draw(file_name, &self.embedding)
+    ///
+    /// Replaces the subtree of the node with the given `ord` by the given embedding and updates
+    /// the layout without embedding the whole tree again, e.g. after a statement was re-parsed in
+    /// an editor. The embedding of the new subtree is typically created with
+    /// [with_subtree][Self::with_subtree] from the changed tree and must be embedded with the same
+    /// options. Only the new subtree, the extents of its ancestors and the subtree whose extent
+    /// changed are placed again, the result equals a new embedding of the changed tree.
+    ///
+    /// The new nodes take the `ord`s following the given one, the nodes after the replaced
+    /// subtree are renumbered. The `node_id`s of the new nodes refer to the tree they were
+    /// embedded from.
+    ///
+    /// Compact layouts can't be updated incrementally and return
+    /// [LayouterError::IncrementalUpdateUnsupported].
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::{Tree, Builder};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct MyNodeData(&'static str);
+    ///
+    /// impl Visualize for MyNodeData {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("block")).unwrap();
+    /// builder.token(MyNodeData("stmt"), 1).unwrap();
+    /// builder.token(MyNodeData("end"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let before = builder.build().unwrap();
+    ///
+    /// // The statement was edited
+    /// let mut builder = Builder::new();
+    /// builder.open(MyNodeData("block")).unwrap();
+    /// let stmt = builder.open(MyNodeData("assignment")).unwrap();
+    /// builder.token(MyNodeData("x"), 1).unwrap();
+    /// builder.token(MyNodeData("="), 1).unwrap();
+    /// builder.token(MyNodeData("1"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// builder.token(MyNodeData("end"), 1).unwrap();
+    /// builder.close().unwrap();
+    /// let after = builder.build().unwrap();
+    ///
+    /// let subtree = Layouter::new(&after).with_subtree(stmt).embed_with_visualize().unwrap();
+    /// let updated = Layouter::new(&before)
+    ///     .embed_with_visualize()
+    ///     .unwrap()
+    ///     .replace_subtree(1, subtree.into_embedding())
+    ///     .unwrap();
+    /// let embedded = Layouter::new(&after).embed_with_visualize().unwrap();
+    /// let positions = |layouter: &Layouter<_, _, _>| {
+    ///     layouter
+    ///         .embedding()
+    ///         .iter()
+    ///         .map(|e| (e.text.clone(), e.parent, e.x_center, e.y_order))
+    ///         .collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(positions(&embedded), positions(&updated));
+    /// ```
+    ///
+    pub fn replace_subtree(mut self, ord: usize, subtree: Embedding) -> Result<Self> {
+        let relayout = self.relayout()?;
+        let nodes = std::mem::take(&mut self.embedding).into_inner();
+        let nodes = relayout.replace_subtree(nodes, ord, subtree.into_inner())?;
+        self.embedding = nodes.into();
+        Ok(self)
     }
 
-    /// Provides access to the embedding data for other uses than drawing, e.g. for tests
-    pub fn embedding(&self) -> &Embedding {
-        &self.embedding
+    /// Returns the settings of the incremental updates or an error if the layout doesn't
+    /// support them.
+    fn relayout(&self) -> Result<Relayout> {
+        if self.options.compact {
+            return Err(LayouterError::IncrementalUpdateUnsupported);
+        }
+        Ok(Relayout {
+            spacing: self.options.spacing,
+            span_order: self.options.span_order,
+            bottom_aligned: self.options.leaf_alignment == LeafAlignment::Bottom,
+        })
     }
 }
 
@@ -201,7 +1885,8 @@ where
     ///
     /// This method creates an embedding of the nodes of the given tree in the plane.
     /// The nodes representation is taken form the [Visualize][crate::Visualize] implementation of
-    /// type T.
+    /// type T. This includes the emphasis, the diagnostic level, the style, the label orientation
+    /// and the tooltip of the nodes.
     ///
     /// # Panics
     ///
@@ -211,15 +1896,123 @@ where
     pub fn embed_with_visualize(self) -> Result<Self> {
         let embedding = Embedder::embed(
             self.tree,
+            &self.options,
             |value: &T, f| value.visualize(f),
-            |value: &T| value.emphasize(),
+            visualized_attributes,
         )?;
-        Ok(Self {
-            tree: self.tree,
-            file_name: self.file_name,
-            drawer: self.drawer,
-            embedding,
-        })
+        Ok(Self { embedding, ..self })
+    }
+
+    ///
+    /// This method creates an embedding of the nodes of the given tree in the plane.
+    /// The nodes representation is taken form the
+    /// [visualize_with_source][crate::Visualize::visualize_with_source] implementation of type T,
+    /// which receives the text of the node's span in the given source. This way labels can mix
+    /// the node's value and its source text. All other properties are taken from the
+    /// [Visualize][crate::Visualize] implementation like in
+    /// [embed_with_visualize][Layouter::embed_with_visualize].
+    ///
+    /// Spans that don't fit into the source are reported as
+    /// [LayouterError::SourceSliceOutOfBounds] unless a placeholder is set with
+    /// [with_source_placeholder][Layouter::with_source_placeholder].
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use syntree_layout::{Layouter, Visualize};
+    /// use syntree::Builder;
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// enum Syntax { Assignment, Identifier, Equals, Number }
+    ///
+    /// impl Visualize for Syntax {
+    ///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:?}", self) }
+    ///
+    ///     fn visualize_with_source(&self, source: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         match self {
+    ///             Syntax::Identifier | Syntax::Number => write!(f, "{:?} '{}'", self, source),
+    ///             _ => self.visualize(f),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(Syntax::Assignment).unwrap();
+    /// builder.token(Syntax::Identifier, 1).unwrap();
+    /// builder.token(Syntax::Equals, 1).unwrap();
+    /// builder.token(Syntax::Number, 2).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let layouter = Layouter::new(&tree).embed_with_visualize_and_source("x=42").unwrap();
+    /// let texts = layouter.embedding().iter().map(|e| e.text.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["Assignment", "Identifier 'x'", "Equals", "Number '42'"], texts);
+    /// ```
+    ///
+    pub fn embed_with_visualize_and_source(self, source: &str) -> Result<Self> {
+        let embedding = Embedder::embed_with_source_and_stringify(
+            self.tree,
+            &self.options,
+            source,
+            |value: &T, node_source, f| value.visualize_with_source(node_source, f),
+            visualized_attributes,
+        )?;
+        Ok(Self { embedding, ..self })
+    }
+
+    ///
+    /// This method embeds the nodes of huge trees with little memory. Instead of building the
+    /// complete embedding it hands the embedded nodes one after another to the given sink, which
+    /// can write them out and drop them. The nodes are passed in depth first order, so parents
+    /// precede their children.
+    ///
+    /// The extents are computed in two passes over the tree without keeping the labels of the
+    /// nodes. Only the [Spacing][crate::Spacing] is honoured, all other options of the layouter
+    /// are ignored. The embedding held by the layouter is not changed.
+    ///
+    /// None of the crate's drawers consumes the stream, they all need the complete embedding.
+    /// The sink has to write the nodes in a format of its own, e.g. as one JSON object per line.
+    ///
+    /// ```
+    /// use syntree::Builder;
+    /// use syntree_layout::{Layouter, Visualize};
+    ///
+    /// #[derive(Copy, Clone, Debug)]
+    /// struct Node(u32);
+    ///
+    /// impl Visualize for Node {
+    ///     fn visualize(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         write!(f, "{}", self.0)
+    ///     }
+    /// }
+    ///
+    /// let mut tree = Builder::new();
+    /// tree.open(Node(1)).unwrap();
+    /// tree.token(Node(2), 1).unwrap();
+    /// tree.token(Node(3), 1).unwrap();
+    /// tree.close().unwrap();
+    /// let tree = tree.build().unwrap();
+    ///
+    /// let mut x_centers = Vec::new();
+    /// Layouter::new(&tree)
+    ///     .stream_with_visualize(|node| {
+    ///         x_centers.push(node.x_center);
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(x_centers, [2, 1, 3]);
+    /// ```
+    ///
+    pub fn stream_with_visualize(
+        &self,
+        sink: impl FnMut(EmbeddedNode) -> Result<()>,
+    ) -> Result<()> {
+        streaming::stream(
+            self.tree,
+            &self.options.spacing,
+            |value: &T, f| value.visualize(f),
+            visualized_attributes,
+            sink,
+        )
     }
 }
 
@@ -233,19 +2026,18 @@ where
     /// This method creates an embedding of the nodes of the given tree in the plane.
     /// The nodes representation is done with the help of the given source string.
     ///
+    /// Spans that don't fit into the source are reported as
+    /// [LayouterError::SourceSliceOutOfBounds] unless a placeholder is set with
+    /// [with_source_placeholder][Layouter::with_source_placeholder].
+    ///
     /// # Panics
     ///
     /// The method should not panic. If you encounter a panic this should be originated from
     /// bugs in coding. Please report such panics.
     ///
     pub fn embed_with_source(self, source: &str) -> Result<Self> {
-        let embedding = Embedder::embed_with_source(self.tree, source)?;
-        Ok(Self {
-            tree: self.tree,
-            file_name: self.file_name,
-            drawer: self.drawer,
-            embedding,
-        })
+        let embedding = Embedder::embed_with_source(self.tree, &self.options, source)?;
+        Ok(Self { embedding, ..self })
     }
 }
 
@@ -260,19 +2052,18 @@ where
     /// The nodes representation is done with the help of the given source string for tokens and the
     /// implementation of the `Display` trait of the node data for inner nodes.
     ///
+    /// Spans of tokens that don't fit into the source are reported as
+    /// [LayouterError::SourceSliceOutOfBounds] unless a placeholder is set with
+    /// [with_source_placeholder][Layouter::with_source_placeholder].
+    ///
     /// # Panics
     ///
     /// The method should not panic. If you encounter a panic this should be originated from
     /// bugs in coding. Please report such panics.
     ///
     pub fn embed_with_source_and_display(self, source: &str) -> Result<Self> {
-        let embedding = Embedder::embed_with_source_and_display(self.tree, source)?;
-        Ok(Self {
-            tree: self.tree,
-            file_name: self.file_name,
-            drawer: self.drawer,
-            embedding,
-        })
+        let embedding = Embedder::embed_with_source_and_display(self.tree, &self.options, source)?;
+        Ok(Self { embedding, ..self })
     }
 }
 
@@ -292,14 +2083,13 @@ where
     /// bugs in coding. Please report such panics.
     ///
     pub fn embed_with_debug(self) -> Result<Self> {
-        let embedding =
-            Embedder::embed(self.tree, |value: &T, f| value.fmt(f), |_value: &T| false)?;
-        Ok(Self {
-            tree: self.tree,
-            file_name: self.file_name,
-            drawer: self.drawer,
-            embedding,
-        })
+        let embedding = Embedder::embed(
+            self.tree,
+            &self.options,
+            |value: &T, f| value.fmt(f),
+            |_value: &T| NodeAttributes::default(),
+        )?;
+        Ok(Self { embedding, ..self })
     }
 }
 
@@ -319,14 +2109,13 @@ where
     /// bugs in coding. Please report such panics.
     ///
     pub fn embed(self) -> Result<Self> {
-        let embedding =
-            Embedder::embed(self.tree, |value: &T, f| value.fmt(f), |_value: &T| false)?;
-        Ok(Self {
-            tree: self.tree,
-            file_name: self.file_name,
-            drawer: self.drawer,
-            embedding,
-        })
+        let embedding = Embedder::embed(
+            self.tree,
+            &self.options,
+            |value: &T, f| value.fmt(f),
+            |_value: &T| NodeAttributes::default(),
+        )?;
+        Ok(Self { embedding, ..self })
     }
 }
 
@@ -351,12 +2140,116 @@ where
         stringify: impl Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
         emphasize: impl Fn(&T) -> bool,
     ) -> Result<Self> {
-        let embedding = Embedder::embed(self.tree, &stringify, &emphasize)?;
+        let embedding = Embedder::embed(self.tree, &self.options, &stringify, |value: &T| {
+            NodeAttributes {
+                is_emphasized: emphasize(value),
+                ..Default::default()
+            }
+        })?;
         Ok(Self {
             tree: self.tree,
             file_name: self.file_name,
+            title: self.title,
+            source: self.source,
+            drawer_style: self.drawer_style,
+            write_options: self.write_options,
             drawer: self.drawer,
+            options: self.options.clone(),
             embedding,
         })
     }
 }
+
+impl<T, F, D> Layouter<'_, T, F, D>
+where
+    T: Copy,
+    F: Flavor,
+    D: ?Sized + Drawer,
+{
+    ///
+    /// This method creates an embedding of the nodes of the given tree in the plane.
+    /// The nodes representation is taken form the fallible function `stringify`, the emphasis
+    /// from the function `emphasize`.
+    ///
+    /// An error of `stringify` aborts the embedding and is returned as
+    /// [LayouterError::NodeError] with the `ord` and the span of the offending node.
+    ///
+    /// ```
+    /// use syntree_layout::{Layouter, LayouterError};
+    /// use syntree::Builder;
+    ///
+    /// let mut builder = Builder::new();
+    /// builder.open(0u32).unwrap();
+    /// builder.token(1u32, 2).unwrap();
+    /// builder.token(u32::MAX, 3).unwrap();
+    /// builder.close().unwrap();
+    /// let tree = builder.build().unwrap();
+    ///
+    /// let result = Layouter::new(&tree).try_embed_with(
+    ///     |value| match *value {
+    ///         u32::MAX => Err(LayouterError::from_description("Unknown kind")),
+    ///         value => Ok(value.to_string()),
+    ///     },
+    ///     |_| false,
+    /// );
+    /// match result {
+    ///     Err(LayouterError::NodeError { ord, span, msg }) => {
+    ///         assert_eq!((2, 2..5), (ord, span));
+    ///         assert_eq!("Unknown kind", msg);
+    ///     }
+    ///     _ => panic!("The error should carry the node"),
+    /// }
+    /// ```
+    ///
+    pub fn try_embed_with(
+        self,
+        stringify: impl Fn(&T) -> Result<String>,
+        emphasize: impl Fn(&T) -> bool,
+    ) -> Result<Self> {
+        let embedding = Embedder::try_embed(self.tree, &self.options, stringify, |value: &T| {
+            NodeAttributes {
+                is_emphasized: emphasize(value),
+                ..Default::default()
+            }
+        })?;
+        Ok(Self { embedding, ..self })
+    }
+}
+
+/// Obtains the attributes of a node from its [Visualize] implementation.
+fn visualized_attributes<T: Visualize>(value: &T) -> NodeAttributes {
+    let mut style = value.style();
+    style.shape = style.shape.or_else(|| value.shape());
+    NodeAttributes {
+        is_emphasized: value.emphasize(),
+        diagnostic: value.diagnostic(),
+        style,
+        orientation: value.orientation(),
+        tooltip: value.tooltip(),
+        group: value.group(),
+        segments: value.rich_text().unwrap_or_default(),
+    }
+}
+
+/// Displays a value by its [Visualize] implementation.
+struct VisualizeWrapper<'a, T>(&'a T);
+
+impl<T: Visualize> Display for VisualizeWrapper<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.visualize(f)
+    }
+}
+
+/// Checks whether the visualized label of the value is an error label.
+pub(crate) fn has_error_label<T: Visualize>(value: &T) -> bool {
+    is_error_label(&VisualizeWrapper(value).to_string())
+}
+
+/// Checks whether the first word of the label starts with `error` in any case.
+fn is_error_label(label: &str) -> bool {
+    label
+        .split(|c: char| !c.is_alphanumeric())
+        .find(|word| !word.is_empty())
+        .and_then(|word| word.get(..5))
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("error"))
+}
@@ -0,0 +1,15 @@
+//! The module with the vertical alignment modes of the leaves.
+
+///
+/// The `LeafAlignment` determines the level on which the leaves of the tree are placed, see
+/// [Layouter::with_leaf_alignment][crate::Layouter::with_leaf_alignment].
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LeafAlignment {
+    /// Each leaf is placed one level below its parent
+    #[default]
+    Natural,
+    /// All leaves are placed on the deepest level of the tree, like the tokens of parse trees in
+    /// textbooks. The tokens can be read from left to right on the bottom row.
+    Bottom,
+}
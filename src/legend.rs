@@ -0,0 +1,136 @@
+//! The module with the legend of a drawing.
+
+use crate::{DiagnosticLevel, NodeStyle, SvgDrawerStyle};
+
+///
+/// The corner of the image in which the [Legend] is drawn.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LegendPosition {
+    /// The upper left corner
+    TopLeft,
+    /// The upper right corner
+    #[default]
+    TopRight,
+    /// The lower left corner
+    BottomLeft,
+    /// The lower right corner
+    BottomRight,
+}
+
+///
+/// A `LegendEntry` explains the meaning of one node style. The legend shows a sample box drawn
+/// in the entry's style next to the entry's label.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LegendEntry {
+    /// The meaning of the style, e.g. "token" or "nonterminal"
+    pub label: String,
+    /// The style of the sample box, properties that are `None` are taken from the drawer
+    pub style: NodeStyle,
+}
+
+impl LegendEntry {
+    /// Creates an entry that explains the given style.
+    pub fn new(label: impl Into<String>, style: NodeStyle) -> Self {
+        Self {
+            label: label.into(),
+            style,
+        }
+    }
+}
+
+///
+/// The `Legend` is a key to the node styles of a drawing. It is drawn in a corner of the image
+/// above the tree, see [SvgDrawer::with_legend][crate::SvgDrawer::with_legend].
+///
+/// ```
+/// use syntree_layout::{Legend, LegendPosition, NodeShape, NodeStyle};
+///
+/// let legend = Legend::new()
+///     .with_entry("token", NodeStyle { fill: Some("lightgreen".into()), ..Default::default() })
+///     .with_entry(
+///         "nonterminal",
+///         NodeStyle { shape: Some(NodeShape::Ellipse), border_color: Some("black".into()), ..Default::default() },
+///     )
+///     .with_diagnostics(true)
+///     .with_position(LegendPosition::BottomLeft);
+/// assert_eq!(2, legend.entries().len());
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Legend {
+    entries: Vec<LegendEntry>,
+    position: LegendPosition,
+    diagnostics: bool,
+}
+
+impl Legend {
+    /// Creates an empty legend.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            position: LegendPosition::TopRight,
+            diagnostics: false,
+        }
+    }
+
+    /// Adds an entry that explains the given style.
+    pub fn with_entry(mut self, label: impl Into<String>, style: NodeStyle) -> Self {
+        self.entries.push(LegendEntry::new(label, style));
+        self
+    }
+
+    /// Sets the corner of the image in which the legend is drawn.
+    pub fn with_position(self, position: LegendPosition) -> Self {
+        Self { position, ..self }
+    }
+
+    /// When set to true the legend explains the colors of the diagnostic levels after the
+    /// registered entries.
+    pub fn with_diagnostics(self, diagnostics: bool) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// The registered entries
+    pub fn entries(&self) -> &[LegendEntry] {
+        &self.entries
+    }
+
+    /// The corner of the image in which the legend is drawn
+    pub fn position(&self) -> LegendPosition {
+        self.position
+    }
+
+    /// Returns true if the legend contains no entries at all.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && !self.diagnostics
+    }
+
+    /// Returns all entries to draw including the diagnostic entries in the colors of the given
+    /// style.
+    pub(crate) fn resolved_entries(&self, style: &SvgDrawerStyle) -> Vec<LegendEntry> {
+        let mut entries = self.entries.clone();
+        if self.diagnostics {
+            for (label, level) in [
+                ("warning", DiagnosticLevel::Warning),
+                ("recovered", DiagnosticLevel::Recovered),
+                ("error", DiagnosticLevel::Error),
+            ] {
+                let color = style.diagnostic_color(level).map(str::to_string);
+                entries.push(LegendEntry::new(
+                    label,
+                    NodeStyle {
+                        text_color: color.clone(),
+                        border_color: color,
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+        entries
+    }
+}
@@ -1,14 +1,108 @@
+mod animated_svg_drawer;
+mod canvas;
+mod color_class;
+mod depth_colors;
+mod diagnostic;
+mod dot_drawer;
+mod draw_context;
 mod drawer;
+mod drawer_registry;
+mod edge_style;
+#[cfg(feature = "egui")]
+mod egui_view;
 mod embedding;
+mod embedding_builder;
 mod errors;
+mod filter_mode;
+mod font;
+mod from_text;
+mod graphml_drawer;
+mod html_drawer;
 mod internal;
+#[cfg(feature = "serde")]
+mod json_drawer;
+mod layout_config;
+mod layout_kind;
 mod layouter;
+mod leaf_alignment;
+mod legend;
+mod mermaid_drawer;
+mod node_style;
+mod orientation;
+#[cfg(feature = "pdf")]
+mod pdf_drawer;
+mod profile;
+mod render;
+mod resolved_embedding;
+mod spacing;
+mod span_coverage;
+mod svg_compare;
 mod svg_drawer;
+mod svg_metadata;
+mod svg_size;
+mod svg_style;
+mod svg_template;
+mod text_segment;
+mod theme;
+mod tikz_drawer;
+mod tree_diff;
+mod tree_stats;
+mod units;
 mod visualize;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod write_options;
 
+pub use animated_svg_drawer::AnimatedSvgDrawer;
+pub use canvas::Canvas;
+pub use color_class::ColorClass;
+pub use depth_colors::DepthColors;
+pub use diagnostic::{Diagnostic, DiagnosticLevel};
+pub use dot_drawer::DotDrawer;
+pub use draw_context::DrawContext;
 pub use drawer::Drawer;
+pub use drawer_registry::DrawerRegistry;
+pub use edge_style::EdgeStyle;
+#[cfg(feature = "egui")]
+pub use egui_view::{TreeView, TreeViewOutput};
 pub use embedding::{EmbeddedNode, Embedding};
-pub use errors::{LayouterError, Result};
+pub use embedding_builder::EmbeddingBuilder;
+pub use errors::{ErrorKind, LayouterError, Result};
+pub use filter_mode::FilterMode;
+pub use font::{EmbeddedFont, FontFormat, FontMetrics};
+pub use from_text::{from_text, TextLabel};
+pub use graphml_drawer::GraphMlDrawer;
+pub use html_drawer::HtmlDrawer;
+#[cfg(feature = "serde")]
+pub use json_drawer::JsonDrawer;
+pub use layout_config::LayoutConfig;
+pub use layout_kind::LayoutKind;
 pub use layouter::Layouter;
+pub use leaf_alignment::LeafAlignment;
+pub use legend::{Legend, LegendEntry, LegendPosition};
+pub use mermaid_drawer::MermaidDrawer;
+pub use node_style::{BorderStyle, LabelOrientation, NodeShape, NodeStyle};
+pub use orientation::Orientation;
+#[cfg(feature = "pdf")]
+pub use pdf_drawer::PdfDrawer;
+pub use profile::Profile;
+pub use render::{render_svg, render_svg_with_source, to_svg_string};
+pub use resolved_embedding::{ResolvedEmbedding, ResolvedNode};
+pub use spacing::Spacing;
+pub use span_coverage::SpanIssue;
+pub use svg_compare::compare_svg;
 pub use svg_drawer::SvgDrawer;
+pub use svg_metadata::SvgMetadata;
+pub use svg_size::SvgSize;
+pub use svg_style::SvgDrawerStyle;
+pub use svg_template::SvgTemplate;
+pub use text_segment::TextSegment;
+pub use theme::Theme;
+pub use tikz_drawer::TikzDrawer;
+pub use tree_diff::{DiffKind, TreeDiff};
+pub use tree_stats::TreeStats;
+pub use units::Units;
 pub use visualize::Visualize;
+#[cfg(feature = "wasm")]
+pub use wasm::{render_to_html_string, render_to_image_map, render_to_svg_string};
+pub use write_options::{OverwritePolicy, WriteOptions};
@@ -0,0 +1,161 @@
+//! The module with the drawer for Mermaid flowcharts.
+
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::{DrawContext, Drawer, EmbeddedNode, LayouterError, NodeShape, Orientation, Result};
+
+///
+/// The `MermaidDrawer` type provides the transformation of the embedding information into a
+/// Mermaid `graph TD` document. Such documents can be pasted directly into GitHub issues, GitLab
+/// wikis or mkdocs pages.
+///
+/// Mermaid does its own layout, thus only the tree structure, the texts and the styles of the
+/// nodes are taken from the embedding.
+///
+#[derive(Debug, Default)]
+pub struct MermaidDrawer {
+    orientation: Orientation,
+}
+
+impl MermaidDrawer {
+    /// Method to create a fresh instance of the `MermaidDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            orientation: Orientation::TopDown,
+        }
+    }
+
+    ///
+    /// Sets whether the root is drawn at the top or at the bottom, i.e. whether a `graph TD` or
+    /// a `graph BT` document is created.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, MermaidDrawer, Orientation};
+    ///
+    /// let embedding = vec![EmbeddedNode { text: "root".to_string(), ..Default::default() }];
+    /// let mermaid = MermaidDrawer::new()
+    ///     .with_orientation(Orientation::BottomUp)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(mermaid.starts_with("graph BT\n"));
+    /// ```
+    ///
+    pub fn with_orientation(self, orientation: Orientation) -> Self {
+        Self { orientation }
+    }
+
+    ///
+    /// Renders the given embedding into a Mermaid document and returns it as a `String`.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, MermaidDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let mermaid = MermaidDrawer::new().render_string(&embedding).unwrap();
+    /// assert!(mermaid.starts_with("graph TD\n"));
+    /// assert!(mermaid.contains("    n0 --> n1\n"));
+    /// ```
+    ///
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        self.build_document(embedding)
+            .map_err(|e| LayouterError::from_description(&e.to_string()))
+    }
+
+    ///
+    /// Escapes characters that have a special meaning inside of quoted Mermaid labels, which are
+    /// rendered as HTML, with Mermaid's entity codes. The `#` is escaped as well, because it
+    /// starts an entity code. Line breaks of wrapped labels become `<br/>`.
+    ///
+    fn escape(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '#' => escaped.push_str("#35;"),
+                '"' => escaped.push_str("#quot;"),
+                '<' => escaped.push_str("#lt;"),
+                '>' => escaped.push_str("#gt;"),
+                '&' => escaped.push_str("#amp;"),
+                '\n' => escaped.push_str("<br/>"),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn build_document(
+        &self,
+        embedding: &[EmbeddedNode],
+    ) -> std::result::Result<String, std::fmt::Error> {
+        let mut doc = String::new();
+        match self.orientation {
+            Orientation::TopDown => writeln!(doc, "graph TD")?,
+            Orientation::BottomUp => writeln!(doc, "graph BT")?,
+        }
+
+        for node in embedding {
+            let text = Self::escape(&node.text);
+            let (open, close) = match node.style.shape.unwrap_or_default() {
+                // Mermaid has no nodes without a box
+                NodeShape::Rect | NodeShape::Plain => ("[\"", "\"]"),
+                NodeShape::RoundedRect => ("(\"", "\")"),
+                NodeShape::Ellipse => ("([\"", "\"])"),
+                NodeShape::Diamond => ("{\"", "\"}"),
+            };
+            writeln!(doc, "    n{}{}{}{}", node.ord, open, text, close)?;
+        }
+
+        for node in embedding {
+            if let Some(parent) = node.parent {
+                writeln!(doc, "    n{} --> n{}", parent, node.ord)?;
+            }
+        }
+
+        writeln!(doc, "    classDef emphasized font-weight:bold;")?;
+        writeln!(doc, "    classDef error stroke:red,color:red;")?;
+        writeln!(
+            doc,
+            "    classDef collapsed stroke-dasharray:2 2,color:gray;"
+        )?;
+        for node in embedding {
+            if node.diagnostic.is_error() {
+                writeln!(doc, "    class n{} error;", node.ord)?;
+            } else if node.collapsed > 0 {
+                writeln!(doc, "    class n{} collapsed;", node.ord)?;
+            } else if node.is_emphasized {
+                writeln!(doc, "    class n{} emphasized;", node.ord)?;
+            }
+
+            let style = [
+                ("fill", &node.style.fill),
+                ("color", &node.style.text_color),
+                ("stroke", &node.style.border_color),
+            ]
+            .iter()
+            .filter_map(|(property, value)| {
+                value
+                    .as_ref()
+                    .map(|value| format!("{}:{}", property, value))
+            })
+            .collect::<Vec<_>>();
+            if !style.is_empty() {
+                writeln!(doc, "    style n{} {}", node.ord, style.join(","))?;
+            }
+        }
+
+        Ok(doc)
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `MermaidDrawer`.
+///
+impl Drawer for MermaidDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let doc = self.render_string(ctx.embedding())?;
+        fs::write(ctx.file_name(), doc).map_err(LayouterError::from_io_error)
+    }
+}
@@ -0,0 +1,121 @@
+//! The module with the per-node style types.
+
+use unicode_width::UnicodeWidthStr;
+
+///
+/// The shape of a node's box.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeShape {
+    /// A rectangle
+    #[default]
+    Rect,
+    /// A rectangle with rounded corners
+    RoundedRect,
+    /// An ellipse
+    Ellipse,
+    /// A diamond, i.e. a square standing on one of its corners
+    Diamond,
+    /// No box at all, only the text
+    Plain,
+}
+
+///
+/// The style of a node's border.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BorderStyle {
+    /// A solid line
+    #[default]
+    Solid,
+    /// A dashed line
+    Dashed,
+    /// A dotted line
+    Dotted,
+}
+
+///
+/// The orientation of a node's label.
+/// Vertical labels need only the width of a single character, which helps to keep trees with a
+/// few long labels narrow. Such labels grow downwards, so a larger
+/// [level_factor][crate::SvgDrawerStyle::level_factor] may be needed to avoid overlaps with the
+/// next level.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LabelOrientation {
+    /// The label is written from left to right
+    #[default]
+    Horizontal,
+    /// The label is rotated clockwise by 90 degrees and reads from top to bottom
+    Rotated,
+    /// The label's characters are stacked upon each other
+    Stacked,
+}
+
+impl LabelOrientation {
+    /// Returns true if the label is not written horizontally.
+    pub fn is_vertical(self) -> bool {
+        self != LabelOrientation::Horizontal
+    }
+
+    /// The x-extent of a label with the given text drawn at the given scale in logical coordinate
+    /// units. The text is measured in terminal columns, i.e. wide characters like CJK ideographs
+    /// count twice and combining characters don't count. Wrapped labels are as wide as their
+    /// longest line.
+    pub(crate) fn x_extent(self, text: &str, scale: f32) -> usize {
+        let extent = if self.is_vertical() {
+            2
+        } else {
+            text.lines().map(|line| line.width()).max().unwrap_or(0) + 1
+        };
+        if scale == 1.0 {
+            extent
+        } else {
+            ((extent as f32 * scale).ceil() as usize).max(1)
+        }
+    }
+}
+
+///
+/// The `NodeStyle` holds the visual properties of a single node.
+/// It is obtained from the [Visualize::style][crate::Visualize::style] hook. Properties that are
+/// `None` are taken from the drawer's own configuration.
+///
+/// ```
+/// use syntree_layout::{NodeShape, NodeStyle};
+///
+/// let style = NodeStyle {
+///     fill: Some("lightgreen".to_string()),
+///     shape: Some(NodeShape::Ellipse),
+///     ..Default::default()
+/// };
+/// assert!(style.has_box());
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeStyle {
+    /// The fill color of the node's box
+    pub fill: Option<String>,
+    /// The color of the node's text
+    pub text_color: Option<String>,
+    /// The color of the node's border
+    pub border_color: Option<String>,
+    /// The line style of the node's border
+    pub border: BorderStyle,
+    /// The shape of the node's box
+    pub shape: Option<NodeShape>,
+}
+
+impl NodeStyle {
+    /// Returns true if the style requests a box to be drawn around the node's text, i.e. it has a
+    /// fill or border color or a shape other than [NodeShape::Plain].
+    pub fn has_box(&self) -> bool {
+        self.fill.is_some()
+            || self.border_color.is_some()
+            || self.shape.is_some_and(|shape| shape != NodeShape::Plain)
+    }
+}
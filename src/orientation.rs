@@ -0,0 +1,32 @@
+//! The module with the vertical orientations of layered trees.
+
+///
+/// The `Orientation` determines whether the root of a layered tree is drawn at the top or at the
+/// bottom, see e.g. [SvgDrawer::with_orientation][crate::SvgDrawer::with_orientation].
+///
+/// The embedding itself doesn't depend on the orientation, its levels count from the root in
+/// both cases. Drawers that place the nodes on their own mirror the levels, drawers for tools
+/// with their own layout pass the direction on.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// The root is at the top and the leaves are below their parents
+    #[default]
+    TopDown,
+    /// The root is at the bottom and the leaves are above their parents, like in derivation
+    /// trees of linguistics and some compiler courses. Together with
+    /// [LeafAlignment::Bottom][crate::LeafAlignment::Bottom] all tokens are placed on the top
+    /// level.
+    BottomUp,
+}
+
+impl Orientation {
+    /// Returns the level on which a node of the given level is drawn in a tree whose deepest
+    /// level is `tree_depth`, counted from the top.
+    pub(crate) fn level(&self, y_order: usize, tree_depth: usize) -> usize {
+        match self {
+            Orientation::TopDown => y_order,
+            Orientation::BottomUp => tree_depth.saturating_sub(y_order),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! The module with the drawer for PDF documents.
+
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::{
+    embedding::tree_width, internal::color::parse_color, DrawContext, Drawer, EdgeStyle,
+    EmbeddedNode, LayouterError, Orientation, Result, SvgDrawerStyle,
+};
+
+///
+/// The `PdfDrawer` type provides the transformation of the embedding information into a single
+/// page vector PDF document. This allows to include parse trees into papers without conversion
+/// artifacts.
+///
+/// The drawer uses the same [SvgDrawerStyle] as the [SvgDrawer][crate::SvgDrawer] so that both
+/// outputs look alike. The texts are set in the standard PDF fonts *Courier* and *Courier-Bold*,
+/// which every PDF viewer provides. Colors are supported as `#rgb`, `#rrggbb` and the common color
+/// names, other colors are drawn black. Characters outside of Latin-1 are replaced by `?`.
+///
+/// This drawer is only available with the `pdf` feature.
+///
+#[derive(Debug, Default)]
+pub struct PdfDrawer {
+    style: SvgDrawerStyle,
+    orientation: Orientation,
+}
+
+impl PdfDrawer {
+    /// Method to create a fresh instance of the `PdfDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            style: SvgDrawerStyle::new(),
+            orientation: Orientation::TopDown,
+        }
+    }
+
+    /// Sets the style used to draw the embedding.
+    pub fn with_style(self, style: SvgDrawerStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    /// Sets whether the root is drawn at the top or at the bottom of the page.
+    pub fn with_orientation(self, orientation: Orientation) -> Self {
+        Self {
+            orientation,
+            ..self
+        }
+    }
+
+    ///
+    /// Renders the given embedding into a PDF document and returns its bytes.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, PdfDrawer};
+    ///
+    /// let embedding = vec![EmbeddedNode { text: "root".to_string(), ..Default::default() }];
+    /// let pdf = PdfDrawer::new().render_bytes(&embedding).unwrap();
+    /// assert!(pdf.starts_with(b"%PDF-1.4"));
+    /// ```
+    ///
+    pub fn render_bytes(&self, embedding: &[EmbeddedNode]) -> Result<Vec<u8>> {
+        let (width, height) = self.page_size(embedding);
+        let content = self
+            .build_content(embedding, width, height)
+            .map_err(|e| LayouterError::from_description(&e.to_string()))?;
+
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+                width, height
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ),
+        ];
+
+        let mut pdf = Vec::new();
+        pdf.extend_from_slice(b"%PDF-1.4\n");
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+        let xref_offset = pdf.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            trailer.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        trailer.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        ));
+        pdf.extend_from_slice(trailer.as_bytes());
+        Ok(pdf)
+    }
+
+    fn scale_y(&self, y: usize) -> f32 {
+        self.style.units().y_to_pixels(y)
+    }
+
+    fn scale_x(&self, x: usize) -> f32 {
+        self.style.units().x_to_pixels(x)
+    }
+
+    fn tree_depth(embedding: &[EmbeddedNode]) -> usize {
+        embedding
+            .iter()
+            .map(|e| e.y_order)
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn page_size(&self, embedding: &[EmbeddedNode]) -> (f32, f32) {
+        let tree_depth = Self::tree_depth(embedding);
+        let tree_width = tree_width(embedding);
+        (self.scale_x(tree_width), self.scale_y(tree_depth + 1))
+    }
+
+    /// Escapes a text for a PDF string literal and maps it to the WinAnsi encoding.
+    fn escape(text: &str) -> String {
+        text.chars()
+            .map(|c| match c {
+                '(' | ')' | '\\' => format!("\\{}", c),
+                c if (c as u32) < 0x20 => "?".to_string(),
+                c if (c as u32) < 0x80 => c.to_string(),
+                c if (c as u32) < 0x100 => format!("\\{:03o}", c as u32),
+                _ => "?".to_string(),
+            })
+            .collect()
+    }
+
+    fn color_operator(color: &str, operator: &str) -> String {
+        let (r, g, b) = parse_color(color).unwrap_or((0.0, 0.0, 0.0));
+        format!("{:.3} {:.3} {:.3} {}\n", r, g, b, operator)
+    }
+
+    fn build_content(
+        &self,
+        embedding: &[EmbeddedNode],
+        page_width: f32,
+        page_height: f32,
+    ) -> std::result::Result<String, std::fmt::Error> {
+        let style = &self.style;
+        let mut content = String::new();
+        // PDF's origin is at the bottom left corner
+        let flip = |y: f32| page_height - y;
+        let tree_depth = Self::tree_depth(embedding);
+        // The baseline of a node's label from the top of the page
+        let baseline =
+            |data: &EmbeddedNode| self.scale_y(self.orientation.level(data.y_order, tree_depth));
+
+        if let Some(background) = &style.background {
+            content.push_str(&Self::color_operator(background, "rg"));
+            writeln!(content, "0 0 {} {} re f", page_width, page_height)?;
+        }
+
+        for data in embedding {
+            let szx = data.text.chars().count() as f32 * style.char_width;
+            let x = self.scale_x(data.x_center) - szx / 2.0;
+            let y = baseline(data);
+
+            if let Some(parent_data) = data
+                .parent
+                .and_then(|p| embedding.iter().find(|e| e.ord == p))
+            {
+                content.push_str(&Self::color_operator(&style.edge_color, "RG"));
+                // Edges leave the parent's box at the bottom and enter the child's box at the top,
+                // bottom up trees vice versa
+                let parent_y = baseline(parent_data);
+                let (y1, y2) = match self.orientation {
+                    Orientation::TopDown => (
+                        flip(parent_y + style.text_height),
+                        flip(y - style.text_height - 3.0),
+                    ),
+                    Orientation::BottomUp => (
+                        flip(parent_y - style.text_height - 3.0),
+                        flip(y + style.text_height),
+                    ),
+                };
+                let x1 = self.scale_x(parent_data.x_center);
+                let x2 = self.scale_x(data.x_center);
+                let y_middle = (y1 + y2) / 2.0;
+                match style.edge_style {
+                    EdgeStyle::Straight => writeln!(content, "{} {} m {} {} l S", x1, y1, x2, y2)?,
+                    EdgeStyle::Orthogonal => writeln!(
+                        content,
+                        "{} {} m {} {} l {} {} l {} {} l S",
+                        x1, y1, x1, y_middle, x2, y_middle, x2, y2
+                    )?,
+                    EdgeStyle::Curved => writeln!(
+                        content,
+                        "{} {} m {} {} {} {} {} {} c S",
+                        x1, y1, x1, y_middle, x2, y_middle, x2, y2
+                    )?,
+                }
+            }
+
+            let diagnostic_color = style.diagnostic_color(data.diagnostic);
+            let fill = data.style.fill.as_deref().or(style.node_fill.as_deref());
+            let stroke = diagnostic_color
+                .or(data.style.border_color.as_deref())
+                .or(style.node_stroke.as_deref());
+            if fill.is_some() || stroke.is_some() {
+                let rect = format!(
+                    "{} {} {} {} re",
+                    x - style.node_padding,
+                    flip(y + style.text_height),
+                    szx + 2.0 * style.node_padding,
+                    2.0 * style.text_height + 3.0
+                );
+                if let Some(fill) = fill {
+                    content.push_str(&Self::color_operator(fill, "rg"));
+                    writeln!(content, "{} f", rect)?;
+                }
+                if let Some(stroke) = stroke {
+                    content.push_str(&Self::color_operator(stroke, "RG"));
+                    writeln!(content, "{} S", rect)?;
+                }
+            }
+
+            let text_color = diagnostic_color
+                .or(data.style.text_color.as_deref())
+                .unwrap_or(if data.is_emphasized {
+                    &style.emphasized_text_color
+                } else {
+                    &style.text_color
+                });
+            content.push_str(&Self::color_operator(text_color, "rg"));
+            writeln!(
+                content,
+                "BT /{} {} Tf {} {} Td ({}) Tj ET",
+                if data.is_emphasized { "F2" } else { "F1" },
+                style.font_size,
+                x,
+                flip(y),
+                Self::escape(&data.text)
+            )?;
+        }
+        Ok(content)
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `PdfDrawer`.
+///
+impl Drawer for PdfDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let pdf = self.render_bytes(ctx.embedding())?;
+        fs::write(ctx.file_name(), pdf).map_err(LayouterError::from_io_error)
+    }
+}
@@ -0,0 +1,81 @@
+//! The module with the layout profiles.
+
+use std::sync::OnceLock;
+
+use syntree::Flavor;
+
+use crate::{internal::options::EmbedOptions, Spacing, SvgDrawer};
+
+///
+/// A `Profile` is a named preset that bundles the layout, label width and style options of the
+/// crate, so that good looking output can be obtained with a single call without learning every
+/// knob.
+///
+/// Use it with [Layouter::with_profile][crate::Layouter::with_profile] or
+/// [LayoutConfig::with_profile][crate::LayoutConfig::with_profile], which set the options of the
+/// embedding and the style of the drawer. [SvgDrawer::with_profile] only sets the style, an
+/// [SvgDrawerStyle][crate::SvgDrawerStyle] converted from a profile can be adjusted afterwards.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// The classic look of the crate: black text on a white background without node boxes and
+    /// with the default layout
+    #[default]
+    Classic,
+    /// Small fonts, a compact layout without additional gaps and labels wrapped at 16 characters
+    /// to fit large trees on a screen
+    Compact,
+    /// Large fonts, rounded and filled node boxes, wider gaps between the subtrees and labels
+    /// wrapped at 24 characters for slides and documentation
+    Presentation,
+    /// Framed nodes separated by small gaps to make the extent of each node visible while
+    /// debugging a grammar, the labels are never wrapped
+    Debug,
+}
+
+impl Profile {
+    /// The svg-drawer with the style of this profile, shared by all layouters.
+    pub(crate) fn drawer(self) -> &'static SvgDrawer {
+        static DRAWERS: OnceLock<[SvgDrawer; 4]> = OnceLock::new();
+        let drawers = DRAWERS.get_or_init(|| {
+            [
+                Profile::Classic,
+                Profile::Compact,
+                Profile::Presentation,
+                Profile::Debug,
+            ]
+            .map(|profile| SvgDrawer::new().with_profile(profile))
+        });
+        &drawers[self as usize]
+    }
+
+    /// Sets the layout options of this profile in the given options of the embedding.
+    pub(crate) fn apply<T: Copy, F: Flavor>(self, options: &mut EmbedOptions<T, F>) {
+        let (compact, spacing, max_label_width) = match self {
+            Profile::Classic => (false, Spacing::new(), None),
+            Profile::Compact => (true, Spacing::new(), Some(16)),
+            Profile::Presentation => (
+                false,
+                Spacing {
+                    sibling_gap: 1,
+                    subtree_gap: 2,
+                    margin: 1,
+                    ..Spacing::new()
+                },
+                Some(24),
+            ),
+            Profile::Debug => (
+                false,
+                Spacing {
+                    sibling_gap: 1,
+                    subtree_gap: 1,
+                    ..Spacing::new()
+                },
+                None,
+            ),
+        };
+        options.compact = compact;
+        options.spacing = spacing;
+        options.max_label_width = max_label_width;
+    }
+}
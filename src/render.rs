@@ -0,0 +1,129 @@
+//! The module with one-line functions for the common use cases.
+
+use std::fmt::Display;
+use std::path::Path;
+
+use syntree::{Flavor, Tree};
+
+use crate::{Layouter, Result, Visualize};
+
+///
+/// Draws the tree with the default options into the given SVG file. The nodes are represented by
+/// their [Visualize] implementation.
+///
+/// This is a shortcut for
+/// `Layouter::new(tree).with_file_path(path).embed_with_visualize()?.write()`.
+///
+/// ```
+/// use std::fmt;
+/// use syntree_layout::Visualize;
+/// use syntree::Builder;
+///
+/// #[derive(Copy, Clone, Debug)]
+/// struct MyNodeData(i32);
+///
+/// impl Visualize for MyNodeData {
+///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+/// }
+///
+/// let mut builder = Builder::new();
+/// builder.open(MyNodeData(0)).unwrap();
+/// builder.token(MyNodeData(1), 1).unwrap();
+/// builder.close().unwrap();
+/// let tree = builder.build().unwrap();
+///
+/// std::fs::create_dir_all("target/tmp").unwrap();
+/// syntree_layout::render_svg(&tree, "target/tmp/render_svg.svg").unwrap();
+/// ```
+///
+pub fn render_svg<T, F>(tree: &Tree<T, F>, path: impl AsRef<Path>) -> Result<()>
+where
+    T: Copy + Visualize,
+    F: Flavor,
+{
+    Layouter::new(tree)
+        .with_file_path(path.as_ref())
+        .embed_with_visualize()?
+        .write()
+}
+
+///
+/// Draws the tree with the default options into the given SVG file. The tokens are represented
+/// by their text in the given source, the inner nodes by their [Display] implementation.
+///
+/// This is a shortcut for
+/// `Layouter::new(tree).with_file_path(path).embed_with_source_and_display(source)?.write()`.
+///
+/// ```
+/// use std::fmt;
+/// use syntree::Builder;
+///
+/// #[derive(Copy, Clone, Debug)]
+/// enum Syntax { Sum, Number, Plus }
+///
+/// impl fmt::Display for Syntax {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:?}", self) }
+/// }
+///
+/// let source = "1+2";
+/// let mut builder = Builder::new();
+/// builder.open(Syntax::Sum).unwrap();
+/// builder.token(Syntax::Number, 1).unwrap();
+/// builder.token(Syntax::Plus, 1).unwrap();
+/// builder.token(Syntax::Number, 1).unwrap();
+/// builder.close().unwrap();
+/// let tree = builder.build().unwrap();
+///
+/// std::fs::create_dir_all("target/tmp").unwrap();
+/// syntree_layout::render_svg_with_source(&tree, source, "target/tmp/render_source.svg").unwrap();
+/// ```
+///
+pub fn render_svg_with_source<T, F>(
+    tree: &Tree<T, F>,
+    source: &str,
+    path: impl AsRef<Path>,
+) -> Result<()>
+where
+    T: Copy + Display,
+    F: Flavor,
+{
+    Layouter::new(tree)
+        .with_file_path(path.as_ref())
+        .embed_with_source_and_display(source)?
+        .write()
+}
+
+///
+/// Draws the tree with the default options and returns the SVG document. The nodes are
+/// represented by their [Visualize] implementation.
+///
+/// This is a shortcut for `Layouter::new(tree).embed_with_visualize()?.to_svg_string()`.
+///
+/// ```
+/// use std::fmt;
+/// use syntree_layout::Visualize;
+/// use syntree::Builder;
+///
+/// #[derive(Copy, Clone, Debug)]
+/// struct MyNodeData(i32);
+///
+/// impl Visualize for MyNodeData {
+///     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+/// }
+///
+/// let mut builder = Builder::new();
+/// builder.open(MyNodeData(42)).unwrap();
+/// builder.close().unwrap();
+/// let tree = builder.build().unwrap();
+///
+/// let svg = syntree_layout::to_svg_string(&tree).unwrap();
+/// assert!(svg.contains(">42</text>"));
+/// ```
+///
+pub fn to_svg_string<T, F>(tree: &Tree<T, F>) -> Result<String>
+where
+    T: Copy + Visualize,
+    F: Flavor,
+{
+    Layouter::new(tree).embed_with_visualize()?.to_svg_string()
+}
@@ -0,0 +1,101 @@
+//! The module with the embedding resolved into pixel coordinates.
+
+use std::ops::Deref;
+
+///
+/// The [ResolvedNode] is the box of one embedded node in pixel coordinates.
+/// The box includes the node's padding and touches the edges leading to the node and away from
+/// it.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResolvedNode {
+    /// The `ord` of the corresponding [EmbeddedNode][crate::EmbeddedNode]
+    pub ord: usize,
+    /// The left edge of the node's box
+    pub x: f32,
+    /// The top edge of the node's box
+    pub y: f32,
+    /// The width of the node's box
+    pub width: f32,
+    /// The height of the node's box
+    pub height: f32,
+}
+
+impl ResolvedNode {
+    /// Returns the center of the node's box.
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Checks whether the given point lies inside of the node's box.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.x <= x && x <= self.x + self.width && self.y <= y && y <= self.y + self.height
+    }
+
+    /// Checks whether the node's box overlaps the given rectangle, touching edges count.
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        self.x <= x + width
+            && x <= self.x + self.width
+            && self.y <= y + height
+            && y <= self.y + self.height
+    }
+}
+
+///
+/// The `ResolvedEmbedding` is the companion of an [Embedding][crate::Embedding] in pixel
+/// coordinates. It contains one [ResolvedNode] for each embedded node in the same order and the
+/// size of the whole image.
+///
+/// The geometry pass that produces it is configured by the drawer, see
+/// [SvgDrawer::resolve][crate::SvgDrawer::resolve]. Custom drawers can use it instead of scaling
+/// the logical coordinates themselves.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedEmbedding {
+    nodes: Vec<ResolvedNode>,
+    width: f32,
+    height: f32,
+}
+
+impl ResolvedEmbedding {
+    /// Creates a resolved embedding from the boxes of the nodes and the size of the image.
+    pub fn new(nodes: Vec<ResolvedNode>, width: f32, height: f32) -> Self {
+        Self {
+            nodes,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the width of the image.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Returns the height of the image.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Consumes the resolved embedding and returns the vector of resolved nodes.
+    pub fn into_inner(self) -> Vec<ResolvedNode> {
+        self.nodes
+    }
+}
+
+impl Deref for ResolvedEmbedding {
+    type Target = [ResolvedNode];
+
+    fn deref(&self) -> &Self::Target {
+        &self.nodes
+    }
+}
+
+impl<'a> IntoIterator for &'a ResolvedEmbedding {
+    type Item = &'a ResolvedNode;
+    type IntoIter = std::slice::Iter<'a, ResolvedNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter()
+    }
+}
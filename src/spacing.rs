@@ -0,0 +1,69 @@
+//! The module with the spacing configuration of the layered embedding.
+
+///
+/// The `Spacing` controls the distances between the nodes of the embedding, see
+/// [Layouter::with_spacing][crate::Layouter::with_spacing].
+/// All distances are given in logical units of the embedding, the drawers convert them into
+/// pixels, see [Units][crate::Units]. The default values reproduce the classic layout.
+///
+/// ```
+/// use syntree_layout::Spacing;
+///
+/// let spacing = Spacing {
+///     sibling_gap: 1,
+///     subtree_gap: 2,
+///     ..Spacing::new()
+/// };
+/// assert_eq!(1, spacing.level_gap);
+/// assert_eq!(Spacing::new(), Spacing::default());
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spacing {
+    /// The difference of the `y_order` of parents and their children. It must be at least 1,
+    /// because the levels would overlap otherwise, a level gap of 0 is treated as 1.
+    pub level_gap: usize,
+    /// The additional horizontal distance between neighboring siblings
+    pub sibling_gap: usize,
+    /// The additional horizontal distance between neighboring siblings of which at least one has
+    /// children, which separates the subtrees more clearly than the leaves
+    pub subtree_gap: usize,
+    /// The horizontal distance between the tree and both sides of the image
+    pub margin: usize,
+}
+
+impl Spacing {
+    /// Creates the default spacing.
+    pub const fn new() -> Self {
+        Self {
+            level_gap: 1,
+            sibling_gap: 0,
+            subtree_gap: 0,
+            margin: 0,
+        }
+    }
+
+    /// The spacing with a level gap of at least 1.
+    pub(crate) fn normalized(self) -> Self {
+        Self {
+            level_gap: self.level_gap.max(1),
+            ..self
+        }
+    }
+
+    /// The horizontal gap between two neighboring siblings depending on whether they have
+    /// children.
+    pub(crate) fn gap(&self, left_has_children: bool, right_has_children: bool) -> usize {
+        if left_has_children || right_has_children {
+            self.sibling_gap + self.subtree_gap
+        } else {
+            self.sibling_gap
+        }
+    }
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
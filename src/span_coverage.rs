@@ -0,0 +1,77 @@
+//! The module with the validation of the leaves' coverage of the source text.
+
+use std::ops::Range;
+
+///
+/// A problem of the coverage of the source text by the spans of the leaves, see
+/// [Embedding::span_issues][crate::Embedding::span_issues]. Such problems usually point to bugs
+/// in the tokenizer that drop or duplicate characters.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpanIssue {
+    /// The range of the source is covered by no leaf and contains more than whitespace
+    Gap {
+        /// The uncovered range of the source
+        span: Range<usize>,
+    },
+    /// The spans of two leaves overlap
+    Overlap {
+        /// The range of the source covered by both leaves
+        span: Range<usize>,
+        /// The `ord` of the leaf that starts first
+        first: usize,
+        /// The `ord` of the leaf that starts second
+        second: usize,
+    },
+}
+
+///
+/// Checks that the given spans of the leaves cover the source without gaps and overlaps. The
+/// leaves are given as pairs of their `ord`s and spans in any order. Gaps that contain only
+/// whitespace are accepted, because many tokenizers skip it, gaps that aren't on char boundaries
+/// are not. Empty spans neither cover nor overlap anything.
+///
+pub(crate) fn span_issues(
+    leaves: impl IntoIterator<Item = (usize, Range<usize>)>,
+    source: &str,
+) -> Vec<SpanIssue> {
+    let mut leaves = leaves
+        .into_iter()
+        .filter(|(_, span)| !span.is_empty())
+        .collect::<Vec<_>>();
+    leaves.sort_by_key(|(ord, span)| (span.start, span.end, *ord));
+
+    let mut issues = Vec::new();
+    // Ranges that split a character or reach behind the source are gaps in any case
+    let gap = |span: Range<usize>, issues: &mut Vec<SpanIssue>| {
+        let whitespace = source
+            .get(span.clone())
+            .is_some_and(|text| text.chars().all(char::is_whitespace));
+        if !span.is_empty() && !whitespace {
+            issues.push(SpanIssue::Gap { span });
+        }
+    };
+    // The leaf that reaches furthest into the source so far
+    let mut covering: Option<(usize, usize)> = None;
+    for (ord, span) in leaves {
+        let covered_end = covering.map_or(0, |(_, end)| end);
+        if span.start > covered_end {
+            gap(covered_end..span.start, &mut issues);
+        } else if let Some((first, end)) = covering.filter(|(_, end)| span.start < *end) {
+            issues.push(SpanIssue::Overlap {
+                span: span.start..end.min(span.end),
+                first,
+                second: ord,
+            });
+        }
+        if span.end > covered_end {
+            covering = Some((ord, span.end));
+        }
+    }
+    gap(
+        covering.map_or(0, |(_, end)| end)..source.len(),
+        &mut issues,
+    );
+    issues
+}
@@ -0,0 +1,262 @@
+//! The module with the comparison of SVG documents for regression tests.
+
+use std::collections::BTreeMap;
+
+use crate::{LayouterError, Result};
+
+/// The maximal number of differences listed in the error message
+const MAX_REPORTED_DIFFERENCES: usize = 10;
+
+///
+/// Compares two SVG documents, e.g. a golden file and the output of the current version, and
+/// returns a [LayouterError::SvgMismatch] that lists the differences if they don't match.
+///
+/// The documents are compared element by element. Formatting differences like whitespace between
+/// the elements, the order of the attributes or the quoting style are ignored. All numbers found in
+/// attribute values and texts, e.g. coordinates, are compared with the given absolute tolerance,
+/// which makes the comparison robust against rounding differences between platforms.
+///
+/// ```
+/// use syntree_layout::compare_svg;
+///
+/// let expected = r#"<svg width="100"><text x="10.0" y="25">root</text></svg>"#;
+/// let actual = "<svg width='100'>\n  <text y=\"25\" x=\"10.004\">root</text>\n</svg>";
+/// assert!(compare_svg(expected, actual, 0.01).is_ok());
+/// assert!(compare_svg(expected, actual, 0.001).is_err());
+/// ```
+///
+pub fn compare_svg(expected: &str, actual: &str, tolerance: f64) -> Result<()> {
+    let expected = parse(expected)?;
+    let actual = parse(actual)?;
+
+    let mut differences = Vec::new();
+    for (index, (e, a)) in expected.iter().zip(&actual).enumerate() {
+        if !e.matches(a, tolerance) {
+            differences.push(format!(
+                "item {}: expected {}, found {}",
+                index,
+                e.describe(),
+                a.describe()
+            ));
+        }
+    }
+    if expected.len() != actual.len() {
+        differences.push(format!(
+            "expected {} items, found {}",
+            expected.len(),
+            actual.len()
+        ));
+    }
+
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        let count = differences.len();
+        differences.truncate(MAX_REPORTED_DIFFERENCES);
+        Err(LayouterError::SvgMismatch { count, differences })
+    }
+}
+
+/// A part of an attribute value or a text
+#[derive(Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Other(String),
+}
+
+/// The parsed content of an SVG document in document order
+#[derive(Debug)]
+enum Item {
+    Start(String, BTreeMap<String, String>),
+    End(String),
+    Text(String),
+}
+
+impl Item {
+    fn matches(&self, other: &Item, tolerance: f64) -> bool {
+        match (self, other) {
+            (Item::Start(name, attributes), Item::Start(other_name, other_attributes)) => {
+                name == other_name
+                    && attributes.len() == other_attributes.len()
+                    && attributes.iter().all(|(key, value)| {
+                        other_attributes
+                            .get(key)
+                            .is_some_and(|other| values_match(value, other, tolerance))
+                    })
+            }
+            (Item::End(name), Item::End(other_name)) => name == other_name,
+            (Item::Text(text), Item::Text(other_text)) => values_match(text, other_text, tolerance),
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Item::Start(name, attributes) => format!(
+                "<{}{}>",
+                name,
+                attributes
+                    .iter()
+                    .map(|(key, value)| format!(" {}=\"{}\"", key, value))
+                    .collect::<String>()
+            ),
+            Item::End(name) => format!("</{}>", name),
+            Item::Text(text) => format!("text {:?}", text),
+        }
+    }
+}
+
+fn values_match(value: &str, other: &str, tolerance: f64) -> bool {
+    let tokens = tokenize(value);
+    let other_tokens = tokenize(other);
+    tokens.len() == other_tokens.len()
+        && tokens
+            .iter()
+            .zip(&other_tokens)
+            .all(|(token, other)| match (token, other) {
+                (Token::Number(n), Token::Number(m)) => (n - m).abs() <= tolerance,
+                _ => token == other,
+            })
+}
+
+/// Splits a value into numbers and the remaining parts with normalized whitespace.
+fn tokenize(value: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut other = String::new();
+    let mut chars = value.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let starts_number = c.is_ascii_digit()
+            || ((c == '-' || c == '.')
+                && chars.peek().is_some_and(|(_, next)| next.is_ascii_digit()));
+        if starts_number {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, next)) = chars.peek() {
+                if next.is_ascii_digit() || next == '.' {
+                    end = i + next.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            match value[start..end].parse::<f64>() {
+                Ok(number) => {
+                    push_other(&mut tokens, &mut other);
+                    tokens.push(Token::Number(number));
+                }
+                Err(_) => other.push_str(&value[start..end]),
+            }
+        } else if c.is_whitespace() {
+            if !other.is_empty() && !other.ends_with(' ') {
+                other.push(' ');
+            }
+        } else {
+            other.push(c);
+        }
+    }
+    push_other(&mut tokens, &mut other);
+    tokens
+}
+
+fn push_other(tokens: &mut Vec<Token>, other: &mut String) {
+    let trimmed = other.trim();
+    if !trimmed.is_empty() {
+        tokens.push(Token::Other(trimmed.to_string()));
+    }
+    other.clear();
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parses the elements and texts of an SVG document. Declarations and comments are skipped.
+fn parse(svg: &str) -> Result<Vec<Item>> {
+    let malformed = |msg: &'static str| {
+        move || LayouterError::MalformedSvg {
+            msg: msg.to_string(),
+        }
+    };
+    let mut items = Vec::new();
+    let mut rest = svg;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            push_text(&mut items, rest);
+            break;
+        };
+        push_text(&mut items, &rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("<!--") {
+            let end = rest
+                .find("-->")
+                .ok_or_else(malformed("unterminated comment"))?;
+            rest = &rest[end + 3..];
+            continue;
+        }
+        let end = tag_end(rest).ok_or_else(malformed("unterminated tag"))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            items.push(Item::End(name.trim().to_string()));
+            continue;
+        }
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let name = tag[..name_end].to_string();
+        let attributes =
+            parse_attributes(&tag[name_end..]).ok_or_else(malformed("invalid attributes"))?;
+        items.push(Item::Start(name.clone(), attributes));
+        if self_closing {
+            items.push(Item::End(name));
+        }
+    }
+    Ok(items)
+}
+
+/// Finds the closing `>` of the tag at the start of the given text, skipping quoted values.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn push_text(items: &mut Vec<Item>, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        items.push(Item::Text(unescape(text)));
+    }
+}
+
+fn parse_attributes(mut text: &str) -> Option<BTreeMap<String, String>> {
+    let mut attributes = BTreeMap::new();
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Some(attributes);
+        }
+        let eq = text.find('=')?;
+        let key = text[..eq].trim().to_string();
+        text = text[eq + 1..].trim_start();
+        let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = text[1..].find(quote)? + 1;
+        attributes.insert(key, unescape(&text[1..end]));
+        text = &text[end + 1..];
+    }
+}
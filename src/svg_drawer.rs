@@ -1,158 +1,2501 @@
 //! The module with the crate's default drawer.
 
-use crate::{Drawer, EmbeddedNode, LayouterError, Result};
-use std::path::Path;
+use crate::{
+    embedding::tree_width, BorderStyle, DepthColors, DiagnosticLevel, DrawContext, Drawer,
+    EdgeStyle, EmbeddedNode, FontMetrics, LabelOrientation, LayoutKind, LayouterError, Legend,
+    LegendEntry, LegendPosition, NodeShape, Orientation, Profile, ResolvedEmbedding, ResolvedNode,
+    Result, SvgDrawerStyle, SvgMetadata, SvgSize, SvgTemplate, Theme,
+};
 use xml_writer::XmlWriter;
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
+use std::io::Write;
+use std::ops::Range;
+use std::sync::Arc;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-const X_MARGIN: f32 = 10.0;
-const Y_MARGIN: f32 = 25.0;
-const Y_FACTOR: f32 = 3.5;
-const FONT_X_SIZE: f32 = 10.0;
-const FONT_Y_SIZE: f32 = 10.0;
+/// A function that maps the logical x coordinate and the y order of a node to pixels
+type CoordinateMapping = Arc<dyn Fn(usize, usize) -> (f64, f64) + Send + Sync>;
+
+/// A function that post-processes the text of a label before it is drawn
+type LabelFilter = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// A function that returns the target of the link of a node, if any
+type LinkFunction = Arc<dyn Fn(&EmbeddedNode) -> Option<String> + Send + Sync>;
+
+/// A function that returns additional CSS classes of a node
+type ClassFunction = Arc<dyn Fn(&EmbeddedNode) -> Vec<String> + Send + Sync>;
+
+/// A function that writes custom markup of a node into the given buffer
+type NodeRenderer = Arc<dyn Fn(&ResolvedNode, &EmbeddedNode, &mut String) + Send + Sync>;
+
+///
+/// Escapes the characters that would end or break a double quoted attribute value. Single quotes
+/// are kept because they quote the family names of CSS font stacks.
+///
+fn escape_attribute(value: &str) -> Cow<'_, str> {
+    if !value.contains(['&', '<', '"']) {
+        return Cow::Borrowed(value);
+    }
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+        .into()
+}
+
+///
+/// Replaces the control characters that are not allowed in XML documents by their visible
+/// counterparts from the Unicode block *Control Pictures*, e.g. `\0` by `␀`. Line breaks are
+/// kept because they separate the lines of wrapped labels.
+///
+fn sanitize(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| c.is_control() && c != '\n') {
+        return Cow::Borrowed(text);
+    }
+    text.chars()
+        .map(|c| match c {
+            '\n' => c,
+            '\0'..='\u{1f}' => char::from_u32(0x2400 + c as u32).unwrap_or('\u{fffd}'),
+            '\u{7f}' => '\u{2421}',
+            c if c.is_control() => '\u{fffd}',
+            c => c,
+        })
+        .collect::<String>()
+        .into()
+}
+
+///
+/// Quotes the given text as a JSON string.
+///
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+///
+/// Quotes the given text as a JavaScript string that can be placed into an inline `<script>`.
+/// The `<` is escaped as well, so that the text can't close the script element.
+///
+pub(crate) fn script_string(text: &str) -> String {
+    json_string(text).replace('<', "\\u003c")
+}
+
+/// The script that pans the drawing by dragging and zooms it with the mouse wheel around the
+/// pointer, a double click restores the initial view. It expects the global `prefix` with the id
+/// prefix of the document and transforms the group with the id `viewport`.
+const PAN_ZOOM_SCRIPT: &str = r#"
+(function () {
+  const viewport = document.getElementById(prefix + "viewport");
+  if (!viewport) { return; }
+  const svg = viewport.ownerSVGElement;
+  let x = 0, y = 0, scale = 1, drag = null;
+  function apply() {
+    viewport.setAttribute("transform", "translate(" + x + " " + y + ") scale(" + scale + ")");
+  }
+  function point(event) {
+    const p = svg.createSVGPoint();
+    p.x = event.clientX;
+    p.y = event.clientY;
+    return p.matrixTransform(svg.getScreenCTM().inverse());
+  }
+  svg.style.cursor = "grab";
+  svg.addEventListener("wheel", (event) => {
+    event.preventDefault();
+    const p = point(event);
+    const next = Math.min(Math.max(scale * Math.exp(-event.deltaY * 0.002), 0.05), 50);
+    x = p.x - (p.x - x) * next / scale;
+    y = p.y - (p.y - y) * next / scale;
+    scale = next;
+    apply();
+  }, { passive: false });
+  svg.addEventListener("pointerdown", (event) => {
+    const p = point(event);
+    drag = { x: p.x - x, y: p.y - y };
+  });
+  window.addEventListener("pointermove", (event) => {
+    if (!drag) { return; }
+    const p = point(event);
+    x = p.x - drag.x;
+    y = p.y - drag.y;
+    apply();
+  });
+  window.addEventListener("pointerup", () => { drag = null; });
+  svg.addEventListener("dblclick", () => { x = 0; y = 0; scale = 1; apply(); });
+})();
+"#;
+
+/// The colors of the group boxes, groups with equal labels get equal colors
+const GROUP_COLORS: [&str; 6] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#b07aa1", "#76b7b2",
+];
+
+/// The area covered by a node's box in pixels
+#[derive(Debug, Clone, Copy)]
+struct NodeFrame {
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+impl NodeFrame {
+    fn bottom(&self) -> f32 {
+        self.top + self.height
+    }
+}
+
+/// The mapping of the embedding's logical coordinates into the plane for the radial layout
+#[derive(Debug, Clone, Copy)]
+struct RadialGeometry {
+    center: (f32, f32),
+    tree_width: f32,
+    level_distance: f32,
+}
+
+impl RadialGeometry {
+    /// The angle of the given logical x coordinate, clockwise from the top
+    fn angle(&self, x_center: usize) -> f32 {
+        if self.tree_width > 0.0 {
+            std::f32::consts::TAU * x_center as f32 / self.tree_width
+        } else {
+            0.0
+        }
+    }
+
+    fn radius(&self, y_order: usize) -> f32 {
+        y_order as f32 * self.level_distance
+    }
+
+    /// The point at the given angle and radius. The coordinates are rounded to thousandths of a
+    /// pixel, because the precision of `sin` and `cos` differs between platforms and the output
+    /// must not.
+    fn point(&self, angle: f32, radius: f32) -> (f32, f32) {
+        let round = |v: f32| (v * 1000.0).round() / 1000.0;
+        (
+            round(self.center.0 + radius * angle.sin()),
+            round(self.center.1 - radius * angle.cos()),
+        )
+    }
+
+    /// The horizontal center and the baseline of a node's label. The label's box reaches from
+    /// `text_height + 3` above the baseline to `text_height` below, thus it is centered around
+    /// the node's point.
+    fn anchor(&self, data: &EmbeddedNode) -> (f32, f32) {
+        let (x, y) = self.point(self.angle(data.x_center), self.radius(data.y_order));
+        (x, y + 1.5)
+    }
+}
+
+/// The placement of all nodes and the size of the image in pixels
+#[derive(Debug)]
+struct SvgLayout {
+    radial: Option<RadialGeometry>,
+    frames: Vec<NodeFrame>,
+    /// The top of the source text strip below the tree
+    strip_top: Option<f32>,
+    width: f32,
+    height: f32,
+}
 
 ///
 /// The `SvgDrawer` type provides the transformation of the embedding information into the Svg
 /// format.
 ///
-#[derive(Debug, Default)]
-pub struct SvgDrawer;
+/// The output is deterministic: the same embedding and options produce the same document on
+/// every run and platform, so generated SVGs can be diffed, see the section *Output stability*
+/// of the README.
+///
+#[derive(Default)]
+pub struct SvgDrawer {
+    style: SvgDrawerStyle,
+    id_prefix: Cow<'static, str>,
+    tooltips: bool,
+    span_bands: Vec<Range<usize>>,
+    layout_kind: LayoutKind,
+    orientation: Orientation,
+    coordinate_mapping: Option<CoordinateMapping>,
+    image_map: bool,
+    template: SvgTemplate,
+    label_filter: Option<LabelFilter>,
+    links: Option<LinkFunction>,
+    depth_colors: Option<DepthColors>,
+    size: SvgSize,
+    preserve_aspect_ratio: Option<Cow<'static, str>>,
+    legend: Legend,
+    source_strip: Option<Cow<'static, str>>,
+    css_classes: bool,
+    node_classes: Option<ClassFunction>,
+    stylesheet: Option<Cow<'static, str>>,
+    pan_zoom: bool,
+    squiggles: bool,
+    font_metrics: Option<FontMetrics>,
+    layer_guides: bool,
+    metadata: Option<SvgMetadata>,
+    node_renderer: Option<NodeRenderer>,
+}
 
 impl SvgDrawer {
     /// Method to create a fresh instance of the `SvgDrawer` type.
     pub const fn new() -> Self {
-        Self
+        Self {
+            style: SvgDrawerStyle::new(),
+            id_prefix: Cow::Borrowed(""),
+            tooltips: false,
+            span_bands: Vec::new(),
+            layout_kind: LayoutKind::Layered,
+            orientation: Orientation::TopDown,
+            coordinate_mapping: None,
+            image_map: false,
+            template: SvgTemplate::new(),
+            label_filter: None,
+            links: None,
+            depth_colors: None,
+            size: SvgSize::Natural,
+            preserve_aspect_ratio: None,
+            legend: Legend::new(),
+            source_strip: None,
+            css_classes: false,
+            node_classes: None,
+            stylesheet: None,
+            pan_zoom: false,
+            squiggles: false,
+            font_metrics: None,
+            layer_guides: false,
+            metadata: None,
+            node_renderer: None,
+        }
     }
 
-    fn scale_y(y: usize) -> f32 {
-        y as f32 * FONT_Y_SIZE * Y_FACTOR + Y_MARGIN
+    ///
+    /// Sets the style used to draw the embedding.
+    ///
+    /// ```
+    /// use syntree_layout::{SvgDrawer, SvgDrawerStyle};
+    ///
+    /// let drawer = SvgDrawer::new().with_style(SvgDrawerStyle {
+    ///     font_family: "monospace".into(),
+    ///     node_stroke: Some("gray".into()),
+    ///     ..SvgDrawerStyle::new()
+    /// });
+    /// assert_eq!("monospace", drawer.style().font_family);
+    /// ```
+    ///
+    pub fn with_style(self, style: SvgDrawerStyle) -> Self {
+        Self { style, ..self }
     }
 
-    fn scale_x(x: usize) -> f32 {
-        x as f32 * FONT_X_SIZE + X_MARGIN
+    ///
+    /// Sets a prefix that is put in front of all ids of the generated elements.
+    /// Use distinct prefixes when several generated SVGs are inlined into one HTML page to avoid
+    /// colliding ids.
+    ///
+    /// ```
+    /// use syntree_layout::SvgDrawer;
+    ///
+    /// let drawer = SvgDrawer::new().with_id_prefix("tree1-");
+    /// assert_eq!("tree1-", drawer.id_prefix());
+    /// ```
+    ///
+    pub fn with_id_prefix(self, id_prefix: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            id_prefix: id_prefix.into(),
+            ..self
+        }
     }
 
-    fn measure_string(str: &str) -> f32 {
-        str.len() as f32 * FONT_X_SIZE
+    /// Provides access to the id prefix used by this drawer.
+    pub fn id_prefix(&self) -> &str {
+        &self.id_prefix
     }
-}
 
-///
-/// The concrete implementation of the `Drawer` trait for `SvgDrawer`.
-///
-impl Drawer for SvgDrawer {
     ///
-    /// The concrete implementation of the `Drawer::draw` trait method.
-    /// The realization is as it is - with no way to configure for instance the font used.
-    /// This decision was mode for the sake of simplicity.
+    /// When set to true each node's text gets a `<title>` element that is shown as tooltip when
+    /// hovering over the node. The tooltip contains the node's id and span in the syntax tree and
+    /// the text provided by the [Visualize::tooltip][crate::Visualize::tooltip] hook.
+    /// Additionally the attributes `data-node-id` and `data-span` are written.
     ///
-    /// Anyway it should be easy to provide ones own Drawer implementation that fits the concrete
-    /// use case better.
-    /// When using the Layouter API you can set the Drawer instance by calling the `with_drawer`
-    /// method.
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
     ///
-    /// # Panics
+    /// let embedding = vec![EmbeddedNode {
+    ///     text: "root".to_string(),
+    ///     span: 0..4,
+    ///     tooltip: Some("The root".to_string()),
+    ///     ..Default::default()
+    /// }];
+    /// let svg = SvgDrawer::new()
+    ///     .with_tooltips(true)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains("<title>node 0, span 0..4\nThe root</title>"));
+    /// assert!(svg.contains(r#"data-span="0..4""#));
+    /// ```
     ///
-    /// The method should not panic. If you encounter a panic this should be originated from
-    /// bugs in coding. Please report such panics.
+    pub fn with_tooltips(self, tooltips: bool) -> Self {
+        Self { tooltips, ..self }
+    }
+
     ///
-    /// # Complexity
+    /// Sets source ranges that are highlighted by translucent vertical bands behind all nodes
+    /// whose spans lie inside of them, e.g. to connect a selection in an editor with the regions
+    /// of the tree.
+    /// Color and opacity of the bands are configured in the [SvgDrawerStyle].
     ///
-    /// The algorithm is of time complexity class O(n).
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
     ///
-    fn draw(&self, file_name: &Path, embedding: &[EmbeddedNode]) -> Result<()> {
-        let file = File::create(file_name).map_err(LayouterError::from_io_error)?;
-        let xml = XmlWriter::new(file);
+    /// let embedding = vec![EmbeddedNode {
+    ///     text: "root".to_string(),
+    ///     span: 2..4,
+    ///     ..Default::default()
+    /// }];
+    /// let svg = SvgDrawer::new()
+    ///     .with_span_bands([0..5, 8..9])
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"id="band-0""#));
+    /// ```
+    ///
+    pub fn with_span_bands(self, span_bands: impl IntoIterator<Item = Range<usize>>) -> Self {
+        Self {
+            span_bands: span_bands.into_iter().collect(),
+            ..self
+        }
+    }
 
-        fn build_xml(mut xml: XmlWriter<File>, embedding: &[EmbeddedNode]) -> std::io::Result<()> {
-            xml.dtd("UTF-8")?;
-            xml.begin_elem("svg")?;
-            xml.attr("xmlns", "http://www.w3.org/2000/svg")?;
-            xml.attr("version", "1.1")?;
-            xml.attr("lang", "en")?;
+    ///
+    /// Sets the kind of layout used to map the embedding into the plane.
+    /// Span bands are only drawn in the [LayoutKind::Layered] layout.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, LayoutKind, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, y_order: 1, ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_layout_kind(LayoutKind::Radial)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains("<path id=\"edge-1\""));
+    /// ```
+    ///
+    pub fn with_layout_kind(self, layout_kind: LayoutKind) -> Self {
+        Self {
+            layout_kind,
+            ..self
+        }
+    }
 
-            const STRING_FONT: &str = "font-family: 'Courier'; font-style: normal";
-            const EMPHASIZE_FONT: &str =
-                "font-family: 'Courier'; font-weight: bold; font-style: normal";
+    ///
+    /// Sets whether the root of the [LayoutKind::Layered] layout is drawn at the top or at the
+    /// bottom. With [Orientation::BottomUp] the levels are mirrored and the edges lead from the
+    /// top of each parent up to the bottom of its children. The radial layout ignores the
+    /// orientation.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, Orientation, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, y_order: 1, ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let resolved = SvgDrawer::new()
+    ///     .with_orientation(Orientation::BottomUp)
+    ///     .resolve(&embedding);
+    /// assert!(resolved[1].y < resolved[0].y);
+    /// ```
+    ///
+    pub fn with_orientation(self, orientation: Orientation) -> Self {
+        Self {
+            orientation,
+            ..self
+        }
+    }
 
-            let tree_depth =
-                embedding
-                    .iter()
-                    .fold(0, |acc, e| if e.y_order > acc { e.y_order } else { acc });
-            let tree_width = embedding.iter().fold(0, |acc, e| {
-                if e.x_extent_children > acc {
-                    e.x_extent_children
-                } else {
-                    acc
+    ///
+    /// Sets a function that maps the logical x coordinate and the y order of a node to the pixel
+    /// position of its label, i.e. the label's horizontal center and its baseline.
+    /// This replaces the linear mapping of the [LayoutKind::Layered] layout by the style's
+    /// [Units][crate::Units], e.g. to apply a logarithmic depth spacing. The image is sized to fit
+    /// all nodes.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, y_order: 1, ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_coordinate_mapping(|x, y_order| {
+    ///         (x as f64 * 10.0 + 10.0, 40.0 * (y_order as f64 + 1.0).ln() + 30.0)
+    ///     })
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<line id="edge-1" x1="30" y1="40" x2="30""#));
+    /// ```
+    ///
+    pub fn with_coordinate_mapping(
+        self,
+        mapping: impl Fn(usize, usize) -> (f64, f64) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            coordinate_mapping: Some(Arc::new(mapping)),
+            ..self
+        }
+    }
+
+    ///
+    /// Lets the drawer write an image map next to each SVG file. The image map is a JSON file
+    /// with the same name and the extension `map.json`, e.g. `tree.map.json` for `tree.svg`.
+    /// It lists the pixel rectangle and the span of each node, see [SvgDrawer::render_image_map],
+    /// so that web frontends can overlay their own hotspots on static images.
+    ///
+    pub fn with_image_map(self, image_map: bool) -> Self {
+        Self { image_map, ..self }
+    }
+
+    ///
+    /// Renders the image map of the given embedding as JSON. The image map contains the size of
+    /// the image and, for each node, its `ord`, its `node_id`, the rectangle of its box in pixels,
+    /// its span, the `ord` of its parent, the id of its label element in the SVG document and its
+    /// label. The element ids let viewers attach their handlers to the drawn nodes.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, span: 0..4, ..Default::default() },
+    /// ];
+    /// let map = SvgDrawer::new().render_image_map(&embedding).unwrap();
+    /// assert!(map.contains(r#""width": 60, "height": 60"#));
+    /// assert!(map.contains(
+    ///     r#"{"ord": 0, "node_id": 0, "x": 7, "y": 12, "width": 46, "height": 23, "span": [0, 4], "#
+    /// ));
+    /// assert!(map.contains(r#""parent": null, "id": "node-0", "text": "root"}"#));
+    /// ```
+    ///
+    pub fn render_image_map(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        let resolved = self.resolve(embedding);
+        let nodes = embedding
+            .iter()
+            .zip(&resolved)
+            .map(|(data, node)| {
+                format!(
+                    r#"    {{"ord": {}, "node_id": {}, "x": {}, "y": {}, "width": {}, "height": {}, "span": [{}, {}], "parent": {}, "id": {}, "text": {}}}"#,
+                    data.ord,
+                    data.node_id,
+                    node.x,
+                    node.y,
+                    node.width,
+                    node.height,
+                    data.span.start,
+                    data.span.end,
+                    data.parent
+                        .map_or_else(|| "null".to_string(), |parent| parent.to_string()),
+                    json_string(&format!("{}node-{}", self.id_prefix, data.ord)),
+                    json_string(&data.text)
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(format!(
+            "{{\n  \"width\": {}, \"height\": {},\n  \"nodes\": [\n{}\n  ]\n}}\n",
+            resolved.width(),
+            resolved.height(),
+            nodes.join(",\n")
+        ))
+    }
+
+    ///
+    /// Resolves the given embedding into pixel coordinates exactly like this drawer places the
+    /// nodes, i.e. with its style, its layout kind and its coordinate mapping.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, y_order: 1, ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let resolved = SvgDrawer::new().resolve(&embedding);
+    /// assert_eq!((60.0, 95.0), (resolved.width(), resolved.height()));
+    /// assert_eq!((7.0, 12.0, 46.0, 23.0), (resolved[0].x, resolved[0].y, resolved[0].width, resolved[0].height));
+    /// assert_eq!((30.0, 58.5), resolved[1].center());
+    /// ```
+    ///
+    pub fn resolve(&self, embedding: &[EmbeddedNode]) -> ResolvedEmbedding {
+        let layout = self.layout(embedding);
+        let nodes = embedding
+            .iter()
+            .zip(&layout.frames)
+            .map(|(data, frame)| resolved_node(data, frame))
+            .collect();
+        ResolvedEmbedding::new(nodes, layout.width, layout.height)
+    }
+
+    ///
+    /// Returns the width and the height of the document this drawer generates for the given
+    /// embedding, including the gutter of the layer guides and the requested [SvgSize].
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer, SvgSize};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, y_order: 1, ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// assert_eq!((60.0, 95.0), SvgDrawer::new().document_size(&embedding));
+    /// ```
+    ///
+    pub fn document_size(&self, embedding: &[EmbeddedNode]) -> (f32, f32) {
+        let layout = self.layout(embedding);
+        let guides = self.layer_guides(embedding, layout.radial.is_some());
+        self.size
+            .apply(layout.width + self.guide_gutter(&guides), layout.height)
+    }
+
+    /// Sets the template whose snippets are inserted into the generated document.
+    pub fn with_template(self, template: SvgTemplate) -> Self {
+        Self { template, ..self }
+    }
+
+    ///
+    /// Sets the size of the generated documents. The drawing keeps its pixel coordinates and is
+    /// scaled by the viewer into the requested size via the document's `viewBox`. The
+    /// [ResolvedEmbedding] and the image map refer to the coordinates of the drawing.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer, SvgSize};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_size(SvgSize::Scaled(2.0))
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"width="120" height="120" viewBox="0 0 60 60""#));
+    /// ```
+    ///
+    pub fn with_size(self, size: SvgSize) -> Self {
+        Self { size, ..self }
+    }
+
+    ///
+    /// Sets the `preserveAspectRatio` attribute of the generated documents, e.g.
+    /// `"xMinYMin meet"` to align the drawing with the top left corner of a
+    /// [SvgSize::Fixed] area. Viewers use `"xMidYMid meet"` if it is not set.
+    ///
+    pub fn with_preserve_aspect_ratio(
+        self,
+        preserve_aspect_ratio: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            preserve_aspect_ratio: Some(preserve_aspect_ratio.into()),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets the margins between the drawing and the borders of the document in pixels. This is a
+    /// shortcut for setting [SvgDrawerStyle::x_margin] and [SvgDrawerStyle::y_margin].
+    ///
+    /// ```
+    /// use syntree_layout::SvgDrawer;
+    ///
+    /// let drawer = SvgDrawer::new().with_margins(4.0, 8.0);
+    /// assert_eq!((4.0, 8.0), (drawer.style().x_margin, drawer.style().y_margin));
+    /// ```
+    ///
+    pub fn with_margins(mut self, x_margin: f32, y_margin: f32) -> Self {
+        self.style.x_margin = x_margin;
+        self.style.y_margin = y_margin;
+        self
+    }
+
+    ///
+    /// Sets the legend that is drawn in a corner of the image to explain the node styles.
+    /// The image grows if it is too small for the legend.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, Legend, NodeStyle, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let legend = Legend::new()
+    ///     .with_entry("token", NodeStyle { fill: Some("lightgreen".into()), ..Default::default() });
+    /// let svg = SvgDrawer::new().with_legend(legend).render_string(&embedding).unwrap();
+    /// assert!(svg.contains(r#"<g id="legend">"#));
+    /// assert!(svg.contains(">token</text>"));
+    /// ```
+    ///
+    pub fn with_legend(self, legend: Legend) -> Self {
+        Self { legend, ..self }
+    }
+
+    ///
+    /// Tints the background of the nodes by their depth in the tree, either with the colors of a
+    /// palette or with a gradient from the root to the deepest level.
+    /// A fill given in the node's style takes precedence over the tint.
+    ///
+    /// ```
+    /// use syntree_layout::{DepthColors, EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), y_order: 1, x_center: 2, x_extent: 5, ..Default::default() },
+    /// ];
+    /// let drawer = SvgDrawer::new()
+    ///     .with_depth_colors(DepthColors::Palette(vec!["#ffeeee".into(), "#eeeeff".into()]));
+    /// let svg = drawer.render_string(&embedding).unwrap();
+    /// assert!(svg.contains(r##"fill="#ffeeee""##));
+    /// assert!(svg.contains(r##"fill="#eeeeff""##));
+    /// ```
+    ///
+    pub fn with_depth_colors(mut self, depth_colors: DepthColors) -> Self {
+        self.depth_colors = Some(depth_colors);
+        self
+    }
+
+    ///
+    /// Draws the given source text as a strip below the tree in the layered layout. Each leaf is
+    /// connected to its span in the source text and the spans are marked by brackets, which
+    /// makes gaps, overlaps and other tokenization mistakes immediately visible.
+    /// Line breaks are shown as `↵`.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, span: 0..3, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_source_strip("let")
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(">let</text>"));
+    /// assert!(svg.contains(r#"<path id="source-span-0" d="M 10 "#));
+    /// ```
+    ///
+    pub fn with_source_strip(self, source: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            source_strip: Some(source.into()),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets a function that post-processes the text of each label before it is measured and
+    /// drawn, e.g. to make whitespace visible or to shorten qualified names. The result is XML
+    /// escaped by the drawer. Control characters that are not allowed in XML documents are
+    /// replaced by their Unicode control pictures afterwards, e.g. `\t` is drawn as `␉`.
+    /// Line breaks separate the lines of a label.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "a\tb".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new().render_string(&embedding).unwrap();
+    /// assert!(svg.contains(">a\u{2409}b<"));
+    ///
+    /// let svg = SvgDrawer::new()
+    ///     .with_label_filter(|text| text.replace('\t', " "))
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(">a b<"));
+    /// ```
+    ///
+    pub fn with_label_filter(
+        self,
+        filter: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            label_filter: Some(Arc::new(filter)),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets a function that draws nodes with custom markup, e.g. icons, gradients or HTML content
+    /// in a `foreignObject`. It receives the box of the node in pixel coordinates and the node
+    /// and appends the markup to the given buffer, which replaces the node's box and label. If it
+    /// appends nothing the node is drawn as usual. Edges, badges and links are still drawn by the
+    /// drawer.
+    ///
+    /// The markup is written verbatim, thus it must be well-formed and the function is
+    /// responsible to escape the texts it writes.
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_node_renderer(|geometry, node, out| {
+    ///         let (cx, cy) = geometry.center();
+    ///         write!(out, r#"<circle cx="{}" cy="{}" r="5" data-label="{}"/>"#, cx, cy, node.text)
+    ///             .unwrap();
+    ///     })
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"r="5" data-label="root"/>"#));
+    /// assert!(!svg.contains(r#"id="node-0""#));
+    /// ```
+    ///
+    pub fn with_node_renderer(
+        self,
+        renderer: impl Fn(&ResolvedNode, &EmbeddedNode, &mut String) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            node_renderer: Some(Arc::new(renderer)),
+            ..self
+        }
+    }
+
+    ///
+    /// Sets a function that returns the link target of each node. Nodes for which it returns a
+    /// URL are clickable: their box and label are wrapped in an `<a>` element, e.g. to link to
+    /// the documentation of a grammar rule or to a source location in a code browser. The
+    /// [HtmlDrawer][crate::HtmlDrawer] inherits the links from its `SvgDrawer`.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "Expr".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, span: 0..3, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_links(|node| Some(format!("https://example.com/rules#{}", node.text)))
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<a href="https://example.com/rules#Expr">"#));
+    /// ```
+    ///
+    pub fn with_links(
+        self,
+        links: impl Fn(&EmbeddedNode) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            links: Some(Arc::new(links)),
+            ..self
+        }
+    }
+
+    ///
+    /// When set to true the nodes and edges are tagged with CSS classes and the texts are styled
+    /// with presentation attributes instead of inline styles, so that style sheets of the page
+    /// or a referenced style sheet can restyle the diagram without generating it again. The
+    /// drawn image stays the same as long as no style sheet applies.
+    ///
+    /// The boxes and texts of the nodes get the classes `box` and `label` respectively, the
+    /// edges get the class `edge`. Each node gets the classes
+    /// * `node`,
+    /// * `leaf` for nodes without children,
+    /// * `emphasized` for emphasized nodes,
+    /// * `depth-N` with the depth `N` of the node, the root has the depth 0,
+    /// * `error`, `warning` or `recovered` according to the node's diagnostic level,
+    /// * `collapsed` for collapsed stubs
+    /// * `gap` for the pseudonodes of gaps in the source coverage
+    /// * `trivia` for trivia tokens
+    /// * and the classes returned by the function given to
+    ///   [with_node_classes][Self::with_node_classes].
+    ///
+    /// Edges get the `depth-N` class of the node they lead to. Every node gets a box, which is
+    /// invisible unless requested by the style, to have something to restyle.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), x_center: 2, x_extent: 5, y_order: 1, is_emphasized: true, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_css_classes(true)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<text id="node-0" class="label node depth-0""#));
+    /// assert!(svg.contains(r#"class="box node leaf emphasized depth-1""#));
+    /// assert!(svg.contains(r#"<line id="edge-1" class="edge depth-1""#));
+    /// assert!(!svg.contains(" style="));
+    /// ```
+    ///
+    pub fn with_css_classes(self, css_classes: bool) -> Self {
+        Self {
+            css_classes,
+            ..self
+        }
+    }
+
+    ///
+    /// Sets a function that returns additional CSS classes of each node, e.g. the kind of the
+    /// node's grammar rule. This enables the CSS classes, see
+    /// [with_css_classes][Self::with_css_classes]. Classes must not contain whitespace.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "Expr".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_node_classes(|node| vec![format!("rule-{}", node.text.to_lowercase())])
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"class="label node leaf depth-0 rule-expr""#));
+    /// ```
+    ///
+    pub fn with_node_classes(
+        self,
+        node_classes: impl Fn(&EmbeddedNode) -> Vec<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            css_classes: true,
+            node_classes: Some(Arc::new(node_classes)),
+            ..self
+        }
+    }
+
+    ///
+    /// References an external style sheet by an `xml-stylesheet` processing instruction at the
+    /// start of the document. This enables the CSS classes, see
+    /// [with_css_classes][Self::with_css_classes].
+    ///
+    /// Browsers apply such style sheets to standalone SVG documents. SVGs that are inlined into
+    /// HTML pages are styled by the style sheets of the page instead.
+    ///
+    /// ```
+    /// use syntree_layout::SvgDrawer;
+    ///
+    /// let svg = SvgDrawer::new()
+    ///     .with_stylesheet("trees.css")
+    ///     .render_string(&[])
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<?xml-stylesheet type="text/css" href="trees.css"?>"#));
+    /// ```
+    ///
+    pub fn with_stylesheet(self, href: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            css_classes: true,
+            stylesheet: Some(href.into()),
+            ..self
+        }
+    }
+
+    ///
+    /// When set to true the document can be navigated in a browser: dragging pans the tree, the
+    /// mouse wheel zooms it around the pointer and a double click restores the initial view.
+    /// The tree is drawn into a group with the id `viewport`, which is transformed by a small
+    /// embedded script. The background and the legend stay in place.
+    ///
+    /// The group also has the class `svg-pan-zoom_viewport`, thus the svg-pan-zoom library uses
+    /// it as well if it is preferred over the embedded script, e.g. in a page that already loads
+    /// the library.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_pan_zoom(true)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<g id="viewport" class="svg-pan-zoom_viewport">"#));
+    /// assert!(svg.contains("<script><![CDATA["));
+    /// ```
+    ///
+    pub fn with_pan_zoom(self, pan_zoom: bool) -> Self {
+        Self { pan_zoom, ..self }
+    }
+
+    ///
+    /// When set to true nodes with a [DiagnosticLevel] other than `None` are underlined with a
+    /// wavy line in the color of their level, like editors mark problems in the source text, see
+    /// [Layouter::with_diagnostics][crate::Layouter::with_diagnostics]. The lines have the ids
+    /// `squiggle-N` and the class `squiggle`.
+    ///
+    /// ```
+    /// use syntree_layout::{DiagnosticLevel, EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, diagnostic: DiagnosticLevel::Warning, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_squiggles(true)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<path id="squiggle-0" class="squiggle" d="M 10 "#));
+    /// assert!(svg.contains(r#"fill="none" stroke="darkorange""#));
+    /// ```
+    ///
+    pub fn with_squiggles(self, squiggles: bool) -> Self {
+        Self { squiggles, ..self }
+    }
+
+    ///
+    /// When set to true faint horizontal lines are drawn through the layers of a layered tree and
+    /// the layers are numbered in a gutter on the left side, starting with 0 at the root. This
+    /// helps to talk about "the node at depth 7". The number of a layer is the depth of its nodes
+    /// unless the leaves are aligned to the bottom. The guides are grouped into an element with
+    /// the id `guides`. Radial layouts and custom coordinate mappings are drawn without guides.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_layer_guides(true)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"<g id="guides" class="guides">"#));
+    /// assert!(svg.contains(r#"<line id="guide-0" class="guide" "#));
+    /// ```
+    ///
+    pub fn with_layer_guides(self, layer_guides: bool) -> Self {
+        Self {
+            layer_guides,
+            ..self
+        }
+    }
+
+    ///
+    /// Writes the given metadata into a `<metadata>` element at the start of the document, so that
+    /// generated files can be traced back to the crate version and the tree they were drawn from.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, SvgDrawer, SvgMetadata};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), x_center: 2, x_extent: 5, x_extent_children: 5, ..Default::default() },
+    /// ];
+    /// let svg = SvgDrawer::new()
+    ///     .with_metadata(SvgMetadata::new().with_source("example.txt"))
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(svg.contains(r#"nodes="1""#));
+    /// assert!(svg.contains(r#"source="example.txt""#));
+    /// ```
+    ///
+    pub fn with_metadata(self, metadata: SvgMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+            ..self
+        }
+    }
+
+    ///
+    /// Renders the labels in the given font family and measures them with its metrics. The font
+    /// size and the character width of the style are taken from the metrics. Give the same
+    /// metrics to [Layouter::with_font_metrics][crate::Layouter::with_font_metrics], so that the
+    /// boxes of the nodes fit into the space the layout reserves for them.
+    ///
+    /// ```
+    /// use syntree_layout::{FontMetrics, SvgDrawer};
+    ///
+    /// let drawer = SvgDrawer::new().with_font("Courier New", FontMetrics::monospace(14.0, 0.6));
+    /// assert_eq!("Courier New", drawer.style().font_family);
+    /// assert_eq!(14.0, drawer.style().font_size);
+    /// ```
+    ///
+    pub fn with_font(mut self, family: impl Into<Cow<'static, str>>, metrics: FontMetrics) -> Self {
+        self.style.font_family = family.into();
+        self.style.font_size = metrics.font_size();
+        self.style.char_width = metrics.unit_width();
+        Self {
+            font_metrics: Some(metrics),
+            ..self
+        }
+    }
+
+    ///
+    /// Returns the CSS classes of each node of the embedding without the element's own class,
+    /// or `None` if the classes are disabled, see [with_css_classes][Self::with_css_classes].
+    ///
+    fn node_classes(&self, embedding: &[EmbeddedNode]) -> Vec<Option<String>> {
+        if !self.css_classes {
+            return vec![None; embedding.len()];
+        }
+        let depths = depths(embedding);
+        let parents = embedding
+            .iter()
+            .filter_map(|node| node.parent)
+            .collect::<std::collections::HashSet<_>>();
+        embedding
+            .iter()
+            .map(|node| {
+                let mut classes = vec!["node".to_string()];
+                if !parents.contains(&node.ord) {
+                    classes.push("leaf".to_string());
                 }
-            });
+                if node.is_emphasized {
+                    classes.push("emphasized".to_string());
+                }
+                classes.push(format!("depth-{}", depths[&node.ord]));
+                match node.diagnostic {
+                    DiagnosticLevel::None => {}
+                    DiagnosticLevel::Warning => classes.push("warning".to_string()),
+                    DiagnosticLevel::Recovered => classes.push("recovered".to_string()),
+                    DiagnosticLevel::Error => classes.push("error".to_string()),
+                }
+                if node.collapsed > 0 {
+                    classes.push("collapsed".to_string());
+                }
+                if node.is_gap {
+                    classes.push("gap".to_string());
+                }
+                if node.is_trivia {
+                    classes.push("trivia".to_string());
+                }
+                if let Some(node_classes) = &self.node_classes {
+                    classes.extend(node_classes(node));
+                }
+                Some(classes.join(" "))
+            })
+            .collect()
+    }
 
-            let img_width = SvgDrawer::scale_x(tree_width);
-            let img_height = SvgDrawer::scale_y(tree_depth + 1);
+    /// Applies the label filter and the sanitization to the given text of a label.
+    fn label<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match &self.label_filter {
+            Some(filter) => Cow::Owned(sanitize(&filter(text)).into_owned()),
+            None => sanitize(text),
+        }
+    }
 
-            xml.attr("width", format!("{}", img_width).as_str())?;
+    /// The horizontal center and the baseline of a node's label in the layered layout
+    fn layered_anchor(&self, data: &EmbeddedNode, tree_depth: usize) -> (f32, f32) {
+        let level = self.orientation.level(data.y_order, tree_depth);
+        match &self.coordinate_mapping {
+            Some(mapping) => {
+                let (x, y) = mapping(data.x_center, level);
+                (x as f32, y as f32)
+            }
+            None => self.style.units().to_pixels(data.x_center, level),
+        }
+    }
+
+    /// Creates a document wide unique id from the given local id, escaped for an attribute value.
+    fn id(&self, local_id: std::fmt::Arguments<'_>) -> String {
+        escape_attribute(&format!("{}{}", self.id_prefix, local_id)).into_owned()
+    }
+
+    ///
+    /// Sets the style of the given profile.
+    ///
+    /// ```
+    /// use syntree_layout::{Profile, SvgDrawer};
+    ///
+    /// let drawer = SvgDrawer::new().with_profile(Profile::Compact);
+    /// assert_eq!(12.0, drawer.style().font_size);
+    /// ```
+    ///
+    pub fn with_profile(self, profile: Profile) -> Self {
+        self.with_style(profile.into())
+    }
+
+    ///
+    /// Sets the style of the given theme, see [SvgDrawerStyle::preset] to override individual
+    /// properties.
+    ///
+    /// ```
+    /// use syntree_layout::{SvgDrawer, Theme};
+    ///
+    /// let drawer = SvgDrawer::new().with_theme(Theme::Dark);
+    /// assert_eq!("#e6edf3", drawer.style().text_color);
+    /// ```
+    ///
+    pub fn with_theme(self, theme: Theme) -> Self {
+        self.with_style(theme.into())
+    }
+
+    /// Provides access to the style used by this drawer.
+    pub fn style(&self) -> &SvgDrawerStyle {
+        &self.style
+    }
+
+    fn scale_y(&self, y: usize) -> f32 {
+        self.style.units().y_to_pixels(y)
+    }
+
+    fn scale_x(&self, x: usize) -> f32 {
+        self.style.units().x_to_pixels(x)
+    }
+
+    /// The width of the longest line of the given text
+    fn measure_string(&self, str: &str) -> f32 {
+        match &self.font_metrics {
+            Some(metrics) => metrics.text_width(str),
+            None => {
+                str.lines().map(|line| line.width()).max().unwrap_or(0) as f32
+                    * self.style.char_width
+            }
+        }
+    }
+
+    ///
+    /// Renders the given embedding into a SVG document and returns it as a `String`.
+    /// This is useful when the SVG should be embedded inline, e.g. in a web page or a notebook,
+    /// without writing it to the filesystem first.
+    ///
+    /// ```
+    /// use syntree_layout::SvgDrawer;
+    ///
+    /// let svg = SvgDrawer::new().render_string(&[]).unwrap();
+    /// assert!(svg.starts_with("<?xml"));
+    /// ```
+    ///
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        let mut xml = XmlWriter::new(Vec::new());
+        self.build_xml(&mut xml, embedding)
+            .map_err(LayouterError::from_io_error)?;
+        String::from_utf8(xml.into_inner())
+            .map_err(|e| LayouterError::from_description(&e.to_string()))
+    }
+
+    ///
+    /// Calculates the frame of a node's box. The frame includes the node's padding and touches
+    /// the edges leading to the node and away from it.
+    /// Vertical and wrapped labels start below the frame's top and grow downwards.
+    /// The label of a scaled node shrinks by the node's scale.
+    ///
+    fn node_frame(&self, data: &EmbeddedNode, anchor: (f32, f32)) -> NodeFrame {
+        let style = &self.style;
+        let top = anchor.1 - style.text_height - 3.0;
+        let text = self.label(&data.text);
+        let (width, label_height) = match data.orientation {
+            LabelOrientation::Horizontal => (
+                self.measure_string(&text),
+                style.text_height + text.lines().count().saturating_sub(1) as f32 * style.font_size,
+            ),
+            LabelOrientation::Rotated => (style.text_height, self.measure_string(&text)),
+            LabelOrientation::Stacked => (
+                style.char_width,
+                style.text_height + text.chars().count().saturating_sub(1) as f32 * style.font_size,
+            ),
+        };
+        let (width, label_height) = (width * data.scale, label_height * data.scale);
+        NodeFrame {
+            left: anchor.0 - width / 2.0 - style.node_padding,
+            top,
+            width: width + 2.0 * style.node_padding,
+            height: label_height + style.text_height + 3.0,
+        }
+    }
+
+    /// Writes the text element of a node's label into its frame.
+    fn draw_label<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        data: &EmbeddedNode,
+        frame: &NodeFrame,
+        classes: Option<&str>,
+    ) -> std::io::Result<()> {
+        let style = &self.style;
+        let x = frame.left + style.node_padding;
+        let y = frame.top + style.text_height + 3.0;
+        let text = self.label(&data.text);
+        xml.begin_elem("text")?;
+        xml.attr("id", &self.id(format_args!("node-{}", data.ord)))?;
+        if let Some(classes) = classes {
+            xml.attr("class", &escape_attribute(&format!("label {}", classes)))?;
+        }
+        if data.orientation == LabelOrientation::Rotated {
+            // The glyphs of the rotated text extend to the right of the baseline
+            let y = frame.top + 3.0;
+            xml.attr("x", format!("{}", x).as_str())?;
+            xml.attr("y", format!("{}", y).as_str())?;
+            xml.attr("transform", format!("rotate(90 {} {})", x, y).as_str())?;
+        } else {
+            xml.attr("x", format!("{}", x).as_str())?;
+            xml.attr("y", format!("{}", y).as_str())?;
+        }
+        self.write_text_style(xml, data)?;
+        if self.tooltips {
+            xml.attr("data-node-id", format!("{}", data.node_id).as_str())?;
+            xml.attr(
+                "data-span",
+                format!("{}..{}", data.span.start, data.span.end).as_str(),
+            )?;
+            xml.begin_elem("title")?;
+            let mut title = format!(
+                "node {}, span {}..{}",
+                data.node_id, data.span.start, data.span.end
+            );
+            if let Some(tooltip) = &data.tooltip {
+                title.push('\n');
+                title.push_str(tooltip);
+            }
+            xml.text(&sanitize(&title))?;
+            xml.end_elem()?;
+        }
+        match data.orientation {
+            LabelOrientation::Horizontal | LabelOrientation::Rotated
+                if !data.segments.is_empty() =>
+            {
+                // The diagnostic color takes precedence over the colors of the segments
+                let diagnostic_color = style.diagnostic_color(data.diagnostic);
+                for segment in &data.segments {
+                    xml.begin_elem("tspan")?;
+                    let mut segment_style = Vec::new();
+                    if segment.is_emphasized {
+                        segment_style.push(("font-weight", &*style.emphasized_font_weight));
+                    }
+                    if let Some(color) = diagnostic_color.or(segment.color.as_deref()) {
+                        segment_style.push(("fill", color));
+                    }
+                    if self.css_classes {
+                        for (name, value) in segment_style {
+                            xml.attr(name, &escape_attribute(value))?;
+                        }
+                    } else if !segment_style.is_empty() {
+                        let segment_style = segment_style
+                            .iter()
+                            .map(|(name, value)| format!("{}: {}", name, value))
+                            .collect::<Vec<_>>();
+                        xml.attr("style", &escape_attribute(&segment_style.join("; ")))?;
+                    }
+                    xml.text(&self.label(&segment.text))?;
+                    xml.end_elem()?;
+                }
+            }
+            LabelOrientation::Horizontal if text.contains('\n') => {
+                // Wrapped labels are written line by line
+                for (i, line) in text.lines().enumerate() {
+                    xml.begin_elem("tspan")?;
+                    xml.attr("x", format!("{}", x).as_str())?;
+                    if i > 0 {
+                        xml.attr("dy", format!("{}", style.font_size * data.scale).as_str())?;
+                    }
+                    xml.text(line)?;
+                    xml.end_elem()?;
+                }
+            }
+            LabelOrientation::Horizontal | LabelOrientation::Rotated => {
+                xml.text(&text)?;
+            }
+            LabelOrientation::Stacked => {
+                for (i, c) in text.chars().enumerate() {
+                    xml.begin_elem("tspan")?;
+                    xml.attr("x", format!("{}", x).as_str())?;
+                    if i > 0 {
+                        xml.attr("dy", format!("{}", style.font_size * data.scale).as_str())?;
+                    }
+                    xml.text(c.encode_utf8(&mut [0; 4]))?;
+                    xml.end_elem()?;
+                }
+            }
+        }
+        xml.end_elem()
+    }
+
+    ///
+    /// Draws the bands behind the nodes whose spans lie inside of one of the span bands.
+    /// Overlapping bands are merged to avoid stacking the translucent colors.
+    ///
+    fn draw_span_bands<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        embedding: &[EmbeddedNode],
+        frames: &[NodeFrame],
+        img_height: f32,
+    ) -> std::io::Result<()> {
+        let mut bands = embedding
+            .iter()
+            .zip(frames)
+            .filter(|(data, _)| {
+                self.span_bands
+                    .iter()
+                    .any(|band| band.start <= data.span.start && data.span.end <= band.end)
+            })
+            .map(|(_, frame)| (frame.left, frame.left + frame.width))
+            .collect::<Vec<_>>();
+        bands.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut merged: Vec<(f32, f32)> = Vec::with_capacity(bands.len());
+        for (left, right) in bands {
+            match merged.last_mut() {
+                Some(last) if left <= last.1 => last.1 = last.1.max(right),
+                _ => merged.push((left, right)),
+            }
+        }
+
+        for (i, (left, right)) in merged.into_iter().enumerate() {
+            xml.begin_elem("rect")?;
+            xml.attr("id", &self.id(format_args!("band-{}", i)))?;
+            xml.attr("x", format!("{}", left).as_str())?;
+            xml.attr("y", "0")?;
+            xml.attr("width", format!("{}", right - left).as_str())?;
             xml.attr("height", format!("{}", img_height).as_str())?;
+            xml.attr("fill", &escape_attribute(&self.style.band_color))?;
+            xml.attr(
+                "fill-opacity",
+                format!("{}", self.style.band_opacity).as_str(),
+            )?;
+            xml.end_elem()?;
+        }
+        Ok(())
+    }
 
-            // Draw on a white rectangle to be visible also on black backgrounds.
+    ///
+    /// Draws a labeled, semi-transparent box behind the subtree of each node that starts a
+    /// group. Outer groups are drawn before the groups nested in them.
+    ///
+    fn draw_groups<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        embedding: &[EmbeddedNode],
+        frames: &[NodeFrame],
+    ) -> std::io::Result<()> {
+        if embedding.iter().all(|data| data.group.is_none()) {
+            return Ok(());
+        }
+        let style = &self.style;
+        let index_of = embedding
+            .iter()
+            .enumerate()
+            .map(|(i, data)| (data.ord, i))
+            .collect::<HashMap<_, _>>();
+
+        // The bounds of the subtrees as left, top, right, bottom. Children come after their
+        // parents, thus a reverse pass visits all descendants of a node before the node.
+        let mut bounds = frames
+            .iter()
+            .map(|f| (f.left, f.top, f.left + f.width, f.bottom()))
+            .collect::<Vec<_>>();
+        for (i, data) in embedding.iter().enumerate().rev() {
+            if let Some(&parent) = data.parent.as_ref().and_then(|p| index_of.get(p)) {
+                let (left, top, right, bottom) = bounds[i];
+                let parent_bounds = &mut bounds[parent];
+                parent_bounds.0 = parent_bounds.0.min(left);
+                parent_bounds.1 = parent_bounds.1.min(top);
+                parent_bounds.2 = parent_bounds.2.max(right);
+                parent_bounds.3 = parent_bounds.3.max(bottom);
+            }
+        }
+
+        let label_style = EmbeddedNode {
+            scale: 0.75,
+            ..Default::default()
+        };
+        let label_height = 0.75 * style.text_height;
+        let mut labels: Vec<&str> = Vec::new();
+        for (data, (left, top, right, bottom)) in embedding.iter().zip(bounds) {
+            let Some(label) = &data.group else {
+                continue;
+            };
+            let color_index = labels.iter().position(|l| l == label).unwrap_or_else(|| {
+                labels.push(label);
+                labels.len() - 1
+            });
+            let color = GROUP_COLORS[color_index % GROUP_COLORS.len()];
+            // Sibling groups are only a few pixels apart, thus the padding is mostly vertical
+            let padding = style.node_padding;
+            let (x, y) = (left - 1.0, top - padding - label_height);
+            let (width, height) = (right - left + 2.0, bottom - y + padding);
+
+            xml.begin_elem("g")?;
+            xml.attr("id", &self.id(format_args!("group-{}", data.ord)))?;
+            xml.begin_elem("rect")?;
+            xml.attr("x", format!("{}", x).as_str())?;
+            xml.attr("y", format!("{}", y).as_str())?;
+            xml.attr("width", format!("{}", width).as_str())?;
+            xml.attr("height", format!("{}", height).as_str())?;
+            xml.attr("rx", format!("{}", padding).as_str())?;
+            xml.attr("fill", color)?;
+            xml.attr("fill-opacity", format!("{}", style.group_opacity).as_str())?;
+            xml.attr("stroke", color)?;
+            xml.end_elem()?;
+            xml.begin_elem("text")?;
+            xml.attr("x", format!("{}", left + padding).as_str())?;
+            xml.attr("y", format!("{}", top - padding).as_str())?;
+            self.write_text_style(xml, &label_style)?;
+            xml.text(&sanitize(label))?;
+            xml.end_elem()?;
+            xml.end_elem()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the tint of each node of the embedding by its depth, see
+    /// [SvgDrawer::with_depth_colors].
+    fn depth_fills(&self, embedding: &[EmbeddedNode]) -> Vec<Option<String>> {
+        let Some(depth_colors) = &self.depth_colors else {
+            return vec![None; embedding.len()];
+        };
+        let depths = depths(embedding);
+        let max_depth = depths.values().copied().max().unwrap_or_default();
+        embedding
+            .iter()
+            .map(|node| depth_colors.color(depths[&node.ord], max_depth))
+            .collect()
+    }
+
+    /// Draws the box behind a node's text if the node's style, the drawer's style, the node's
+    /// diagnostic level or the node's collapsed state requests one.
+    /// The box touches the edges leading to the node and away from it.
+    fn draw_node_box<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        data: &EmbeddedNode,
+        frame: &NodeFrame,
+        depth_fill: Option<&str>,
+        classes: Option<&str>,
+    ) -> std::io::Result<()> {
+        let style = &self.style;
+        let diagnostic_color = style.diagnostic_color(data.diagnostic);
+        let is_collapsed = data.collapsed > 0;
+        let shape = data.style.shape.unwrap_or(style.node_shape);
+        let requests_box = data.style.has_box()
+            || depth_fill.is_some()
+            || style.node_fill.is_some()
+            || style.node_stroke.is_some();
+        // Erroneous and collapsed nodes always get a box, all nodes if style sheets may show them
+        if classes.is_none()
+            && !is_collapsed
+            && diagnostic_color.is_none()
+            && (!requests_box || shape == NodeShape::Plain)
+        {
+            return Ok(());
+        }
+
+        let hatch = data
+            .is_gap
+            .then(|| format!("url(#{})", self.id(format_args!("hatch"))));
+        let fill = hatch
+            .as_deref()
+            .or(data.style.fill.as_deref())
+            .or(depth_fill)
+            .or(style.node_fill.as_deref())
+            .unwrap_or("none");
+        // Erroneous nodes always get a box in their diagnostic color
+        let stroke = diagnostic_color
+            .or(is_collapsed.then_some(&*style.collapsed_color))
+            .or(data.style.border_color.as_deref())
+            .or(style.node_stroke.as_deref())
+            // Shapes requested by the node are outlined
+            .or(data.style.shape.is_some().then_some(&*style.edge_color))
+            .unwrap_or("none");
+        let NodeFrame {
+            left,
+            top,
+            width,
+            height,
+        } = *frame;
+
+        match shape {
+            // Plain nodes only get a box to show their diagnostic level or collapsed state
+            NodeShape::Rect | NodeShape::RoundedRect | NodeShape::Plain => {
+                xml.begin_elem("rect")?;
+                xml.attr("id", &self.id(format_args!("box-{}", data.ord)))?;
+                Self::write_class(xml, "box", classes)?;
+                xml.attr("x", format!("{}", left).as_str())?;
+                xml.attr("y", format!("{}", top).as_str())?;
+                xml.attr("width", format!("{}", width).as_str())?;
+                xml.attr("height", format!("{}", height).as_str())?;
+                if shape == NodeShape::RoundedRect {
+                    xml.attr("rx", format!("{}", style.text_height / 2.0).as_str())?;
+                }
+            }
+            NodeShape::Ellipse => {
+                xml.begin_elem("ellipse")?;
+                xml.attr("id", &self.id(format_args!("box-{}", data.ord)))?;
+                Self::write_class(xml, "box", classes)?;
+                xml.attr("cx", format!("{}", left + width / 2.0).as_str())?;
+                xml.attr("cy", format!("{}", top + height / 2.0).as_str())?;
+                // Enlarge the ellipse so that the text's corners stay inside
+                xml.attr(
+                    "rx",
+                    format!("{}", width / std::f32::consts::SQRT_2).as_str(),
+                )?;
+                xml.attr(
+                    "ry",
+                    format!("{}", height / std::f32::consts::SQRT_2).as_str(),
+                )?;
+            }
+            NodeShape::Diamond => {
+                xml.begin_elem("polygon")?;
+                xml.attr("id", &self.id(format_args!("box-{}", data.ord)))?;
+                Self::write_class(xml, "box", classes)?;
+                // The text's corners touch the sides of the diamond
+                let (cx, cy) = (left + width / 2.0, top + height / 2.0);
+                xml.attr("points", &diamond_points(cx, cy, width, height))?;
+            }
+        }
+        xml.attr("fill", &escape_attribute(fill))?;
+        xml.attr("stroke", &escape_attribute(stroke))?;
+        // Collapsed stubs are always dotted
+        let border = if is_collapsed {
+            BorderStyle::Dotted
+        } else {
+            data.style.border
+        };
+        Self::write_dasharray(xml, border)?;
+        xml.end_elem()
+    }
+
+    /// Writes the style of a text element for the given node, as presentation attributes if the
+    /// CSS classes are enabled.
+    fn write_text_style<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        data: &EmbeddedNode,
+    ) -> std::io::Result<()> {
+        if self.css_classes {
+            for (name, value) in self.style.text_attributes(data) {
+                xml.attr(name, &escape_attribute(&value))?;
+            }
+            Ok(())
+        } else {
+            xml.attr("style", &escape_attribute(&self.style.text_style(data)))
+        }
+    }
+
+    /// Writes the class attribute of an element with the given own class and the given classes.
+    fn write_class<W: Write>(
+        xml: &mut XmlWriter<W>,
+        class: &str,
+        classes: Option<&str>,
+    ) -> std::io::Result<()> {
+        match classes {
+            Some(classes) => xml.attr(
+                "class",
+                &escape_attribute(&format!("{} {}", class, classes)),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the dash pattern of the given border style.
+    fn write_dasharray<W: Write>(
+        xml: &mut XmlWriter<W>,
+        border: BorderStyle,
+    ) -> std::io::Result<()> {
+        match border {
+            BorderStyle::Solid => Ok(()),
+            BorderStyle::Dashed => xml.attr("stroke-dasharray", "4 2"),
+            BorderStyle::Dotted => xml.attr("stroke-dasharray", "1 2"),
+        }
+    }
+
+    ///
+    /// Returns the byte offsets of the displayed characters of the source text strip with the x
+    /// coordinates of their left sides. The last entry holds the end of the text and no character.
+    /// Line breaks are shown as `↵` and the other control characters as their control pictures.
+    ///
+    fn strip_positions(&self, source: &str) -> Vec<(usize, Option<char>, f32)> {
+        let mut x = self.style.x_margin;
+        let mut positions = Vec::with_capacity(source.len() + 1);
+        for (offset, c) in source.char_indices() {
+            let c = if c == '\n' { '\u{21b5}' } else { c };
+            let c = sanitize(c.encode_utf8(&mut [0; 4]))
+                .chars()
+                .next()
+                .unwrap_or(c);
+            positions.push((offset, Some(c), x));
+            x += match &self.font_metrics {
+                Some(metrics) => metrics.advance(c),
+                None => c.width().unwrap_or(0) as f32 * self.style.char_width,
+            };
+        }
+        positions.push((source.len(), None, x));
+        positions
+    }
+
+    ///
+    /// Draws the source text as a strip below the tree. The span of each leaf is marked by a
+    /// bracket below the text and the leaf is connected to the middle of its span, which makes
+    /// gaps and overlaps in the tokenization visible.
+    ///
+    fn draw_source_strip<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        embedding: &[EmbeddedNode],
+        frames: &[NodeFrame],
+        source: &str,
+        strip_top: f32,
+    ) -> std::io::Result<()> {
+        let style = &self.style;
+        let positions = self.strip_positions(source);
+        // The x coordinate of a byte offset, offsets inside of characters are rounded down
+        let x_of = |offset: usize| {
+            let index = positions.partition_point(|(start, _, _)| *start <= offset);
+            positions[index.saturating_sub(1)].2
+        };
+        let baseline = strip_top + style.text_height + 3.0;
+        let bracket_top = baseline + 3.0;
+        let bracket_bottom = bracket_top + style.text_height / 2.0;
+
+        xml.begin_elem("g")?;
+        xml.attr("id", &self.id(format_args!("source")))?;
+        xml.begin_elem("text")?;
+        xml.attr("x", format!("{}", style.x_margin).as_str())?;
+        xml.attr("y", format!("{}", baseline).as_str())?;
+        xml.attr("xml:space", "preserve")?;
+        self.write_text_style(xml, &EmbeddedNode::default())?;
+        let text = positions
+            .iter()
+            .filter_map(|(_, c, _)| *c)
+            .collect::<String>();
+        xml.text(&text)?;
+        xml.end_elem()?;
+
+        let parents = embedding
+            .iter()
+            .filter_map(|data| data.parent)
+            .collect::<std::collections::HashSet<_>>();
+        for (data, frame) in embedding.iter().zip(frames) {
+            if parents.contains(&data.ord) {
+                continue;
+            }
+            let (start, end) = (x_of(data.span.start), x_of(data.span.end));
+            xml.begin_elem("line")?;
+            xml.attr("id", &self.id(format_args!("source-link-{}", data.ord)))?;
+            xml.attr("x1", format!("{}", frame.left + frame.width / 2.0).as_str())?;
+            xml.attr("y1", format!("{}", frame.bottom()).as_str())?;
+            xml.attr("x2", format!("{}", (start + end) / 2.0).as_str())?;
+            xml.attr("y2", format!("{}", strip_top).as_str())?;
+            xml.attr("stroke", &escape_attribute(&style.edge_color))?;
+            Self::write_dasharray(xml, BorderStyle::Dotted)?;
+            xml.end_elem()?;
+
+            xml.begin_elem("path")?;
+            xml.attr("id", &self.id(format_args!("source-span-{}", data.ord)))?;
+            xml.attr(
+                "d",
+                format!(
+                    "M {} {} V {} H {} V {}",
+                    start, bracket_top, bracket_bottom, end, bracket_top
+                )
+                .as_str(),
+            )?;
+            xml.attr("fill", "none")?;
+            xml.attr("stroke", &escape_attribute(&style.edge_color))?;
+            xml.end_elem()?;
+        }
+        xml.end_elem()
+    }
+
+    /// The size of the legend in pixels
+    fn legend_size(&self, entries: &[LegendEntry]) -> (f32, f32) {
+        let style = &self.style;
+        let label_width = entries
+            .iter()
+            .map(|entry| self.measure_string(&self.label(&entry.label)))
+            .fold(0.0, f32::max);
+        (
+            2.0 * style.node_padding + 4.0 * style.char_width + label_width,
+            2.0 * style.node_padding + entries.len() as f32 * 2.0 * style.text_height,
+        )
+    }
+
+    ///
+    /// Draws the legend into its corner of the image. Each entry gets a row with a sample box in
+    /// the entry's style and the entry's label in the entry's text color.
+    ///
+    fn draw_legend<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        img_width: f32,
+        img_height: f32,
+    ) -> std::io::Result<()> {
+        let style = &self.style;
+        let entries = self.legend.resolved_entries(style);
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let (width, height) = self.legend_size(&entries);
+        let inset = style.node_padding;
+        let left = match self.legend.position() {
+            LegendPosition::TopLeft | LegendPosition::BottomLeft => inset,
+            LegendPosition::TopRight | LegendPosition::BottomRight => img_width - width - inset,
+        };
+        let top = match self.legend.position() {
+            LegendPosition::TopLeft | LegendPosition::TopRight => inset,
+            LegendPosition::BottomLeft | LegendPosition::BottomRight => img_height - height - inset,
+        };
+
+        xml.begin_elem("g")?;
+        xml.attr("id", &self.id(format_args!("legend")))?;
+        xml.begin_elem("rect")?;
+        xml.attr("x", format!("{}", left).as_str())?;
+        xml.attr("y", format!("{}", top).as_str())?;
+        xml.attr("width", format!("{}", width).as_str())?;
+        xml.attr("height", format!("{}", height).as_str())?;
+        xml.attr(
+            "fill",
+            &escape_attribute(style.background.as_deref().unwrap_or("white")),
+        )?;
+        xml.attr("stroke", &escape_attribute(&style.edge_color))?;
+        xml.end_elem()?;
+
+        let row_height = 2.0 * style.text_height;
+        let sample_width = 3.0 * style.char_width;
+        for (i, entry) in entries.iter().enumerate() {
+            let row_top = top + style.node_padding + i as f32 * row_height;
+            let (x, y) = (left + style.node_padding, row_top + 0.2 * row_height);
+            let sample_height = 0.6 * row_height;
+            let fill = entry
+                .style
+                .fill
+                .as_deref()
+                .or(style.node_fill.as_deref())
+                .unwrap_or("none");
+            let stroke = entry
+                .style
+                .border_color
+                .as_deref()
+                .or(style.node_stroke.as_deref())
+                .unwrap_or(&style.edge_color);
+            match entry.style.shape.unwrap_or(style.node_shape) {
+                shape @ (NodeShape::Rect | NodeShape::RoundedRect) => {
+                    xml.begin_elem("rect")?;
+                    xml.attr("x", format!("{}", x).as_str())?;
+                    xml.attr("y", format!("{}", y).as_str())?;
+                    xml.attr("width", format!("{}", sample_width).as_str())?;
+                    xml.attr("height", format!("{}", sample_height).as_str())?;
+                    if shape == NodeShape::RoundedRect {
+                        xml.attr("rx", format!("{}", sample_height / 2.0).as_str())?;
+                    }
+                }
+                NodeShape::Ellipse => {
+                    xml.begin_elem("ellipse")?;
+                    xml.attr("cx", format!("{}", x + sample_width / 2.0).as_str())?;
+                    xml.attr("cy", format!("{}", y + sample_height / 2.0).as_str())?;
+                    xml.attr("rx", format!("{}", sample_width / 2.0).as_str())?;
+                    xml.attr("ry", format!("{}", sample_height / 2.0).as_str())?;
+                }
+                NodeShape::Diamond => {
+                    xml.begin_elem("polygon")?;
+                    let points = diamond_points(
+                        x + sample_width / 2.0,
+                        y + sample_height / 2.0,
+                        sample_width / 2.0,
+                        sample_height / 2.0,
+                    );
+                    xml.attr("points", &points)?;
+                }
+                // Plain nodes have no box, the sample is just an invisible rectangle
+                NodeShape::Plain => {
+                    xml.begin_elem("rect")?;
+                    xml.attr("x", format!("{}", x).as_str())?;
+                    xml.attr("y", format!("{}", y).as_str())?;
+                    xml.attr("width", format!("{}", sample_width).as_str())?;
+                    xml.attr("height", format!("{}", sample_height).as_str())?;
+                    xml.attr("visibility", "hidden")?;
+                }
+            }
+            xml.attr("fill", &escape_attribute(fill))?;
+            xml.attr("stroke", &escape_attribute(stroke))?;
+            Self::write_dasharray(xml, entry.style.border)?;
+            xml.end_elem()?;
+
+            xml.begin_elem("text")?;
+            xml.attr(
+                "x",
+                format!("{}", x + sample_width + style.char_width).as_str(),
+            )?;
+            xml.attr("y", format!("{}", y + sample_height).as_str())?;
+            let sample = EmbeddedNode {
+                style: entry.style.clone(),
+                ..Default::default()
+            };
+            self.write_text_style(xml, &sample)?;
+            xml.text(&self.label(&entry.label))?;
+            xml.end_elem()?;
+        }
+        xml.end_elem()
+    }
+
+    ///
+    /// Draws the node's badge as a small chip with a rounded border centered on the top right
+    /// corner of the node's frame. The text is set at three quarters of the node's font size.
+    ///
+    fn draw_badge<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        data: &EmbeddedNode,
+        badge: &str,
+        frame: &NodeFrame,
+        classes: Option<&str>,
+    ) -> std::io::Result<()> {
+        let style = &self.style;
+        let scale = 0.75 * data.scale;
+        let badge = self.label(badge);
+        let width = self.measure_string(&badge) * scale + 4.0;
+        let height = style.text_height * scale + 4.0;
+        let (center_x, center_y) = (frame.left + frame.width, frame.top);
+
+        xml.begin_elem("g")?;
+        xml.attr("id", &self.id(format_args!("badge-{}", data.ord)))?;
+        Self::write_class(xml, "badge", classes)?;
+        xml.begin_elem("rect")?;
+        xml.attr("x", format!("{}", center_x - width / 2.0).as_str())?;
+        xml.attr("y", format!("{}", center_y - height / 2.0).as_str())?;
+        xml.attr("width", format!("{}", width).as_str())?;
+        xml.attr("height", format!("{}", height).as_str())?;
+        xml.attr("rx", format!("{}", height / 2.0).as_str())?;
+        xml.attr(
+            "fill",
+            &escape_attribute(style.background.as_deref().unwrap_or("white")),
+        )?;
+        xml.attr("stroke", &escape_attribute(&style.edge_color))?;
+        xml.end_elem()?;
+        xml.begin_elem("text")?;
+        xml.attr("x", format!("{}", center_x).as_str())?;
+        xml.attr(
+            "y",
+            format!("{}", center_y + style.text_height * scale / 2.0).as_str(),
+        )?;
+        xml.attr("text-anchor", "middle")?;
+        let sample = EmbeddedNode {
+            scale,
+            ..Default::default()
+        };
+        self.write_text_style(xml, &sample)?;
+        xml.text(&badge)?;
+        xml.end_elem()?;
+        xml.end_elem()
+    }
+
+    /// Underlines the node's label with a wavy line in the color of the node's diagnostic level.
+    fn draw_squiggle<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        data: &EmbeddedNode,
+        frame: &NodeFrame,
+    ) -> std::io::Result<()> {
+        let Some(color) = self.style.diagnostic_color(data.diagnostic) else {
+            return Ok(());
+        };
+        // Each wave is 4 pixels long, the line is centered below the label
+        let waves = ((frame.width - 2.0 * self.style.node_padding) / 4.0)
+            .floor()
+            .max(1.0);
+        let left = frame.left + (frame.width - waves * 4.0) / 2.0;
+        let y = frame.bottom() - 2.0;
+        let mut d = format!("M {} {} q 1 -2 2 0 t 2 0", left, y);
+        for _ in 1..waves as usize {
+            d.push_str(" t 2 0 t 2 0");
+        }
+        xml.begin_elem("path")?;
+        xml.attr("id", &self.id(format_args!("squiggle-{}", data.ord)))?;
+        xml.attr("class", "squiggle")?;
+        xml.attr("d", &d)?;
+        xml.attr("fill", "none")?;
+        xml.attr("stroke", &escape_attribute(color))?;
+        xml.end_elem()
+    }
+
+    /// Calculates the geometry of the radial layout. The image is a square that contains the
+    /// circle of the deepest level plus the widest label.
+    fn radial_geometry(
+        &self,
+        embedding: &[EmbeddedNode],
+        tree_depth: usize,
+        tree_width: usize,
+    ) -> RadialGeometry {
+        let style = &self.style;
+        let level_distance = style.text_height * style.level_factor;
+        let label_radius = embedding
+            .iter()
+            .map(|data| self.measure_string(&self.label(&data.text)) / 2.0 + style.node_padding)
+            .fold(style.text_height * 2.0, f32::max);
+        let radius = tree_depth as f32 * level_distance + label_radius;
+        RadialGeometry {
+            center: (radius + style.x_margin, radius + style.y_margin),
+            tree_width: tree_width as f32,
+            level_distance,
+        }
+    }
+
+    /// Draws a curved edge between the given nodes of the radial layout.
+    /// The edge leaves the parent and enters the child in radial direction.
+    fn draw_radial_edge<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        radial: &RadialGeometry,
+        parent_data: &EmbeddedNode,
+        data: &EmbeddedNode,
+        classes: Option<&str>,
+    ) -> std::io::Result<()> {
+        let gap = self.style.text_height * 1.2;
+        let child_angle = radial.angle(data.x_center);
+        let child_radius = radial.radius(data.y_order);
+        let (parent_angle, parent_radius) = if parent_data.y_order == 0 {
+            // The root sits in the center, thus edges leave it in the child's direction
+            (child_angle, 0.0)
+        } else {
+            (
+                radial.angle(parent_data.x_center),
+                radial.radius(parent_data.y_order),
+            )
+        };
+        let middle_radius = (parent_radius + child_radius) / 2.0;
+        let start = radial.point(parent_angle, parent_radius + gap);
+        let control1 = radial.point(parent_angle, middle_radius);
+        let control2 = radial.point(child_angle, middle_radius);
+        let end = radial.point(child_angle, child_radius - gap);
+
+        xml.begin_elem("path")?;
+        xml.attr("id", &self.id(format_args!("edge-{}", data.ord)))?;
+        Self::write_class(xml, "edge", classes)?;
+        xml.attr(
+            "d",
+            format!(
+                "M {} {} C {} {} {} {} {} {}",
+                start.0, start.1, control1.0, control1.1, control2.0, control2.1, end.0, end.1
+            )
+            .as_str(),
+        )?;
+        xml.attr("fill", "none")?;
+        xml.attr("stroke", &escape_attribute(&self.style.edge_color))?;
+        xml.end_elem()
+    }
+
+    /// Draws the edge from the bottom of the parent's frame down to the top of the node's frame,
+    /// or from the top of the parent's frame up to the bottom of the node's frame if the tree is
+    /// drawn bottom up.
+    fn draw_layered_edge<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        data: &EmbeddedNode,
+        parent_frame: &NodeFrame,
+        frame: &NodeFrame,
+        classes: Option<&str>,
+    ) -> std::io::Result<()> {
+        let (y1, y2) = match self.orientation {
+            Orientation::TopDown => (parent_frame.bottom(), frame.top),
+            Orientation::BottomUp => (parent_frame.top, frame.bottom()),
+        };
+        let x1 = parent_frame.left + parent_frame.width / 2.0;
+        let x2 = frame.left + frame.width / 2.0;
+        let y_middle = (y1 + y2) / 2.0;
+        match self.style.edge_style {
+            EdgeStyle::Straight => {
+                xml.begin_elem("line")?;
+                xml.attr("id", &self.id(format_args!("edge-{}", data.ord)))?;
+                Self::write_class(xml, "edge", classes)?;
+                xml.attr("x1", format!("{}", x1).as_str())?;
+                xml.attr("y1", format!("{}", y1).as_str())?;
+                xml.attr("x2", format!("{}", x2).as_str())?;
+                xml.attr("y2", format!("{}", y2).as_str())?;
+            }
+            EdgeStyle::Orthogonal => {
+                xml.begin_elem("path")?;
+                xml.attr("id", &self.id(format_args!("edge-{}", data.ord)))?;
+                Self::write_class(xml, "edge", classes)?;
+                xml.attr(
+                    "d",
+                    format!("M {} {} V {} H {} V {}", x1, y1, y_middle, x2, y2).as_str(),
+                )?;
+                xml.attr("fill", "none")?;
+            }
+            EdgeStyle::Curved => {
+                xml.begin_elem("path")?;
+                xml.attr("id", &self.id(format_args!("edge-{}", data.ord)))?;
+                Self::write_class(xml, "edge", classes)?;
+                xml.attr(
+                    "d",
+                    format!(
+                        "M {} {} C {} {}, {} {}, {} {}",
+                        x1, y1, x1, y_middle, x2, y_middle, x2, y2
+                    )
+                    .as_str(),
+                )?;
+                xml.attr("fill", "none")?;
+            }
+        }
+        xml.attr("stroke", &escape_attribute(&self.style.edge_color))?;
+        xml.end_elem()
+    }
+
+    /// Places all nodes of the embedding and calculates the size of the image.
+    fn layout(&self, embedding: &[EmbeddedNode]) -> SvgLayout {
+        let style = &self.style;
+        let tree_depth = embedding
+            .iter()
+            .fold(0, |acc, e| if e.y_order > acc { e.y_order } else { acc });
+        let tree_width = tree_width(embedding);
+
+        let radial = (self.layout_kind == LayoutKind::Radial)
+            .then(|| self.radial_geometry(embedding, tree_depth, tree_width));
+        let frames = embedding
+            .iter()
+            .map(|data| {
+                let anchor = match &radial {
+                    Some(radial) => radial.anchor(data),
+                    None => self.layered_anchor(data, tree_depth),
+                };
+                self.node_frame(data, anchor)
+            })
+            .collect::<Vec<_>>();
+        let (width, height) = match &radial {
+            Some(radial) => (2.0 * radial.center.0, 2.0 * radial.center.1),
+            // Custom coordinates are only bounded by the nodes
+            None if self.coordinate_mapping.is_some() => (
+                frames
+                    .iter()
+                    .map(|frame| frame.left + frame.width + style.x_margin)
+                    .fold(0.0, f32::max),
+                frames
+                    .iter()
+                    .map(|frame| frame.bottom() + style.y_margin)
+                    .fold(0.0, f32::max),
+            ),
+            // Vertical labels can reach deeper than the last level
+            None => (
+                self.scale_x(tree_width),
+                frames
+                    .iter()
+                    .map(|frame| frame.bottom() + style.y_margin)
+                    .fold(self.scale_y(tree_depth + 1), f32::max),
+            ),
+        };
+        // The source text strip is placed below the layered tree
+        let strip_top = self
+            .source_strip
+            .as_ref()
+            .filter(|_| radial.is_none())
+            .map(|_| height);
+        let (width, height) = match (&self.source_strip, strip_top) {
+            (Some(source), Some(strip_top)) => (
+                width.max(
+                    self.strip_positions(source)
+                        .last()
+                        .map_or(0.0, |(_, _, x)| *x)
+                        + style.x_margin,
+                ),
+                strip_top + 2.0 * style.text_height + 3.0 + style.y_margin,
+            ),
+            _ => (width, height),
+        };
+        // The legend must fit into the image
+        let (width, height) = if self.legend.is_empty() {
+            (width, height)
+        } else {
+            let (legend_width, legend_height) =
+                self.legend_size(&self.legend.resolved_entries(style));
+            (
+                width.max(legend_width + 2.0 * style.node_padding),
+                height.max(legend_height + 2.0 * style.node_padding),
+            )
+        };
+        SvgLayout {
+            radial,
+            frames,
+            strip_top,
+            width,
+            height,
+        }
+    }
+
+    /// Writes the pattern that fills the pseudonodes of gaps in the source coverage.
+    fn write_hatch_pattern<W: Write>(&self, xml: &mut XmlWriter<W>) -> std::io::Result<()> {
+        xml.begin_elem("defs")?;
+        xml.begin_elem("pattern")?;
+        xml.attr("id", &self.id(format_args!("hatch")))?;
+        xml.attr("width", "6")?;
+        xml.attr("height", "6")?;
+        xml.attr("patternUnits", "userSpaceOnUse")?;
+        xml.attr("patternTransform", "rotate(45)")?;
+        xml.begin_elem("line")?;
+        xml.attr("x1", "0")?;
+        xml.attr("y1", "0")?;
+        xml.attr("x2", "0")?;
+        xml.attr("y2", "6")?;
+        xml.attr("stroke", &escape_attribute(&self.style.collapsed_color))?;
+        xml.attr("stroke-width", "2")?;
+        xml.end_elem()?;
+        xml.end_elem()?;
+        xml.end_elem()
+    }
+
+    /// Returns the y coordinates of the layers of a layered tree from the root downwards, or
+    /// nothing if no layer guides are drawn.
+    fn layer_guides(&self, embedding: &[EmbeddedNode], radial: bool) -> Vec<f32> {
+        if !self.layer_guides || radial || self.coordinate_mapping.is_some() {
+            return Vec::new();
+        }
+        let tree_depth = embedding.iter().map(|e| e.y_order).max().unwrap_or(0);
+        let mut y_orders = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+        y_orders.sort_unstable();
+        y_orders.dedup();
+        y_orders
+            .into_iter()
+            .map(|y_order| self.scale_y(self.orientation.level(y_order, tree_depth)))
+            .collect()
+    }
+
+    /// The width of the gutter left of the tree that holds the numbers of the layers
+    fn guide_gutter(&self, guides: &[f32]) -> f32 {
+        if guides.is_empty() {
+            0.0
+        } else {
+            self.measure_string(&(guides.len() - 1).to_string()) + 2.0 * self.style.node_padding
+        }
+    }
+
+    /// Draws a faint line through each layer and its number into the gutter.
+    fn draw_layer_guides<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        guides: &[f32],
+        gutter: f32,
+        img_width: f32,
+    ) -> std::io::Result<()> {
+        let style = &self.style;
+        xml.begin_elem("g")?;
+        xml.attr("id", &self.id(format_args!("guides")))?;
+        xml.attr("class", "guides")?;
+        for (layer, baseline) in guides.iter().enumerate() {
+            // The line runs through the middle of the nodes' frames
+            let y = baseline - 1.5;
+            xml.begin_elem("line")?;
+            xml.attr("id", &self.id(format_args!("guide-{}", layer)))?;
+            xml.attr("class", "guide")?;
+            xml.attr("x1", format!("{}", gutter).as_str())?;
+            xml.attr("y1", format!("{}", y).as_str())?;
+            xml.attr("x2", format!("{}", img_width).as_str())?;
+            xml.attr("y2", format!("{}", y).as_str())?;
+            xml.attr("stroke", &escape_attribute(&style.edge_color))?;
+            xml.attr("stroke-opacity", "0.2")?;
+            xml.attr("stroke-dasharray", "2 4")?;
+            xml.end_elem()?;
+
+            xml.begin_elem("text")?;
+            xml.attr("id", &self.id(format_args!("guide-label-{}", layer)))?;
+            xml.attr("x", format!("{}", gutter - style.node_padding).as_str())?;
+            xml.attr("y", format!("{}", baseline).as_str())?;
+            xml.attr("text-anchor", "end")?;
+            xml.attr("fill-opacity", "0.6")?;
+            self.write_text_style(xml, &EmbeddedNode::default())?;
+            xml.text(&layer.to_string())?;
+            xml.end_elem()?;
+        }
+        xml.end_elem()
+    }
+
+    /// Writes a snippet of the template verbatim into the document.
+    fn write_snippet<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        snippet: &str,
+    ) -> std::io::Result<()> {
+        if snippet.is_empty() {
+            return Ok(());
+        }
+        // Writing an empty text closes the start tag of the current element
+        xml.text("")?;
+        xml.write(snippet)
+    }
+
+    fn build_xml<W: Write>(
+        &self,
+        xml: &mut XmlWriter<W>,
+        embedding: &[EmbeddedNode],
+    ) -> std::io::Result<()> {
+        xml.dtd("UTF-8")?;
+        if let Some(stylesheet) = &self.stylesheet {
+            xml.write(&format!(
+                "<?xml-stylesheet type=\"text/css\" href=\"{}\"?>\n",
+                escape_attribute(stylesheet)
+            ))?;
+        }
+        xml.begin_elem("svg")?;
+        xml.attr("xmlns", "http://www.w3.org/2000/svg")?;
+        xml.attr("version", "1.1")?;
+        xml.attr("lang", "en")?;
+
+        let style = &self.style;
+        let SvgLayout {
+            radial,
+            frames,
+            strip_top,
+            width: img_width,
+            height: img_height,
+        } = self.layout(embedding);
+        let guides = self.layer_guides(embedding, radial.is_some());
+        let gutter = self.guide_gutter(&guides);
+        let img_width = img_width + gutter;
+
+        let (doc_width, doc_height) = self.size.apply(img_width, img_height);
+        xml.attr("width", format!("{}", doc_width).as_str())?;
+        xml.attr("height", format!("{}", doc_height).as_str())?;
+        xml.attr(
+            "viewBox",
+            format!("0 0 {} {}", img_width, img_height).as_str(),
+        )?;
+        if let Some(preserve_aspect_ratio) = &self.preserve_aspect_ratio {
+            xml.attr(
+                "preserveAspectRatio",
+                &escape_attribute(preserve_aspect_ratio),
+            )?;
+        }
+
+        if let Some(metadata) = &self.metadata {
+            xml.begin_elem("metadata")?;
+            xml.begin_elem("generator")?;
+            xml.attr("xmlns", "https://github.com/jsinger67/syntree_layout")?;
+            for (name, value) in metadata.attributes(embedding) {
+                xml.attr(name, &escape_attribute(&value))?;
+            }
+            xml.end_elem()?;
+            xml.end_elem()?;
+        }
+        if embedding.iter().any(|data| data.is_gap) {
+            self.write_hatch_pattern(xml)?;
+        }
+        if let Some(font) = &style.embedded_font {
+            xml.begin_elem("defs")?;
+            xml.begin_elem("style")?;
+            xml.text(&font.font_face_rule())?;
+            xml.end_elem()?;
+            xml.end_elem()?;
+        }
+        self.write_snippet(xml, &self.template.fill_header(img_width, img_height))?;
+
+        // Draw on a background rectangle to be visible also on black backgrounds.
+        if let Some(background) = &style.background {
             xml.begin_elem("rect")?;
             xml.attr("x", "0")?;
             xml.attr("y", "0")?;
             xml.attr("width", format!("{}", img_width).as_str())?;
             xml.attr("height", format!("{}", img_height).as_str())?;
-            xml.attr("fill", "white")?;
+            xml.attr("fill", &escape_attribute(background))?;
             xml.end_elem()?;
+        }
 
-            for data in embedding {
-                let font = if data.is_emphasized {
-                    EMPHASIZE_FONT
-                } else {
-                    STRING_FONT
-                };
-                let szx = SvgDrawer::measure_string(&data.text);
-                let x = SvgDrawer::scale_x(data.x_center) - szx / 2.0;
-                let y = SvgDrawer::scale_y(data.y_order);
-                xml.begin_elem("text")?;
-                xml.attr("x", format!("{}", x).as_str())?;
-                xml.attr("y", format!("{}", y).as_str())?;
-                xml.attr("style", font)?;
-                xml.text(data.text.as_str())?;
+        if self.pan_zoom {
+            xml.begin_elem("g")?;
+            xml.attr("id", &self.id(format_args!("viewport")))?;
+            xml.attr("class", "svg-pan-zoom_viewport")?;
+        }
+        if !guides.is_empty() {
+            self.draw_layer_guides(xml, &guides, gutter, img_width)?;
+            xml.begin_elem("g")?;
+            xml.attr("transform", format!("translate({} 0)", gutter).as_str())?;
+        }
+        if radial.is_none() {
+            self.draw_span_bands(xml, embedding, &frames, img_height)?;
+            self.draw_groups(xml, embedding, &frames)?;
+        }
+
+        let depth_fills = self.depth_fills(embedding);
+        let node_classes = self.node_classes(embedding);
+        let edge_depths = self.css_classes.then(|| depths(embedding));
+        for (((data, frame), depth_fill), classes) in embedding
+            .iter()
+            .zip(&frames)
+            .zip(&depth_fills)
+            .zip(&node_classes)
+        {
+            let classes = classes.as_deref();
+            let link = self.links.as_ref().and_then(|links| links(data));
+            if let Some(link) = &link {
+                xml.begin_elem("a")?;
+                xml.attr("href", &escape_attribute(link))?;
+            }
+            let mut markup = String::new();
+            if let Some(renderer) = &self.node_renderer {
+                renderer(&resolved_node(data, frame), data, &mut markup);
+            }
+            if markup.is_empty() {
+                // Trivia is only marked by its label
+                if !data.is_trivia {
+                    self.draw_node_box(xml, data, frame, depth_fill.as_deref(), classes)?;
+                }
+                self.draw_label(xml, data, frame, classes)?;
+            } else {
+                self.write_snippet(xml, &markup)?;
+            }
+            if self.squiggles {
+                self.draw_squiggle(xml, data, frame)?;
+            }
+            if let Some(badge) = &data.badge {
+                self.draw_badge(xml, data, badge, frame, classes)?;
+            }
+            self.write_snippet(
+                xml,
+                &self
+                    .template
+                    .fill_node(data, (frame.left, frame.top, frame.width, frame.height)),
+            )?;
+            if link.is_some() {
                 xml.end_elem()?;
+            }
 
-                if let Some(parent_index) = data.parent {
-                    let parent_data = embedding.iter().find(|e| e.ord == parent_index).unwrap();
-
-                    // Draw a line from the nodes parent down to this node
-                    xml.begin_elem("line")?;
-                    xml.attr(
-                        "x1",
-                        format!("{}", (SvgDrawer::scale_x(parent_data.x_center))).as_str(),
-                    )?;
-                    xml.attr(
-                        "y1",
-                        format!(
-                            "{}",
-                            (SvgDrawer::scale_y(parent_data.y_order) + FONT_Y_SIZE)
-                        )
-                        .as_str(),
-                    )?;
-                    xml.attr(
-                        "x2",
-                        format!("{}", (SvgDrawer::scale_x(data.x_center))).as_str(),
-                    )?;
-                    xml.attr("y2", format!("{}", (y - FONT_Y_SIZE - 3.0)).as_str())?;
-                    xml.attr("stroke", "black")?;
-                    xml.end_elem()?;
+            if let Some(parent_index) = data.parent {
+                let parent_index = embedding
+                    .iter()
+                    .position(|e| e.ord == parent_index)
+                    .unwrap();
+                let parent_data = &embedding[parent_index];
+                let edge_classes = edge_depths
+                    .as_ref()
+                    .map(|depths| format!("depth-{}", depths[&data.ord]));
+                let edge_classes = edge_classes.as_deref();
+
+                if let Some(radial) = &radial {
+                    self.draw_radial_edge(xml, radial, parent_data, data, edge_classes)?;
+                    continue;
                 }
+
+                self.draw_layered_edge(xml, data, &frames[parent_index], frame, edge_classes)?;
             }
+        }
 
+        if let (Some(source), Some(strip_top)) = (&self.source_strip, strip_top) {
+            self.draw_source_strip(xml, embedding, &frames, source, strip_top)?;
+        }
+        if !guides.is_empty() {
+            xml.end_elem()?;
+        }
+        if self.pan_zoom {
+            xml.end_elem()?;
+            xml.begin_elem("script")?;
+            // The prefix is written as a string literal, which must not end the CDATA section
+            let prefix = script_string(&self.id_prefix).replace("]]>", "]]\\>");
+            xml.cdata(&format!("const prefix = {};{}", prefix, PAN_ZOOM_SCRIPT))?;
             xml.end_elem()?;
-            xml.close()?;
-            xml.flush()?;
-
-            Ok(())
         }
+        self.draw_legend(xml, img_width, img_height)?;
+        self.write_snippet(xml, &self.template.fill_footer(img_width, img_height))?;
+        xml.end_elem()?;
+        xml.close()?;
+        xml.flush()
+    }
+}
 
-        build_xml(xml, embedding).map_err(LayouterError::from_io_error)
+/// The box of a node in pixel coordinates
+fn resolved_node(data: &EmbeddedNode, frame: &NodeFrame) -> ResolvedNode {
+    ResolvedNode {
+        ord: data.ord,
+        x: frame.left,
+        y: frame.top,
+        width: frame.width,
+        height: frame.height,
+    }
+}
+
+/// Returns the depth of each node by its `ord`, the root has the depth 0.
+fn depths(embedding: &[EmbeddedNode]) -> HashMap<usize, usize> {
+    // Parents precede their children in the embedding
+    let mut depths: HashMap<usize, usize> = HashMap::with_capacity(embedding.len());
+    for node in embedding {
+        let depth = node
+            .parent
+            .and_then(|parent| depths.get(&parent))
+            .map_or(0, |depth| depth + 1);
+        depths.insert(node.ord, depth);
+    }
+    depths
+}
+
+/// The points of a diamond around the given center with the given half diagonals.
+fn diamond_points(cx: f32, cy: f32, half_width: f32, half_height: f32) -> String {
+    format!(
+        "{},{} {},{} {},{} {},{}",
+        cx,
+        cy - half_height,
+        cx + half_width,
+        cy,
+        cx,
+        cy + half_height,
+        cx - half_width,
+        cy
+    )
+}
+
+impl fmt::Debug for SvgDrawer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SvgDrawer")
+            .field("style", &self.style)
+            .field("id_prefix", &self.id_prefix)
+            .field("tooltips", &self.tooltips)
+            .field("span_bands", &self.span_bands)
+            .field("layout_kind", &self.layout_kind)
+            .field("orientation", &self.orientation)
+            .field("coordinate_mapping", &self.coordinate_mapping.is_some())
+            .field("image_map", &self.image_map)
+            .field("template", &self.template)
+            .field("label_filter", &self.label_filter.is_some())
+            .field("links", &self.links.is_some())
+            .field("depth_colors", &self.depth_colors)
+            .field("size", &self.size)
+            .field("preserve_aspect_ratio", &self.preserve_aspect_ratio)
+            .field("legend", &self.legend)
+            .field("source_strip", &self.source_strip)
+            .field("css_classes", &self.css_classes)
+            .field("node_classes", &self.node_classes.is_some())
+            .field("stylesheet", &self.stylesheet)
+            .field("pan_zoom", &self.pan_zoom)
+            .field("squiggles", &self.squiggles)
+            .field("font_metrics", &self.font_metrics)
+            .field("layer_guides", &self.layer_guides)
+            .field("metadata", &self.metadata)
+            .field("node_renderer", &self.node_renderer.is_some())
+            .finish()
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `SvgDrawer`.
+///
+impl Drawer for SvgDrawer {
+    ///
+    /// The concrete implementation of the `Drawer::draw` trait method.
+    /// The visual properties like fonts and colors can be configured with an
+    /// [SvgDrawerStyle][crate::SvgDrawerStyle] via [SvgDrawer::with_style].
+    ///
+    /// Anyway it should be easy to provide ones own Drawer implementation that fits the concrete
+    /// use case better.
+    /// When using the Layouter API you can set the Drawer instance by calling the `with_drawer`
+    /// method.
+    ///
+    /// # Panics
+    ///
+    /// The method should not panic. If you encounter a panic this should be originated from
+    /// bugs in coding. Please report such panics.
+    ///
+    /// # Complexity
+    ///
+    /// The algorithm is of time complexity class O(n).
+    ///
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let file = File::create(ctx.file_name()).map_err(LayouterError::from_io_error)?;
+        let mut xml = XmlWriter::new(file);
+        self.build_xml(&mut xml, ctx.embedding())
+            .map_err(LayouterError::from_io_error)?;
+        if self.image_map {
+            let image_map = self.render_image_map(ctx.embedding())?;
+            std::fs::write(ctx.file_name().with_extension("map.json"), image_map)
+                .map_err(LayouterError::from_io_error)?;
+        }
+        Ok(())
     }
 }
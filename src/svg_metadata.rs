@@ -0,0 +1,125 @@
+//! The module with the metadata that makes generated SVG documents traceable.
+
+use std::borrow::Cow;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::EmbeddedNode;
+
+///
+/// The `SvgMetadata` is written into a `<metadata>` element of the generated SVG documents, see
+/// [SvgDrawer::with_metadata][crate::SvgDrawer::with_metadata]. It always records the crate
+/// version, the number of nodes and a hash of the tree, and optionally an identifier of the
+/// source the tree was parsed from and the time of generation.
+///
+/// The timestamp is off by default, so that regenerating an unchanged tree yields an identical
+/// document.
+///
+/// ```
+/// use syntree_layout::SvgMetadata;
+///
+/// let metadata = SvgMetadata::new().with_source("src/main.rs").with_timestamp(true);
+/// assert_eq!(Some("src/main.rs"), metadata.source.as_deref());
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SvgMetadata {
+    /// An identifier of the source the tree was created from, e.g. a file path
+    pub source: Option<Cow<'static, str>>,
+    /// Whether the time of generation is recorded
+    pub timestamp: bool,
+}
+
+impl SvgMetadata {
+    /// Creates metadata without source identifier and timestamp.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the identifier of the source the tree was created from.
+    pub fn with_source(self, source: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            source: Some(source.into()),
+            ..self
+        }
+    }
+
+    /// Sets whether the time of generation is recorded.
+    pub fn with_timestamp(self, timestamp: bool) -> Self {
+        Self { timestamp, ..self }
+    }
+
+    /// Returns the attributes of the `generator` element for the given embedding.
+    pub(crate) fn attributes(&self, embedding: &[EmbeddedNode]) -> Vec<(&'static str, String)> {
+        let mut attributes = vec![
+            ("name", env!("CARGO_PKG_NAME").to_string()),
+            ("version", env!("CARGO_PKG_VERSION").to_string()),
+            ("nodes", embedding.len().to_string()),
+            ("hash", format!("{:016x}", tree_hash(embedding))),
+        ];
+        if let Some(source) = &self.source {
+            attributes.push(("source", source.to_string()));
+        }
+        if self.timestamp {
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs());
+            attributes.push(("timestamp", format_timestamp(seconds)));
+        }
+        attributes
+    }
+}
+
+///
+/// A FNV-1a hash of the structure, the labels and the spans of the nodes. Unlike the hashers of
+/// the standard library it is stable across Rust versions and platforms.
+///
+fn tree_hash(embedding: &[EmbeddedNode]) -> u64 {
+    const PRIME: u64 = 0x0100_0000_01b3;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+    for node in embedding {
+        write(&(node.ord as u64).to_le_bytes());
+        write(
+            &node
+                .parent
+                .map_or(u64::MAX, |parent| parent as u64)
+                .to_le_bytes(),
+        );
+        write(&(node.span.start as u64).to_le_bytes());
+        write(&(node.span.end as u64).to_le_bytes());
+        write(&(node.text.len() as u64).to_le_bytes());
+        write(node.text.as_bytes());
+    }
+    hash
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 timestamp in UTC.
+fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let time = seconds % 86_400;
+    // The civil date of the day count, see Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
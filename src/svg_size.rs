@@ -0,0 +1,52 @@
+//! The module with the sizing options of the SVG output.
+
+///
+/// The `SvgSize` determines the `width` and `height` attributes of the generated SVG documents,
+/// see [SvgDrawer::with_size][crate::SvgDrawer::with_size].
+/// The drawing itself is always done in the pixel coordinates of the [Units][crate::Units] and
+/// the documents carry a `viewBox` covering the whole drawing, thus the viewer scales the drawing
+/// into the requested size.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SvgSize {
+    /// The document has the natural size of the drawing
+    #[default]
+    Natural,
+    /// The natural size of the drawing is multiplied by the given factor
+    Scaled(f32),
+    /// The document has the given width, the height keeps the drawing's aspect ratio. This is
+    /// useful to fit drawings into documentation pages of constrained width.
+    FitWidth(f32),
+    /// The document has the given width and height. How the drawing is fitted into this area is
+    /// determined by the drawer's `preserveAspectRatio` setting, see
+    /// [SvgDrawer::with_preserve_aspect_ratio][crate::SvgDrawer::with_preserve_aspect_ratio].
+    Fixed {
+        /// The width of the document
+        width: f32,
+        /// The height of the document
+        height: f32,
+    },
+}
+
+impl SvgSize {
+    ///
+    /// Returns the size of the document for a drawing of the given natural size.
+    ///
+    /// ```
+    /// use syntree_layout::SvgSize;
+    ///
+    /// assert_eq!((200.0, 100.0), SvgSize::Natural.apply(200.0, 100.0));
+    /// assert_eq!((100.0, 50.0), SvgSize::Scaled(0.5).apply(200.0, 100.0));
+    /// assert_eq!((400.0, 200.0), SvgSize::FitWidth(400.0).apply(200.0, 100.0));
+    /// ```
+    ///
+    pub fn apply(self, width: f32, height: f32) -> (f32, f32) {
+        match self {
+            SvgSize::Natural => (width, height),
+            SvgSize::Scaled(factor) => (width * factor, height * factor),
+            SvgSize::FitWidth(fit_width) if width > 0.0 => (fit_width, height * fit_width / width),
+            SvgSize::FitWidth(fit_width) => (fit_width, height),
+            SvgSize::Fixed { width, height } => (width, height),
+        }
+    }
+}
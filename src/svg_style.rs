@@ -0,0 +1,383 @@
+//! The module with the style configuration of the crate's default drawer.
+
+use std::borrow::Cow;
+
+use crate::{
+    DiagnosticLevel, EdgeStyle, EmbeddedFont, EmbeddedNode, NodeShape, Profile, Theme, Units,
+};
+
+///
+/// The `SvgDrawerStyle` type bundles all visual properties used by the
+/// [SvgDrawer][crate::SvgDrawer].
+///
+/// The default values reproduce the classic look of the drawer: black Courier text on a white
+/// background without node boxes.
+///
+/// ```
+/// use syntree_layout::{SvgDrawer, SvgDrawerStyle};
+///
+/// let style = SvgDrawerStyle {
+///     background: Some("#1e1e1e".into()),
+///     text_color: "#d4d4d4".into(),
+///     edge_color: "#808080".into(),
+///     ..SvgDrawerStyle::new()
+/// };
+/// let drawer = SvgDrawer::new().with_style(style);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgDrawerStyle {
+    /// The font family used for all node texts. This can also be a comma separated font stack
+    /// like [SvgDrawerStyle::WEB_SAFE_MONOSPACE].
+    pub font_family: Cow<'static, str>,
+    /// A font that is embedded into the document and used in front of the `font_family`
+    pub embedded_font: Option<EmbeddedFont>,
+    /// The font size in pixels
+    pub font_size: f32,
+    /// The width of one character in pixels, i.e. of one logical x unit of the embedding
+    pub char_width: f32,
+    /// The height of a text line in pixels, used to place edges above and below the texts
+    pub text_height: f32,
+    /// The factor applied to the text height to obtain the distance between two levels
+    pub level_factor: f32,
+    /// The color of the node texts
+    pub text_color: Cow<'static, str>,
+    /// The font weight of emphasized node texts
+    pub emphasized_font_weight: Cow<'static, str>,
+    /// The color of emphasized node texts
+    pub emphasized_text_color: Cow<'static, str>,
+    /// The fill color of the node boxes, no boxes are filled if `None`
+    pub node_fill: Option<Cow<'static, str>>,
+    /// The stroke color of the node boxes, no box borders are drawn if `None`
+    pub node_stroke: Option<Cow<'static, str>>,
+    /// The shape of the node boxes if the node's style doesn't specify one
+    pub node_shape: NodeShape,
+    /// The horizontal padding between a node's text and its box
+    pub node_padding: f32,
+    /// The color of the texts and boxes of nodes with `DiagnosticLevel::Warning`
+    pub warning_color: Cow<'static, str>,
+    /// The color of the texts and boxes of nodes with `DiagnosticLevel::Recovered`
+    pub recovered_color: Cow<'static, str>,
+    /// The color of the texts and boxes of nodes with `DiagnosticLevel::Error`
+    pub error_color: Cow<'static, str>,
+    /// The color of the texts and boxes of collapsed stubs
+    pub collapsed_color: Cow<'static, str>,
+    /// The color of the edges between parents and their children
+    pub edge_color: Cow<'static, str>,
+    /// The shape of the edges between parents and their children
+    pub edge_style: EdgeStyle,
+    /// The color of the bands highlighting source ranges, see
+    /// [SvgDrawer::with_span_bands][crate::SvgDrawer::with_span_bands]
+    pub band_color: Cow<'static, str>,
+    /// The opacity of the bands highlighting source ranges
+    pub band_opacity: f32,
+    /// The opacity of the background boxes of node groups, see
+    /// [Visualize::group][crate::Visualize::group]
+    pub group_opacity: f32,
+    /// The background color of the image, the background is transparent if `None`
+    pub background: Option<Cow<'static, str>>,
+    /// The horizontal margin of the image
+    pub x_margin: f32,
+    /// The vertical margin of the image
+    pub y_margin: f32,
+}
+
+impl SvgDrawerStyle {
+    /// A monospace font stack that is available on virtually all platforms
+    pub const WEB_SAFE_MONOSPACE: &'static str = "Courier New, Courier, monospace";
+
+    /// Creates the default style.
+    pub const fn new() -> Self {
+        Self {
+            font_family: Cow::Borrowed("Courier"),
+            embedded_font: None,
+            font_size: 16.0,
+            char_width: 10.0,
+            text_height: 10.0,
+            level_factor: 3.5,
+            text_color: Cow::Borrowed("black"),
+            emphasized_font_weight: Cow::Borrowed("bold"),
+            emphasized_text_color: Cow::Borrowed("black"),
+            node_fill: None,
+            node_stroke: None,
+            node_shape: NodeShape::Rect,
+            node_padding: 3.0,
+            warning_color: Cow::Borrowed("darkorange"),
+            recovered_color: Cow::Borrowed("purple"),
+            error_color: Cow::Borrowed("red"),
+            collapsed_color: Cow::Borrowed("gray"),
+            edge_color: Cow::Borrowed("black"),
+            edge_style: EdgeStyle::Straight,
+            band_color: Cow::Borrowed("gold"),
+            band_opacity: 0.3,
+            group_opacity: 0.15,
+            background: Some(Cow::Borrowed("white")),
+            x_margin: 10.0,
+            y_margin: 25.0,
+        }
+    }
+
+    ///
+    /// Creates the style for the given profile.
+    ///
+    /// ```
+    /// use syntree_layout::{Profile, SvgDrawerStyle};
+    ///
+    /// let style = SvgDrawerStyle {
+    ///     edge_color: "navy".into(),
+    ///     ..SvgDrawerStyle::from_profile(Profile::Presentation)
+    /// };
+    /// assert_eq!(SvgDrawerStyle::new(), SvgDrawerStyle::from_profile(Profile::Classic));
+    /// ```
+    ///
+    pub fn from_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Classic => Self::new(),
+            Profile::Compact => Self {
+                font_size: 12.0,
+                char_width: 7.5,
+                text_height: 8.0,
+                level_factor: 3.0,
+                node_padding: 2.0,
+                x_margin: 5.0,
+                y_margin: 15.0,
+                ..Self::new()
+            },
+            Profile::Presentation => Self {
+                font_size: 20.0,
+                char_width: 12.5,
+                text_height: 12.5,
+                level_factor: 4.0,
+                text_color: Cow::Borrowed("#1a1a2e"),
+                emphasized_text_color: Cow::Borrowed("#1f4e8c"),
+                node_fill: Some(Cow::Borrowed("#eef2f8")),
+                node_stroke: Some(Cow::Borrowed("#4a6fa5")),
+                node_shape: NodeShape::RoundedRect,
+                node_padding: 5.0,
+                edge_color: Cow::Borrowed("#4a6fa5"),
+                x_margin: 20.0,
+                y_margin: 35.0,
+                ..Self::new()
+            },
+            Profile::Debug => Self {
+                node_stroke: Some(Cow::Borrowed("lightgray")),
+                node_padding: 0.0,
+                edge_color: Cow::Borrowed("gray"),
+                ..Self::new()
+            },
+        }
+    }
+
+    ///
+    /// Creates the style with the palette of the given theme. Individual properties can be
+    /// overridden with the struct update syntax.
+    ///
+    /// ```
+    /// use syntree_layout::{SvgDrawerStyle, Theme};
+    ///
+    /// let style = SvgDrawerStyle {
+    ///     edge_color: "#ff79c6".into(),
+    ///     ..SvgDrawerStyle::preset(Theme::Dark)
+    /// };
+    /// assert_eq!(Some("#0d1117"), style.background.as_deref());
+    /// assert_eq!("#ff79c6", style.edge_color);
+    /// ```
+    ///
+    pub fn preset(theme: Theme) -> Self {
+        match theme {
+            Theme::Light => Self {
+                text_color: Cow::Borrowed("#24292f"),
+                emphasized_text_color: Cow::Borrowed("#0550ae"),
+                node_fill: Some(Cow::Borrowed("#f6f8fa")),
+                node_stroke: Some(Cow::Borrowed("#d0d7de")),
+                node_shape: NodeShape::RoundedRect,
+                warning_color: Cow::Borrowed("#9a6700"),
+                recovered_color: Cow::Borrowed("#8250df"),
+                error_color: Cow::Borrowed("#cf222e"),
+                collapsed_color: Cow::Borrowed("#6e7781"),
+                edge_color: Cow::Borrowed("#8c959f"),
+                band_color: Cow::Borrowed("#d4a72c"),
+                background: Some(Cow::Borrowed("#ffffff")),
+                ..Self::new()
+            },
+            Theme::Dark => Self {
+                text_color: Cow::Borrowed("#e6edf3"),
+                emphasized_text_color: Cow::Borrowed("#79c0ff"),
+                node_fill: Some(Cow::Borrowed("#161b22")),
+                node_stroke: Some(Cow::Borrowed("#30363d")),
+                node_shape: NodeShape::RoundedRect,
+                warning_color: Cow::Borrowed("#d29922"),
+                recovered_color: Cow::Borrowed("#bc8cff"),
+                error_color: Cow::Borrowed("#f85149"),
+                collapsed_color: Cow::Borrowed("#8b949e"),
+                edge_color: Cow::Borrowed("#6e7681"),
+                band_color: Cow::Borrowed("#e3b341"),
+                band_opacity: 0.25,
+                background: Some(Cow::Borrowed("#0d1117")),
+                ..Self::new()
+            },
+            Theme::HighContrast => Self {
+                text_color: Cow::Borrowed("#000000"),
+                emphasized_font_weight: Cow::Borrowed("900"),
+                emphasized_text_color: Cow::Borrowed("#000000"),
+                node_fill: Some(Cow::Borrowed("#ffffff")),
+                node_stroke: Some(Cow::Borrowed("#000000")),
+                warning_color: Cow::Borrowed("#8a4b00"),
+                recovered_color: Cow::Borrowed("#5b00a0"),
+                error_color: Cow::Borrowed("#c00000"),
+                collapsed_color: Cow::Borrowed("#404040"),
+                edge_color: Cow::Borrowed("#000000"),
+                band_color: Cow::Borrowed("#ffff00"),
+                band_opacity: 0.5,
+                background: Some(Cow::Borrowed("#ffffff")),
+                ..Self::new()
+            },
+            Theme::ColorblindSafe => Self {
+                text_color: Cow::Borrowed("#000000"),
+                emphasized_text_color: Cow::Borrowed("#0072b2"),
+                node_fill: Some(Cow::Borrowed("#ffffff")),
+                node_stroke: Some(Cow::Borrowed("#0072b2")),
+                node_shape: NodeShape::RoundedRect,
+                warning_color: Cow::Borrowed("#e69f00"),
+                recovered_color: Cow::Borrowed("#cc79a7"),
+                error_color: Cow::Borrowed("#d55e00"),
+                collapsed_color: Cow::Borrowed("#999999"),
+                edge_color: Cow::Borrowed("#555555"),
+                band_color: Cow::Borrowed("#56b4e9"),
+                background: Some(Cow::Borrowed("#ffffff")),
+                ..Self::new()
+            },
+        }
+    }
+
+    /// Returns the color used for nodes with the given diagnostic level, if any.
+    pub fn diagnostic_color(&self, level: DiagnosticLevel) -> Option<&str> {
+        match level {
+            DiagnosticLevel::None => None,
+            DiagnosticLevel::Warning => Some(&self.warning_color),
+            DiagnosticLevel::Recovered => Some(&self.recovered_color),
+            DiagnosticLevel::Error => Some(&self.error_color),
+        }
+    }
+
+    ///
+    /// Returns the CSS `font-family` value, i.e. the font stack with all family names quoted
+    /// except the generic ones.
+    ///
+    /// ```
+    /// use syntree_layout::SvgDrawerStyle;
+    ///
+    /// let style = SvgDrawerStyle {
+    ///     font_family: SvgDrawerStyle::WEB_SAFE_MONOSPACE.into(),
+    ///     ..SvgDrawerStyle::new()
+    /// };
+    /// assert_eq!("'Courier New', 'Courier', monospace", style.css_font_family());
+    /// ```
+    ///
+    pub fn css_font_family(&self) -> String {
+        const GENERIC_FAMILIES: &[&str] = &[
+            "serif",
+            "sans-serif",
+            "monospace",
+            "cursive",
+            "fantasy",
+            "system-ui",
+        ];
+        self.embedded_font
+            .iter()
+            .map(|font| &*font.family)
+            .chain(self.font_family.split(','))
+            .map(str::trim)
+            .filter(|family| !family.is_empty())
+            .map(|family| {
+                if GENERIC_FAMILIES.contains(&family)
+                    || family.starts_with('\'')
+                    || family.starts_with('"')
+                {
+                    family.to_string()
+                } else {
+                    format!("'{}'", family)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns the conversion of the embedding's logical units into pixels for this style.
+    pub fn units(&self) -> Units {
+        Units::new(
+            self.char_width,
+            self.text_height * self.level_factor,
+            self.x_margin,
+            self.y_margin,
+        )
+    }
+
+    /// Returns the value of the `style` attribute of a node's text element.
+    /// The text color is taken from the node's diagnostic level, the node's collapsed state, the
+    /// node's own style or the drawer's style, in this order.
+    pub(crate) fn text_style(&self, node: &EmbeddedNode) -> String {
+        let font_size = self.font_size * node.scale;
+        if node.is_emphasized {
+            format!(
+                "font-family: {}; font-size: {}px; font-weight: {}; font-style: normal; fill: {}",
+                self.css_font_family(),
+                font_size,
+                self.emphasized_font_weight,
+                self.text_fill(node)
+            )
+        } else {
+            format!(
+                "font-family: {}; font-size: {}px; font-style: normal; fill: {}",
+                self.css_font_family(),
+                font_size,
+                self.text_fill(node)
+            )
+        }
+    }
+
+    /// Returns the same properties as [text_style][Self::text_style] as presentation attributes
+    /// of a node's text element, which style sheets can override.
+    pub(crate) fn text_attributes(&self, node: &EmbeddedNode) -> Vec<(&'static str, String)> {
+        let mut attributes = vec![
+            ("font-family", self.css_font_family()),
+            ("font-size", format!("{}px", self.font_size * node.scale)),
+        ];
+        if node.is_emphasized {
+            attributes.push(("font-weight", self.emphasized_font_weight.to_string()));
+        }
+        attributes.push(("font-style", "normal".to_string()));
+        attributes.push(("fill", self.text_fill(node).to_string()));
+        attributes
+    }
+
+    /// Returns the color of a node's text, see [text_style][Self::text_style].
+    fn text_fill<'s>(&'s self, node: &'s EmbeddedNode) -> &'s str {
+        self.diagnostic_color(node.diagnostic)
+            .or((node.collapsed > 0).then_some(&*self.collapsed_color))
+            .or(node.style.text_color.as_deref())
+            .unwrap_or(if node.is_emphasized {
+                &self.emphasized_text_color
+            } else {
+                &self.text_color
+            })
+    }
+}
+
+impl Default for SvgDrawerStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Theme> for SvgDrawerStyle {
+    fn from(theme: Theme) -> Self {
+        Self::preset(theme)
+    }
+}
+
+impl From<Profile> for SvgDrawerStyle {
+    fn from(profile: Profile) -> Self {
+        Self::from_profile(profile)
+    }
+}
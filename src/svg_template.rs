@@ -0,0 +1,137 @@
+//! The module with the templates that customize the output of the crate's default drawer.
+
+use std::borrow::Cow;
+
+use crate::EmbeddedNode;
+
+///
+/// The `SvgTemplate` type contains snippets that the [SvgDrawer][crate::SvgDrawer] inserts
+/// verbatim into the generated document, see
+/// [SvgDrawer::with_template][crate::SvgDrawer::with_template]. This allows deep customization,
+/// e.g. additional definitions, corporate styling or extra elements per node, without
+/// reimplementing the emission of the geometry.
+///
+/// The snippets may contain placeholders in curly braces that are replaced before insertion.
+/// The `header` and the `footer` know the placeholders `{width}` and `{height}` of the image.
+/// The `node` snippet knows these placeholders:
+///
+/// * `{ord}` and `{node_id}` of the node
+/// * `{x}`, `{y}`, `{width}` and `{height}` of the node's box in pixels
+/// * `{cx}` and `{cy}`, the center of the node's box
+/// * `{depth}`, the node's `y_order`
+/// * `{span_start}` and `{span_end}` of the node's span
+/// * `{text}`, the node's label escaped for XML
+///
+/// ```
+/// use syntree_layout::{EmbeddedNode, SvgDrawer, SvgTemplate};
+///
+/// let embedding = vec![
+///     EmbeddedNode { text: "a<b".to_string(), x_center: 2, x_extent: 4, x_extent_children: 4, ..Default::default() },
+/// ];
+/// let svg = SvgDrawer::new()
+///     .with_template(SvgTemplate {
+///         header: "<defs><style>text { font-variant: small-caps; }</style></defs>".into(),
+///         node: r#"<circle cx="{cx}" cy="{cy}" r="2"><title>{text}</title></circle>"#.into(),
+///         ..SvgTemplate::new()
+///     })
+///     .render_string(&embedding)
+///     .unwrap();
+/// assert!(svg.contains("<defs><style>text { font-variant: small-caps; }</style></defs>"));
+/// assert!(svg.contains(r#"<circle cx="30" cy="23.5" r="2"><title>a&lt;b</title></circle>"#));
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SvgTemplate {
+    /// The snippet inserted at the start of the document, behind the embedded font
+    pub header: Cow<'static, str>,
+    /// The snippet inserted at the end of the document
+    pub footer: Cow<'static, str>,
+    /// The snippet inserted behind the label of each node
+    pub node: Cow<'static, str>,
+}
+
+impl SvgTemplate {
+    /// Creates an empty template that doesn't change the output.
+    pub const fn new() -> Self {
+        Self {
+            header: Cow::Borrowed(""),
+            footer: Cow::Borrowed(""),
+            node: Cow::Borrowed(""),
+        }
+    }
+
+    /// Fills the placeholders of the header with the size of the image.
+    pub(crate) fn fill_header(&self, width: f32, height: f32) -> String {
+        Self::fill(
+            &self.header,
+            &[("width", width.to_string()), ("height", height.to_string())],
+        )
+    }
+
+    /// Fills the placeholders of the footer with the size of the image.
+    pub(crate) fn fill_footer(&self, width: f32, height: f32) -> String {
+        Self::fill(
+            &self.footer,
+            &[("width", width.to_string()), ("height", height.to_string())],
+        )
+    }
+
+    /// Fills the placeholders of the node snippet with the properties of the node and its box.
+    pub(crate) fn fill_node(&self, data: &EmbeddedNode, frame: (f32, f32, f32, f32)) -> String {
+        let (x, y, width, height) = frame;
+        Self::fill(
+            &self.node,
+            &[
+                ("ord", data.ord.to_string()),
+                ("node_id", data.node_id.to_string()),
+                ("x", x.to_string()),
+                ("y", y.to_string()),
+                ("width", width.to_string()),
+                ("height", height.to_string()),
+                ("cx", (x + width / 2.0).to_string()),
+                ("cy", (y + height / 2.0).to_string()),
+                ("depth", data.y_order.to_string()),
+                ("span_start", data.span.start.to_string()),
+                ("span_end", data.span.end.to_string()),
+                ("text", escape(&data.text)),
+            ],
+        )
+    }
+
+    /// Replaces the placeholders in one pass, thus values can't introduce new placeholders.
+    fn fill(snippet: &str, values: &[(&str, String)]) -> String {
+        let mut result = String::with_capacity(snippet.len());
+        let mut rest = snippet;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = rest.find('}').and_then(|end| {
+                values
+                    .iter()
+                    .find(|(name, _)| *name == &rest[1..end])
+                    .map(|(_, value)| (end, value))
+            });
+            match value {
+                Some((end, value)) => {
+                    result.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    // Braces of unknown placeholders, e.g. in CSS, are kept
+                    result.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
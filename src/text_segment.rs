@@ -0,0 +1,59 @@
+//! The module with the styled segments of rich text labels.
+
+///
+/// A `TextSegment` is a part of a rich text label with its own emphasis and color.
+/// Rich text labels are obtained from the [Visualize::rich_text][crate::Visualize::rich_text]
+/// hook.
+///
+/// ```
+/// use syntree_layout::TextSegment;
+///
+/// let label = vec![
+///     TextSegment::plain("Ident "),
+///     TextSegment::emphasized("foo").with_color("blue"),
+/// ];
+/// assert_eq!("Ident foo", TextSegment::concat(&label));
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextSegment {
+    /// The text of the segment
+    pub text: String,
+    /// When true the segment is printed bold
+    pub is_emphasized: bool,
+    /// The color of the segment, the node's text color is used if not set
+    pub color: Option<String>,
+}
+
+impl TextSegment {
+    /// Creates a segment without emphasis and color.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an emphasized segment.
+    pub fn emphasized(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            is_emphasized: true,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the color of the segment.
+    pub fn with_color(self, color: impl Into<String>) -> Self {
+        Self {
+            color: Some(color.into()),
+            ..self
+        }
+    }
+
+    /// Returns the plain text of the given segments.
+    pub fn concat(segments: &[TextSegment]) -> String {
+        segments.iter().map(|s| s.text.as_str()).collect()
+    }
+}
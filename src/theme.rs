@@ -0,0 +1,25 @@
+//! The module with the color themes of the crate's default drawer.
+
+///
+/// A `Theme` is a preset palette of the [SvgDrawer][crate::SvgDrawer], so that diagrams fit into
+/// light or dark documents and stay readable for everyone.
+///
+/// Use it with [SvgDrawer::with_theme][crate::SvgDrawer::with_theme] or obtain the style with
+/// [SvgDrawerStyle::preset][crate::SvgDrawerStyle::preset] and adjust individual properties
+/// afterwards. A theme only sets colors and the node boxes, the fonts and the spacing are the
+/// ones of the default style.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Theme {
+    /// Dark text in light, framed node boxes on a white background
+    #[default]
+    Light,
+    /// Light text in dark, framed node boxes on a dark background for dark-mode documents
+    Dark,
+    /// Black text and black frames on white with strong diagnostic colors for low vision and
+    /// projectors
+    HighContrast,
+    /// The Okabe-Ito colors, which stay distinguishable with the common forms of color vision
+    /// deficiency
+    ColorblindSafe,
+}
@@ -0,0 +1,214 @@
+//! The module with the drawer for LaTeX/TikZ pictures.
+
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::{
+    internal::color::parse_color, DrawContext, Drawer, EmbeddedNode, LabelOrientation,
+    LayouterError, Orientation, Result,
+};
+
+///
+/// The `TikzDrawer` type provides the transformation of the embedding information into a
+/// `tikzpicture` environment. Nodes are positioned from the embedding and connected by edges,
+/// the texts are set in the document's typewriter font.
+///
+/// By default only the `tikzpicture` is emitted, so that it can be `\input` into an existing
+/// LaTeX document that loads the `tikz` package. Use [TikzDrawer::with_standalone] to create a
+/// complete document instead.
+///
+#[derive(Debug)]
+pub struct TikzDrawer {
+    x_unit: f32,
+    level_distance: f32,
+    standalone: bool,
+    orientation: Orientation,
+}
+
+impl Default for TikzDrawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TikzDrawer {
+    /// Method to create a fresh instance of the `TikzDrawer` type.
+    pub const fn new() -> Self {
+        Self {
+            x_unit: 0.2,
+            level_distance: 1.2,
+            standalone: false,
+            orientation: Orientation::TopDown,
+        }
+    }
+
+    ///
+    /// Sets the width of one logical x unit of the embedding and the distance between two levels
+    /// of the tree, both in centimeters.
+    ///
+    pub fn with_units(self, x_unit: f32, level_distance: f32) -> Self {
+        Self {
+            x_unit,
+            level_distance,
+            ..self
+        }
+    }
+
+    ///
+    /// Sets whether the root is drawn at the top or at the bottom of the picture.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, Orientation, TikzDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, x_center: 2, ..Default::default() },
+    ///     EmbeddedNode { text: "leaf".to_string(), ord: 1, parent: Some(0), x_center: 2, y_order: 1, ..Default::default() },
+    /// ];
+    /// let tikz = TikzDrawer::new()
+    ///     .with_orientation(Orientation::BottomUp)
+    ///     .render_string(&embedding)
+    ///     .unwrap();
+    /// assert!(tikz.contains("(n1) at (0.40cm, 1.20cm) {leaf}"));
+    /// ```
+    ///
+    pub fn with_orientation(self, orientation: Orientation) -> Self {
+        Self {
+            orientation,
+            ..self
+        }
+    }
+
+    ///
+    /// When set to true the picture is wrapped into a complete LaTeX document of class
+    /// `standalone`.
+    ///
+    pub fn with_standalone(self, standalone: bool) -> Self {
+        Self { standalone, ..self }
+    }
+
+    ///
+    /// Renders the given embedding into a `tikzpicture` and returns it as a `String`.
+    ///
+    /// ```
+    /// use syntree_layout::{EmbeddedNode, TikzDrawer};
+    ///
+    /// let embedding = vec![
+    ///     EmbeddedNode { text: "root".to_string(), ord: 0, ..Default::default() },
+    ///     EmbeddedNode { text: "a_b".to_string(), ord: 1, parent: Some(0), ..Default::default() },
+    /// ];
+    /// let tikz = TikzDrawer::new().render_string(&embedding).unwrap();
+    /// assert!(tikz.starts_with("\\begin{tikzpicture}"));
+    /// assert!(tikz.contains("{a\\_b};"));
+    /// assert!(tikz.contains("\\draw (n0) -- (n1);"));
+    /// ```
+    ///
+    pub fn render_string(&self, embedding: &[EmbeddedNode]) -> Result<String> {
+        self.build_document(embedding)
+            .map_err(|e| LayouterError::from_description(&e.to_string()))
+    }
+
+    /// Escapes the characters that have a special meaning in LaTeX.
+    fn escape(text: &str) -> String {
+        text.chars()
+            .map(|c| match c {
+                '\\' => "\\textbackslash{}".to_string(),
+                '{' | '}' | '$' | '&' | '#' | '_' | '%' => format!("\\{}", c),
+                '^' => "\\textasciicircum{}".to_string(),
+                '~' => "\\textasciitilde{}".to_string(),
+                c => c.to_string(),
+            })
+            .collect()
+    }
+
+    /// Converts a CSS color into a color expression understood by TikZ.
+    fn color(color: &str) -> Option<String> {
+        parse_color(color)
+            .map(|(r, g, b)| format!("{{rgb,1:red,{:.3};green,{:.3};blue,{:.3}}}", r, g, b))
+    }
+
+    fn build_document(
+        &self,
+        embedding: &[EmbeddedNode],
+    ) -> std::result::Result<String, std::fmt::Error> {
+        let mut doc = String::new();
+        if self.standalone {
+            writeln!(doc, "\\documentclass[tikz]{{standalone}}")?;
+            writeln!(doc, "\\begin{{document}}")?;
+        }
+        writeln!(doc, "\\begin{{tikzpicture}}")?;
+
+        for node in embedding {
+            let mut options = vec!["font=\\ttfamily".to_string(), "inner sep=2pt".to_string()];
+            if node.is_emphasized {
+                options[0].push_str("\\bfseries");
+            }
+            let text_color = if node.diagnostic.is_error() {
+                Some("red")
+            } else {
+                node.style.text_color.as_deref()
+            };
+            if let Some(color) = text_color.and_then(Self::color) {
+                options.push(format!("text={}", color));
+            }
+            if let Some(color) = node.style.fill.as_deref().and_then(Self::color) {
+                options.push(format!("fill={}", color));
+            }
+            if node.diagnostic.is_error() {
+                options.push("draw=red".to_string());
+            } else if node.collapsed > 0 {
+                options.push("draw=gray, dotted".to_string());
+            } else if let Some(color) = node.style.border_color.as_deref().and_then(Self::color) {
+                options.push(format!("draw={}", color));
+            }
+            let text = match node.orientation {
+                LabelOrientation::Horizontal => Self::escape(&node.text),
+                LabelOrientation::Rotated => {
+                    options.push("rotate=-90".to_string());
+                    Self::escape(&node.text)
+                }
+                LabelOrientation::Stacked => {
+                    options.push("align=center".to_string());
+                    node.text
+                        .chars()
+                        .map(|c| Self::escape(c.encode_utf8(&mut [0; 4])))
+                        .collect::<Vec<_>>()
+                        .join("\\\\")
+                }
+            };
+            writeln!(
+                doc,
+                "  \\node[{}] (n{}) at ({:.2}cm, {:.2}cm) {{{}}};",
+                options.join(", "),
+                node.ord,
+                node.x_center as f32 * self.x_unit,
+                match self.orientation {
+                    Orientation::TopDown => -(node.y_order as f32),
+                    Orientation::BottomUp => node.y_order as f32,
+                } * self.level_distance,
+                text
+            )?;
+        }
+
+        for node in embedding {
+            if let Some(parent) = node.parent {
+                writeln!(doc, "  \\draw (n{}) -- (n{});", parent, node.ord)?;
+            }
+        }
+
+        writeln!(doc, "\\end{{tikzpicture}}")?;
+        if self.standalone {
+            writeln!(doc, "\\end{{document}}")?;
+        }
+        Ok(doc)
+    }
+}
+
+///
+/// The concrete implementation of the `Drawer` trait for `TikzDrawer`.
+///
+impl Drawer for TikzDrawer {
+    fn draw(&self, ctx: &DrawContext<'_>) -> Result<()> {
+        let doc = self.render_string(ctx.embedding())?;
+        fs::write(ctx.file_name(), doc).map_err(LayouterError::from_io_error)
+    }
+}
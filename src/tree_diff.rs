@@ -0,0 +1,224 @@
+//! The module with the comparison of two trees.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use syntree::{Flavor, Pointer, Tree};
+
+use crate::{DrawContext, Drawer, Embedding, Legend, NodeStyle, Result, SvgDrawer, WriteOptions};
+
+///
+/// The result of the comparison of a single node, see [TreeDiff].
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DiffKind {
+    /// A node with the same value and span exists in the other tree
+    #[default]
+    Unchanged,
+    /// The node exists only in the second tree
+    Added,
+    /// The node exists only in the first tree
+    Removed,
+    /// The other tree has a node with the same span but a different value
+    Changed,
+}
+
+impl DiffKind {
+    /// The fill color of the nodes of this kind, `None` for unchanged nodes.
+    pub fn color(self) -> Option<&'static str> {
+        match self {
+            DiffKind::Unchanged => None,
+            DiffKind::Added => Some("#c8e6c9"),
+            DiffKind::Removed => Some("#ffcdd2"),
+            DiffKind::Changed => Some("#fff59d"),
+        }
+    }
+
+    fn style(self) -> Option<NodeStyle> {
+        self.color().map(|color| NodeStyle {
+            fill: Some(color.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+///
+/// The `TreeDiff` is a node-level comparison of two trees, e.g. the parser output before and
+/// after a grammar change. It is created by [Layouter::diff][crate::Layouter::diff].
+///
+/// Nodes are keyed on their value and their span. A node of the first tree that has a node with
+/// the same value and span in the second tree is unchanged. Nodes that only share the span with
+/// a node of the other tree are changed, all others are removed from the first tree or added to
+/// the second one.
+///
+/// Both trees are embedded on their own and the nodes that differ are filled with the color of
+/// their [DiffKind], so the trees can be drawn side by side with any drawer. The
+/// [legend][TreeDiff::legend] explains the colors.
+///
+#[derive(Debug, Clone, Default)]
+pub struct TreeDiff {
+    before: Embedding,
+    after: Embedding,
+    before_kinds: Vec<DiffKind>,
+    after_kinds: Vec<DiffKind>,
+    write_options: WriteOptions,
+}
+
+impl TreeDiff {
+    /// Compares the embedded nodes of both trees and colors the nodes that differ.
+    pub(crate) fn compute<T, F>(
+        tree_before: &Tree<T, F>,
+        before: Embedding,
+        tree_after: &Tree<T, F>,
+        after: Embedding,
+    ) -> Self
+    where
+        T: Copy + PartialEq,
+        F: Flavor,
+    {
+        let values_before = values(tree_before);
+        let values_after = values(tree_after);
+        let value = |values: &[Option<T>], node_id: usize| values.get(node_id).copied().flatten();
+
+        let mut before_kinds = vec![DiffKind::Removed; before.len()];
+        let mut after_kinds = vec![DiffKind::Added; after.len()];
+
+        // The nodes of the second tree by their spans
+        let mut by_span: HashMap<Range<usize>, Vec<usize>> = HashMap::new();
+        for node in after.iter() {
+            by_span.entry(node.span.clone()).or_default().push(node.ord);
+        }
+
+        // First the nodes with equal values, then the remaining nodes with equal spans
+        for node in before.iter() {
+            let value_before = value(&values_before, node.node_id);
+            let candidates = by_span.get(&node.span).into_iter().flatten();
+            if let Some(ord) = candidates.copied().find(|ord| {
+                after_kinds[*ord] == DiffKind::Added
+                    && value(&values_after, after[*ord].node_id) == value_before
+            }) {
+                before_kinds[node.ord] = DiffKind::Unchanged;
+                after_kinds[ord] = DiffKind::Unchanged;
+            }
+        }
+        for node in before.iter() {
+            if before_kinds[node.ord] != DiffKind::Removed {
+                continue;
+            }
+            let candidates = by_span.get(&node.span).into_iter().flatten();
+            if let Some(ord) = candidates
+                .copied()
+                .find(|ord| after_kinds[*ord] == DiffKind::Added)
+            {
+                before_kinds[node.ord] = DiffKind::Changed;
+                after_kinds[ord] = DiffKind::Changed;
+            }
+        }
+
+        Self {
+            before: colored(before, &before_kinds),
+            after: colored(after, &after_kinds),
+            before_kinds,
+            after_kinds,
+            write_options: WriteOptions::new(),
+        }
+    }
+
+    /// The embedding of the first tree, removed and changed nodes are colored.
+    pub fn before(&self) -> &Embedding {
+        &self.before
+    }
+
+    /// The embedding of the second tree, added and changed nodes are colored.
+    pub fn after(&self) -> &Embedding {
+        &self.after
+    }
+
+    /// The kinds of the nodes of the first tree, indexed by their `ord`.
+    pub fn before_kinds(&self) -> &[DiffKind] {
+        &self.before_kinds
+    }
+
+    /// The kinds of the nodes of the second tree, indexed by their `ord`.
+    pub fn after_kinds(&self) -> &[DiffKind] {
+        &self.after_kinds
+    }
+
+    /// Returns true if both trees consist of the same nodes.
+    pub fn is_unchanged(&self) -> bool {
+        self.before_kinds
+            .iter()
+            .chain(&self.after_kinds)
+            .all(|kind| *kind == DiffKind::Unchanged)
+    }
+
+    /// Sets how the output files are written by [TreeDiff::draw], see [WriteOptions].
+    pub fn with_write_options(self, write_options: WriteOptions) -> Self {
+        Self {
+            write_options,
+            ..self
+        }
+    }
+
+    /// Returns a legend that explains the colors of the differing nodes.
+    pub fn legend(&self) -> Legend {
+        [DiffKind::Added, DiffKind::Removed, DiffKind::Changed]
+            .into_iter()
+            .fold(Legend::new(), |legend, kind| {
+                let label = format!("{:?}", kind).to_lowercase();
+                legend.with_entry(label, kind.style().unwrap_or_default())
+            })
+    }
+
+    ///
+    /// Draws both trees with the given drawer into the given files according to the
+    /// [write options][TreeDiff::with_write_options].
+    ///
+    pub fn draw(&self, drawer: &dyn Drawer, before_file: &Path, after_file: &Path) -> Result<()> {
+        self.write_options.write(before_file, |path| {
+            drawer.draw(&DrawContext::new(path, &self.before))
+        })?;
+        self.write_options.write(after_file, |path| {
+            drawer.draw(&DrawContext::new(path, &self.after))
+        })
+    }
+
+    ///
+    /// Renders both trees as SVG documents with the [legend][TreeDiff::legend] of the colors.
+    ///
+    pub fn to_svg_strings(&self) -> Result<(String, String)> {
+        let drawer = SvgDrawer::new().with_legend(self.legend());
+        Ok((
+            drawer.render_string(&self.before)?,
+            drawer.render_string(&self.after)?,
+        ))
+    }
+}
+
+/// The values of the nodes of the tree indexed by the nodes' ids.
+fn values<T: Copy, F: Flavor>(tree: &Tree<T, F>) -> Vec<Option<T>> {
+    let mut values = vec![None; tree.len()];
+    for node in tree.walk() {
+        let id = node.id().get();
+        if id >= values.len() {
+            values.resize(id + 1, None);
+        }
+        values[id] = Some(node.value());
+    }
+    values
+}
+
+/// Fills the nodes that differ with the colors of their kinds.
+fn colored(embedding: Embedding, kinds: &[DiffKind]) -> Embedding {
+    embedding
+        .into_iter()
+        .zip(kinds)
+        .map(|(mut node, kind)| {
+            if let Some(color) = kind.color() {
+                node.style.fill = Some(color.to_string());
+            }
+            node
+        })
+        .collect()
+}
@@ -0,0 +1,116 @@
+//! The module with the statistics of an embedded tree.
+
+use std::collections::BTreeMap;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{embedding::tree_width, EmbeddedNode, SvgDrawerStyle};
+
+///
+/// The `TreeStats` summarize the shape of an embedded tree, e.g. to decide whether a huge tree
+/// should be truncated or split before it is rendered. They are obtained by
+/// [Layouter::stats][crate::Layouter::stats] or [Embedding::stats][crate::Embedding::stats].
+///
+/// ```
+/// use syntree_layout::{from_text, Layouter, SvgDrawerStyle};
+///
+/// let tree = from_text("(Expr (Call f x y) + 1)").unwrap();
+/// let stats = Layouter::new(&tree).embed().unwrap().stats();
+/// assert_eq!(7, stats.node_count);
+/// assert_eq!(5, stats.leaf_count);
+/// assert_eq!(3, stats.height);
+/// assert_eq!(3, stats.max_fan_out);
+/// assert_eq!((1, 3), (stats.widest_layer, stats.widest_layer_nodes));
+/// assert_eq!(Some(&5), stats.label_lengths.get(&1));
+///
+/// let (width, height) = stats.estimated_size(&SvgDrawerStyle::new());
+/// assert!(width > 100.0 && height > 100.0);
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeStats {
+    /// The number of nodes
+    pub node_count: usize,
+    /// The number of nodes without children
+    pub leaf_count: usize,
+    /// The number of levels, 0 for an empty tree
+    pub height: usize,
+    /// The largest number of children of a node
+    pub max_fan_out: usize,
+    /// The depth of the level with the most nodes, the root has the depth 0. The first of
+    /// several levels with equal numbers of nodes is taken.
+    pub widest_layer: usize,
+    /// The number of nodes on the widest level
+    pub widest_layer_nodes: usize,
+    /// The number of nodes by the width of their labels in terminal columns. The width of a
+    /// wrapped label is the width of its longest line.
+    pub label_lengths: BTreeMap<usize, usize>,
+    /// The width of the tree in logical units including the margins
+    pub width: usize,
+    /// The largest `y_order` of the nodes
+    pub max_y_order: usize,
+}
+
+impl TreeStats {
+    /// Collects the statistics of the given nodes.
+    pub(crate) fn of(embedding: &[EmbeddedNode]) -> Self {
+        let mut stats = Self {
+            node_count: embedding.len(),
+            width: tree_width(embedding),
+            ..Default::default()
+        };
+        // Parents precede their children in the embedding
+        let len = embedding.iter().map(|e| e.ord + 1).max().unwrap_or(0);
+        let mut depths = vec![0; len];
+        let mut children = vec![0; len];
+        let mut layers: Vec<usize> = Vec::new();
+        for node in embedding {
+            let depth = node.parent.map_or(0, |parent| depths[parent] + 1);
+            depths[node.ord] = depth;
+            if let Some(parent) = node.parent {
+                children[parent] += 1;
+            }
+            if layers.len() <= depth {
+                layers.resize(depth + 1, 0);
+            }
+            layers[depth] += 1;
+            let label_length = node.text.lines().map(|line| line.width()).max();
+            *stats
+                .label_lengths
+                .entry(label_length.unwrap_or(0))
+                .or_default() += 1;
+            stats.max_y_order = stats.max_y_order.max(node.y_order);
+        }
+        stats.leaf_count = embedding.iter().filter(|e| children[e.ord] == 0).count();
+        stats.max_fan_out = children.iter().copied().max().unwrap_or(0);
+        stats.height = layers.len();
+        // The first of the widest levels
+        if let Some((depth, nodes)) = layers
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, nodes)| **nodes)
+        {
+            stats.widest_layer = depth;
+            stats.widest_layer_nodes = *nodes;
+        }
+        stats
+    }
+
+    ///
+    /// Returns the estimated width and height in pixels of the image that the
+    /// [SvgDrawer][crate::SvgDrawer] draws with the given style in the layered layout. Labels
+    /// that reach below the last level, legends and source strips are not taken into account.
+    ///
+    pub fn estimated_size(&self, style: &SvgDrawerStyle) -> (f32, f32) {
+        if self.node_count == 0 {
+            return (0.0, 0.0);
+        }
+        let units = style.units();
+        (
+            units.x_to_pixels(self.width),
+            units.y_to_pixels(self.max_y_order + 1),
+        )
+    }
+}
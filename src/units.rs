@@ -0,0 +1,68 @@
+//! The module with the conversion of the embedding's logical units into pixels.
+
+///
+/// The `Units` type converts the logical coordinates of an embedding into pixels.
+/// One logical x unit is the width of one character of a node's text, one logical y unit is the
+/// distance between two levels of the tree.
+///
+/// The pixel based drawers of this crate, i.e. the [SvgDrawer][crate::SvgDrawer], the
+/// [HtmlDrawer][crate::HtmlDrawer] and the `PdfDrawer`, use this conversion to place the nodes.
+/// Applications can use the same conversion to draw overlays that line up exactly with the
+/// rendered output. The units of a drawer's style are obtained by
+/// [SvgDrawerStyle::units][crate::SvgDrawerStyle::units].
+///
+/// ```
+/// use syntree_layout::{SvgDrawerStyle, Units};
+///
+/// let units = SvgDrawerStyle::new().units();
+/// assert_eq!(Units::new(10.0, 35.0, 10.0, 25.0), units);
+/// assert_eq!((30.0, 95.0), units.to_pixels(2, 2));
+/// assert_eq!(50.0, units.extent_to_pixels(5));
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Units {
+    /// The width of one logical x unit in pixels
+    pub x_unit: f32,
+    /// The distance between two levels of the tree in pixels
+    pub y_unit: f32,
+    /// The horizontal margin of the image in pixels
+    pub x_margin: f32,
+    /// The vertical margin of the image in pixels
+    pub y_margin: f32,
+}
+
+impl Units {
+    /// Creates the conversion from the sizes of the units and the margins in pixels.
+    pub const fn new(x_unit: f32, y_unit: f32, x_margin: f32, y_margin: f32) -> Self {
+        Self {
+            x_unit,
+            y_unit,
+            x_margin,
+            y_margin,
+        }
+    }
+
+    ///
+    /// Converts the logical position of a node, i.e. its `x_center` and its `y_order`, into the
+    /// pixel position of its label's horizontal center and baseline.
+    ///
+    pub fn to_pixels(&self, x: usize, y_order: usize) -> (f32, f32) {
+        (self.x_to_pixels(x), self.y_to_pixels(y_order))
+    }
+
+    /// Converts a logical x coordinate into pixels.
+    pub fn x_to_pixels(&self, x: usize) -> f32 {
+        x as f32 * self.x_unit + self.x_margin
+    }
+
+    /// Converts the y order of a node into the pixel position of its baseline.
+    pub fn y_to_pixels(&self, y_order: usize) -> f32 {
+        y_order as f32 * self.y_unit + self.y_margin
+    }
+
+    /// Converts a logical horizontal extent, e.g. a node's `x_extent`, into pixels.
+    pub fn extent_to_pixels(&self, extent: usize) -> f32 {
+        extent as f32 * self.x_unit
+    }
+}
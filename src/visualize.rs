@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+use crate::{DiagnosticLevel, LabelOrientation, NodeShape, NodeStyle, TextSegment};
+
 /// The `Visualize` trait abstracts the visual presentation of the node's data.
 /// It can be implemented by the Tree<T, ...>'s node type T when custom visualization is desired.
 /// Only mandatory to implement is the `visualize` method.
@@ -9,9 +11,63 @@ pub trait Visualize {
     /// Writes the string representation of the nodes data.
     fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 
+    /// Writes the string representation of the nodes data with the help of the node's text in
+    /// the source, e.g. to show the name of an identifier token next to its kind. It is used by
+    /// [Layouter::embed_with_visualize_and_source][crate::Layouter::embed_with_visualize_and_source].
+    /// The default implementation ignores the source and calls `visualize`.
+    fn visualize_with_source(&self, source: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let _ = source;
+        self.visualize(f)
+    }
+
     /// When this method returns true the drawer can emphasize the node's string representation
     /// in an implementation dependent way, i.e. it can print it bold.
     fn emphasize(&self) -> bool {
         false
     }
+
+    /// When this method returns a level other than `DiagnosticLevel::None` the node is marked as
+    /// erroneous and the drawer can style it accordingly, i.e. it can print it in red.
+    fn diagnostic(&self) -> DiagnosticLevel {
+        DiagnosticLevel::None
+    }
+
+    /// Returns the visual style of the node, e.g. its fill color, text color and shape.
+    /// The default style leaves all decisions to the drawer.
+    fn style(&self) -> NodeStyle {
+        NodeStyle::default()
+    }
+
+    /// Returns the shape of the node's box, e.g. ellipses for tokens and rectangles for
+    /// nonterminals. A shape set in the [style][Visualize::style] takes precedence, `None` leaves
+    /// the decision to the drawer.
+    fn shape(&self) -> Option<NodeShape> {
+        None
+    }
+
+    /// Returns the orientation of the node's label. Vertical labels reduce the width the node
+    /// occupies in the layout.
+    fn orientation(&self) -> LabelOrientation {
+        LabelOrientation::Horizontal
+    }
+
+    /// Returns an additional text that drawers can show when hovering over the node, see
+    /// [SvgDrawer::with_tooltips][crate::SvgDrawer::with_tooltips].
+    fn tooltip(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the label of the group the node's subtree forms, e.g. the name of the grammar
+    /// production that produced it. Drawers can mark groups, e.g. the
+    /// [SvgDrawer][crate::SvgDrawer] draws a labeled background box around each group.
+    fn group(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the node's label as list of styled segments, e.g. to highlight a name within the
+    /// label. When this method returns `Some` the concatenated segments replace the text written
+    /// by `visualize`. Drawers without support for rich text use the plain text.
+    fn rich_text(&self) -> Option<Vec<TextSegment>> {
+        None
+    }
 }
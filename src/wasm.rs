@@ -0,0 +1,52 @@
+//! The module with the functions exported to JavaScript.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{from_text, HtmlDrawer, Layouter, SvgDrawer};
+
+///
+/// Draws the tree written in one of the formats understood by [from_text] with the default
+/// options and returns the SVG document, ready to be inserted into the DOM, e.g. with
+/// `element.innerHTML = render_to_svg_string(tree)`.
+///
+/// Parse errors are thrown as JavaScript `Error`s.
+///
+/// This function is only available with the `wasm` feature.
+///
+/// ```
+/// let svg = syntree_layout::render_to_svg_string("(Expr 1 + 2)").unwrap();
+/// assert!(svg.contains(">Expr</text>"));
+/// ```
+///
+#[wasm_bindgen]
+pub fn render_to_svg_string(tree: &str) -> Result<String, JsError> {
+    let tree = from_text(tree)?;
+    Ok(Layouter::new(&tree).embed()?.to_svg_string()?)
+}
+
+///
+/// Draws the tree written in one of the formats understood by [from_text] as an HTML document
+/// whose inner nodes can be collapsed and expanded by clicking on them, see [HtmlDrawer].
+///
+/// This function is only available with the `wasm` feature.
+///
+#[wasm_bindgen]
+pub fn render_to_html_string(tree: &str) -> Result<String, JsError> {
+    let tree = from_text(tree)?;
+    let layouter = Layouter::new(&tree).embed()?;
+    Ok(HtmlDrawer::new().render_string(layouter.embedding())?)
+}
+
+///
+/// Returns the image map of the SVG document drawn by [render_to_svg_string] for the same tree
+/// as JSON, see [SvgDrawer::render_image_map]. Web tools can use it to put their own hotspots
+/// on the nodes.
+///
+/// This function is only available with the `wasm` feature.
+///
+#[wasm_bindgen]
+pub fn render_to_image_map(tree: &str) -> Result<String, JsError> {
+    let tree = from_text(tree)?;
+    let layouter = Layouter::new(&tree).embed()?;
+    Ok(SvgDrawer::new().render_image_map(layouter.embedding())?)
+}
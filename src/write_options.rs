@@ -0,0 +1,159 @@
+//! The module with the options that control how output files are written.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{LayouterError, Result};
+
+///
+/// The `OverwritePolicy` determines what happens when an output file already exists.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OverwritePolicy {
+    /// The existing file is replaced
+    #[default]
+    Overwrite,
+    /// Writing fails with [LayouterError::OutputFileExists]
+    Fail,
+    /// The existing file is copied to a backup with `.bak` appended to its name before it is
+    /// replaced. An older backup is replaced.
+    Backup,
+}
+
+///
+/// The `WriteOptions` control how the [Layouter][crate::Layouter] writes its output files, see
+/// [Layouter::with_write_options][crate::Layouter::with_write_options].
+///
+/// By default missing parent directories are not created, existing files are overwritten and the
+/// drawers write directly into the output files.
+///
+/// ```
+/// use syntree_layout::{OverwritePolicy, WriteOptions};
+///
+/// let options = WriteOptions::new()
+///     .with_create_dirs(true)
+///     .with_overwrite(OverwritePolicy::Backup);
+/// assert!(options.create_dirs);
+/// ```
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WriteOptions {
+    /// Whether missing parent directories of the output file are created
+    pub create_dirs: bool,
+    /// What happens when the output file already exists
+    pub overwrite: OverwritePolicy,
+    /// Whether the drawers write into temporary files that are renamed into place when they are
+    /// complete, so that file watchers never see partially written files
+    pub atomic: bool,
+}
+
+impl WriteOptions {
+    /// Creates the default options.
+    pub const fn new() -> Self {
+        Self {
+            create_dirs: false,
+            overwrite: OverwritePolicy::Overwrite,
+            atomic: false,
+        }
+    }
+
+    /// Sets whether missing parent directories of the output file are created.
+    pub fn with_create_dirs(self, create_dirs: bool) -> Self {
+        Self {
+            create_dirs,
+            ..self
+        }
+    }
+
+    /// Sets what happens when the output file already exists.
+    pub fn with_overwrite(self, overwrite: OverwritePolicy) -> Self {
+        Self { overwrite, ..self }
+    }
+
+    /// Sets whether the output files are written into temporary files first and renamed into
+    /// place when they are complete.
+    pub fn with_atomic(self, atomic: bool) -> Self {
+        Self { atomic, ..self }
+    }
+
+    ///
+    /// Writes the given file with the given function according to these options. In atomic mode
+    /// the function writes into a temporary directory next to the file, which also catches
+    /// additional files like image maps, and the written files are renamed into place when the
+    /// function succeeded.
+    ///
+    pub(crate) fn write(&self, path: &Path, draw: impl FnOnce(&Path) -> Result<()>) -> Result<()> {
+        self.prepare(path)?;
+        if !self.atomic {
+            return draw(path);
+        }
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let Some(file_name) = path.file_name() else {
+            return Err(LayouterError::MissingFileName {
+                path: path.to_path_buf(),
+            });
+        };
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let mut temp_name = OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(
+            ".{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp_dir = parent.join(temp_name);
+        std::fs::create_dir_all(&temp_dir).map_err(LayouterError::from_io_error)?;
+        let result = draw(&temp_dir.join(file_name)).and_then(|()| {
+            for entry in std::fs::read_dir(&temp_dir).map_err(LayouterError::from_io_error)? {
+                let entry = entry.map_err(LayouterError::from_io_error)?;
+                std::fs::rename(entry.path(), parent.join(entry.file_name()))
+                    .map_err(LayouterError::from_io_error)?;
+            }
+            Ok(())
+        });
+        // The temporary directory is removed even if drawing failed
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        result
+    }
+
+    /// Prepares the file system for writing the given file according to these options.
+    fn prepare(&self, path: &Path) -> Result<()> {
+        if self.create_dirs {
+            if let Some(parent) = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                std::fs::create_dir_all(parent).map_err(LayouterError::from_io_error)?;
+            }
+        }
+        if !path.exists() {
+            return Ok(());
+        }
+        match self.overwrite {
+            OverwritePolicy::Overwrite => Ok(()),
+            OverwritePolicy::Fail => Err(LayouterError::OutputFileExists {
+                path: path.to_path_buf(),
+            }),
+            OverwritePolicy::Backup => {
+                let backup = backup_path(path);
+                let _ = std::fs::remove_file(&backup);
+                // In atomic mode the existing file is replaced by a rename and never written, so
+                // a hard link keeps its content without copying it
+                if self.atomic && std::fs::hard_link(path, &backup).is_ok() {
+                    return Ok(());
+                }
+                std::fs::copy(path, backup)
+                    .map(|_| ())
+                    .map_err(LayouterError::from_io_error)
+            }
+        }
+    }
+}
+
+/// The path of the backup of the given file
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    backup.into()
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_syntree-layout"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The tool may exit because of invalid arguments before it reads its input
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn sexp_to_ascii() {
+    let output = run(
+        &["--format", "ascii"],
+        r#"(Expr (Num 1) "+" (Str "2 \"3\""))"#,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        "Expr\n├── Num\n│   └── 1\n├── +\n└── Str\n    └── 2 \"3\"\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn json_to_svg_and_dot() {
+    let input = r#"{"label": "E", "children": ["a", {"label": "b", "len": 3}]}"#;
+    let output = run(&[], input);
+    assert!(output.status.success());
+    let svg = String::from_utf8(output.stdout).unwrap();
+    assert!(svg.contains("<svg"));
+    assert!(svg.contains(r#"id="node-2""#));
+
+    let output = run(&["-i", "json", "-f", "dot"], input);
+    let dot = String::from_utf8(output.stdout).unwrap();
+    assert!(dot.contains("n0 -> n2;"));
+}
+
+#[test]
+fn invalid_input() {
+    let output = run(&["-f", "ascii"], "(a (b)");
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Missing ')' of node 'a'"));
+
+    let output = run(&["--format", "png"], "a");
+    assert_eq!(Some(2), output.status.code());
+}
+
+#[test]
+fn deeply_nested_input() {
+    let depth = 20_000;
+    let input = format!("{}x{}", "(a ".repeat(depth), ")".repeat(depth));
+    for format in ["svg", "dot"] {
+        let output = run(&["--format", format], &input);
+        assert!(output.status.success(), "{}", format);
+    }
+    let output = run(&["--format", "ascii"], "(a (b (c d)))");
+    assert_eq!(
+        "a\n└── b\n    └── c\n        └── d\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
@@ -0,0 +1,36 @@
+use syntree_layout::{DotDrawer, EmbeddedNode};
+
+fn embedding() -> Vec<EmbeddedNode> {
+    vec![
+        EmbeddedNode {
+            text: "root".to_string(),
+            ord: 0,
+            ..Default::default()
+        },
+        EmbeddedNode {
+            text: "leaf".to_string(),
+            ord: 1,
+            parent: Some(0),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn graph_attribute_keys() {
+    let dot = DotDrawer::new()
+        .with_graph_attribute("rankdir", "LR")
+        .with_graph_attribute("label loc", "t")
+        .with_graph_attribute("a\"b", "c")
+        .with_graph_attribute("2d", "yes")
+        .with_graph_attribute("", "empty")
+        .render_string(&embedding())
+        .unwrap();
+    // Plain names stay unquoted, all other keys are quoted and escaped
+    assert!(dot.contains("\n    rankdir=\"LR\";\n"));
+    assert!(dot.contains("\n    \"label loc\"=\"t\";\n"));
+    assert!(dot.contains("\n    \"a\\\"b\"=\"c\";\n"));
+    assert!(dot.contains("\n    \"2d\"=\"yes\";\n"));
+    assert!(dot.contains("\n    \"\"=\"empty\";\n"));
+    assert!(dot.contains("    n0 -> n1;\n"));
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "egui")]
+
+use std::fmt;
+
+use egui::{CentralPanel, Context, Event, Modifiers, PointerButton, Pos2, RawInput, Rect, Vec2};
+use syntree::Builder;
+use syntree_layout::{EmbeddedNode, Layouter, SvgDrawer, TreeView, Visualize};
+
+#[derive(Copy, Clone, Debug)]
+struct MyNodeData(i32);
+
+impl Visualize for MyNodeData {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Runs one frame of a 400x300 screen showing the tree view and returns the area of the view,
+/// the hovered and the clicked node
+fn frame(
+    ctx: &Context,
+    embedding: &[EmbeddedNode],
+    events: Vec<Event>,
+) -> (Rect, Option<usize>, Option<usize>) {
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(400.0, 300.0))),
+        events,
+        ..Default::default()
+    };
+    let mut result = (Rect::NOTHING, None, None);
+    let _ = ctx.run(input, |ctx| {
+        CentralPanel::default().show(ctx, |ui| {
+            let output = TreeView::new(embedding).show(ui);
+            result = (output.response.rect, output.hovered, output.clicked);
+        });
+    });
+    result
+}
+
+#[test]
+fn tree_view_hit_testing() {
+    //      0
+    //     / \
+    //    1   2
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+
+    // The tree is smaller than the screen and is centered in the panel
+    let resolved = SvgDrawer::new().resolve(embedding);
+    let ctx = Context::default();
+    let (view, hovered, clicked) = frame(&ctx, embedding, Vec::new());
+    assert_eq!((None, None), (hovered, clicked));
+    let offset = (view.size() - Vec2::new(resolved.width(), resolved.height())) / 2.0;
+    let (x, y) = resolved[2].center();
+    let pos = view.min + offset + Vec2::new(x, y);
+
+    let (_, hovered, _) = frame(&ctx, embedding, vec![Event::PointerMoved(pos)]);
+    assert_eq!(Some(2), hovered);
+    let button = |pressed| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::NONE,
+    };
+    frame(&ctx, embedding, vec![button(true)]);
+    let (_, hovered, clicked) = frame(&ctx, embedding, vec![button(false)]);
+    assert_eq!((Some(2), Some(2)), (hovered, clicked));
+}
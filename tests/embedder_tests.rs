@@ -1,9 +1,15 @@
 use std::fmt;
+use std::time::Instant;
 
-use syntree::{Builder, Tree};
-use syntree_layout::{Layouter, Visualize};
+use syntree::{Builder, FlavorDefault, Span, Tree};
+use syntree_layout::{
+    from_text, Canvas, ColorClass, Diagnostic, DiagnosticLevel, DotDrawer, DrawerRegistry,
+    EmbeddedNode, EmbeddingBuilder, ErrorKind, FilterMode, HtmlDrawer, LayoutConfig, Layouter,
+    LayouterError, LeafAlignment, OverwritePolicy, Profile, Spacing, SpanIssue, SvgDrawer,
+    TextLabel, Visualize, WriteOptions,
+};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct MyNodeData(i32);
 
 impl Visualize for MyNodeData {
@@ -114,3 +120,1685 @@ fn more_complex_tree() {
         assert_eq!(2, e.x_extent_children);
     }
 }
+
+#[test]
+fn svg_as_string() {
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let svg = Layouter::new(&tree)
+        .embed_with_visualize()
+        .unwrap()
+        .to_svg_string()
+        .unwrap();
+    assert!(svg.starts_with("<?xml"));
+    assert_eq!(2, svg.matches("<text").count());
+    assert_eq!(1, svg.matches("<line").count());
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn collapsed_subtree() {
+    //      0
+    //     / \
+    //    1   2
+    //   / \
+    //  3   4
+    let mut tree = Builder::new();
+
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.token(MyNodeData(4), 1).unwrap();
+    tree.close().unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.close().unwrap();
+
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_collapsed(|value| value.0 == 1)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(3, embedding.len());
+    assert_eq!(
+        vec![0, 1, 2],
+        embedding.iter().map(|e| e.ord).collect::<Vec<_>>()
+    );
+    assert_eq!(3, embedding[1].collapsed);
+    assert_eq!(Some(0), embedding[1].parent);
+    assert_eq!("2", embedding[2].text);
+    assert_eq!(0, embedding[2].collapsed);
+    assert_eq!(Some(0), embedding[2].parent);
+    // The collapsed stub is a leaf of the embedding
+    assert_eq!(
+        vec![2, 0, 0],
+        embedding.iter().map(|e| e.child_count).collect::<Vec<_>>()
+    );
+    assert!(embedding[1].is_leaf());
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    assert_eq!(
+        vec![false, false, true, true, true],
+        layouter
+            .embedding()
+            .iter()
+            .map(|e| e.is_leaf())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn limited_children() {
+    //        0
+    //   / /  |  \
+    //  1  2  3  4
+    // / \    |
+    // 5 6    7
+    let mut tree = Builder::new();
+
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(5), 1).unwrap();
+    tree.token(MyNodeData(6), 1).unwrap();
+    tree.close().unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.open(MyNodeData(3)).unwrap();
+    tree.token(MyNodeData(7), 1).unwrap();
+    tree.close().unwrap();
+    tree.token(MyNodeData(4), 1).unwrap();
+    tree.close().unwrap();
+
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_max_children(2)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(
+        vec!["0", "1", "5", "6", "2", "\u{2026} 2 more"],
+        embedding
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(Some(0), embedding[5].parent);
+    assert_eq!(3, embedding[5].collapsed);
+    assert_eq!(3..5, embedding[5].span);
+    assert!(embedding[5].is_leaf());
+
+    let layouter = Layouter::new(&tree)
+        .with_max_children(1)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(
+        vec!["0", "1", "5", "\u{2026} 1 more", "\u{2026} 3 more"],
+        embedding
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![None, Some(0), Some(1), Some(1), Some(0)],
+        embedding.iter().map(|e| e.parent).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![0, 0, 0, 1, 4],
+        embedding.iter().map(|e| e.collapsed).collect::<Vec<_>>()
+    );
+
+    // Nothing changes if all children fit
+    let limited = Layouter::new(&tree)
+        .with_max_children(4)
+        .embed_with_visualize()
+        .unwrap();
+    let full = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let positions = |embedding: &[EmbeddedNode]| {
+        embedding
+            .iter()
+            .map(|e| (e.text.clone(), e.x_center, e.y_order))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(positions(full.embedding()), positions(limited.embedding()));
+}
+
+#[test]
+fn builder_matches_layouter() {
+    //      0
+    //     / \
+    //    1   2
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+
+    let mut builder = EmbeddingBuilder::new();
+    for e in layouter.embedding().iter() {
+        let ord = builder.add_node(e.parent, e.text.clone()).unwrap();
+        builder.set_x_center(ord, e.x_center).unwrap();
+    }
+    let embedding = builder.build().unwrap();
+
+    for (expected, built) in layouter.embedding().iter().zip(embedding.iter()) {
+        assert_eq!(expected.ord, built.ord);
+        assert_eq!(expected.y_order, built.y_order);
+        assert_eq!(expected.x_extent, built.x_extent);
+        assert_eq!(expected.x_extent_children, built.x_extent_children);
+    }
+}
+
+#[test]
+fn builder_rejects_invalid_structure() {
+    let mut builder = EmbeddingBuilder::new();
+    let err = builder.add_node(Some(0), "orphan").unwrap_err();
+    assert!(matches!(err, LayouterError::NodeLookupFailed { ord: 0 }));
+    builder.add_node(None, "root").unwrap();
+    let err = builder.add_node(None, "second root").unwrap_err();
+    assert!(matches!(err, LayouterError::MultipleRoots { count: 2 }));
+    assert_eq!(
+        ErrorKind::NodeLookupFailed,
+        builder.set_x_center(1, 0).unwrap_err().kind()
+    );
+}
+
+#[test]
+fn error_kinds() {
+    let mut tree = Builder::new();
+    tree.token(MyNodeData(0), 1).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    let tree = tree.build().unwrap();
+    let err = Layouter::new(&tree).embed_with_visualize().err().unwrap();
+    assert!(matches!(err, LayouterError::MultipleRoots { count: 3 }));
+    assert_eq!(
+        "Currently we support only one root, found 3",
+        err.to_string()
+    );
+
+    let mut tree = Builder::new();
+    tree.token(MyNodeData(0), 1).unwrap();
+    let tree = tree.build().unwrap();
+    let err = Layouter::new(&tree)
+        .embed_with_visualize()
+        .unwrap()
+        .write()
+        .unwrap_err();
+    assert_eq!(ErrorKind::MissingFilePath, err.kind());
+}
+
+#[test]
+fn compact_layout() {
+    //        0
+    //      /   \
+    //   1000    2
+    //    |      |
+    //    3    20000
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1000)).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.close().unwrap();
+    tree.open(MyNodeData(2)).unwrap();
+    tree.token(MyNodeData(20000), 1).unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let wide = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let compact = Layouter::new(&tree)
+        .with_compact(true)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = compact.embedding();
+    assert_eq!(11, wide.embedding()[0].x_extent_children);
+    assert_eq!(9, embedding[0].x_extent_children);
+
+    // Nodes on the same level must not overlap
+    for a in embedding.iter() {
+        for b in embedding
+            .iter()
+            .filter(|b| b.ord > a.ord && b.y_order == a.y_order)
+        {
+            let (left, right) = if a.x_center < b.x_center {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            assert!(left.x_center + left.x_extent / 2 <= right.x_center - right.x_extent / 2);
+        }
+    }
+    // Parents are centered above their children
+    assert_eq!(
+        embedding[0].x_center,
+        (embedding[1].x_center + embedding[3].x_center) / 2
+    );
+}
+
+#[test]
+fn chain_collapsing() {
+    //      0
+    //      |
+    //      1
+    //      |
+    //      2
+    //     / \
+    //    3   4
+    //    |
+    //    5
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.open(MyNodeData(2)).unwrap();
+    tree.open(MyNodeData(3)).unwrap();
+    tree.token(MyNodeData(5), 1).unwrap();
+    tree.close().unwrap();
+    tree.token(MyNodeData(4), 1).unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_chain_collapsing(true)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let texts = embedding
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect::<Vec<_>>();
+    // The chain ends above the token 5
+    assert_eq!(vec!["0 \u{2192} \u{2026} \u{2192} 2", "3", "5", "4"], texts);
+    let parents = embedding.iter().map(|e| e.parent).collect::<Vec<_>>();
+    assert_eq!(vec![None, Some(0), Some(1), Some(0)], parents);
+    let y_orders = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+    assert_eq!(vec![0, 1, 2, 1], y_orders);
+    assert_eq!(embedding[0].x_extent, embedding[0].x_extent_children);
+    // The children are centered below the merged node
+    let x_centers = embedding.iter().map(|e| e.x_center).collect::<Vec<_>>();
+    assert_eq!(vec![5, 4, 4, 6], x_centers);
+}
+
+fn tree_with_group() -> Tree<MyNodeData, syntree::FlavorDefault> {
+    //      0
+    //     / \
+    //    1   2
+    //   / \
+    //  3   4
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.token(MyNodeData(4), 1).unwrap();
+    tree.close().unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.close().unwrap();
+    tree.build().unwrap()
+}
+
+#[test]
+fn filter_drops_subtrees() {
+    let tree = tree_with_group();
+    let layouter = Layouter::new(&tree)
+        .with_filter(|node| node.value().0 == 1, FilterMode::DropSubtree)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let texts = embedding
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["0", "2"], texts);
+    assert_eq!(2, embedding[0].x_extent_children);
+}
+
+#[test]
+fn filter_splices_children() {
+    let tree = tree_with_group();
+    let layouter = Layouter::new(&tree)
+        .with_filter(|node| node.value().0 == 1, FilterMode::Splice)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let texts = embedding
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["0", "3", "4", "2"], texts);
+    let parents = embedding.iter().map(|e| e.parent).collect::<Vec<_>>();
+    assert_eq!(vec![None, Some(0), Some(0), Some(0)], parents);
+    let y_orders = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+    assert_eq!(vec![0, 1, 1, 1], y_orders);
+    let x_centers = embedding.iter().map(|e| e.x_center).collect::<Vec<_>>();
+    assert_eq!(vec![3, 1, 3, 5], x_centers);
+
+    // Splicing the root leaves two roots
+    assert!(Layouter::new(&tree)
+        .with_filter(|node| node.value().0 == 0, FilterMode::Splice)
+        .embed_with_visualize()
+        .is_err());
+}
+
+#[test]
+fn subtree_embedding() {
+    let tree = tree_with_group();
+    let group = tree.first().and_then(|root| root.first()).unwrap();
+    let layouter = Layouter::new(&tree)
+        .with_subtree(group.id())
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let texts = embedding
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["1", "3", "4"], texts);
+    let y_orders = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+    assert_eq!(vec![0, 1, 1], y_orders);
+    assert_eq!(4, embedding[0].x_extent_children);
+
+    // The id of the token 2 doesn't exist in a tree with a single node
+    let token = tree.first().and_then(|root| root.last()).unwrap();
+    let mut single = Builder::new();
+    single.token(MyNodeData(0), 1).unwrap();
+    let single = single.build().unwrap();
+    let err = Layouter::new(&single)
+        .with_subtree(token.id())
+        .embed_with_visualize()
+        .err()
+        .unwrap();
+    assert_eq!(ErrorKind::SubtreeRootNotFound, err.kind());
+    assert!(matches!(
+        err,
+        LayouterError::SubtreeRootNotFound { node_id } if node_id == token.id().get()
+    ));
+}
+
+#[test]
+fn expandable_aggregation() {
+    //        0
+    //     /  |  \
+    //    1   2   3
+    //   / \
+    //  4   5
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(4), 1).unwrap();
+    tree.token(MyNodeData(5), 1).unwrap();
+    tree.close().unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_aggregation(
+            |value: &MyNodeData| value.0 < 3,
+            |_, count| format!("{} items", count),
+            true,
+        )
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let texts = embedding
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["0", "2 items", "1", "4", "5", "2", "3"], texts);
+    let parents = embedding.iter().map(|e| e.parent).collect::<Vec<_>>();
+    assert_eq!(
+        vec![None, Some(0), Some(1), Some(2), Some(2), Some(1), Some(0)],
+        parents
+    );
+    let y_orders = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+    assert_eq!(vec![0, 1, 2, 3, 3, 2, 1], y_orders);
+    assert_eq!(2, embedding[1].aggregated);
+
+    // The aggregate node is initially folded in interactive output
+    let html = HtmlDrawer::new().render_string(embedding).unwrap();
+    assert!(html.contains("const aggregates = [1];"));
+}
+
+#[test]
+fn embedding_navigation() {
+    let tree = tree_with_group();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    let texts = |nodes: Vec<&syntree_layout::EmbeddedNode>| {
+        nodes.iter().map(|e| e.text.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!("0", embedding.root().unwrap().text);
+    assert_eq!(vec!["1", "2"], texts(embedding.children(0).collect()));
+    assert_eq!(vec!["3", "4"], texts(embedding.children(1).collect()));
+    assert_eq!(vec!["3", "4", "2"], texts(embedding.leaves().collect()));
+    assert_eq!("1", embedding.parent(2).unwrap().text);
+    assert!(embedding.parent(0).is_none());
+    assert!(embedding.node(5).is_none());
+}
+
+#[test]
+fn embedding_traversals() {
+    let tree = tree_with_group();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    let texts =
+        |nodes: Vec<&EmbeddedNode>| nodes.iter().map(|e| e.text.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        vec!["0", "1", "3", "4", "2"],
+        texts(embedding.pre_order().collect())
+    );
+    assert_eq!(
+        vec!["3", "4", "1", "2", "0"],
+        texts(embedding.post_order().collect())
+    );
+
+    // The visitor gets the children in the drawn order, also if the siblings are reordered
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.set_cursor(6);
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(3), 2).unwrap();
+    tree.close().unwrap();
+    tree.token_with(MyNodeData(2), Span::new(0, 2)).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree)
+        .with_span_order(true)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let mut visited = Vec::new();
+    embedding.visit(|node, children| {
+        for child in children {
+            assert_eq!(Some(node.ord), embedding[*child].parent);
+        }
+        visited.push((node.text.clone(), children.to_vec()));
+    });
+    assert_eq!(
+        vec![
+            ("0".to_string(), vec![3, 1]),
+            ("2".to_string(), vec![]),
+            ("1".to_string(), vec![2]),
+            ("3".to_string(), vec![]),
+        ],
+        visited
+    );
+    assert_eq!(
+        vec!["2", "3", "1", "0"],
+        texts(embedding.post_order().collect())
+    );
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Label(&'static str);
+
+impl Visualize for Label {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn unicode_width() {
+    let mut tree = Builder::new();
+    tree.open(Label("ab")).unwrap();
+    tree.token(Label("日本"), 1).unwrap();
+    tree.token(Label("e\u{301}"), 1).unwrap();
+    tree.token(Label("über"), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let x_extents = layouter
+        .embedding()
+        .iter()
+        .map(|e| e.x_extent)
+        .collect::<Vec<_>>();
+    // Wide characters count twice, combining characters don't count
+    assert_eq!(vec![3, 5, 2, 5], x_extents);
+}
+
+#[test]
+fn subtree_gaps() {
+    let tree = tree_with_group();
+    let spacing = Spacing {
+        subtree_gap: 3,
+        ..Spacing::new()
+    };
+    let x_centers = |compact| {
+        Layouter::new(&tree)
+            .with_spacing(spacing)
+            .with_compact(compact)
+            .embed_with_visualize()
+            .unwrap()
+            .embedding()
+            .iter()
+            .map(|e| e.x_center)
+            .collect::<Vec<_>>()
+    };
+    // Only the siblings 1 and 2 are separated by the subtree gap
+    assert_eq!(vec![4, 2, 1, 3, 8], x_centers(false));
+    assert_eq!(vec![4, 2, 1, 3, 7], x_centers(true));
+}
+
+#[test]
+fn zero_level_gap() {
+    let tree = tree_with_group();
+    let spacing = Spacing {
+        level_gap: 0,
+        ..Spacing::new()
+    };
+    let layouter = Layouter::new(&tree)
+        .with_spacing(spacing)
+        .embed_with_visualize()
+        .unwrap();
+    let levels = layouter
+        .embedding()
+        .iter()
+        .map(|e| e.y_order)
+        .collect::<Vec<_>>();
+    // The levels don't collapse into one
+    assert_eq!(vec![0, 1, 2, 2, 1], levels);
+}
+
+#[test]
+fn leaves_at_bottom() {
+    let tree = tree_with_group();
+    for compact in [false, true] {
+        let layouter = Layouter::new(&tree)
+            .with_leaf_alignment(LeafAlignment::Bottom)
+            .with_compact(compact)
+            .embed_with_visualize()
+            .unwrap();
+        let embedding = layouter.embedding();
+        let levels = embedding.iter().map(|e| e.y_order).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 2, 2, 2], levels);
+        // The leaves are in source order and don't overlap on the bottom row
+        let bottom_row = embedding
+            .iter()
+            .filter(|e| e.y_order == 2)
+            .map(|e| (e.x_center - e.x_extent / 2, e.x_center + e.x_extent / 2))
+            .collect::<Vec<_>>();
+        assert!(bottom_row.windows(2).all(|w| w[0].1 <= w[1].0));
+    }
+}
+
+#[test]
+fn span_order() {
+    //      0
+    //     / \
+    //    1   2 (0..2)
+    //    |
+    //    3 (6..8)
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.set_cursor(6);
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(3), 2).unwrap();
+    tree.close().unwrap();
+    tree.token_with(MyNodeData(2), Span::new(0, 2)).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    for compact in [false, true] {
+        let layouter = Layouter::new(&tree)
+            .with_span_order(true)
+            .with_compact(compact)
+            .embed_with_visualize()
+            .unwrap();
+        let x_centers = layouter
+            .embedding()
+            .iter()
+            .map(|e| e.x_center)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2, 3, 3, 1], x_centers);
+    }
+}
+
+#[test]
+fn streamed_nodes_match_embedding() {
+    let tree = tree_with_group();
+    let spacing = Spacing {
+        level_gap: 2,
+        sibling_gap: 1,
+        subtree_gap: 3,
+        margin: 2,
+    };
+    let layouter = Layouter::new(&tree).with_spacing(spacing);
+    let mut streamed = Vec::new();
+    layouter
+        .stream_with_visualize(|node| {
+            streamed.push(format!("{node:?}"));
+            Ok(())
+        })
+        .unwrap();
+    let embedded = layouter
+        .embed_with_visualize()
+        .unwrap()
+        .embedding()
+        .iter()
+        .map(|node| format!("{node:?}"))
+        .collect::<Vec<_>>();
+    assert_eq!(embedded, streamed);
+}
+
+#[test]
+fn tree_from_text() {
+    //      0
+    //     / \
+    //    1   2
+    //        |
+    //        3
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 2).unwrap();
+    tree.open(MyNodeData(2)).unwrap();
+    tree.token(MyNodeData(3), 3).unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree).embed_with_debug().unwrap();
+
+    // The dump of the tree is drawn like the tree itself
+    let mut dump = Vec::new();
+    syntree::print::print_with_source(&mut dump, &tree, "ab cde").unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+    let parsed = from_text(&dump).unwrap();
+    let parsed_layouter = Layouter::new(&parsed).embed().unwrap();
+    let nodes = |embedding: &syntree_layout::Embedding| {
+        embedding
+            .iter()
+            .map(|e| (e.text.clone(), e.x_center, e.y_order, e.span.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        nodes(layouter.embedding()),
+        nodes(parsed_layouter.embedding())
+    );
+
+    let parsed = from_text(r#"(root "MyNodeData(1)" (x y))"#).unwrap();
+    let labels = parsed
+        .walk()
+        .map(|node| node.value().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["root", "MyNodeData(1)", "x", "y"], labels);
+    assert_eq!(14, parsed.span().end);
+
+    // Escapes within quotes are resolved, the tokens are as long as their labels in characters
+    let parsed = from_text(r#"(a "x\"y" "\\n\n" "ä€")"#).unwrap();
+    let tokens = parsed
+        .walk()
+        .skip(1)
+        .map(|node| (node.value().to_string(), node.span().len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ("x\"y".to_string(), 3),
+            ("\\n\n".to_string(), 3),
+            ("ä€".to_string(), 2)
+        ],
+        tokens
+    );
+    assert!(from_text(r#"(a "x\")"#).is_err());
+
+    // Deeply nested trees don't exhaust the stack
+    let depth = 20_000;
+    let deep = format!("{}x{}", "(a ".repeat(depth), ")".repeat(depth));
+    assert_eq!(depth + 1, from_text(&deep).unwrap().len());
+
+    assert!(from_text("(a (b c)").is_err());
+    assert!(from_text("a@0..1\n    b@0..1").is_err());
+    assert!(from_text("a without span").is_err());
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Failing(i32);
+
+impl Visualize for Failing {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 < 0 {
+            return Err(fmt::Error);
+        }
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn failing_visualizer() {
+    let mut tree = Builder::new();
+    tree.open(Failing(0)).unwrap();
+    tree.token(Failing(1), 2).unwrap();
+    tree.token(Failing(-1), 3).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    // The error carries the node instead of panicking
+    let expected = (2, 2..5);
+    match Layouter::new(&tree).embed_with_visualize() {
+        Err(LayouterError::NodeError { ord, span, .. }) => assert_eq!(expected, (ord, span)),
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+    match Layouter::new(&tree).stream_with_visualize(|_| Ok(())) {
+        Err(LayouterError::NodeError { ord, span, .. }) => assert_eq!(expected, (ord, span)),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    let err = Layouter::new(&tree)
+        .with_chain_collapsing(true)
+        .try_embed_with(
+            |value| {
+                (value.0 >= 0)
+                    .then(|| value.0.to_string())
+                    .ok_or_else(|| LayouterError::from_description("negative"))
+            },
+            |_| false,
+        )
+        .err()
+        .unwrap();
+    assert_eq!("Error at node 2 with span 2..5: negative", err.to_string());
+}
+
+#[test]
+fn invalid_source_slices() {
+    #[derive(Copy, Clone, Debug)]
+    struct Kind(&'static str);
+
+    impl fmt::Display for Kind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    let mut tree = Builder::new();
+    tree.open(Kind("word")).unwrap();
+    tree.token(Kind("a"), 1).unwrap();
+    tree.token(Kind("b"), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    // The second token ends inside of the two byte wide 'ä'
+    let source = "aä";
+    match Layouter::new(&tree).embed_with_source_and_display(source) {
+        Err(LayouterError::SourceSliceOutOfBounds { span }) => assert_eq!(1..2, span),
+        other => panic!("Unexpected result {:?}", other.err()),
+    }
+    let layouter = Layouter::new(&tree)
+        .with_source_placeholder("<invalid>")
+        .embed_with_source_and_display(source)
+        .unwrap();
+    let texts = layouter
+        .embedding()
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["word", "'a'", "'<invalid>'"], texts);
+}
+
+#[test]
+fn visualize_with_source() {
+    #[derive(Copy, Clone, Debug)]
+    struct Token(bool);
+
+    impl Visualize for Token {
+        fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "token")
+        }
+
+        fn visualize_with_source(&self, source: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", source)
+        }
+
+        fn emphasize(&self) -> bool {
+            self.0
+        }
+    }
+
+    let mut tree = Builder::new();
+    tree.open(Token(false)).unwrap();
+    tree.token(Token(true), 2).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree)
+        .embed_with_visualize_and_source("ab")
+        .unwrap();
+    let nodes = layouter
+        .embedding()
+        .iter()
+        .map(|e| (e.text.as_str(), e.is_emphasized))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![("\"ab\"", false), ("\"ab\"", true)], nodes);
+
+    // The default implementation ignores the source
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 2).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree)
+        .embed_with_visualize_and_source("ab")
+        .unwrap();
+    assert_eq!("1", layouter.embedding()[1].text);
+}
+
+#[test]
+fn node_numbers() {
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    for i in 1..12 {
+        tree.token(MyNodeData(i), 1).unwrap();
+    }
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_node_numbers(true)
+        .with_span_labels(true)
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!("0⁰@0..11", embedding[0].text);
+    assert_eq!("11¹¹@10..11", embedding[11].text);
+    // The superscripts are as wide as the digits
+    assert_eq!(12, embedding[11].x_extent);
+}
+
+#[test]
+fn color_classes() {
+    //    0
+    //  / | \
+    // 1  2  3
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_color_by(|value: &MyNodeData| match value.0 {
+            0 => ColorClass::None,
+            1 => ColorClass::Operator,
+            n => ColorClass::Custom(n as usize),
+        })
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let classes = embedding.iter().map(|e| e.color_class).collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ColorClass::None,
+            ColorClass::Operator,
+            ColorClass::Custom(2),
+            ColorClass::Custom(3)
+        ],
+        classes
+    );
+    assert_eq!(None, embedding[0].style.fill);
+    assert_eq!(
+        ColorClass::Operator.color(),
+        embedding[1].style.fill.as_deref()
+    );
+    assert_ne!(embedding[2].style.fill, embedding[3].style.fill);
+
+    // The colors reach every drawer through the node styles
+    let dot = DotDrawer::new().render_string(embedding).unwrap();
+    assert!(dot.contains(ColorClass::Operator.color().unwrap()));
+    let svg = layouter.to_svg_string().unwrap();
+    assert!(svg.contains(ColorClass::Custom(3).color().unwrap()));
+}
+
+#[test]
+fn diagnostics() {
+    // The spans of the tokens are 0..1, 1..2, 2..4 and 4..5
+    let tree = from_text("(E (T a +) (T bb c))").unwrap();
+    let diagnostics = [
+        Diagnostic::new(2..3, DiagnosticLevel::Warning, "suspicious name"),
+        Diagnostic::new(2..4, DiagnosticLevel::Error, "unknown name"),
+        Diagnostic::new(1..1, DiagnosticLevel::Recovered, "inserted operand"),
+    ];
+    let layouter = Layouter::new(&tree)
+        .with_diagnostics(&diagnostics)
+        .embed()
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(
+        vec!["E", "T", "a", "+", "T", "bb", "c"],
+        embedding
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect::<Vec<_>>()
+    );
+    // Only the tokens are marked, the empty span touches both of its neighbors
+    assert_eq!(
+        vec![
+            DiagnosticLevel::None,
+            DiagnosticLevel::None,
+            DiagnosticLevel::Recovered,
+            DiagnosticLevel::Recovered,
+            DiagnosticLevel::None,
+            DiagnosticLevel::Error,
+            DiagnosticLevel::None,
+        ],
+        embedding.iter().map(|e| e.diagnostic).collect::<Vec<_>>()
+    );
+    assert!(embedding[5].is_emphasized);
+    assert!(!embedding[4].is_emphasized);
+    assert_eq!(
+        Some("suspicious name\nunknown name"),
+        embedding[5].tooltip.as_deref()
+    );
+
+    let svg = SvgDrawer::new()
+        .with_squiggles(true)
+        .render_string(embedding)
+        .unwrap();
+    assert_eq!(3, svg.matches(r#"class="squiggle""#).count());
+    assert!(svg.contains(r#"<path id="squiggle-5""#));
+    let svg = SvgDrawer::new().render_string(embedding).unwrap();
+    assert!(!svg.contains("squiggle"));
+}
+
+#[test]
+fn measured_extents() {
+    //    0
+    //  / | \
+    // 1  2  3
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let default = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let measured = Layouter::new(&tree)
+        .with_measure(|value: &MyNodeData, label: &str| match value.0 {
+            2 => 10,
+            _ => label.len(),
+        })
+        .embed_with_visualize()
+        .unwrap();
+    let (default, measured) = (default.embedding(), measured.embedding());
+    assert_eq!(10, measured[2].x_extent);
+    assert_eq!(1, measured[1].x_extent);
+    // The wider node pushes its siblings away and widens its parent
+    assert!(
+        measured[3].x_center - measured[1].x_center > default[3].x_center - default[1].x_center
+    );
+    assert!(measured[0].x_extent_children > default[0].x_extent_children);
+
+    // Relabeling measures the new label with the same function
+    let relabeled = Layouter::new(&tree)
+        .with_measure(|value: &MyNodeData, label: &str| match value.0 {
+            2 => 10,
+            _ => 2 * label.len(),
+        })
+        .embed_with_visualize()
+        .unwrap()
+        .relabel(3, "333")
+        .unwrap();
+    assert_eq!(6, relabeled.embedding()[3].x_extent);
+}
+
+#[test]
+fn incremental_updates() {
+    type Layout = Vec<(String, Option<usize>, usize, usize, usize)>;
+    fn layout(embedding: &[syntree_layout::EmbeddedNode]) -> Layout {
+        embedding
+            .iter()
+            .map(|e| {
+                let text = e.text.clone();
+                (text, e.parent, e.x_center, e.x_extent_children, e.y_order)
+            })
+            .collect()
+    }
+    fn configure<'a>(
+        layouter: Layouter<'a, TextLabel<'a>, syntree::FlavorDefault, SvgDrawer>,
+        config: usize,
+    ) -> Layouter<'a, TextLabel<'a>, syntree::FlavorDefault, SvgDrawer> {
+        let spacing = Spacing {
+            level_gap: 2,
+            sibling_gap: 1,
+            subtree_gap: 2,
+            margin: 3,
+        };
+        match config {
+            0 => layouter,
+            1 => layouter.with_spacing(spacing),
+            _ => layouter
+                .with_spacing(spacing)
+                .with_leaf_alignment(LeafAlignment::Bottom),
+        }
+    }
+
+    let before = "(r (p a bb) (q c) (s ddd (t e)))";
+    let tree = from_text(before).unwrap();
+    for config in 0..3 {
+        let embed = |text: &str| {
+            let tree = from_text(text).unwrap();
+            let embedding = configure(Layouter::new(&tree), config)
+                .embed()
+                .unwrap()
+                .into_embedding();
+            embedding
+        };
+        let labels = layout(&embed(before))
+            .into_iter()
+            .map(|(text, ..)| text)
+            .collect::<Vec<_>>();
+
+        // Longer and shorter labels on every level
+        for (ord, label) in labels.iter().enumerate() {
+            for new_label in ["a_much_longer_label", "x"] {
+                let updated = configure(Layouter::new(&tree), config)
+                    .embed()
+                    .unwrap()
+                    .relabel(ord, new_label)
+                    .unwrap();
+                let expected = embed(&before.replace(label, new_label));
+                assert_eq!(layout(&expected), layout(updated.embedding()));
+            }
+        }
+
+        // Replaced inner nodes, leaves and the root
+        let edits = [
+            ("(q c)", "(q2 (u v w x) y)"),
+            ("bb", "(bb (g (h i)))"),
+            ("(t e)", "z"),
+            (before, "(n (m o))"),
+        ];
+        for (old, new) in edits {
+            let after = before.replace(old, new);
+            let after_tree = from_text(&after).unwrap();
+            let ord = labels
+                .iter()
+                .position(|label| old.trim_start_matches('(').starts_with(label.as_str()))
+                .unwrap();
+            let new_root = after_tree.walk().nth(ord).map(|node| node.id()).unwrap();
+            let subtree = configure(Layouter::new(&after_tree), config)
+                .with_subtree(new_root)
+                .embed()
+                .unwrap()
+                .into_embedding();
+            let updated = configure(Layouter::new(&tree), config)
+                .embed()
+                .unwrap()
+                .replace_subtree(ord, subtree)
+                .unwrap();
+            assert_eq!(
+                layout(&embed(&after)),
+                layout(updated.embedding()),
+                "{}",
+                after
+            );
+        }
+    }
+
+    let err = Layouter::new(&tree)
+        .with_compact(true)
+        .embed()
+        .unwrap()
+        .relabel(0, "x")
+        .err()
+        .unwrap();
+    assert_eq!(ErrorKind::IncrementalUpdateUnsupported, err.kind());
+}
+
+#[test]
+fn pagination() {
+    // The second function is too wide for a page on its own and is split among its statements
+    let tree = from_text(
+        "(Module (Fn main (Call print hello)) \
+         (Fn long (Let first (Add one two)) (Let second (Add three four)) (Let third five)) \
+         (Fn tail))",
+    )
+    .unwrap();
+    let layouter = Layouter::new(&tree).embed().unwrap();
+    let max_width = 30;
+    assert!(layouter.stats().width > 2 * max_width);
+    let pages = layouter.embedding().paginate(max_width);
+
+    let mut leaves = Vec::new();
+    for page in &pages {
+        assert!(page.stats().width <= max_width);
+        assert_eq!("Module", page[0].text);
+        for (ord, node) in page.iter().enumerate() {
+            assert_eq!(ord, node.ord);
+            assert!(node.parent.is_none_or(|parent| parent < ord));
+            assert_eq!(node.child_count, page.children(ord).count());
+        }
+        // The children are placed below their parents from left to right
+        for node in page.iter().skip(1) {
+            let parent = page.parent(node.ord).unwrap();
+            assert!(node.y_order > parent.y_order);
+        }
+        leaves.extend(page.leaves().map(|e| e.text.clone()));
+    }
+    // Each leaf is drawn on exactly one page
+    let expected = layouter
+        .embedding()
+        .leaves()
+        .map(|e| e.text.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(expected, leaves);
+    // The split function is repeated as a stub above its remaining statements
+    assert_eq!(4, pages.len());
+    let texts = |page: &syntree_layout::Embedding| {
+        page.iter()
+            .take(3)
+            .map(|e| e.text.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec!["Module", "Fn", "long"], texts(&pages[1]));
+    assert_eq!(vec!["Module", "Fn", "Let"], texts(&pages[2]));
+
+    // The pages are written into numbered files
+    let file_name = std::env::temp_dir().join("syntree_layout_pages.svg");
+    let paths = Layouter::new(&tree)
+        .with_file_path(&file_name)
+        .embed()
+        .unwrap()
+        .write_pages(max_width)
+        .unwrap();
+    assert_eq!(pages.len(), paths.len());
+    for (number, path) in paths.iter().enumerate() {
+        let name = format!("syntree_layout_pages_{}.svg", number + 1);
+        assert!(path.ends_with(&name));
+        assert!(std::fs::read_to_string(path)
+            .unwrap()
+            .contains(">Module</text>"));
+    }
+}
+
+#[test]
+fn write_options() {
+    let dir = std::path::Path::new("target/tmp/write_options_test");
+    let _ = std::fs::remove_dir_all(dir);
+    let path = dir.join("nested/tree.svg");
+    let mut tree = Builder::new();
+    tree.token(MyNodeData(0), 1).unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree)
+        .with_file_path(&path)
+        .embed_with_visualize()
+        .unwrap();
+
+    // Missing directories are only created on request
+    let err = layouter.write().unwrap_err();
+    assert_eq!(ErrorKind::Io, err.kind());
+    let layouter = layouter.with_write_options(WriteOptions::new().with_create_dirs(true));
+    layouter.write().unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+
+    let layouter =
+        layouter.with_write_options(WriteOptions::new().with_overwrite(OverwritePolicy::Fail));
+    let err = layouter.write().unwrap_err();
+    assert!(matches!(&err, LayouterError::OutputFileExists { path: p } if *p == path));
+
+    std::fs::write(&path, "old").unwrap();
+    let layouter =
+        layouter.with_write_options(WriteOptions::new().with_overwrite(OverwritePolicy::Backup));
+    layouter.write().unwrap();
+    assert_eq!(written, std::fs::read_to_string(&path).unwrap());
+    let backup = dir.join("nested/tree.svg.bak");
+    assert_eq!("old", std::fs::read_to_string(&backup).unwrap());
+    // An older backup is replaced
+    layouter.write().unwrap();
+    assert_eq!(written, std::fs::read_to_string(&backup).unwrap());
+
+    // A failing drawer leaves the existing file in place
+    struct Failing;
+
+    impl syntree_layout::Drawer for Failing {
+        fn draw(&self, _ctx: &syntree_layout::DrawContext<'_>) -> syntree_layout::Result<()> {
+            Err(LayouterError::from_description("interrupted"))
+        }
+    }
+
+    std::fs::write(&path, "old").unwrap();
+    layouter.with_drawer(&Failing).write().unwrap_err();
+    assert_eq!("old", std::fs::read_to_string(&path).unwrap());
+    assert_eq!("old", std::fs::read_to_string(&backup).unwrap());
+}
+
+#[test]
+fn drawer_registry() {
+    assert_eq!(
+        DrawerRegistry::default().extensions(),
+        DrawerRegistry::new().extensions()
+    );
+    assert!(DrawerRegistry::empty().extensions().is_empty());
+
+    let mut tree = Builder::new();
+    tree.token(MyNodeData(0), 1).unwrap();
+    let tree = tree.build().unwrap();
+    let err = Layouter::new(&tree)
+        .with_file_path("target/tmp/registry_test.bmp")
+        .embed_with_visualize()
+        .unwrap()
+        .write_with_registry(&DrawerRegistry::new())
+        .unwrap_err();
+    assert_eq!(ErrorKind::NoDrawerForExtension, err.kind());
+    assert!(matches!(
+        &err,
+        LayouterError::NoDrawerForExtension { extension } if extension == "bmp"
+    ));
+}
+
+#[test]
+fn atomic_writes() {
+    struct Failing;
+
+    impl syntree_layout::Drawer for Failing {
+        fn draw(&self, ctx: &syntree_layout::DrawContext<'_>) -> syntree_layout::Result<()> {
+            std::fs::write(ctx.file_name(), "partial").unwrap();
+            Err(LayouterError::from_description("interrupted"))
+        }
+    }
+
+    let dir = std::path::Path::new("target/tmp/atomic_writes_test");
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+    let path = dir.join("tree.svg");
+    let mut tree = Builder::new();
+    tree.token(MyNodeData(0), 1).unwrap();
+    let tree = tree.build().unwrap();
+    let drawer = SvgDrawer::new().with_image_map(true);
+    let layouter = Layouter::new(&tree)
+        .with_file_path(&path)
+        .with_drawer(&drawer)
+        .with_write_options(WriteOptions::new().with_atomic(true))
+        .embed_with_visualize()
+        .unwrap();
+    layouter.write().unwrap();
+    // The additional files of the drawer are moved into place, too
+    let mut files = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(vec!["tree.map.json", "tree.svg"], files);
+    let written = std::fs::read_to_string(&path).unwrap();
+
+    // A failing drawer leaves the existing file untouched
+    let layouter = layouter.with_drawer(&Failing);
+    let err = layouter.write().unwrap_err();
+    assert_eq!("Error occurred: interrupted", err.to_string());
+    assert_eq!(written, std::fs::read_to_string(&path).unwrap());
+    assert_eq!(2, std::fs::read_dir(dir).unwrap().count());
+
+    // The backup is kept next to the target, which is only replaced by a complete file
+    let layouter = layouter.with_write_options(
+        WriteOptions::new()
+            .with_atomic(true)
+            .with_overwrite(OverwritePolicy::Backup),
+    );
+    std::fs::write(&path, "old").unwrap();
+    layouter.write().unwrap_err();
+    assert_eq!("old", std::fs::read_to_string(&path).unwrap());
+    let backup = dir.join("tree.svg.bak");
+    assert_eq!("old", std::fs::read_to_string(&backup).unwrap());
+    layouter.with_drawer(&drawer).write().unwrap();
+    assert_eq!(written, std::fs::read_to_string(&path).unwrap());
+    assert_eq!("old", std::fs::read_to_string(&backup).unwrap());
+
+    // Composed and compared trees are written with the write options, too
+    let options = WriteOptions::new().with_create_dirs(true).with_atomic(true);
+    Canvas::new()
+        .with_write_options(options)
+        .add(Layouter::new(&tree).embed_with_visualize().unwrap())
+        .write(dir.join("canvas/tree.svg"))
+        .unwrap();
+    // The temporary files need the name of the output file
+    let err = Canvas::new()
+        .with_write_options(options)
+        .write(dir.join(".."))
+        .unwrap_err();
+    assert_eq!(ErrorKind::MissingFileName, err.kind());
+    assert!(dir.join("canvas/tree.svg").exists());
+    Layouter::diff(&tree, &tree)
+        .unwrap()
+        .with_write_options(options)
+        .draw(
+            &SvgDrawer::new(),
+            &dir.join("diff/before.svg"),
+            &dir.join("diff/after.svg"),
+        )
+        .unwrap();
+    assert_eq!(2, std::fs::read_dir(dir.join("diff")).unwrap().count());
+}
+
+#[test]
+fn span_coverage() {
+    // The tokenizer dropped the `+`, produced overlapping tokens around `c` and nothing for `;`
+    let source = "ab + cd;\n";
+    let mut builder = Builder::new();
+    builder.open(MyNodeData(0)).unwrap();
+    builder.open(MyNodeData(1)).unwrap();
+    builder.token(MyNodeData(2), 2).unwrap();
+    builder.token(MyNodeData(3), 1).unwrap();
+    builder.set_cursor(4);
+    builder.token(MyNodeData(4), 2).unwrap();
+    builder.close().unwrap();
+    builder.set_cursor(5);
+    builder.token(MyNodeData(5), 2).unwrap();
+    builder.close().unwrap();
+    let tree = builder.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_source(source).unwrap();
+    assert_eq!(
+        vec![
+            SpanIssue::Gap { span: 3..4 },
+            SpanIssue::Overlap {
+                span: 5..6,
+                first: 4,
+                second: 5
+            },
+            SpanIssue::Gap { span: 7..9 },
+        ],
+        layouter.embedding().span_issues(source)
+    );
+    assert!(!layouter.embedding().iter().any(|node| node.is_gap));
+
+    let layouter = Layouter::new(&tree)
+        .with_span_gaps(true)
+        .embed_with_source(source)
+        .unwrap();
+    let embedding = layouter.embedding();
+    let gaps = embedding
+        .iter()
+        .filter(|node| node.is_gap)
+        .map(|node| (node.text.as_str(), node.span.clone(), node.parent))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (r#"missing "+""#, 3..4, Some(1)),
+            (r#"missing ";\n""#, 7..9, Some(0)),
+        ],
+        gaps
+    );
+    // The pseudonodes are placed between their neighbors
+    let mut by_x = embedding.leaves().collect::<Vec<_>>();
+    by_x.sort_by_key(|node| node.x_center);
+    assert_eq!(
+        vec!["ab", " ", r#"missing "+""#, " c", "cd", r#"missing ";\n""#],
+        by_x.iter()
+            .map(|node| node.text.as_str())
+            .collect::<Vec<_>>()
+    );
+    // The pseudonodes don't hide the issues
+    assert_eq!(3, embedding.span_issues(source).len());
+
+    let svg = SvgDrawer::new().render_string(embedding).unwrap();
+    assert!(svg.contains(r#"<pattern id="hatch""#));
+    assert_eq!(2, svg.matches(r#"fill="url(#hatch)""#).count());
+
+    // The text of dropped nodes isn't missing
+    let gaps = |layouter: &Layouter<'_, MyNodeData, _, _>| {
+        layouter
+            .embedding()
+            .iter()
+            .filter(|node| node.is_gap)
+            .map(|node| node.span.clone())
+            .collect::<Vec<_>>()
+    };
+    let layouter = Layouter::new(&tree)
+        .with_span_gaps(true)
+        .with_filter(|node| node.value().0 == 1, FilterMode::DropSubtree)
+        .embed_with_source(source)
+        .unwrap();
+    assert_eq!(vec![3..4, 7..9], gaps(&layouter));
+    let layouter = Layouter::new(&tree)
+        .with_span_gaps(true)
+        .with_filter(|node| node.value().0 == 3, FilterMode::DropSubtree)
+        .embed_with_source(source)
+        .unwrap();
+    assert_eq!(vec![3..4, 7..9], gaps(&layouter));
+    // Neither is the text outside of the embedded subtree
+    let inner = tree.first().unwrap().first().unwrap().id();
+    let layouter = Layouter::new(&tree)
+        .with_span_gaps(true)
+        .with_subtree(inner)
+        .embed_with_source(source)
+        .unwrap();
+    assert_eq!(vec![3..4], gaps(&layouter));
+}
+
+#[test]
+fn span_coverage_multibyte() {
+    // A token ends inside `ü` and the last one lies behind the end of the source
+    let source = "é  ü";
+    let mut builder = Builder::new();
+    builder.open(MyNodeData(0)).unwrap();
+    builder.token(MyNodeData(1), 2).unwrap();
+    builder.set_cursor(3);
+    builder.token(MyNodeData(2), 1).unwrap();
+    builder.token(MyNodeData(3), 1).unwrap();
+    builder.set_cursor(7);
+    builder.token(MyNodeData(4), 1).unwrap();
+    builder.close().unwrap();
+    let tree = builder.build().unwrap();
+
+    // Only the whitespace between `é` and the space token is skipped
+    let layouter = Layouter::new(&tree)
+        .with_source_placeholder("?")
+        .embed_with_source(source)
+        .unwrap();
+    assert_eq!(
+        vec![SpanIssue::Gap { span: 5..7 }],
+        layouter.embedding().span_issues(source)
+    );
+
+    let layouter = Layouter::new(&tree)
+        .with_source_placeholder("?")
+        .with_span_gaps(true)
+        .embed_with_source(source)
+        .unwrap();
+    let gaps = layouter
+        .embedding()
+        .iter()
+        .filter(|node| node.is_gap)
+        .map(|node| (node.text.as_str(), node.span.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![("missing \"\u{fffd}\"", 5..7)], gaps);
+}
+
+#[test]
+fn bounds_before_drawing() {
+    let mut builder = Builder::new();
+    builder.open(MyNodeData(0)).unwrap();
+    builder.open(MyNodeData(1)).unwrap();
+    builder.token(MyNodeData(3), 1).unwrap();
+    builder.close().unwrap();
+    builder.token(MyNodeData(2), 1).unwrap();
+    builder.close().unwrap();
+    let tree = builder.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let stats = layouter.stats();
+    assert_eq!((stats.width, 3), layouter.bounds());
+
+    // The projected size is the size of the written document
+    let drawer = SvgDrawer::new().with_layer_guides(true);
+    let layouter = Layouter::new(&tree)
+        .with_drawer(&drawer)
+        .embed_with_visualize()
+        .unwrap();
+    let (width, height) = layouter.pixel_size();
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+    assert!(svg.contains(&format!(r#"width="{}" height="{}""#, width, height)));
+
+    // The levels spread by a wider level gap are counted once
+    let layouter = Layouter::new(&tree)
+        .with_drawer(&drawer)
+        .with_spacing(Spacing {
+            level_gap: 2,
+            ..Spacing::new()
+        })
+        .embed_with_visualize()
+        .unwrap();
+    assert_eq!(
+        vec![0, 2, 4, 2],
+        layouter
+            .embedding()
+            .iter()
+            .map(|e| e.y_order)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!((stats.width, 3), layouter.bounds());
+    let (width, spread_height) = layouter.pixel_size();
+    assert!(spread_height > height);
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+    assert!(svg.contains(&format!(r#"width="{}" height="{}""#, width, spread_height)));
+}
+
+#[test]
+fn layout_config() {
+    let config = LayoutConfig::new()
+        .with_node_numbers(true)
+        .with_collapsed(|value: &MyNodeData| value.0 == 1);
+
+    for case in [1, 2] {
+        let mut builder = Builder::new();
+        builder.open(MyNodeData(0)).unwrap();
+        builder.open(MyNodeData(case)).unwrap();
+        builder.token(MyNodeData(3), 1).unwrap();
+        builder.close().unwrap();
+        builder.close().unwrap();
+        let tree = builder.build().unwrap();
+
+        let configured = config.layout(&tree).embed_with_visualize().unwrap();
+        let chained = Layouter::new(&tree)
+            .with_node_numbers(true)
+            .with_collapsed(|value: &MyNodeData| value.0 == 1)
+            .embed_with_visualize()
+            .unwrap();
+        let nodes = |layouter: &Layouter<'_, MyNodeData, _, _>| {
+            layouter
+                .embedding()
+                .iter()
+                .map(|node| (node.text.clone(), node.x_center, node.y_order))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(nodes(&chained), nodes(&configured));
+        // The collapsed node hides its child
+        assert_eq!(if case == 1 { 2 } else { 3 }, configured.embedding().len());
+    }
+
+    let config = LayoutConfig::new().with_error_nodes_by_label();
+    let mut builder = Builder::new();
+    builder.open(Label("stmt")).unwrap();
+    builder.token(Label("ERROR"), 1).unwrap();
+    builder.token(Label("terror"), 1).unwrap();
+    builder.close().unwrap();
+    let tree = builder.build().unwrap();
+    let layouter = config.layout(&tree).embed_with_visualize().unwrap();
+    let errors = layouter
+        .embedding()
+        .errors()
+        .map(|e| e.ord)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![1], errors);
+}
+
+#[test]
+fn profiles() {
+    let tree =
+        from_text(r#"(Call (Path std mem replace) (Args (Ref "a very long target") (Lit 0)))"#)
+            .unwrap();
+    let embed = |profile| {
+        Layouter::new(&tree)
+            .with_profile(profile)
+            .embed()
+            .unwrap()
+            .into_embedding()
+    };
+    let classic = embed(Profile::Classic);
+    let compact = embed(Profile::Compact);
+    let presentation = embed(Profile::Presentation);
+    let width = |embedding: &syntree_layout::Embedding| embedding[0].x_extent_children;
+    let nodes = |embedding: &syntree_layout::Embedding| {
+        embedding
+            .iter()
+            .map(|node| (node.text.clone(), node.x_center, node.y_order))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        nodes(Layouter::new(&tree).embed().unwrap().embedding()),
+        nodes(&classic)
+    );
+    // The compact profile packs the subtrees and wraps the long label
+    assert!(width(&compact) < width(&classic));
+    assert!(compact.iter().any(|e| e.text == "a very long\ntarget"));
+    // The presentation profile separates the subtrees
+    assert!(width(&presentation) > width(&classic));
+
+    // The configuration applies the same options and style
+    let configured = LayoutConfig::new()
+        .with_profile(Profile::Compact)
+        .layout(&tree)
+        .embed()
+        .unwrap();
+    assert_eq!(nodes(&compact), nodes(configured.embedding()));
+    assert!(configured
+        .to_svg_string()
+        .unwrap()
+        .contains("font-size: 12px"));
+    // Individual options can be changed afterwards
+    let wide = Layouter::new(&tree)
+        .with_profile(Profile::Compact)
+        .with_compact(false)
+        .embed()
+        .unwrap();
+    assert!(width(wide.embedding()) > width(&compact));
+}
+
+/// A chain of inner nodes of the given depth, each with an additional leaf.
+fn caterpillar(depth: i32) -> Tree<MyNodeData, FlavorDefault> {
+    let mut tree = Builder::new();
+    for i in 0..depth {
+        tree.open(MyNodeData(i)).unwrap();
+        tree.token(MyNodeData(i), 1).unwrap();
+    }
+    for _ in 0..depth {
+        tree.close().unwrap();
+    }
+    tree.build().unwrap()
+}
+
+#[test]
+fn deep_tree_scaling() {
+    // The placement used to scan all nodes for every layer. Sixteen times the depth then takes
+    // about 256 times as long instead of 16 times, which leaves plenty of headroom both ways.
+    let fastest = |depth| {
+        let tree = caterpillar(depth);
+        (0..5)
+            .map(|_| {
+                let start = Instant::now();
+                let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+                let elapsed = start.elapsed();
+                assert_eq!(tree.len(), layouter.embedding().len());
+                elapsed
+            })
+            .min()
+            .unwrap_or_default()
+    };
+    let small = fastest(1_000);
+    let large = fastest(16_000);
+    let ratio = large.as_secs_f64() / small.as_secs_f64();
+    assert!(
+        ratio < 64.0,
+        "sixteen times the depth took {ratio:.1} times as long"
+    );
+}
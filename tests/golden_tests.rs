@@ -0,0 +1,103 @@
+//! Golden-file tests of the SVG output.
+//!
+//! The generated documents are compared byte by byte with the files in `tests/golden`. After an
+//! intended change of the output the files are regenerated with
+//! `UPDATE_GOLDEN=1 cargo test --test golden_tests` and the changes are reviewed in the diff.
+
+use std::path::PathBuf;
+
+use syntree_layout::{
+    from_text, DepthColors, EdgeStyle, LayoutKind, Layouter, SvgDrawer, SvgDrawerStyle, Theme,
+};
+
+const TREE: &str = "(Module (Fn main (Params) (Block (Let x (Add 1 2)) (Call print x))))";
+
+fn render(drawer: SvgDrawer) -> String {
+    let tree = from_text(TREE).unwrap();
+    let layouter = Layouter::new(&tree).embed().unwrap();
+    drawer.render_string(layouter.embedding()).unwrap()
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Missing golden file {}: {}, run with UPDATE_GOLDEN=1 to create it",
+            path.display(),
+            e
+        )
+    });
+    // Checkouts may convert the line breaks of the golden files
+    let expected = expected.replace("\r\n", "\n");
+    if let Some((line, (expected, actual))) = expected
+        .lines()
+        .zip(actual.lines())
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+    {
+        panic!(
+            "{} differs in line {}:\nexpected: {}\n  actual: {}\nRun with UPDATE_GOLDEN=1 if the change is intended",
+            name,
+            line + 1,
+            expected,
+            actual
+        );
+    }
+    assert_eq!(
+        expected.lines().count(),
+        actual.lines().count(),
+        "{} differs in its number of lines",
+        name
+    );
+}
+
+#[test]
+fn classic() {
+    assert_golden("classic.svg", &render(SvgDrawer::new()));
+}
+
+#[test]
+fn themed_boxes() {
+    let drawer = SvgDrawer::new()
+        .with_style(SvgDrawerStyle {
+            edge_style: EdgeStyle::Orthogonal,
+            ..SvgDrawerStyle::preset(Theme::Dark)
+        })
+        .with_tooltips(true);
+    assert_golden("themed_boxes.svg", &render(drawer));
+}
+
+#[test]
+fn radial() {
+    let drawer = SvgDrawer::new()
+        .with_theme(Theme::Light)
+        .with_layout_kind(LayoutKind::Radial);
+    assert_golden("radial.svg", &render(drawer));
+}
+
+#[test]
+fn css_classes() {
+    let drawer = SvgDrawer::new()
+        .with_depth_colors(DepthColors::Gradient {
+            from: "#ffffff".into(),
+            to: "#4e79a7".into(),
+        })
+        .with_stylesheet("tree.css");
+    assert_golden("css_classes.svg", &render(drawer));
+}
+
+#[test]
+fn repeated_rendering() {
+    let drawer = SvgDrawer::new().with_layout_kind(LayoutKind::Radial);
+    assert_eq!(
+        render(drawer),
+        render(SvgDrawer::new().with_layout_kind(LayoutKind::Radial))
+    );
+}
@@ -0,0 +1,33 @@
+use syntree_layout::{compare_svg, EmbeddedNode, GraphMlDrawer};
+
+fn embedding() -> Vec<EmbeddedNode> {
+    vec![
+        EmbeddedNode {
+            text: "root".to_string(),
+            ord: 0,
+            ..Default::default()
+        },
+        EmbeddedNode {
+            text: "<leaf>".to_string(),
+            ord: 1,
+            parent: Some(0),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn graph_data_keys() {
+    let graphml = GraphMlDrawer::new()
+        .with_graph_data("grammar", "calc")
+        .with_graph_data("a\"b & <c>", "x < y")
+        .render_string(&embedding())
+        .unwrap();
+    assert!(graphml.contains(
+        r#"<key id="g_a&quot;b &amp; &lt;c&gt;" for="graph" attr.name="a&quot;b &amp; &lt;c&gt;" attr.type="string"></key>"#
+    ));
+    assert!(graphml.contains(r#"<data key="g_a&quot;b &amp; &lt;c&gt;">x &lt; y</data>"#));
+    assert!(graphml.contains(r#"<data key="label">&lt;leaf&gt;</data>"#));
+    // The document is well-formed
+    compare_svg(&graphml, &graphml, 0.0).unwrap();
+}
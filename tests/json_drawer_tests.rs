@@ -0,0 +1,59 @@
+#![cfg(feature = "serde")]
+
+use std::fmt;
+
+use syntree::Builder;
+use syntree_layout::{DiagnosticLevel, Embedding, JsonDrawer, Layouter, NodeStyle, Visualize};
+
+#[derive(Copy, Clone, Debug)]
+struct MyNodeData(i32);
+
+impl Visualize for MyNodeData {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn diagnostic(&self) -> DiagnosticLevel {
+        if self.0 == 1 {
+            DiagnosticLevel::Error
+        } else {
+            DiagnosticLevel::None
+        }
+    }
+
+    fn style(&self) -> NodeStyle {
+        NodeStyle {
+            fill: (self.0 == 0).then(|| "yellow".into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn json_round_trip() {
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 2).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let json = JsonDrawer::new()
+        .with_pretty(false)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(json.starts_with(r#"[{"y_order":0,"#));
+    assert!(json.contains(r#""span":{"start":1,"end":3}"#));
+
+    let read_back: Embedding = serde_json::from_str(&json).unwrap();
+    assert_eq!(layouter.embedding().len(), read_back.len());
+    for (original, node) in layouter.embedding().iter().zip(&read_back) {
+        assert_eq!(original.text, node.text);
+        assert_eq!(original.x_center, node.x_center);
+        assert_eq!(original.parent, node.parent);
+        assert_eq!(original.diagnostic, node.diagnostic);
+        assert_eq!(original.style, node.style);
+        assert_eq!(original.span, node.span);
+    }
+}
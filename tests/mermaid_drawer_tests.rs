@@ -0,0 +1,24 @@
+use syntree_layout::{EmbeddedNode, MermaidDrawer};
+
+#[test]
+fn escaped_labels() {
+    let labels = ["Vec<T>", "<=", "a && b", "#amp;", "\"s\"", "if\nelse"];
+    let embedding = labels
+        .iter()
+        .enumerate()
+        .map(|(ord, text)| EmbeddedNode {
+            text: text.to_string(),
+            ord,
+            parent: (ord > 0).then_some(0),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let mermaid = MermaidDrawer::new().render_string(&embedding).unwrap();
+    assert!(mermaid.contains("    n0[\"Vec#lt;T#gt;\"]\n"));
+    assert!(mermaid.contains("    n1[\"#lt;=\"]\n"));
+    assert!(mermaid.contains("    n2[\"a #amp;#amp; b\"]\n"));
+    // A literal entity code is not decoded
+    assert!(mermaid.contains("    n3[\"#35;amp;\"]\n"));
+    assert!(mermaid.contains("    n4[\"#quot;s#quot;\"]\n"));
+    assert!(mermaid.contains("    n5[\"if<br/>else\"]\n"));
+}
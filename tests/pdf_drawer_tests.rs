@@ -0,0 +1,46 @@
+#![cfg(feature = "pdf")]
+
+use std::fmt;
+
+use syntree::Builder;
+use syntree_layout::{Layouter, PdfDrawer, Visualize};
+
+#[derive(Copy, Clone, Debug)]
+struct MyNodeData(i32);
+
+impl Visualize for MyNodeData {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({})", self.0)
+    }
+}
+
+#[test]
+fn pdf_document() {
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let pdf = PdfDrawer::new().render_bytes(layouter.embedding()).unwrap();
+    let pdf = String::from_utf8(pdf).unwrap();
+    assert!(pdf.starts_with("%PDF-1.4\n"));
+    assert!(pdf.ends_with("%%EOF\n"));
+    assert_eq!(2, pdf.matches(" Tj ET").count());
+    assert!(pdf.contains(r"(\(1\)) Tj"));
+    assert_eq!(1, pdf.matches(" l S").count());
+
+    // The cross-reference table must point to the objects
+    let xref = pdf.find("xref\n").unwrap();
+    let startxref = pdf.rsplit("startxref\n").next().unwrap();
+    assert_eq!(
+        xref,
+        startxref.lines().next().unwrap().parse::<usize>().unwrap()
+    );
+    for line in pdf[xref..].lines().skip(3).take(6) {
+        let offset = line[..10].parse::<usize>().unwrap();
+        assert!(pdf[offset..].split_whitespace().nth(1) == Some("0"));
+        assert!(pdf[offset..].contains(" obj\n"));
+    }
+}
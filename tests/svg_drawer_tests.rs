@@ -0,0 +1,1544 @@
+use std::fmt;
+
+use syntree::{Builder, Tree};
+use syntree_layout::{
+    compare_svg, AnimatedSvgDrawer, BorderStyle, Canvas, DepthColors, DiagnosticLevel, DiffKind,
+    DrawContext, Drawer, EdgeStyle, EmbeddedFont, ErrorKind, FontFormat, FontMetrics, HtmlDrawer,
+    LabelOrientation, LayoutKind, Layouter, LeafAlignment, Legend, LegendPosition, NodeShape,
+    NodeStyle, Orientation, Profile, Spacing, SvgDrawer, SvgDrawerStyle, SvgMetadata, SvgSize,
+    SvgTemplate, TextSegment, Theme, Visualize,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct MyNodeData(i32);
+
+impl Visualize for MyNodeData {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn emphasize(&self) -> bool {
+        self.0 == 1
+    }
+
+    fn tooltip(&self) -> Option<String> {
+        (self.0 == 2).then(|| "<two>".to_string())
+    }
+}
+
+fn small_tree() -> Tree<MyNodeData, syntree::FlavorDefault> {
+    //      0
+    //     / \
+    //    1   2
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.token(MyNodeData(1), 1).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.close().unwrap();
+    tree.build().unwrap()
+}
+
+#[test]
+fn default_style() {
+    let tree = small_tree();
+    let svg = Layouter::new(&tree)
+        .embed_with_visualize()
+        .unwrap()
+        .to_svg_string()
+        .unwrap();
+    assert!(svg.contains(r#"fill="white""#));
+    assert!(svg.contains("font-family: 'Courier'"));
+    assert_eq!(1, svg.matches("font-weight: bold").count());
+    assert_eq!(2, svg.matches(r#"stroke="black""#).count());
+}
+
+#[test]
+fn custom_style() {
+    let tree = small_tree();
+    let drawer = SvgDrawer::new().with_style(SvgDrawerStyle {
+        font_family: "monospace".into(),
+        background: None,
+        edge_color: "gray".into(),
+        node_fill: Some("yellow".into()),
+        ..SvgDrawerStyle::new()
+    });
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+    assert!(!svg.contains(r#"fill="white""#));
+    assert!(svg.contains("font-family: monospace;"));
+    assert_eq!(2, svg.matches(r#"stroke="gray""#).count());
+    assert_eq!(3, svg.matches(r#"fill="yellow""#).count());
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Checked(i32, DiagnosticLevel);
+
+impl Visualize for Checked {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn diagnostic(&self) -> DiagnosticLevel {
+        self.1
+    }
+}
+
+#[test]
+fn error_nodes() {
+    let mut tree = Builder::new();
+    tree.open(Checked(0, DiagnosticLevel::None)).unwrap();
+    tree.token(Checked(1, DiagnosticLevel::Error), 1).unwrap();
+    tree.token(Checked(2, DiagnosticLevel::Warning), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let errors = layouter.embedding().errors().collect::<Vec<_>>();
+    assert_eq!(1, errors.len());
+    assert_eq!("1", errors[0].text);
+
+    let svg = layouter.to_svg_string().unwrap();
+    assert_eq!(
+        2,
+        svg.matches(r#"stroke="red""#).count() + svg.matches("fill: red").count()
+    );
+    assert_eq!(1, svg.matches(r#"stroke="darkorange""#).count());
+}
+
+#[test]
+fn error_node_predicate() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree)
+        .with_error_nodes(|value: &MyNodeData| value.0 == 2)
+        .embed_with_visualize()
+        .unwrap();
+    let errors = layouter.embedding().errors().collect::<Vec<_>>();
+    assert_eq!(1, errors.len());
+    assert_eq!("2", errors[0].text);
+    assert_eq!(BorderStyle::Dashed, errors[0].style.border);
+
+    // The error node gets a red dashed border
+    let svg = layouter.to_svg_string().unwrap();
+    assert!(svg.contains(r#"<rect id="box-2""#));
+    assert_eq!(
+        1,
+        svg.matches(r#"stroke="red" stroke-dasharray="4 2""#)
+            .count()
+    );
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Styled(i32);
+
+impl Visualize for Styled {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn style(&self) -> NodeStyle {
+        if self.0 == 0 {
+            NodeStyle::default()
+        } else {
+            NodeStyle {
+                fill: Some("lightgreen".to_string()),
+                text_color: Some("darkgreen".to_string()),
+                border_color: Some("green".to_string()),
+                border: BorderStyle::Dashed,
+                shape: Some(NodeShape::Ellipse),
+            }
+        }
+    }
+}
+
+#[test]
+fn per_node_style() {
+    let mut tree = Builder::new();
+    tree.open(Styled(0)).unwrap();
+    tree.token(Styled(1), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    assert_eq!(
+        Some(NodeShape::Ellipse),
+        layouter.embedding()[1].style.shape
+    );
+
+    let svg = layouter.to_svg_string().unwrap();
+    assert_eq!(1, svg.matches("<ellipse").count());
+    assert!(svg.contains(r#"fill="lightgreen""#));
+    assert!(svg.contains(r#"stroke="green""#));
+    assert!(svg.contains("stroke-dasharray"));
+    assert!(svg.contains("fill: darkgreen"));
+}
+
+#[test]
+fn embedded_font() {
+    let tree = small_tree();
+    let drawer = SvgDrawer::new().with_style(SvgDrawerStyle {
+        font_family: SvgDrawerStyle::WEB_SAFE_MONOSPACE.into(),
+        embedded_font: Some(EmbeddedFont::new(
+            "MyMono",
+            FontFormat::Woff2,
+            b"wOF2".as_slice(),
+        )),
+        ..SvgDrawerStyle::new()
+    });
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+    assert!(svg.contains(
+        "@font-face { font-family: &apos;MyMono&apos;; src: url(data:font/woff2;base64,d09GMg==) format(&apos;woff2&apos;); }"
+    ));
+    assert!(svg.contains("font-family: 'MyMono', 'Courier New', 'Courier', monospace;"));
+}
+
+#[test]
+fn id_prefix() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = SvgDrawer::new()
+        .with_id_prefix("t1-")
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(3, svg.matches(r#"id="t1-node-"#).count());
+    assert!(svg.contains(r#"id="t1-edge-1""#));
+    assert!(svg.contains(r#"id="t1-edge-2""#));
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Oriented(&'static str, LabelOrientation);
+
+impl Visualize for Oriented {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn orientation(&self) -> LabelOrientation {
+        self.1
+    }
+}
+
+#[test]
+fn vertical_labels() {
+    let mut tree = Builder::new();
+    tree.open(Oriented("root", LabelOrientation::Horizontal))
+        .unwrap();
+    tree.token(Oriented("rotated", LabelOrientation::Rotated), 1)
+        .unwrap();
+    tree.token(Oriented("stack", LabelOrientation::Stacked), 1)
+        .unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(2, embedding[1].x_extent);
+    assert_eq!(2, embedding[2].x_extent);
+    assert_eq!(5, embedding[0].x_extent_children);
+
+    let svg = layouter.to_svg_string().unwrap();
+    assert_eq!(1, svg.matches("transform=\"rotate(90").count());
+    assert_eq!(5, svg.matches("<tspan").count());
+    let (_, height) = SvgDrawer::new().document_size(embedding);
+    assert!(svg.contains(&format!(r#"height="{}""#, height)));
+
+    // The vertical labels reach below the last level, the longest one determines how far
+    let height_with_leaf = |label: &'static str, orientation: LabelOrientation| {
+        let mut tree = Builder::new();
+        tree.open(Oriented("root", LabelOrientation::Horizontal))
+            .unwrap();
+        tree.token(Oriented(label, orientation), 1).unwrap();
+        tree.close().unwrap();
+        let tree = tree.build().unwrap();
+        let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+        SvgDrawer::new().document_size(layouter.embedding()).1
+    };
+    let horizontal = height_with_leaf("rotated", LabelOrientation::Horizontal);
+    let rotated = height_with_leaf("rotated", LabelOrientation::Rotated);
+    let longer = height_with_leaf("rotated label", LabelOrientation::Rotated);
+    assert!(horizontal < rotated);
+    assert!(rotated < longer);
+}
+
+#[test]
+fn tooltips() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    assert_eq!(1..2, layouter.embedding()[2].span);
+
+    let svg = layouter.to_svg_string().unwrap();
+    assert!(!svg.contains("<title>"));
+
+    let svg = SvgDrawer::new()
+        .with_tooltips(true)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(3, svg.matches("<title>").count());
+    assert!(svg.contains("<title>node 0, span 0..2</title>"));
+    assert!(svg.contains("span 1..2\n&lt;two&gt;</title>"));
+    assert!(svg.contains(r#"data-node-id="2" data-span="1..2""#));
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Ident(&'static str);
+
+impl Visualize for Ident {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ident {}", self.0)
+    }
+
+    fn rich_text(&self) -> Option<Vec<TextSegment>> {
+        Some(vec![
+            TextSegment::plain("Ident "),
+            TextSegment::emphasized(format!("`{}`", self.0)).with_color("blue"),
+        ])
+    }
+}
+
+#[test]
+fn rich_text_labels() {
+    let mut tree = Builder::new();
+    tree.token(Ident("foo"), 3).unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    assert_eq!("Ident `foo`", layouter.embedding()[0].text);
+    assert_eq!(12, layouter.embedding()[0].x_extent);
+
+    let svg = layouter.to_svg_string().unwrap();
+    assert!(svg.contains("<tspan>Ident </tspan>"));
+    assert!(svg.contains(r#"<tspan style="font-weight: bold; fill: blue">`foo`</tspan>"#));
+}
+
+#[test]
+fn wrapped_labels() {
+    let mut tree = Builder::new();
+    tree.token(Ident("foo"), 3).unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree)
+        .with_max_label_width(6)
+        .embed_with_visualize()
+        .unwrap();
+    assert_eq!("Ident\n`foo`", layouter.embedding()[0].text);
+    assert_eq!(6, layouter.embedding()[0].x_extent);
+
+    // The lines are written below each other and the box grows downwards
+    let drawer = SvgDrawer::new().with_style(SvgDrawerStyle {
+        node_stroke: Some("black".into()),
+        ..SvgDrawerStyle::new()
+    });
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+    assert!(svg.contains(r#"<tspan x="15">Ident</tspan>"#));
+    assert!(svg.contains(r#"<tspan x="15" dy="16">`foo`</tspan>"#));
+    let resolved = drawer.resolve(layouter.embedding());
+    let node = resolved.iter().next().unwrap();
+    assert_eq!((56.0, 39.0), (node.width, node.height));
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Raw(&'static str);
+
+impl Visualize for Raw {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn style(&self) -> NodeStyle {
+        NodeStyle {
+            fill: Some("url(\"#a&b\")".into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn escaped_labels() {
+    let mut tree = Builder::new();
+    tree.open(Raw("<a & 'b'>")).unwrap();
+    tree.token(Raw("\"\0\u{7}\""), 4).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+
+    let svg = SvgDrawer::new()
+        .with_id_prefix("a\"b-")
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains(">&lt;a &amp; &apos;b&apos;&gt;<"));
+    assert!(svg.contains(">&quot;\u{2400}\u{2407}&quot;<"));
+    assert!(svg.contains(r#"fill="url(&quot;#a&amp;b&quot;)""#));
+    assert!(svg.contains(r#"id="a&quot;b-node-0""#));
+
+    let svg = SvgDrawer::new()
+        .with_label_filter(|text| text.replace('\0', "NUL"))
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains(">&quot;NUL\u{2407}&quot;<"));
+}
+
+#[test]
+fn edge_styles() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let render = |edge_style| {
+        SvgDrawer::new()
+            .with_style(SvgDrawerStyle {
+                edge_style,
+                ..SvgDrawerStyle::new()
+            })
+            .render_string(layouter.embedding())
+            .unwrap()
+    };
+
+    let svg = render(EdgeStyle::Straight);
+    assert!(svg.contains(r#"<line id="edge-1" x1="30" y1="35" x2="20" y2="47""#));
+    let svg = render(EdgeStyle::Orthogonal);
+    assert!(svg.contains(r#"<path id="edge-1" d="M 30 35 V 41 H 20 V 47" fill="none""#));
+    let svg = render(EdgeStyle::Curved);
+    assert!(svg.contains(r#"<path id="edge-1" d="M 30 35 C 30 41, 20 41, 20 47" fill="none""#));
+}
+
+#[test]
+fn document_size() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let natural = SvgDrawer::new().resolve(layouter.embedding());
+    let view_box = format!(r#"viewBox="0 0 {} {}""#, natural.width(), natural.height());
+
+    let svg = SvgDrawer::new()
+        .with_size(SvgSize::FitWidth(natural.width() / 2.0))
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains(&format!(
+        r#"width="{}" height="{}" {}"#,
+        natural.width() / 2.0,
+        natural.height() / 2.0,
+        view_box
+    )));
+
+    let svg = SvgDrawer::new()
+        .with_size(SvgSize::Fixed {
+            width: 640.0,
+            height: 480.0,
+        })
+        .with_preserve_aspect_ratio("xMinYMin meet")
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains(&format!(
+        r#"width="640" height="480" {} preserveAspectRatio="xMinYMin meet""#,
+        view_box
+    )));
+}
+
+#[test]
+fn legend() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let legend = Legend::new()
+        .with_entry(
+            "nonterminal",
+            NodeStyle {
+                shape: Some(NodeShape::Ellipse),
+                border: BorderStyle::Dashed,
+                ..Default::default()
+            },
+        )
+        .with_diagnostics(true)
+        .with_position(LegendPosition::BottomRight);
+    let drawer = SvgDrawer::new().with_legend(legend);
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+
+    assert!(svg.contains(r#"stroke="black" stroke-dasharray="4 2"></ellipse>"#));
+    assert!(svg.contains(">nonterminal</text>"));
+    assert!(svg.contains(r#"stroke="red"></rect>"#));
+    assert!(svg.contains("fill: red\">error</text>"));
+    // The image grows to fit the wide legend which touches the lower right corner
+    let resolved = drawer.resolve(layouter.embedding());
+    let natural = SvgDrawer::new().resolve(layouter.embedding());
+    assert!(resolved.width() > natural.width());
+    assert_eq!(resolved.height(), natural.height());
+    assert!(svg.contains(&format!(
+        r#"<rect x="3" y="{}" width="{}""#,
+        resolved.height() - 86.0 - 3.0,
+        resolved.width() - 6.0
+    )));
+}
+
+#[test]
+fn source_strip() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let drawer = SvgDrawer::new().with_source_strip("x\n");
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+
+    assert!(svg.contains(">x\u{21b5}</text>"));
+    // Only the leaves are linked to their spans
+    assert!(!svg.contains(r#"id="source-span-0""#));
+    assert!(svg.contains(r#"<line id="source-link-1" x1="20" y1="70" x2="15" y2="95""#));
+    assert!(svg.contains(r#"<path id="source-span-1" d="M 10 111 V 116 H 20 V 111""#));
+    assert!(svg.contains(r#"<path id="source-span-2" d="M 20 111 V 116 H 30 V 111""#));
+    let natural = SvgDrawer::new().resolve(layouter.embedding());
+    assert_eq!(
+        natural.height() + 48.0,
+        drawer.resolve(layouter.embedding()).height()
+    );
+}
+
+#[test]
+fn span_bands() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+
+    // Only the first leaf lies inside of the range
+    let svg = SvgDrawer::new()
+        .with_span_bands(Some(0..1))
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(1, svg.matches(r#"fill="gold""#).count());
+    assert!(svg.contains(r#"<rect id="band-0" x="12" y="0" width="16""#));
+
+    // The bands of neighboring nodes are merged
+    let svg = SvgDrawer::new()
+        .with_span_bands(Some(0..2))
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(1, svg.matches(r#"fill="gold""#).count());
+}
+
+#[test]
+fn radial_layout() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = SvgDrawer::new()
+        .with_layout_kind(LayoutKind::Radial)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(2, svg.matches("<path").count());
+    assert!(!svg.contains("<line"));
+    // The image is a square around the root
+    assert!(svg.contains(r#"width="130" height="160""#));
+}
+
+#[test]
+fn scaled_subtrees() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree)
+        .with_scaled_subtrees(|value: &MyNodeData| value.0 == 2, 0.5)
+        .embed_with_visualize()
+        .unwrap();
+    let svg = layouter.to_svg_string().unwrap();
+    assert_eq!(2, svg.matches("font-size: 16px").count());
+    assert_eq!(1, svg.matches("font-size: 8px").count());
+}
+
+#[test]
+fn coordinate_mapping() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = SvgDrawer::new()
+        .with_coordinate_mapping(|x, y_order| {
+            (x as f64 * 20.0 + 20.0, y_order as f64 * 30.0 + 30.0)
+        })
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains(r#"<line id="edge-1" x1="60" y1="40" x2="40" y2="47""#));
+    // The image fits the nodes
+    assert!(svg.contains(r#"width="98" height="95""#));
+}
+
+#[test]
+fn units_line_up_with_output() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let drawer = SvgDrawer::new().with_profile(Profile::Compact);
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+    let units = drawer.style().units();
+    let embedding = layouter.embedding();
+    let root_x = units.x_to_pixels(embedding[0].x_center);
+    for node in embedding.iter().skip(1) {
+        let (x, _) = units.to_pixels(node.x_center, node.y_order);
+        let edge = format!(r#"id="edge-{}" x1="{}" "#, node.ord, root_x);
+        assert!(svg.contains(&edge));
+        assert!(svg.contains(&format!(r#" x2="{}" "#, x)));
+    }
+}
+
+#[test]
+fn theme_presets() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let themes = [
+        Theme::Light,
+        Theme::Dark,
+        Theme::HighContrast,
+        Theme::ColorblindSafe,
+    ];
+    for theme in themes {
+        let style = SvgDrawerStyle::preset(theme);
+        assert_ne!(SvgDrawerStyle::new(), style);
+        let diagnostics = [
+            &style.warning_color,
+            &style.recovered_color,
+            &style.error_color,
+        ];
+        assert!(diagnostics.iter().all(|color| **color != style.text_color));
+        assert_ne!(style.warning_color, style.error_color);
+        assert_ne!(style.recovered_color, style.error_color);
+    }
+    assert_eq!(
+        SvgDrawerStyle::preset(Theme::Light),
+        SvgDrawerStyle::from(Theme::default())
+    );
+
+    let svg = SvgDrawer::new()
+        .with_theme(Theme::Dark)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains("#0d1117"));
+    assert!(svg.contains("fill: #e6edf3"));
+
+    // Single properties can be overridden
+    let style = SvgDrawerStyle {
+        text_color: "#ffffff".into(),
+        ..SvgDrawerStyle::preset(Theme::Dark)
+    };
+    let svg = SvgDrawer::new()
+        .with_style(style)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains("fill: #ffffff"));
+    assert!(!svg.contains("fill: #e6edf3"));
+    assert!(svg.contains("#0d1117"));
+}
+
+#[test]
+fn css_classes() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let mut embedding = layouter.embedding().to_vec();
+    embedding[2].diagnostic = DiagnosticLevel::Error;
+    embedding[2].segments = vec![TextSegment {
+        text: "2".to_string(),
+        is_emphasized: true,
+        color: None,
+    }];
+    let drawer = SvgDrawer::new()
+        .with_node_classes(|node| vec![format!("kind-{}", node.text)])
+        .with_stylesheet("tree.css")
+        .with_legend(Legend::new().with_diagnostics(true));
+    let svg = drawer.render_string(&embedding).unwrap();
+
+    assert!(svg.starts_with(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>\n\
+         <?xml-stylesheet type=\"text/css\" href=\"tree.css\"?>\n<svg"
+    ));
+    assert!(svg.contains(r#"<text id="node-0" class="label node depth-0 kind-0""#));
+    assert!(svg.contains(r#"<rect id="box-0" class="box node depth-0 kind-0""#));
+    assert!(svg.contains(r#"class="label node leaf emphasized depth-1 kind-1""#));
+    assert!(svg.contains(r#"class="box node leaf depth-1 error kind-2""#));
+    assert!(svg.contains(r#"<line id="edge-2" class="edge depth-1""#));
+    // Style sheets can override all styles
+    assert!(!svg.contains(" style="));
+    assert!(svg.contains(r#"font-family="'Courier'" font-size="16px" font-weight="bold""#));
+    assert!(svg.contains(r#"<tspan font-weight="bold" fill="red">2</tspan>"#));
+
+    // Without the classes nothing changes
+    let svg = SvgDrawer::new().render_string(&embedding).unwrap();
+    assert!(!svg.contains("class="));
+    assert!(!svg.contains("box-0"));
+    assert!(svg.contains(r#"<tspan style="font-weight: bold; fill: red">2</tspan>"#));
+}
+
+#[test]
+fn bottom_up_orientation() {
+    // A derivation style tree with all tokens on the top level
+    let tree = syntree_layout::from_text("(S (NP she) (VP (V saw) (NP him)))").unwrap();
+    let layouter = Layouter::new(&tree)
+        .with_leaf_alignment(LeafAlignment::Bottom)
+        .embed()
+        .unwrap();
+    let embedding = layouter.embedding();
+    let top_down = SvgDrawer::new().resolve(embedding);
+    let drawer = SvgDrawer::new().with_orientation(Orientation::BottomUp);
+    let bottom_up = drawer.resolve(embedding);
+
+    // The levels are mirrored, the size is unchanged
+    assert_eq!(
+        (top_down.width(), top_down.height()),
+        (bottom_up.width(), bottom_up.height())
+    );
+    for (node, (down, up)) in embedding.iter().zip(top_down.iter().zip(bottom_up.iter())) {
+        assert_eq!(down.x, up.x);
+        if node.is_leaf() {
+            assert_eq!(top_down[0].y, up.y);
+        }
+    }
+    assert_eq!(
+        top_down.iter().map(|n| n.y).fold(0.0, f32::max),
+        bottom_up[0].y
+    );
+
+    // The edges lead from the top of the parents up to the bottom of the children
+    let svg = drawer.render_string(embedding).unwrap();
+    let (root, np) = (&bottom_up[0], &bottom_up[1]);
+    assert!(svg.contains(&format!(
+        r#"<line id="edge-1" x1="{}" y1="{}" x2="{}" y2="{}""#,
+        root.x + root.width / 2.0,
+        root.y,
+        np.x + np.width / 2.0,
+        np.y + np.height
+    )));
+}
+
+#[test]
+fn badges() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree)
+        .with_badges(|value: &MyNodeData| (value.0 > 0).then(|| format!("#{}", value.0)))
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(None, embedding[0].badge);
+    assert_eq!(Some("#2"), embedding[2].badge.as_deref());
+
+    let drawer = SvgDrawer::new();
+    let svg = drawer.render_string(embedding).unwrap();
+    assert_eq!(2, svg.matches(r#"<g id="badge-"#).count());
+    // The chip is centered on the top right corner of the node's frame
+    let frame = &drawer.resolve(embedding)[1];
+    assert!(svg.contains(&format!(
+        r#"<text x="{}" y="{}" text-anchor="middle" style="font-family: 'Courier'; font-size: 12px;"#,
+        frame.x + frame.width,
+        frame.y + 3.75
+    )));
+    // The badges don't change the layout
+    let plain = Layouter::new(&tree).embed_with_visualize().unwrap();
+    assert_eq!(drawer.resolve(plain.embedding()), drawer.resolve(embedding));
+
+    let svg = SvgDrawer::new()
+        .with_css_classes(true)
+        .render_string(embedding)
+        .unwrap();
+    assert!(svg.contains(r#"<g id="badge-1" class="badge node leaf emphasized depth-1">"#));
+
+    let dot = syntree_layout::DotDrawer::new()
+        .render_string(embedding)
+        .unwrap();
+    assert!(dot.contains(r##"xlabel="#1""##));
+}
+
+#[test]
+fn font_metrics() {
+    let tree = small_tree();
+    let metrics = FontMetrics::monospace(20.0, 0.8);
+    let layouter = Layouter::new(&tree)
+        .with_font_metrics(metrics.clone())
+        .embed_with_visualize()
+        .unwrap();
+    let embedding = layouter.embedding();
+    // Monospace metrics measure in whole characters like the default estimate
+    let plain = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let extents = |embedding: &[syntree_layout::EmbeddedNode]| {
+        embedding.iter().map(|n| n.x_extent).collect::<Vec<_>>()
+    };
+    assert_eq!(extents(plain.embedding()), extents(embedding));
+
+    let drawer = SvgDrawer::new().with_font("Courier New", metrics);
+    assert_eq!(16.0, drawer.style().char_width);
+    let svg = drawer.render_string(embedding).unwrap();
+    assert!(svg.contains("font-family: 'Courier New'; font-size: 20px;"));
+    // The frames of the siblings don't overlap
+    let frames = drawer.resolve(embedding);
+    let mut leaves = frames.iter().skip(1).collect::<Vec<_>>();
+    leaves.sort_by(|a, b| a.x.total_cmp(&b.x));
+    for pair in leaves.windows(2) {
+        assert!(pair[0].x + pair[0].width <= pair[1].x);
+    }
+
+    // A measure function takes precedence over the metrics
+    let layouter = Layouter::new(&tree)
+        .with_font_metrics(FontMetrics::monospace(20.0, 0.8))
+        .with_measure(|_, _| 7)
+        .embed_with_visualize()
+        .unwrap();
+    assert!(layouter.embedding().iter().all(|n| n.x_extent == 7));
+}
+
+#[test]
+fn layer_guides() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    let plain = SvgDrawer::new().render_string(embedding).unwrap();
+    let svg = SvgDrawer::new()
+        .with_layer_guides(true)
+        .render_string(embedding)
+        .unwrap();
+    assert_eq!(2, svg.matches(r#"<line id="guide-"#).count());
+    assert!(svg.contains(r#"text-anchor="end" fill-opacity="0.6""#));
+    assert!(svg.contains(">1</text>"));
+    // The tree is moved right by the gutter, which widens the image
+    let gutter = 10.0 + 2.0 * SvgDrawerStyle::new().node_padding;
+    assert!(svg.contains(&format!(r#"<g transform="translate({} 0)">"#, gutter)));
+    let width = |svg: &str| {
+        let start = svg.find(r#"width=""#).unwrap() + 7;
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].parse::<f32>().unwrap()
+    };
+    assert_eq!(width(&plain) + gutter, width(&svg));
+    // The bottom-up orientation numbers the layers from the root at the bottom
+    let svg = SvgDrawer::new()
+        .with_layer_guides(true)
+        .with_orientation(Orientation::BottomUp)
+        .render_string(embedding)
+        .unwrap();
+    let y = |svg: &str, id: &str| {
+        let start = svg.find(&format!(r#"id="{}" x=""#, id)).unwrap();
+        let start = start + svg[start..].find(r#"y=""#).unwrap() + 3;
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].parse::<f32>().unwrap()
+    };
+    assert!(y(&svg, "guide-label-0") > y(&svg, "guide-label-1"));
+
+    let svg = SvgDrawer::new()
+        .with_layer_guides(true)
+        .with_layout_kind(LayoutKind::Radial)
+        .render_string(embedding)
+        .unwrap();
+    assert!(!svg.contains("guides"));
+}
+
+#[test]
+fn metadata() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    let drawer = SvgDrawer::new().with_metadata(SvgMetadata::new().with_source("a<b>.txt"));
+    let svg = drawer.render_string(embedding).unwrap();
+    assert!(svg.contains(&format!(
+        r#"<generator xmlns="https://github.com/jsinger67/syntree_layout" name="syntree_layout" version="{}" nodes="3" hash=""#,
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(svg.contains(r#"source="a&lt;b>.txt">"#));
+    assert!(!svg.contains("timestamp"));
+    // Without timestamp the output is reproducible
+    assert_eq!(svg, drawer.render_string(embedding).unwrap());
+
+    // The hash changes with the tree
+    let hash = |svg: &str| {
+        let start = svg.find(r#"hash=""#).unwrap() + 6;
+        svg[start..start + 16].to_string()
+    };
+    let mut changed = embedding.to_vec();
+    changed[1].text = "one".to_string();
+    assert_ne!(hash(&svg), hash(&drawer.render_string(&changed).unwrap()));
+
+    let svg = SvgDrawer::new()
+        .with_metadata(SvgMetadata::new().with_timestamp(true))
+        .render_string(embedding)
+        .unwrap();
+    let start = svg.find(r#"timestamp=""#).unwrap() + 11;
+    let timestamp = &svg[start..start + 20];
+    assert_eq!(Some('T'), timestamp.chars().nth(10));
+    assert!(timestamp.ends_with('Z'));
+}
+
+#[test]
+fn node_renderer() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    let drawer = SvgDrawer::new()
+        .with_node_renderer(|geometry, node, out| {
+            // Only the leaves get custom markup
+            if node.parent.is_some() {
+                out.push_str(&format!(
+                    r#"<foreignObject x="{}" y="{}" width="{}" height="{}"><div>{}</div></foreignObject>"#,
+                    geometry.x, geometry.y, geometry.width, geometry.height, node.text
+                ));
+            }
+        });
+    let svg = drawer.render_string(embedding).unwrap();
+    assert_eq!(2, svg.matches("<foreignObject").count());
+    let frame = &drawer.resolve(embedding)[2];
+    assert!(svg.contains(&format!(
+        r#"<foreignObject x="{}" y="{}" width="{}" height="{}"><div>2</div></foreignObject>"#,
+        frame.x, frame.y, frame.width, frame.height
+    )));
+    // The root falls back to the default rendering
+    assert!(svg.contains(r#"<text id="node-0""#));
+    assert!(!svg.contains(r#"id="node-1""#));
+    assert!(!svg.contains(r#"id="node-2""#));
+    // The edges are still drawn
+    assert_eq!(2, svg.matches(r#" id="edge-"#).count());
+}
+
+#[test]
+fn trivia_markers() {
+    let source = "let x  =  1;  ";
+    let mut builder = Builder::new();
+    builder.open("Let").unwrap();
+    for (kind, len) in [
+        ("Keyword", 3),
+        ("Whitespace", 1),
+        ("Ident", 1),
+        ("Whitespace", 2),
+        ("Eq", 1),
+        ("Whitespace", 2),
+        ("Num", 1),
+        ("Semi", 1),
+        ("Whitespace", 2),
+    ] {
+        builder.token(kind, len).unwrap();
+    }
+    builder.close().unwrap();
+    let tree = builder.build().unwrap();
+
+    let plain = Layouter::new(&tree).embed_with_source(source).unwrap();
+    let layouter = Layouter::new(&tree)
+        .with_trivia(|kind: &&str| *kind == "Whitespace")
+        .embed_with_source(source)
+        .unwrap();
+    let embedding = layouter.embedding();
+    assert_eq!(4, embedding.iter().filter(|node| node.is_trivia).count());
+    assert!(embedding.stats().width < plain.embedding().stats().width);
+
+    let svg = SvgDrawer::new()
+        .with_css_classes(true)
+        .with_tooltips(true)
+        .render_string(embedding)
+        .unwrap();
+    // Trivia gets no box, but keeps its edge and its original text as tooltip
+    assert!(!svg.contains(r#"id="box-2""#));
+    assert!(svg.contains(r#"id="box-3""#));
+    assert!(svg.contains(r#"<text id="node-2" class="label node leaf depth-1 trivia""#));
+    assert!(svg.contains(r#"id="edge-2""#));
+    assert!(svg.contains("<title>node 4, span 5..7\n&quot;  &quot;</title>"));
+}
+
+#[test]
+fn pan_zoom() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let drawer = SvgDrawer::new()
+        .with_pan_zoom(true)
+        .with_id_prefix("t1-")
+        .with_legend(Legend::new().with_diagnostics(true));
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+
+    let viewport = svg
+        .find(r#"<g id="t1-viewport" class="svg-pan-zoom_viewport">"#)
+        .unwrap();
+    let script = svg
+        .find("<script><![CDATA[const prefix = \"t1-\";")
+        .unwrap();
+    let legend = svg.find(r#"<g id="t1-legend">"#).unwrap();
+    assert!(viewport < svg.find(r#"id="t1-node-0""#).unwrap());
+    assert!(svg.find(r#"id="t1-node-2""#).unwrap() < script);
+    // The legend stays in place
+    assert!(script < legend);
+    assert!(svg[script..].contains("addEventListener(\"wheel\""));
+
+    let svg = SvgDrawer::new()
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(!svg.contains("viewport"));
+    assert!(!svg.contains("<script>"));
+}
+
+#[test]
+fn tree_stats() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let stats = layouter.stats();
+    assert_eq!(3, stats.node_count);
+    assert_eq!(2, stats.leaf_count);
+    assert_eq!(2, stats.height);
+    assert_eq!(2, stats.max_fan_out);
+    assert_eq!((1, 2), (stats.widest_layer, stats.widest_layer_nodes));
+    assert_eq!(Some(&3), stats.label_lengths.get(&1));
+
+    // The estimate matches the drawn image
+    for style in [SvgDrawerStyle::new(), Profile::Compact.into()] {
+        let (width, height) = stats.estimated_size(&style);
+        let svg = SvgDrawer::new()
+            .with_style(style)
+            .render_string(layouter.embedding())
+            .unwrap();
+        assert!(svg.contains(&format!(r#"width="{}" height="{}""#, width, height)));
+    }
+
+    let empty = Layouter::new(&tree).stats();
+    assert_eq!(0, empty.node_count);
+    assert_eq!(0, empty.height);
+    assert_eq!((0.0, 0.0), empty.estimated_size(&SvgDrawerStyle::new()));
+}
+
+#[test]
+fn image_map_sidecar() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg_path = std::env::temp_dir().join("syntree_layout_image_map.svg");
+    let map_path = std::env::temp_dir().join("syntree_layout_image_map.map.json");
+    let _ = std::fs::remove_file(&map_path);
+    SvgDrawer::new()
+        .with_image_map(true)
+        .draw(&DrawContext::new(&svg_path, layouter.embedding()))
+        .unwrap();
+    let map = std::fs::read_to_string(&map_path).unwrap();
+    assert_eq!(3, map.matches(r#""ord""#).count());
+    assert!(map.contains(r#""span": [1, 2]"#));
+    assert!(map.contains(r#""parent": null, "id": "node-0", "text": "0"}"#));
+    assert!(map.contains(r#""parent": 0, "id": "node-2", "text": "2"}"#));
+
+    // The ids match the elements of the document and the labels are valid JSON strings
+    let mut embedding = layouter.embedding().to_vec();
+    embedding[1].text = "say \"hi\"\\".to_string();
+    let drawer = SvgDrawer::new().with_id_prefix("t1-");
+    let map = drawer.render_image_map(&embedding).unwrap();
+    assert!(map.contains(r#""id": "t1-node-1", "text": "say \"hi\"\\"}"#));
+    let svg = drawer.render_string(&embedding).unwrap();
+    assert!(svg.contains(r#"id="t1-node-1""#));
+}
+
+#[test]
+fn template_snippets() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = SvgDrawer::new()
+        .with_template(SvgTemplate {
+            header: "<style>.hot { fill: red; }</style><!-- {width}x{height} -->".into(),
+            footer: "<text>footer</text>".into(),
+            node: r##"<a href="#n{ord}" data-span="{span_start}..{span_end}"></a>"##.into(),
+        })
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert!(svg.contains("<style>.hot { fill: red; }</style><!-- 50x95 -->"));
+    assert!(svg.contains(r##"<a href="#n2" data-span="1..2"></a>"##));
+    assert!(svg.ends_with("<text>footer</text></svg>"));
+    // The document stays well-formed
+    assert!(compare_svg(&svg, &svg, 0.0).is_ok());
+}
+
+#[test]
+fn resolved_embedding_matches_boxes() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    for layout_kind in [LayoutKind::Layered, LayoutKind::Radial] {
+        let drawer = SvgDrawer::new()
+            .with_style(SvgDrawerStyle {
+                node_stroke: Some("black".into()),
+                ..SvgDrawerStyle::new()
+            })
+            .with_layout_kind(layout_kind);
+        let svg = drawer.render_string(layouter.embedding()).unwrap();
+        let resolved = drawer.resolve(layouter.embedding());
+        assert_eq!(layouter.embedding().len(), resolved.len());
+        assert!(svg.contains(&format!(
+            r#"width="{}" height="{}""#,
+            resolved.width(),
+            resolved.height()
+        )));
+        for node in &resolved {
+            assert!(svg.contains(&format!(
+                r#"<rect id="box-{}" x="{}" y="{}" width="{}" height="{}""#,
+                node.ord, node.x, node.y, node.width, node.height
+            )));
+        }
+    }
+}
+
+#[test]
+fn golden_comparison() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let svg = layouter.to_svg_string().unwrap();
+    let reformatted = svg.replace("\n  ", "\n    ").replace("><", ">\n<");
+    compare_svg(&svg, &reformatted, 0.0).unwrap();
+
+    let shifted = svg.replace(r#"x="25""#, r#"x="25.4""#);
+    compare_svg(&svg, &shifted, 0.5).unwrap();
+    let error = compare_svg(&svg, &shifted, 0.1).unwrap_err();
+    assert_eq!(ErrorKind::SvgMismatch, error.kind());
+    assert!(error.to_string().contains("differ in 1 places"));
+    let error = compare_svg(&svg, "<svg><text", 0.1).unwrap_err();
+    assert_eq!(ErrorKind::MalformedSvg, error.kind());
+
+    let other = Layouter::new(&tree)
+        .with_compact(true)
+        .embed_with_visualize()
+        .unwrap();
+    let renamed = svg.replace(">2<", ">3<");
+    assert!(compare_svg(&svg, &renamed, 0.5).is_err());
+    assert!(compare_svg(&svg, &other.to_svg_string().unwrap(), 0.5).is_ok());
+}
+
+#[test]
+fn tree_diff() {
+    //   before     after
+    //     0          0
+    //    / \       / | \
+    //   1   2     1  3  5
+    //                |
+    //                4
+    let before = small_tree();
+    let mut after = Builder::new();
+    after.open(MyNodeData(0)).unwrap();
+    after.token(MyNodeData(1), 1).unwrap();
+    after.open(MyNodeData(3)).unwrap();
+    after.token(MyNodeData(4), 1).unwrap();
+    after.close().unwrap();
+    after.token(MyNodeData(5), 1).unwrap();
+    after.close().unwrap();
+    let after = after.build().unwrap();
+
+    // The spans of the roots differ, the second leaf keeps its span but changes its value
+    let diff = Layouter::diff(&before, &after).unwrap();
+    assert_eq!(
+        &[DiffKind::Removed, DiffKind::Unchanged, DiffKind::Changed],
+        diff.before_kinds()
+    );
+    assert_eq!(
+        &[
+            DiffKind::Added,
+            DiffKind::Unchanged,
+            DiffKind::Changed,
+            DiffKind::Added,
+            DiffKind::Added
+        ],
+        diff.after_kinds()
+    );
+    assert!(Layouter::diff(&before, &before).unwrap().is_unchanged());
+
+    // The differing nodes and the samples of the legend are filled with the colors
+    let (before_svg, after_svg) = diff.to_svg_strings().unwrap();
+    let fill = |kind: DiffKind| format!(r#"fill="{}""#, kind.color().unwrap());
+    assert_eq!(2, before_svg.matches(&fill(DiffKind::Removed)).count());
+    assert_eq!(2, before_svg.matches(&fill(DiffKind::Changed)).count());
+    assert_eq!(2, after_svg.matches(&fill(DiffKind::Changed)).count());
+    assert_eq!(4, after_svg.matches(&fill(DiffKind::Added)).count());
+}
+
+#[test]
+fn canvas() {
+    let tree = small_tree();
+    let width = |svg: &str| {
+        let start = svg.find(r#"width=""#).unwrap() + 7;
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].parse::<f32>().unwrap()
+    };
+    let single = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let single_width = width(&single.to_svg_string().unwrap());
+    let canvas = Canvas::new()
+        .with_gap(0)
+        .add(Layouter::new(&tree).embed_with_visualize().unwrap())
+        .add(single);
+
+    // The second tree is placed right of the first one and keeps its structure
+    let embedding = canvas.embedding();
+    assert_eq!(
+        vec![None, Some(0), Some(0), None, Some(3), Some(3)],
+        embedding.iter().map(|e| e.parent).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![2, 1, 3, 6, 5, 7],
+        embedding.iter().map(|e| e.x_center).collect::<Vec<_>>()
+    );
+    let svg = canvas.to_svg_string().unwrap();
+    assert_eq!(6, svg.matches("<text").count());
+    assert_eq!(4, svg.matches("<line").count());
+    // The image is as wide as both trees, the margins are not doubled
+    assert_eq!(50.0, single_width);
+    assert_eq!(90.0, width(&svg));
+}
+
+#[test]
+fn canvas_aligned_layers() {
+    let tree = small_tree();
+    let wide_levels = Spacing {
+        level_gap: 3,
+        ..Spacing::new()
+    };
+    let canvas = Canvas::new()
+        .add(Layouter::new(&tree).embed_with_visualize().unwrap())
+        .add(
+            Layouter::new(&tree)
+                .with_spacing(wide_levels)
+                .embed_with_visualize()
+                .unwrap(),
+        );
+    let y_orders = |canvas: &Canvas| {
+        canvas
+            .embedding()
+            .iter()
+            .map(|e| e.y_order)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec![0, 1, 1, 0, 3, 3], y_orders(&canvas));
+
+    // The leaves of both trees are drawn on the same line
+    let canvas = canvas.with_aligned_layers(true);
+    assert_eq!(vec![0, 3, 3, 0, 3, 3], y_orders(&canvas));
+    let resolved = SvgDrawer::new().resolve(&canvas.embedding());
+    assert_eq!(resolved[1].y, resolved[4].y);
+}
+
+#[test]
+fn animated_svg() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let drawer = AnimatedSvgDrawer::new()
+        .with_svg_drawer(SvgDrawer::new().with_id_prefix("t-"))
+        .with_step(0.25)
+        .with_fade(1.0);
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+
+    // The stylesheet follows the start tag and the nodes appear in builder order
+    let svg_start = svg.find("<svg").unwrap();
+    let style_start = svg.find("<style>").unwrap();
+    assert!(svg_start < style_start && style_start < svg.find("<text").unwrap());
+    assert!(svg.contains("animation: reveal 1s ease-in forwards;"));
+    assert!(svg.contains(r#"[id="t-node-0"], [id="t-box-0"], [id="t-edge-0"]"#));
+    let delays = svg
+        .match_indices("animation-delay: ")
+        .map(|(i, m)| &svg[i + m.len()..i + svg[i..].find(';').unwrap()])
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["0s", "0.25s", "0.5s"], delays);
+    assert!(compare_svg(&svg, &svg, 0.0).is_ok());
+}
+
+#[test]
+fn draw_context() {
+    #[derive(Default)]
+    struct Recorder(std::cell::RefCell<Vec<String>>);
+
+    impl Drawer for Recorder {
+        fn draw(&self, ctx: &DrawContext<'_>) -> syntree_layout::Result<()> {
+            self.0.borrow_mut().push(format!(
+                "{} {:?} {} {:?} {:?} {:?}",
+                ctx.file_name().display(),
+                ctx.bounds(),
+                ctx.embedding().len(),
+                ctx.title(),
+                ctx.source(),
+                ctx.style().map(|style| style.font_size),
+            ));
+            Ok(())
+        }
+    }
+
+    let tree = small_tree();
+    let recorder = Recorder::default();
+    let style = SvgDrawerStyle {
+        font_size: 20.0,
+        ..SvgDrawerStyle::new()
+    };
+    Layouter::new(&tree)
+        .with_drawer(&recorder)
+        .with_file_path("tree.txt")
+        .with_title("Small tree")
+        .with_source("12")
+        .with_drawer_style(&style)
+        .embed_with_visualize()
+        .unwrap()
+        .write()
+        .unwrap();
+    assert_eq!(
+        vec![r#"tree.txt (4, 2) 3 Some("Small tree") Some("12") Some(20.0)"#],
+        *recorder.0.borrow()
+    );
+
+    // The HTML page takes the title from the context
+    let path = std::env::temp_dir().join("syntree_layout_draw_context.html");
+    Layouter::new(&tree)
+        .with_drawer(&HtmlDrawer::new())
+        .with_file_path(&path)
+        .with_title("Small <tree>")
+        .embed_with_visualize()
+        .unwrap()
+        .write()
+        .unwrap();
+    let html = std::fs::read_to_string(&path).unwrap();
+    assert!(html.contains("<title>Small &lt;tree&gt;</title>"));
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Shaped(&'static str, Option<NodeShape>);
+
+impl Visualize for Shaped {
+    fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn shape(&self) -> Option<NodeShape> {
+        self.1
+    }
+}
+
+#[test]
+fn node_shapes() {
+    let mut tree = Builder::new();
+    tree.open(Shaped("if", Some(NodeShape::Diamond))).unwrap();
+    tree.token(Shaped("cond", Some(NodeShape::Ellipse)), 1)
+        .unwrap();
+    tree.token(Shaped("then", Some(NodeShape::Plain)), 1)
+        .unwrap();
+    tree.token(Shaped("else", None), 1).unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let shapes = layouter
+        .embedding()
+        .iter()
+        .map(|e| e.style.shape)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            Some(NodeShape::Diamond),
+            Some(NodeShape::Ellipse),
+            Some(NodeShape::Plain),
+            None
+        ],
+        shapes
+    );
+
+    // The shapes are outlined in the edge color, plain nodes and nodes without shape get no box
+    let svg = layouter.to_svg_string().unwrap();
+    assert!(svg.contains(r#"<polygon id="box-0" points="#));
+    assert!(svg.contains(r#"<ellipse id="box-1""#));
+    assert!(!svg.contains(r#"id="box-2""#));
+    assert!(!svg.contains(r#"id="box-3""#));
+    assert_eq!(2, svg.matches(r#"fill="none" stroke="black""#).count());
+}
+
+#[test]
+fn links() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let drawer = SvgDrawer::new().with_links(|node| {
+        (node.parent.is_some())
+            .then(|| format!("rules.html?node={}&span={:?}", node.text, node.span))
+    });
+    let svg = drawer.render_string(layouter.embedding()).unwrap();
+
+    // Only the leaves are linked, their box and label are inside of the link
+    assert_eq!(2, svg.matches("<a href=").count());
+    assert!(svg.contains(r#"<a href="rules.html?node=1&amp;span=0..1">"#));
+    let link = svg
+        .find(r#"<a href="rules.html?node=2&amp;span=1..2">"#)
+        .unwrap();
+    let text = svg.find(r#"id="node-2""#).unwrap();
+    let link_end = link + svg[link..].find("</a>").unwrap();
+    assert!(link < text && text < link_end);
+    assert!(!svg[link..link_end].contains(r#"id="edge-2""#));
+
+    // The HTML drawer draws the links of its SvgDrawer
+    let html = HtmlDrawer::new()
+        .with_svg_drawer(drawer)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(2, html.matches("<a href=").count());
+}
+
+#[test]
+fn convenience_functions() {
+    let tree = small_tree();
+    let expected = Layouter::new(&tree)
+        .embed_with_visualize()
+        .unwrap()
+        .to_svg_string()
+        .unwrap();
+    assert_eq!(expected, syntree_layout::to_svg_string(&tree).unwrap());
+
+    let path = std::env::temp_dir().join("syntree_layout_render_svg.svg");
+    syntree_layout::render_svg(&tree, &path).unwrap();
+    assert_eq!(expected, std::fs::read_to_string(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn map_embedding() {
+    let tree = small_tree();
+    let svg = Layouter::new(&tree)
+        .embed_with_visualize()
+        .unwrap()
+        .map_embedding(|node| {
+            if node.parent.is_some() {
+                node.text = format!("leaf {}", node.text);
+            }
+        })
+        .to_svg_string()
+        .unwrap();
+    assert!(svg.contains(">leaf 1</text>"));
+    assert!(svg.contains(">leaf 2</text>"));
+    assert!(!svg.contains(">1</text>"));
+}
+
+#[test]
+fn group_boxes() {
+    #[derive(Copy, Clone, Debug)]
+    struct Production(&'static str, Option<&'static str>);
+
+    impl Visualize for Production {
+        fn visualize(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+
+        fn group(&self) -> Option<String> {
+            self.1.map(str::to_string)
+        }
+    }
+
+    //      stmt
+    //     /    \
+    //   expr   expr
+    //    |      |
+    //    a      b
+    let mut tree = Builder::new();
+    tree.open(Production("stmt", Some("Statement"))).unwrap();
+    tree.open(Production("expr", Some("Expr"))).unwrap();
+    tree.token(Production("a", None), 1).unwrap();
+    tree.close().unwrap();
+    tree.open(Production("expr", Some("Expr"))).unwrap();
+    tree.token(Production("b", None), 1).unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    assert_eq!(Some("Expr"), layouter.embedding()[1].group.as_deref());
+    let svg = layouter.to_svg_string().unwrap();
+
+    // Outer groups are drawn first and all groups behind the nodes
+    let group = |ord: usize| svg.find(&format!(r#"<g id="group-{}">"#, ord)).unwrap();
+    assert!(group(0) < group(1) && group(1) < group(3));
+    assert!(group(3) < svg.find(r#"id="node-0""#).unwrap());
+    assert!(svg.contains(">Statement</text>"));
+    assert_eq!(2, svg.matches(">Expr</text>").count());
+    // Equal labels get equal colors
+    assert_eq!(2, svg.matches(r##"fill="#f28e2b""##).count());
+
+    let rect = |ord: usize| {
+        let start = group(ord) + svg[group(ord)..].find("<rect").unwrap();
+        let rect = &svg[start..start + svg[start..].find('>').unwrap()];
+        ["x", "y", "width", "height"].map(|name| {
+            let value = &rect[rect.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3..];
+            value[..value.find('"').unwrap()].parse::<f32>().unwrap()
+        })
+    };
+    // The group of the root encloses the nested groups
+    let [x0, y0, w0, h0] = rect(0);
+    for ord in [1, 3] {
+        let [x, y, w, h] = rect(ord);
+        assert!(x0 <= x && y0 <= y && x + w <= x0 + w0 && y + h <= y0 + h0);
+    }
+    assert!(y0 >= 0.0);
+    // Sibling groups don't overlap
+    let [x1, _, w1, _] = rect(1);
+    assert!(x1 + w1 <= rect(3)[0]);
+}
+
+#[test]
+fn depth_colors() {
+    //   0
+    //   |
+    //   1
+    //  / \
+    // 2   3
+    let mut tree = Builder::new();
+    tree.open(MyNodeData(0)).unwrap();
+    tree.open(MyNodeData(1)).unwrap();
+    tree.token(MyNodeData(2), 1).unwrap();
+    tree.token(MyNodeData(3), 1).unwrap();
+    tree.close().unwrap();
+    tree.close().unwrap();
+    let tree = tree.build().unwrap();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+
+    let fill = |svg: &str, ord: usize| {
+        let start = svg.find(&format!(r#"id="box-{}""#, ord)).unwrap();
+        let rect = &svg[start..start + svg[start..].find('>').unwrap()];
+        let value = &rect[rect.find(r#"fill=""#).unwrap() + 6..];
+        value[..value.find('"').unwrap()].to_string()
+    };
+
+    let gradient = DepthColors::Gradient {
+        from: "#ffffff".into(),
+        to: "#0000ff".into(),
+    };
+    let svg = SvgDrawer::new()
+        .with_depth_colors(gradient)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!("#ffffff", fill(&svg, 0));
+    assert_eq!("#8080ff", fill(&svg, 1));
+    assert_eq!("#0000ff", fill(&svg, 2));
+    assert_eq!("#0000ff", fill(&svg, 3));
+
+    // The palette repeats for deeper levels and the node's own fill wins
+    let mut embedding = layouter.embedding().to_vec();
+    embedding[3].style.fill = Some("red".into());
+    let palette = DepthColors::Palette(vec!["#eee".into(), "#ddd".into()]);
+    let svg = SvgDrawer::new()
+        .with_depth_colors(palette)
+        .render_string(&embedding)
+        .unwrap();
+    assert_eq!("#eee", fill(&svg, 0));
+    assert_eq!("#ddd", fill(&svg, 1));
+    assert_eq!("#eee", fill(&svg, 2));
+    assert_eq!("red", fill(&svg, 3));
+}
+
+#[test]
+fn hit_testing() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let embedding = layouter.embedding();
+    for drawer in [
+        SvgDrawer::new(),
+        SvgDrawer::new().with_layout_kind(LayoutKind::Radial),
+    ] {
+        let geometry = drawer.resolve(embedding);
+        for node in &geometry {
+            let (x, y) = node.center();
+            assert_eq!(node.ord, embedding.node_at(x, y, &geometry).unwrap().ord);
+        }
+        let (width, height) = (geometry.width(), geometry.height());
+        assert_eq!(
+            3,
+            embedding
+                .nodes_in_rect(0.0, 0.0, width, height, &geometry)
+                .count()
+        );
+        assert_eq!(
+            0,
+            embedding
+                .nodes_in_rect(width + 1.0, 0.0, 10.0, 10.0, &geometry)
+                .count()
+        );
+    }
+}
+
+#[test]
+fn hostile_id_prefix() {
+    let tree = small_tree();
+    let layouter = Layouter::new(&tree).embed_with_visualize().unwrap();
+    let prefix = "</script><script>alert(1)</script>";
+
+    let html = HtmlDrawer::new()
+        .with_svg_drawer(SvgDrawer::new().with_id_prefix(prefix))
+        .render_string(layouter.embedding())
+        .unwrap();
+    // Only the fold script is closed, the prefix is a string literal inside of it
+    assert_eq!(1, html.matches("</script>").count());
+    assert!(html.contains(r#"const prefix = "\u003c/script>\u003cscript>alert(1)\u003c/script>";"#));
+
+    let svg = SvgDrawer::new()
+        .with_id_prefix(prefix)
+        .with_pan_zoom(true)
+        .render_string(layouter.embedding())
+        .unwrap();
+    assert_eq!(1, svg.matches("</script>").count());
+}
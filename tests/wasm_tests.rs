@@ -0,0 +1,23 @@
+#![cfg(feature = "wasm")]
+
+use syntree_layout::{render_to_html_string, render_to_image_map, render_to_svg_string};
+
+#[test]
+fn string_renderers() {
+    let tree = "(Expr 1 + (Call f))";
+    let svg = render_to_svg_string(tree).unwrap();
+    assert!(svg.contains(">Call</text>"));
+    let html = render_to_html_string(tree).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(">Call</text>"));
+
+    // The image map describes the SVG document
+    let map = render_to_image_map(tree).unwrap();
+    assert_eq!(5, map.matches(r#""ord""#).count());
+    let width = svg
+        .split_once(r#"viewBox="0 0 "#)
+        .and_then(|(_, rest)| rest.split_once(' '))
+        .map(|(width, _)| width)
+        .unwrap();
+    assert!(map.contains(&format!(r#""width": {},"#, width)));
+}